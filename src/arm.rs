@@ -12,6 +12,8 @@
 //!    loop divider value. Commit those values to registers.
 //! 3. Switch (back) to PLL1 as the AHB_CLK_ROOT.
 //!
+//! Use [`set_source`] to run the ARM clock from a source other than PLL1.
+//!
 //! # References
 //!
 //! i.MX RT 1060 reference manual
@@ -19,7 +21,13 @@
 //!   - System Clocks
 //!   - CCM Internal Clock Generation
 
-use crate::register::Field;
+use crate::{
+    glitchless::{self, GlitchlessMux, Wait},
+    register::{self, Field},
+    xtalosc::reference_frequency,
+};
+
+pub use crate::glitchless::HandshakeTimeout;
 
 /// The ARM clock frequency
 ///
@@ -39,53 +47,245 @@ pub struct ARMClock(pub u32);
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct IPGClock(pub u32);
 
+impl ARMClock {
+    /// Returns the frequency of the SysTick counter when it runs from `source`
+    ///
+    /// The core clock, the DWT cycle counter, and the SysTick `Core` source
+    /// all run at the ARM clock frequency.
+    pub fn systick(self, source: SysTickSource) -> u32 {
+        match source {
+            SysTickSource::Core => self.0,
+            SysTickSource::External => reference_frequency() / 240,
+        }
+    }
+}
+
+/// The clock that drives the Cortex-M SysTick counter
+///
+/// This is the CLKSOURCE bit in the SysTick control and status register.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SysTickSource {
+    /// The processor clock, which is the ARM clock (CLKSOURCE = 1)
+    Core,
+    /// The external reference clock (CLKSOURCE = 0)
+    ///
+    /// See [`SYSTICK_EXTERNAL_FREQUENCY_HZ`].
+    External,
+}
+
 const CCM_CACCR: *mut u32 = 0x400F_C010 as _;
 const CCM_CBCDR: *mut u32 = 0x400F_C014 as _;
+const CCM_CBCMR: *mut u32 = 0x400F_C018 as _;
 
-/// Wait for all divider and mux handshakes to complete
-#[inline(always)]
-unsafe fn wait_for_handshake() {
-    const CCM_CDHIPR: *mut u32 = 0x400F_C048 as _;
-    while CCM_CDHIPR.read_volatile() != 0 {}
-}
+const PRE_PERIPH_CLK_SEL: Field = Field::new(18, 0x3);
+/// PRE_PERIPH_CLK_SEL value that selects PLL1
+const PRE_PERIPH_CLK_PLL1: u32 = 3;
 
-/// Runs the function when the AHB_CLK_ROOT is powered by the
-/// 24MHz crystal oscillator. When the function returns, AH_BCLK_ROOT
-/// is powered by the PRE_PERIPH_CLK source.
-///
-/// # Safety
+const PERIPH_CLK_SEL: Field = Field::new(25, 1);
+/// The glitchless multiplexer that selects PERIPH_CLK2 (1) or PRE_PERIPH_CLK (0)
+const PERIPH_CLK: GlitchlessMux = unsafe { GlitchlessMux::new(CCM_CBCDR, 25, 1) };
+const PERIPH_CLK2_PODF: Field = Field::new(27, 0b111);
+const PERIPH_CLK2_SEL: Field = Field::new(12, 0b11);
+/// PERIPH_CLK2_SEL value that selects the 24MHz oscillator
+const PERIPH_CLK2_OSC: u32 = 1;
+
+/// The ARM clock source
 ///
-/// Modifies CCM register memory.
-unsafe fn on_ahb_clk_oscillator<R>(func: impl FnOnce() -> R) -> R {
-    const CCM_CBCMR: *mut u32 = 0x400F_C018 as _;
+/// The source is selected by the PRE_PERIPH_CLK multiplexer. The AHB_CLK_ROOT,
+/// and therefore the ARM and IPG clocks, derive from this source.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Source {
+    /// PLL2, the system PLL
+    Pll2,
+    /// PLL2 PFD2
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    Pll2Pfd2,
+    /// PLL2 PFD0
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    Pll2Pfd0,
+    /// PLL1, the ARM PLL, divided by the ARM divider
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    Pll1,
+    /// PLL3 PFD3
+    #[cfg(not(feature = "imxrt1060"))]
+    Pll3Pfd3,
+    /// PLL2 PFD3
+    #[cfg(not(feature = "imxrt1060"))]
+    Pll2Pfd3,
+    /// PLL6, the 500MHz ENET PLL
+    #[cfg(not(feature = "imxrt1060"))]
+    Pll6,
+}
 
-    const PERIPH_CLK2_PODF: Field = Field::new(27, 0b111);
-    const PERIPH_CLK2_SEL: Field = Field::new(12, 0b11);
+impl Source {
+    /// Returns the PRE_PERIPH_CLK_SEL value for this source
+    const fn field(self) -> u32 {
+        match self {
+            Source::Pll2 => 0,
+            #[cfg(feature = "imxrt1060")]
+            Source::Pll2Pfd2 => 1,
+            #[cfg(feature = "imxrt1060")]
+            Source::Pll2Pfd0 => 2,
+            #[cfg(feature = "imxrt1060")]
+            Source::Pll1 => PRE_PERIPH_CLK_PLL1,
+            #[cfg(not(feature = "imxrt1060"))]
+            Source::Pll3Pfd3 => 1,
+            #[cfg(not(feature = "imxrt1060"))]
+            Source::Pll2Pfd3 => 2,
+            #[cfg(not(feature = "imxrt1060"))]
+            Source::Pll6 => 3,
+        }
+    }
 
-    PERIPH_CLK2_PODF.modify(CCM_CBCDR, 0); // Divide by 1
-    PERIPH_CLK2_SEL.modify(CCM_CBCMR, 1); // Derive from oscillator
-    wait_for_handshake();
+    /// Returns the source described by a PRE_PERIPH_CLK_SEL value
+    pub(crate) const fn from_field(field: u32) -> Self {
+        match field & 0x3 {
+            0 => Source::Pll2,
+            #[cfg(feature = "imxrt1060")]
+            1 => Source::Pll2Pfd2,
+            #[cfg(feature = "imxrt1060")]
+            2 => Source::Pll2Pfd0,
+            #[cfg(feature = "imxrt1060")]
+            _ => Source::Pll1,
+            #[cfg(not(feature = "imxrt1060"))]
+            1 => Source::Pll3Pfd3,
+            #[cfg(not(feature = "imxrt1060"))]
+            2 => Source::Pll2Pfd3,
+            #[cfg(not(feature = "imxrt1060"))]
+            _ => Source::Pll6,
+        }
+    }
+}
 
-    // Switch main peripheral clock to PERIPH_CLK2
-    const PERIPH_CLK_SEL: Field = Field::new(25, 1);
-    PERIPH_CLK_SEL.modify(CCM_CBCDR, 1);
-    wait_for_handshake();
+/// Returns the frequency of a non-PLL1 source, before the AHB divider
+fn source_frequency(source: Source) -> u32 {
+    match source {
+        // Handled by the ARM timings
+        #[cfg(feature = "imxrt1060")]
+        Source::Pll1 => 0,
+        source => crate::ClockSource::from(source).frequency(),
+    }
+}
+
+/// The PERIPH_CLK2 source
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PeriphClk2 {
+    /// PLL3, the USB1 PLL
+    Pll3,
+    /// The 24MHz crystal oscillator
+    Oscillator,
+    /// The PLL2 bypass clock
+    Pll2Bypass,
+}
+
+impl PeriphClk2 {
+    /// Returns the PERIPH_CLK2_SEL value for this source
+    const fn field(self) -> u32 {
+        match self {
+            PeriphClk2::Pll3 => 0,
+            PeriphClk2::Oscillator => PERIPH_CLK2_OSC,
+            PeriphClk2::Pll2Bypass => 2,
+        }
+    }
 
-    let result = func();
+    /// Returns the source described by a PERIPH_CLK2_SEL value
+    pub(crate) const fn from_field(field: u32) -> Self {
+        match field & 0x3 {
+            0 => PeriphClk2::Pll3,
+            PERIPH_CLK2_OSC => PeriphClk2::Oscillator,
+            _ => PeriphClk2::Pll2Bypass,
+        }
+    }
+}
 
-    // Switch back to PRE_PERIPH_CLK
-    const PRE_PERIPH_CLK_SEL: Field = Field::new(18, 0x3);
-    PRE_PERIPH_CLK_SEL.modify(CCM_CBCMR, 3); // Select PLL1
+/// Returns the frequency of a PERIPH_CLK2 source, before the PERIPH_CLK2 divider
+fn periph_clk2_frequency(source: PeriphClk2) -> u32 {
+    crate::ClockSource::from(source).frequency()
+}
+
+/// Indicates that PERIPH_CLK2 drives AHB_CLK_ROOT, so it cannot be reconfigured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeriphClk2Selected;
 
-    PERIPH_CLK_SEL.modify(CCM_CBCDR, 0);
-    wait_for_handshake();
+/// The clock that drives AHB_CLK_ROOT
+///
+/// The glitchless PERIPH_CLK multiplexer selects either PRE_PERIPH_CLK, or
+/// PERIPH_CLK2.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Root {
+    /// PRE_PERIPH_CLK, and its source
+    PrePeriph(Source),
+    /// PERIPH_CLK2, and its source
+    PeriphClk2(PeriphClk2),
+}
+
+/// The ARM and IPG clock frequencies, and the clock that produces them
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Clocks {
+    /// The clock that drives AHB_CLK_ROOT
+    pub root: Root,
+    /// The ARM clock frequency
+    pub arm: ARMClock,
+    /// The IPG clock frequency
+    pub ipg: IPGClock,
+}
+
+const CCM_CDHIPR: *mut u32 = 0x400F_C048 as _;
+
+/// Wait for all divider and mux handshakes to complete, checking the
+/// handshakes at most `max_iterations` times
+///
+/// Returns [`HandshakeTimeout`] if the handshakes did not complete.
+pub fn try_wait_for_handshake(max_iterations: u32) -> Result<(), HandshakeTimeout> {
+    glitchless::wait_for_handshake(Wait::Iterations(max_iterations))
+}
+
+/// Runs the function when the AHB_CLK_ROOT is powered by the
+/// 24MHz crystal oscillator. When the function returns, AH_BCLK_ROOT
+/// is powered by the PRE_PERIPH_CLK source, selected by `pre_periph`.
+///
+/// Returns early if a handshake, or the function, times out.
+///
+/// # Safety
+///
+/// Modifies CCM register memory.
+unsafe fn on_ahb_clk_oscillator<R>(
+    pre_periph: u32,
+    wait: Wait,
+    func: impl FnOnce() -> Result<R, HandshakeTimeout>,
+) -> Result<R, HandshakeTimeout> {
+    ARM_CONTEXT.program_periph_clk2(PERIPH_CLK2_OSC, 0, wait)?;
 
-    result
+    // Run the main peripheral clock from PERIPH_CLK2, then switch back to PRE_PERIPH_CLK
+    glitchless::switch(&PERIPH_CLK, 1, 0, wait, || {
+        let result = func()?;
+        PRE_PERIPH_CLK_SEL.modify(CCM_CBCMR, pre_periph);
+        wait.handshake(CCM_CDHIPR)?;
+        Ok(result)
+    })
 }
 
 /// ARM clock timings
-#[derive(PartialEq, Eq, Debug)]
-pub(crate) struct Timings {
+///
+/// `Timings` describes the PLL1 loop divider, and the ARM, AHB, and IPG
+/// dividers, that [`set_frequency`] uses for a target ARM clock. Compute them
+/// with [`Timings::with_reference`] to check a configuration without hardware:
+///
+/// ```
+/// use imxrt_ccm::arm::Timings;
+///
+/// const TIMINGS: Timings = Timings::with_reference(600_000_000, 24_000_000);
+/// assert_eq!(TIMINGS.arm_hz(), 600_000_000);
+/// assert_eq!(TIMINGS.ipg_hz(), 150_000_000);
+/// assert_eq!(TIMINGS.pll_arm_div_sel(), 100);
+/// assert_eq!((TIMINGS.div_arm(), TIMINGS.div_ahb(), TIMINGS.div_ipg()), (2, 1, 4));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Timings {
     /// PLL_ARM DIV_SEL
     ///
     /// Valid range for divider value: 54-108. `Fout = Fin * div_select/2.0`
@@ -109,16 +309,40 @@ pub(crate) struct Timings {
     div_ipg: u32,
 }
 
+/// Returns the PLL1 frequency for each step of DIV_SELECT, which is half the
+/// reference frequency
+#[inline(always)]
+fn pll1_step_hz() -> u32 {
+    reference_frequency() / 2
+}
+
 #[inline(always)]
 fn compute_arm_hz(div_arm: u32, div_ahb: u32, pll_arm_div_sel: u32) -> u32 {
-    pll_arm_div_sel * 12_000_000 / div_arm / div_ahb
+    pll_arm_div_sel * pll1_step_hz() / div_arm / div_ahb
 }
 
 impl Timings {
     /// Returns a `Timings` that approximates the target ARM clock `arm_hz`
     pub(crate) fn target(arm_hz: u32) -> Self {
-        let (mut div_arm, mut div_ahb) = (1, 1);
-        while arm_hz * div_arm * div_ahb < 648_000_000 {
+        Self::with_reference(arm_hz, reference_frequency())
+    }
+
+    /// Returns a `Timings` that approximates the target ARM clock `arm_hz`,
+    /// given the `reference_hz` crystal oscillator frequency
+    ///
+    /// Unlike [`set_frequency`], `with_reference` doesn't read or write
+    /// the CCM.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `reference_hz` is less than 2Hz.
+    pub const fn with_reference(arm_hz: u32, reference_hz: u32) -> Self {
+        assert!(reference_hz >= 2, "reference frequency is too small");
+
+        // u64 math, so that large frequencies can't overflow
+        let target_hz = arm_hz as u64;
+        let (mut div_arm, mut div_ahb): (u64, u64) = (1, 1);
+        while target_hz * div_arm * div_ahb < 648_000_000 {
             if div_arm < 8 {
                 div_arm += 1;
             } else if div_ahb < 5 {
@@ -129,31 +353,77 @@ impl Timings {
             }
         }
 
-        let pll_arm_div_sel = (arm_hz * div_arm * div_ahb + 6_000_000) / 12_000_000;
-        let pll_arm_div_sel = pll_arm_div_sel.min(108).max(54);
-        let arm_hz = compute_arm_hz(div_arm, div_ahb, pll_arm_div_sel);
+        let step_hz = reference_hz as u64 / 2;
+        let pll_arm_div_sel = (target_hz * div_arm * div_ahb + step_hz / 2) / step_hz;
+        let pll_arm_div_sel = if pll_arm_div_sel < 54 {
+            54
+        } else if pll_arm_div_sel > 108 {
+            108
+        } else {
+            pll_arm_div_sel as u32
+        };
+        let arm_hz = pll_arm_div_sel as u64 * step_hz / div_arm / div_ahb;
+        let arm_hz = if arm_hz > u32::MAX as u64 {
+            u32::MAX
+        } else {
+            arm_hz as u32
+        };
 
-        let div_ipg = (arm_hz + 149_999_999) / 150_000_000;
-        let div_ipg = div_ipg.min(4);
+        let div_ipg = arm_hz.div_ceil(150_000_000);
+        let div_ipg = if div_ipg > 4 { 4 } else { div_ipg };
 
         Timings {
             pll_arm_div_sel,
-            div_arm,
-            div_ahb,
+            div_arm: div_arm as u32,
+            div_ahb: div_ahb as u32,
             arm_hz,
             div_ipg,
         }
     }
 
+    /// Returns the ARM clock frequency described by these timings
+    pub const fn arm_hz(&self) -> u32 {
+        self.arm_hz
+    }
+
     /// Returns the IPG clock frequency described by these timings
-    pub fn ipg_hz(&self) -> u32 {
+    pub const fn ipg_hz(&self) -> u32 {
         self.arm_hz / self.div_ipg
     }
+
+    /// Returns the PLL_ARM DIV_SELECT value, between 54 and 108
+    pub const fn pll_arm_div_sel(&self) -> u32 {
+        self.pll_arm_div_sel
+    }
+
+    /// Returns the ARM_PODF divider, between 1 and 8
+    ///
+    /// Subtract 1 before writing the divider to `CACRR[ARM_PODF]`.
+    pub const fn div_arm(&self) -> u32 {
+        self.div_arm
+    }
+
+    /// Returns the AHB_PODF divider, between 1 and 5
+    ///
+    /// Subtract 1 before writing the divider to `CBCDR[AHB_PODF]`.
+    pub const fn div_ahb(&self) -> u32 {
+        self.div_ahb
+    }
+
+    /// Returns the IPG_PODF divider, between 1 and 4
+    ///
+    /// Subtract 1 before writing the divider to `CBCDR[IPG_PODF]`.
+    pub const fn div_ipg(&self) -> u32 {
+        self.div_ipg
+    }
 }
 
 const CCM_ANALOG_PLL_ARM: *mut u32 = 0x400D_8000 as _;
 
 const DIV_SEL: Field = Field::new(0, 0x7f);
+const POWERDOWN: Field = Field::new(12, 1);
+const ENABLE: Field = Field::new(13, 1);
+const LOCK: u32 = 1 << 31;
 
 /// Restart the ARM PLL with a new `div_sel` value
 ///
@@ -161,9 +431,6 @@ const DIV_SEL: Field = Field::new(0, 0x7f);
 ///
 /// Unsynchronized writes to CCM memory.
 unsafe fn restart_pll_arm(div_sel: u32) {
-    const POWERDOWN: Field = Field::new(12, 1);
-    const ENABLE: Field = Field::new(13, 1);
-
     // Clear all bits except POWERDOWN
     POWERDOWN.write_zero(CCM_ANALOG_PLL_ARM, 1);
     // Clear POWERDOWN write above
@@ -171,8 +438,7 @@ unsafe fn restart_pll_arm(div_sel: u32) {
     // Enable the PLL
     ENABLE.modify(CCM_ANALOG_PLL_ARM, 1);
 
-    const LOCK: u32 = 1 << 31;
-    while CCM_ANALOG_PLL_ARM.read_volatile() & LOCK == 0 {}
+    while register::load(CCM_ANALOG_PLL_ARM) & LOCK == 0 {}
 }
 
 const ARM_PODF: Field = Field::new(0, 0x7);
@@ -184,21 +450,28 @@ const IPG_PODF: Field = Field::new(8, 0x3);
 /// # Safety
 ///
 /// Unsynchronized writes to CCM memory.
-unsafe fn set_timings(timings: &Timings) {
+unsafe fn set_timings(timings: &Timings, wait: Wait) -> Result<(), HandshakeTimeout> {
     ARM_PODF.modify(CCM_CACCR, timings.div_arm.saturating_sub(1));
-    wait_for_handshake();
+    wait.handshake(CCM_CDHIPR)?;
 
     AHB_PODF.modify(CCM_CBCDR, timings.div_ahb.saturating_sub(1));
-    wait_for_handshake();
+    wait.handshake(CCM_CDHIPR)?;
 
     IPG_PODF.modify(CCM_CBCDR, timings.div_ipg.saturating_sub(1));
+    Ok(())
 }
 
 /// ARM timing context
 pub(crate) struct Context<'a> {
     caccr: *mut u32,
     cbcdr: *mut u32,
+    cbcmr: *mut u32,
+    cdhipr: *const u32,
     pll_arm: *mut u32,
+    /// Returns the frequency of a non-PLL1 source
+    source_hz: fn(Source) -> u32,
+    /// Returns the frequency of a PERIPH_CLK2 source
+    periph_clk2_hz: fn(PeriphClk2) -> u32,
     _scope: core::marker::PhantomData<&'a mut ()>,
 }
 
@@ -222,13 +495,221 @@ impl Context<'_> {
             div_ipg,
         }
     }
+
+    /// Returns the selected ARM clock source
+    ///
+    /// # Safety
+    ///
+    /// Assumes that the context memory is valid
+    pub unsafe fn source(&self) -> Source {
+        Source::from_field(PRE_PERIPH_CLK_SEL.read(self.cbcmr))
+    }
+
+    /// Returns the clock that drives AHB_CLK_ROOT
+    ///
+    /// # Safety
+    ///
+    /// Assumes that the context memory is valid
+    pub unsafe fn root(&self) -> Root {
+        if PERIPH_CLK_SEL.read(self.cbcdr) != 0 {
+            Root::PeriphClk2(PeriphClk2::from_field(PERIPH_CLK2_SEL.read(self.cbcmr)))
+        } else {
+            Root::PrePeriph(self.source())
+        }
+    }
+
+    /// Returns the ARM and IPG clock frequencies for the selected root
+    ///
+    /// # Safety
+    ///
+    /// Assumes that the context memory is valid
+    pub unsafe fn clocks(&self) -> Clocks {
+        let timings = self.timings();
+        let root = self.root();
+        let arm_hz = match root {
+            #[cfg(feature = "imxrt1060")]
+            Root::PrePeriph(Source::Pll1) => timings.arm_hz,
+            Root::PrePeriph(source) => (self.source_hz)(source) / timings.div_ahb,
+            Root::PeriphClk2(source) => {
+                (self.periph_clk2_hz)(source)
+                    / (PERIPH_CLK2_PODF.read(self.cbcdr) + 1)
+                    / timings.div_ahb
+            }
+        };
+        Clocks {
+            root,
+            arm: ARMClock(arm_hz),
+            ipg: IPGClock(arm_hz / timings.div_ipg),
+        }
+    }
+}
+
+impl Context<'_> {
+    unsafe fn set_periph_clk2(
+        &self,
+        source: PeriphClk2,
+        divider: u32,
+    ) -> Result<(), PeriphClk2Selected> {
+        if PERIPH_CLK_SEL.read(self.cbcdr) != 0 {
+            return Err(PeriphClk2Selected);
+        }
+        PERIPH_CLK2_PODF.modify(self.cbcdr, divider.clamp(1, 8) - 1);
+        PERIPH_CLK2_SEL.modify(self.cbcmr, source.field());
+        self.wait_for_handshake();
+        Ok(())
+    }
+
+    unsafe fn periph_clk2(&self) -> (PeriphClk2, u32) {
+        (
+            PeriphClk2::from_field(PERIPH_CLK2_SEL.read(self.cbcmr)),
+            PERIPH_CLK2_PODF.read(self.cbcdr) + 1,
+        )
+    }
+
+    unsafe fn select_periph_clk2(&self, selected: bool) {
+        forever(self.periph_clk().select(selected as u32, Wait::Forever));
+    }
+
+    /// Returns the glitchless PERIPH_CLK multiplexer
+    const fn periph_clk(&self) -> GlitchlessMux {
+        // Safety: the context memory is valid
+        unsafe { GlitchlessMux::new(self.cbcdr, 25, 1).with_handshake(self.cdhipr) }
+    }
+
+    /// Run the core from `source`, or from the oscillator if `source` is `None`,
+    /// with the AHB and IPG dividers
+    ///
+    /// Only changes the glitchless PERIPH_CLK multiplexer, and dividers, while
+    /// the core runs. PRE_PERIPH_CLK changes while the core runs on the
+    /// oscillator, and PERIPH_CLK2 changes while the core runs on
+    /// PRE_PERIPH_CLK. Never touches a PLL.
+    pub(crate) unsafe fn switch_core(&self, source: Option<Source>, div_ahb: u32, div_ipg: u32) {
+        let periph_clk = self.periph_clk();
+        forever(self.program_periph_clk2(PERIPH_CLK2_OSC, 0, Wait::Forever));
+        forever(periph_clk.select(1, Wait::Forever));
+
+        AHB_PODF.modify(self.cbcdr, div_ahb.clamp(1, 8) - 1);
+        self.wait_for_handshake();
+        IPG_PODF.modify(self.cbcdr, div_ipg.clamp(1, 4) - 1);
+
+        if let Some(source) = source {
+            PRE_PERIPH_CLK_SEL.modify(self.cbcmr, source.field());
+            self.wait_for_handshake();
+            forever(periph_clk.select(0, Wait::Forever));
+        }
+    }
+}
+
+impl Context<'_> {
+    /// Wait for all divider and mux handshakes to complete
+    unsafe fn wait_for_handshake(&self) {
+        forever(Wait::Forever.handshake(self.cdhipr))
+    }
+
+    /// Returns `true` if the PRE_PERIPH_CLK source is running
+    unsafe fn pre_periph_running(&self) -> bool {
+        match self.source() {
+            #[cfg(feature = "imxrt1060")]
+            Source::Pll1 => {
+                POWERDOWN.read(self.pll_arm) == 0 && register::load(self.pll_arm) & LOCK != 0
+            }
+            source => (self.source_hz)(source) != 0,
+        }
+    }
+
+    /// Program the PERIPH_CLK2 multiplexer and divider fields
+    ///
+    /// If PERIPH_CLK2 drives AHB_CLK_ROOT, and the fields change, the core
+    /// runs from PRE_PERIPH_CLK, behind the largest AHB divider, while the
+    /// PERIPH_CLK2 multiplexer changes. Changing the multiplexer of the
+    /// selected clock could glitch the core clock. If the PRE_PERIPH_CLK
+    /// source isn't running, the core borrows PLL2.
+    unsafe fn program_periph_clk2(
+        &self,
+        select: u32,
+        podf: u32,
+        wait: Wait,
+    ) -> Result<(), HandshakeTimeout> {
+        let periph_clk = self.periph_clk();
+        if periph_clk.selection() == 0 {
+            PERIPH_CLK2_PODF.modify(self.cbcdr, podf);
+            PERIPH_CLK2_SEL.modify(self.cbcmr, select);
+            return wait.handshake(self.cdhipr);
+        }
+        if PERIPH_CLK2_SEL.read(self.cbcmr) == select && PERIPH_CLK2_PODF.read(self.cbcdr) == podf {
+            return Ok(());
+        }
+
+        let ahb_podf = AHB_PODF.read(self.cbcdr);
+        let pre_periph = PRE_PERIPH_CLK_SEL.read(self.cbcmr);
+        AHB_PODF.modify(self.cbcdr, 0b111);
+        wait.handshake(self.cdhipr)?;
+        if !self.pre_periph_running() {
+            PRE_PERIPH_CLK_SEL.modify(self.cbcmr, Source::Pll2.field());
+            wait.handshake(self.cdhipr)?;
+        }
+        periph_clk.select(0, wait)?;
+
+        PERIPH_CLK2_PODF.modify(self.cbcdr, podf);
+        PERIPH_CLK2_SEL.modify(self.cbcmr, select);
+        wait.handshake(self.cdhipr)?;
+
+        periph_clk.select(1, wait)?;
+        PRE_PERIPH_CLK_SEL.modify(self.cbcmr, pre_periph);
+        wait.handshake(self.cdhipr)?;
+        AHB_PODF.modify(self.cbcdr, ahb_podf);
+        wait.handshake(self.cdhipr)
+    }
+
+    unsafe fn enter_low_power_run(&self) -> LowPowerRun {
+        let run = LowPowerRun {
+            cbcdr: register::load(self.cbcdr),
+            cbcmr: register::load(self.cbcmr),
+            #[cfg(feature = "imxrt1060")]
+            pll_arm: register::load(self.pll_arm),
+        };
+
+        forever(self.program_periph_clk2(PERIPH_CLK2_OSC, 0, Wait::Forever));
+        forever(self.periph_clk().select(1, Wait::Forever));
+
+        #[cfg(feature = "imxrt1060")]
+        {
+            ENABLE.modify(self.pll_arm, 0);
+            POWERDOWN.modify(self.pll_arm, 1);
+        }
+
+        run
+    }
+
+    unsafe fn exit_low_power_run(&self, run: LowPowerRun) {
+        #[cfg(feature = "imxrt1060")]
+        if POWERDOWN.read(&run.pll_arm) == 0 {
+            crate::register::write(self.pll_arm, u32::MAX, run.pll_arm);
+            while register::load(self.pll_arm) & LOCK == 0 {}
+        }
+
+        // Leave PERIPH_CLK2 before its multiplexer changes, unless the core
+        // returns to PERIPH_CLK2
+        if PERIPH_CLK_SEL.read(&run.cbcdr) == 0 {
+            forever(self.periph_clk().select(0, Wait::Forever));
+        }
+        forever(self.program_periph_clk2(
+            PERIPH_CLK2_SEL.read(&run.cbcmr),
+            PERIPH_CLK2_PODF.read(&run.cbcdr),
+            Wait::Forever,
+        ));
+    }
 }
 
 /// The context of the embedded ARM system
 pub(crate) const ARM_CONTEXT: Context<'static> = Context {
     caccr: CCM_CACCR,
     cbcdr: CCM_CBCDR,
+    cbcmr: CCM_CBCMR,
+    cdhipr: CCM_CDHIPR,
     pll_arm: CCM_ANALOG_PLL_ARM,
+    source_hz: source_frequency,
+    periph_clk2_hz: periph_clk2_frequency,
     _scope: core::marker::PhantomData,
 };
 
@@ -245,6 +726,8 @@ pub(crate) const ARM_CONTEXT: Context<'static> = Context {
 /// responsible for updating any peripherals to reference the new clock speed.
 ///
 /// When this function returns, the ARM clock runs on PLL1 (the "ARM PLL").
+/// The function clamps `hz` to [`MAX_FREQUENCY_HZ`]; use
+/// [`set_frequency_overclock`] to run faster.
 ///
 /// # Safety
 ///
@@ -253,44 +736,692 @@ pub(crate) const ARM_CONTEXT: Context<'static> = Context {
 /// prefer the safer [`CCM::set_frequency_arm`](crate::CCM::set_frequency_arm)
 /// method.
 pub unsafe fn set_frequency(hz: u32) -> (ARMClock, IPGClock) {
-    on_ahb_clk_oscillator(|| {
-        let timings = Timings::target(hz);
+    forever(set_frequency_(hz, MAX_FREQUENCY_HZ, Wait::Forever))
+}
+
+/// Set the ARM clock frequency, giving up if any divider or mux handshake
+/// doesn't complete within `max_iterations` checks
+///
+/// Otherwise, the function behaves like [`set_frequency`]. If a handshake times
+/// out, the function returns immediately, and the ARM clock may still run from
+/// the 24MHz oscillator. The function still waits for PLL1 to lock.
+///
+/// # Safety
+///
+/// See [`set_frequency`].
+pub unsafe fn set_frequency_bounded(
+    hz: u32,
+    max_iterations: u32,
+) -> Result<(ARMClock, IPGClock), HandshakeTimeout> {
+    set_frequency_(hz, MAX_FREQUENCY_HZ, Wait::Iterations(max_iterations))
+}
+
+/// Run the ARM clock from PLL1 at `hz`, clamped to `max`
+unsafe fn set_frequency_(
+    hz: u32,
+    max: u32,
+    wait: Wait,
+) -> Result<(ARMClock, IPGClock), HandshakeTimeout> {
+    on_ahb_clk_oscillator(PRE_PERIPH_CLK_PLL1, wait, || {
+        let timings = Timings::target(hz.min(max));
         restart_pll_arm(timings.pll_arm_div_sel);
-        set_timings(&timings);
-        (ARMClock(timings.arm_hz), IPGClock(timings.ipg_hz()))
+        set_timings(&timings, wait)?;
+        Ok((ARMClock(timings.arm_hz), IPGClock(timings.ipg_hz())))
     })
 }
 
+/// Unwraps the result of an operation that waits forever
+fn forever<R>(result: Result<R, HandshakeTimeout>) -> R {
+    match result {
+        Ok(result) => result,
+        Err(_) => unreachable!("unbounded waits never time out"),
+    }
+}
+
+/// A VDD_SOC voltage, in millivolts
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Millivolts(pub u32);
+
+/// The largest ARM clock frequency that runs at the lower VDD_SOC operating point
+#[cfg(feature = "imxrt1060")]
+const RUN_MAX_HZ: u32 = 528_000_000;
+#[cfg(not(feature = "imxrt1060"))]
+const RUN_MAX_HZ: u32 = 400_000_000;
+
+/// Returns the VDD_SOC voltage required to run the ARM core at `arm_hz`
+///
+/// The voltages follow the datasheet's run and overdrive operating points. The
+/// overdrive point, 1.25V, covers frequencies above 528MHz on the i.MX RT 1060,
+/// and above 400MHz on the i.MX RT 1010. All other frequencies run at 1.15V.
+pub const fn voltage(arm_hz: u32) -> Millivolts {
+    if arm_hz > RUN_MAX_HZ {
+        Millivolts(1_250)
+    } else {
+        Millivolts(1_150)
+    }
+}
+
+/// Set the ARM clock frequency, coordinating the VDD_SOC voltage with the
+/// frequency change
+///
+/// `set_voltage` should program the DCDC converter, or any other VDD_SOC supply,
+/// to the requested voltage, and return once the voltage is stable. If the ARM
+/// clock frequency increases, the function calls `set_voltage` before changing
+/// the frequency. If the frequency decreases, the function calls `set_voltage`
+/// after changing the frequency. See [`voltage`] for the operating points.
+///
+/// Otherwise, the function behaves like [`set_frequency`].
+///
+/// # Safety
+///
+/// Modifies CCM and CCM_ANALOG peripheral memory. Users should prefer the safer
+/// [`CCM::set_frequency_arm_with_voltage`](crate::CCM::set_frequency_arm_with_voltage)
+/// method.
+pub unsafe fn set_frequency_with_voltage(
+    hz: u32,
+    set_voltage: impl FnOnce(Millivolts),
+) -> (ARMClock, IPGClock) {
+    let (ARMClock(current_hz), _) = frequency();
+    let target_hz = Timings::target(hz.min(MAX_FREQUENCY_HZ)).arm_hz;
+    if target_hz > current_hz {
+        set_voltage(voltage(target_hz));
+        set_frequency(hz)
+    } else {
+        let clocks = set_frequency(hz);
+        if target_hz < current_hz {
+            set_voltage(voltage(target_hz));
+        }
+        clocks
+    }
+}
+
+/// The sources that [`set_frequency_pll2`] considers
+#[cfg(feature = "imxrt1060")]
+const PLL2_SOURCES: [Source; 3] = [Source::Pll2, Source::Pll2Pfd2, Source::Pll2Pfd0];
+#[cfg(not(feature = "imxrt1060"))]
+const PLL2_SOURCES: [Source; 2] = [Source::Pll2, Source::Pll2Pfd3];
+
+/// ARM clock timings for a source other than PLL1
+#[derive(PartialEq, Eq, Debug)]
+struct SourceTimings {
+    source: Source,
+    /// AHB divider (off-by-one for runtime math; subtract 1 before writing)
+    div_ahb: u32,
+    /// IPG divider (off-by-one for runtime math; subtract 1 before writing)
+    div_ipg: u32,
+    arm_hz: u32,
+}
+
+impl SourceTimings {
+    /// Returns the timings that produce the fastest ARM clock that doesn't
+    /// exceed `arm_hz`
+    ///
+    /// `sources` pairs each candidate source with its frequency. If no source
+    /// can produce a clock at or below `arm_hz`, returns the slowest clock.
+    /// Returns `None` if no source has a non-zero frequency.
+    fn target(arm_hz: u32, sources: impl Iterator<Item = (Source, u32)>) -> Option<Self> {
+        let mut best: Option<(Source, u32, u32)> = None;
+        for (source, source_hz) in sources.filter(|&(_, hz)| hz != 0) {
+            let div_ahb = source_hz.div_ceil(arm_hz.max(1)).clamp(1, 8);
+            let hz = source_hz / div_ahb;
+            let better = match best {
+                None => true,
+                Some((_, _, best_hz)) if best_hz > arm_hz => hz < best_hz,
+                Some((_, _, best_hz)) => hz <= arm_hz && hz > best_hz,
+            };
+            if better {
+                best = Some((source, div_ahb, hz));
+            }
+        }
+        best.map(|(source, div_ahb, arm_hz)| SourceTimings {
+            source,
+            div_ahb,
+            div_ipg: arm_hz.div_ceil(150_000_000).min(4),
+            arm_hz,
+        })
+    }
+}
+
+/// Returns the PLL2 timings for `hz`, never exceeding `max`
+fn pll2_target(
+    hz: u32,
+    max: u32,
+    sources: impl Iterator<Item = (Source, u32)>,
+) -> Option<SourceTimings> {
+    SourceTimings::target(hz.min(max), sources)
+}
+
+/// Returns the sources that [`set_frequency_pll2`] considers, and their
+/// frequencies
+fn pll2_sources() -> impl Iterator<Item = (Source, u32)> {
+    PLL2_SOURCES
+        .iter()
+        .map(|&source| (source, source_frequency(source)))
+}
+
+/// Run the ARM clock from the PLL2 output described by `timings`
+///
+/// # Safety
+///
+/// Modifies CCM and CCM_ANALOG peripheral memory.
+unsafe fn switch_pll2(timings: &SourceTimings) {
+    forever(on_ahb_clk_oscillator(
+        timings.source.field(),
+        Wait::Forever,
+        || {
+            AHB_PODF.modify(CCM_CBCDR, timings.div_ahb - 1);
+            Wait::Forever.handshake(CCM_CDHIPR)?;
+            IPG_PODF.modify(CCM_CBCDR, timings.div_ipg - 1);
+            Ok(())
+        },
+    ));
+    #[cfg(feature = "imxrt1060")]
+    {
+        ENABLE.modify(CCM_ANALOG_PLL_ARM, 0);
+        POWERDOWN.modify(CCM_ANALOG_PLL_ARM, 1);
+    }
+}
+
+/// Set the ARM clock frequency using PLL2 or its PFDs, returning the ARM and
+/// IPG clock speeds
+///
+/// The function selects the PLL2 output, and the AHB divider, that produce the
+/// fastest ARM clock that doesn't exceed `hz`, or [`MAX_FREQUENCY_HZ`]; on
+/// the i.MX RT 1010, that excludes the undivided 528MHz PLL2. It considers the
+/// PFDs at their current fractions; for example, the reset PFD fractions
+/// produce 528MHz, 396MHz, and 352MHz on the i.MX RT 1060. Unlike
+/// [`set_frequency`], the function never starts PLL1. On chips with PLL1, the
+/// function powers down PLL1 once the ARM clock no longer uses it.
+///
+/// Like [`set_frequency`], the function temporarily switches the ARM clock to
+/// the 24MHz clock, and any peripherals that use the IPG clock may not be aware
+/// of the new IPG clock frequency. If PLL2 is not running, the function returns
+/// the current frequencies without changing any clocks.
+///
+/// # Safety
+///
+/// Modifies CCM and CCM_ANALOG peripheral memory. Users should prefer the safer
+/// [`CCM::set_frequency_arm_pll2`](crate::CCM::set_frequency_arm_pll2) method.
+pub unsafe fn set_frequency_pll2(hz: u32) -> (ARMClock, IPGClock) {
+    let timings = match pll2_target(hz, MAX_FREQUENCY_HZ, pll2_sources()) {
+        Some(timings) => timings,
+        None => return frequency(),
+    };
+    switch_pll2(&timings);
+    (
+        ARMClock(timings.arm_hz),
+        IPGClock(timings.arm_hz / timings.div_ipg),
+    )
+}
+
+/// The largest ARM clock frequency that the datasheet supports
+#[cfg(feature = "imxrt1060")]
+pub const MAX_FREQUENCY_HZ: u32 = 600_000_000;
+/// The largest ARM clock frequency that the datasheet supports
+#[cfg(not(feature = "imxrt1060"))]
+pub const MAX_FREQUENCY_HZ: u32 = 500_000_000;
+
+/// The largest ARM clock frequency accepted when overclocking
+pub const OVERCLOCK_MAX_FREQUENCY_HZ: u32 = 912_000_000;
+
+/// The smallest ARM clock frequency that PLL1 and the ARM dividers can produce
+pub const PLL1_MIN_FREQUENCY_HZ: u32 = 16_200_000;
+
+/// The smallest ARM clock frequency, produced by dividing the 24MHz reference
+///
+/// This constant assumes a 24MHz reference. If you declare another reference
+/// frequency, use [`min_frequency`] instead.
+pub const MIN_FREQUENCY_HZ: u32 = crate::OSCILLATOR_FREQUENCY_HZ / 64;
+
+/// Returns the smallest ARM clock frequency, produced by dividing the
+/// reference frequency
+///
+/// This is [`MIN_FREQUENCY_HZ`] unless you declare another reference
+/// frequency with
+/// [`set_reference_frequency`](crate::xtalosc::XTALOSC::set_reference_frequency).
+#[inline(always)]
+pub fn min_frequency() -> u32 {
+    reference_frequency() / 64
+}
+
+/// The SysTick external reference clock frequency
+///
+/// The external reference is the 24MHz reference divided by 240. It doesn't
+/// change with the ARM clock. This constant assumes a 24MHz reference; if you
+/// declare another reference frequency, use [`ARMClock::systick`] instead.
+pub const SYSTICK_EXTERNAL_FREQUENCY_HZ: u32 = crate::OSCILLATOR_FREQUENCY_HZ / 240;
+
+/// Oscillator dividers for ARM clock frequencies below [`PLL1_MIN_FREQUENCY_HZ`]
+#[derive(PartialEq, Eq, Debug)]
+struct OscillatorTimings {
+    /// PERIPH_CLK2 divider (off-by-one; subtract 1 before writing)
+    div_periph_clk2: u32,
+    /// AHB divider (off-by-one; subtract 1 before writing)
+    div_ahb: u32,
+    arm_hz: u32,
+}
+
+impl OscillatorTimings {
+    /// Returns the dividers that produce the fastest ARM clock that doesn't
+    /// exceed `arm_hz`, or the slowest ARM clock if none are slow enough
+    fn target(arm_hz: u32) -> Self {
+        let reference_hz = reference_frequency();
+        let total = reference_hz.div_ceil(arm_hz.max(1)).clamp(1, 64);
+        let (div_periph_clk2, div_ahb) = (1..=8)
+            .map(|div_periph_clk2: u32| (div_periph_clk2, total.div_ceil(div_periph_clk2)))
+            .filter(|&(_, div_ahb)| div_ahb <= 8)
+            .min_by_key(|&(div_periph_clk2, div_ahb)| div_periph_clk2 * div_ahb)
+            .unwrap_or((8, 8));
+        OscillatorTimings {
+            div_periph_clk2,
+            div_ahb,
+            arm_hz: reference_hz / div_periph_clk2 / div_ahb,
+        }
+    }
+}
+
+impl Context<'_> {
+    /// Run the core from the divided oscillator
+    unsafe fn switch_oscillator(&self, timings: &OscillatorTimings) {
+        self.switch_core(None, timings.div_ahb, 1);
+        PERIPH_CLK2_PODF.modify(self.cbcdr, timings.div_periph_clk2 - 1);
+        self.wait_for_handshake();
+    }
+}
+
+/// Indicates that a requested ARM clock frequency is out of range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrequencyOutOfRange {
+    /// The requested frequency
+    pub requested: u32,
+    /// The smallest acceptable frequency
+    pub min: u32,
+    /// The largest acceptable frequency
+    pub max: u32,
+}
+
+/// The clock frequencies achieved by a frequency request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Achieved {
+    /// The ARM clock frequency
+    pub arm: ARMClock,
+    /// The IPG clock frequency
+    pub ipg: IPGClock,
+    /// The ARM clock frequency minus the requested frequency, in Hz
+    pub error_hz: i32,
+    /// The clock that drives AHB_CLK_ROOT
+    pub root: Root,
+}
+
+/// Permission to run the ARM core above [`MAX_FREQUENCY_HZ`]
+///
+/// Pass an `Overclock` to [`set_frequency_overclock`] to accept frequencies up
+/// to [`OVERCLOCK_MAX_FREQUENCY_HZ`].
+#[derive(Debug)]
+pub struct Overclock(());
+
+impl Overclock {
+    /// Opt into overclocking
+    ///
+    /// # Safety
+    ///
+    /// Overclocking runs the chip outside of its datasheet operating conditions.
+    /// Before you run above [`MAX_FREQUENCY_HZ`], you must raise VDD_SOC above the
+    /// overdrive operating point; frequencies near [`OVERCLOCK_MAX_FREQUENCY_HZ`]
+    /// typically need 1.4V or more, and may need additional cooling. The IPG clock
+    /// divider saturates, so the IPG clock also exceeds its 150MHz limit. You're
+    /// responsible for making sure that the chip runs reliably.
+    pub const unsafe fn new() -> Self {
+        Overclock(())
+    }
+}
+
+/// Set the ARM clock frequency, returning the achieved frequencies, or an
+/// error if `hz` is outside of [`min_frequency`] and [`MAX_FREQUENCY_HZ`]
+///
+/// Unlike [`set_frequency`], the function never clamps the request. Requests
+/// below [`PLL1_MIN_FREQUENCY_HZ`] run the ARM clock from the divided reference
+/// oscillator, leaving PLL1 untouched; all other requests use PLL1. Chips
+/// without PLL1, like the i.MX RT 1010, use the PLL2 output that produces the
+/// fastest clock that doesn't exceed the request, or the oscillator. The result
+/// reports the achieved ARM and IPG clock speeds, the ARM clock's error
+/// relative to `hz`, and the clock that drives AHB_CLK_ROOT. If the function
+/// returns an error, it does not change any clocks.
+///
+/// # Safety
+///
+/// See [`set_frequency`].
+pub unsafe fn try_set_frequency(hz: u32) -> Result<Achieved, FrequencyOutOfRange> {
+    set_frequency_limited(hz, MAX_FREQUENCY_HZ)
+}
+
+/// Set the ARM clock frequency up to [`OVERCLOCK_MAX_FREQUENCY_HZ`], returning
+/// the achieved frequencies
+///
+/// Returns an error if `hz` is outside of [`min_frequency`] and
+/// [`OVERCLOCK_MAX_FREQUENCY_HZ`]. If the function returns an error, it does
+/// not change any clocks. See [`Overclock`] for the requirements of
+/// overclocking.
+///
+/// # Safety
+///
+/// See [`set_frequency`].
+pub unsafe fn set_frequency_overclock(
+    hz: u32,
+    _: &Overclock,
+) -> Result<Achieved, FrequencyOutOfRange> {
+    set_frequency_limited(hz, OVERCLOCK_MAX_FREQUENCY_HZ)
+}
+
+unsafe fn set_frequency_limited(hz: u32, max: u32) -> Result<Achieved, FrequencyOutOfRange> {
+    check_frequency(hz, max)?;
+    #[cfg(feature = "imxrt1060")]
+    let pll = (hz >= PLL1_MIN_FREQUENCY_HZ).then(|| {
+        let (arm, ipg) = forever(set_frequency_(hz, max, Wait::Forever));
+        (arm, ipg, Root::PrePeriph(Source::Pll1))
+    });
+    // Without PLL1, use a PLL2 output that doesn't exceed the request
+    #[cfg(not(feature = "imxrt1060"))]
+    let pll = pll2_target(hz, max, pll2_sources())
+        .filter(|timings| timings.arm_hz <= hz)
+        .map(|timings| {
+            switch_pll2(&timings);
+            (
+                ARMClock(timings.arm_hz),
+                IPGClock(timings.arm_hz / timings.div_ipg),
+                Root::PrePeriph(timings.source),
+            )
+        });
+    let (arm, ipg, root) = match pll {
+        Some(clocks) => clocks,
+        None => {
+            let timings = OscillatorTimings::target(hz);
+            ARM_CONTEXT.switch_oscillator(&timings);
+            (
+                ARMClock(timings.arm_hz),
+                IPGClock(timings.arm_hz),
+                Root::PeriphClk2(PeriphClk2::Oscillator),
+            )
+        }
+    };
+    Ok(Achieved {
+        arm,
+        ipg,
+        error_hz: arm.0 as i32 - hz as i32,
+        root,
+    })
+}
+
+/// Returns an error if `hz` is outside of [`min_frequency`] and `max`
+fn check_frequency(hz: u32, max: u32) -> Result<(), FrequencyOutOfRange> {
+    let min = min_frequency();
+    if (min..=max).contains(&hz) {
+        Ok(())
+    } else {
+        Err(FrequencyOutOfRange {
+            requested: hz,
+            min,
+            max,
+        })
+    }
+}
+
 /// Returns the ARM and IPG clock frequencies
 ///
-/// The function assumes that the ARM clock runs on PLL1.
-/// The clock values may be incorrect until after the first call to
-/// [`set_frequency`].
+/// The frequencies respect the PERIPH_CLK and PRE_PERIPH_CLK multiplexers. When
+/// the ARM clock runs on PLL1, the clock values may be incorrect until after the
+/// first call to [`set_frequency`]. Use [`clocks`] to also learn the clock that
+/// drives AHB_CLK_ROOT.
 ///
 /// # Safety
 ///
 /// Reads multiple CCM registers without synchronization. It's safer to use
 /// [`CCM::frequency_arm`](crate::CCM::frequency_arm) to read the frequencies.
 pub unsafe fn frequency() -> (ARMClock, IPGClock) {
-    let timings = ARM_CONTEXT.timings();
-    (ARMClock(timings.arm_hz), IPGClock(timings.ipg_hz()))
+    let clocks = clocks();
+    (clocks.arm, clocks.ipg)
+}
+
+/// Returns the PLL1 frequency, or 0 if PLL1 is powered down
+#[cfg(feature = "imxrt1060")]
+pub(crate) fn pll1_frequency() -> u32 {
+    // Safety: reads of a valid register
+    unsafe {
+        if POWERDOWN.read(CCM_ANALOG_PLL_ARM) != 0 {
+            0
+        } else {
+            DIV_SEL.read(CCM_ANALOG_PLL_ARM) * pll1_step_hz()
+        }
+    }
+}
+
+/// Returns the ARM, AHB, and IPG dividers
+pub(crate) fn dividers() -> (u32, u32, u32) {
+    // Safety: reads of valid registers
+    let timings = unsafe { ARM_CONTEXT.timings() };
+    (timings.div_arm, timings.div_ahb, timings.div_ipg)
+}
+
+/// Returns the ARM and IPG clock frequencies, and the clock that drives
+/// AHB_CLK_ROOT
+///
+/// # Safety
+///
+/// Reads multiple CCM registers without synchronization. It's safer to use
+/// [`CCM::clocks_arm`](crate::CCM::clocks_arm) to read the clocks.
+pub unsafe fn clocks() -> Clocks {
+    ARM_CONTEXT.clocks()
+}
+
+/// Configure the PERIPH_CLK2 source and divider
+///
+/// `divider` is clamped between [1, 8]. PERIPH_CLK2 can run the core from PLL3
+/// or the oscillator, for example as a fallback while reconfiguring PRE_PERIPH_CLK.
+/// Use [`select_periph_clk2`] to switch AHB_CLK_ROOT to PERIPH_CLK2.
+///
+/// Returns an error, and makes no changes, if PERIPH_CLK2 currently drives
+/// AHB_CLK_ROOT; changing the PERIPH_CLK2 multiplexer could glitch the core clock.
+///
+/// # Safety
+///
+/// Modifies CCM peripheral memory. Users should prefer the safer
+/// [`CCM::set_periph_clk2`](crate::CCM::set_periph_clk2) method.
+pub unsafe fn set_periph_clk2(source: PeriphClk2, divider: u32) -> Result<(), PeriphClk2Selected> {
+    ARM_CONTEXT.set_periph_clk2(source, divider)
+}
+
+/// Returns the PERIPH_CLK2 source and divider
+#[inline(always)]
+pub fn periph_clk2() -> (PeriphClk2, u32) {
+    // Safety: atomic reads of valid registers
+    unsafe { ARM_CONTEXT.periph_clk2() }
+}
+
+/// Select PERIPH_CLK2 (`true`) or PRE_PERIPH_CLK (`false`) as AHB_CLK_ROOT,
+/// returning the new clocks
+///
+/// The PERIPH_CLK multiplexer is glitchless. Make sure that the selected clock is
+/// running, and that it produces an acceptable ARM clock frequency, before you
+/// select it. Peripherals that use the IPG clock may not be aware of the new
+/// clock frequency.
+///
+/// # Safety
+///
+/// Modifies CCM peripheral memory. Users should prefer the safer
+/// [`CCM::select_periph_clk2`](crate::CCM::select_periph_clk2) method.
+pub unsafe fn select_periph_clk2(selected: bool) -> Clocks {
+    ARM_CONTEXT.select_periph_clk2(selected);
+    clocks()
+}
+
+/// Select the ARM clock source, returning the new ARM and IPG clock speeds
+///
+/// Like [`set_frequency`], the function temporarily switches the ARM clock to
+/// the 24MHz clock while it changes the source. The ARM and AHB dividers are
+/// unchanged; you're responsible for making sure that the source, divided by
+/// those dividers, is an acceptable ARM clock frequency. The source must be
+/// enabled before you select it.
+///
+/// # Safety
+///
+/// Modifies CCM peripheral memory. Users should prefer the safer
+/// [`CCM::set_arm_clock_source`](crate::CCM::set_arm_clock_source) method.
+pub unsafe fn set_source(source: Source) -> (ARMClock, IPGClock) {
+    forever(on_ahb_clk_oscillator(source.field(), Wait::Forever, || {
+        Ok(())
+    }));
+    frequency()
+}
+
+/// Select the ARM clock source, giving up if any divider or mux handshake
+/// doesn't complete within `max_iterations` checks
+///
+/// Otherwise, the function behaves like [`set_source`]. If a handshake times
+/// out, the function returns immediately, and the ARM clock may still run from
+/// the 24MHz oscillator.
+///
+/// # Safety
+///
+/// See [`set_source`].
+pub unsafe fn set_source_bounded(
+    source: Source,
+    max_iterations: u32,
+) -> Result<(ARMClock, IPGClock), HandshakeTimeout> {
+    on_ahb_clk_oscillator(source.field(), Wait::Iterations(max_iterations), || Ok(()))?;
+    Ok(frequency())
+}
+
+/// The clock state saved by [`enter_low_power_run`]
+///
+/// Pass the state to [`exit_low_power_run`] to return to the previous ARM
+/// clock frequency.
+#[must_use]
+#[derive(Debug)]
+pub struct LowPowerRun {
+    cbcdr: u32,
+    cbcmr: u32,
+    #[cfg(feature = "imxrt1060")]
+    pll_arm: u32,
+}
+
+/// Run the ARM core from the 24MHz oscillator, returning the state necessary
+/// to exit low-power run
+///
+/// The function switches AHB_CLK_ROOT to the oscillator using the glitchless
+/// peripheral clock multiplexer, then powers down PLL1. The AHB and IPG
+/// dividers are unchanged, so the ARM clock runs at 24MHz divided by the AHB
+/// divider. Use [`frequency`] to query the new ARM and IPG clock frequencies.
+///
+/// Peripherals that use the IPG clock may not be aware of the new clock
+/// frequency. You're responsible for updating any peripherals to reference
+/// the new clock speed.
+///
+/// # Safety
+///
+/// Modifies CCM and CCM_ANALOG peripheral memory. Users should prefer the
+/// safer [`CCM::enter_low_power_run`](crate::CCM::enter_low_power_run) method.
+pub unsafe fn enter_low_power_run() -> LowPowerRun {
+    ARM_CONTEXT.enter_low_power_run()
+}
+
+/// Return to the ARM clock frequency that preceded [`enter_low_power_run`],
+/// returning the ARM and IPG clock frequencies
+///
+/// If PLL1 was powered before low-power run, the function restarts PLL1 and
+/// waits for it to lock before switching AHB_CLK_ROOT away from the oscillator.
+///
+/// # Safety
+///
+/// Modifies CCM and CCM_ANALOG peripheral memory. Users should prefer the
+/// safer [`CCM::exit_low_power_run`](crate::CCM::exit_low_power_run) method.
+pub unsafe fn exit_low_power_run(run: LowPowerRun) -> (ARMClock, IPGClock) {
+    ARM_CONTEXT.exit_low_power_run(run);
+    frequency()
+}
+
+/// Returns the ARM clock source
+#[inline(always)]
+pub fn source() -> Source {
+    // Safety: atomic read of a valid register
+    unsafe { ARM_CONTEXT.source() }
+}
+
+const CCM_CLPCR: *mut u32 = 0x400F_C054 as _;
+
+/// Gate (`true`) or don't gate (`false`) the ARM core clock during `WFI`
+///
+/// When gated, the ARM core clock stops when the core executes `WFI` in WAIT or
+/// STOP mode, and restarts on wake. The core clock never stops in RUN mode;
+/// see [`lowpower`](crate::lowpower) to select the low-power mode. Gating the
+/// clock unmasks the core's `WFI` signal; ungating the clock masks the signal
+/// again, if gating unmasked it.
+///
+/// # Safety
+///
+/// Modifies CCM peripheral memory. Users should prefer the safer
+/// [`LowPower::set_arm_clock_gated`](crate::lowpower::LowPower::set_arm_clock_gated)
+/// method.
+#[inline(always)]
+pub unsafe fn set_clock_gated_in_wfi(gated: bool) {
+    use crate::lowpower::{set_arm_clock_gated_in_wfi_, WFI_UNMASKED};
+    set_arm_clock_gated_in_wfi_(CCM_CLPCR, &WFI_UNMASKED, gated)
+}
+
+/// Returns `true` if the ARM core clock is gated during `WFI`
+#[inline(always)]
+pub fn clock_gated_in_wfi() -> bool {
+    // Safety: atomic read of a valid register
+    unsafe { crate::lowpower::arm_clock_gated_in_wfi_(CCM_CLPCR) }
 }
 
 #[cfg(test)]
 pub mod tests {
-    use super::{Context, Timings};
+    use super::{
+        check_frequency, voltage, ARMClock, Context, Millivolts, OscillatorTimings, PeriphClk2,
+        PeriphClk2Selected, Root, Source, SourceTimings, SysTickSource, Timings,
+    };
 
     pub(crate) struct TestContext {
         pub caccr: u32,
         pub cbcdr: u32,
+        pub cbcmr: u32,
+        pub cdhipr: u32,
         pub pll_arm: u32,
     }
 
+    /// Source frequencies with PLL2 and PLL3 PFDs at their reset values
+    fn source_hz(source: Source) -> u32 {
+        match source {
+            Source::Pll2 => 528_000_000,
+            #[cfg(feature = "imxrt1060")]
+            Source::Pll2Pfd2 => 396_000_000,
+            #[cfg(feature = "imxrt1060")]
+            Source::Pll2Pfd0 => 352_000_000,
+            #[cfg(feature = "imxrt1060")]
+            Source::Pll1 => unreachable!(),
+            #[cfg(not(feature = "imxrt1060"))]
+            Source::Pll3Pfd3 => 454_736_842,
+            #[cfg(not(feature = "imxrt1060"))]
+            Source::Pll2Pfd3 => 297_000_000,
+            #[cfg(not(feature = "imxrt1060"))]
+            Source::Pll6 => 500_000_000,
+        }
+    }
+
+    fn periph_clk2_hz(source: PeriphClk2) -> u32 {
+        match source {
+            PeriphClk2::Pll3 => 480_000_000,
+            PeriphClk2::Oscillator | PeriphClk2::Pll2Bypass => 24_000_000,
+        }
+    }
+
     impl TestContext {
         pub const fn new() -> Self {
             TestContext {
                 caccr: 0,
                 cbcdr: 0,
+                cbcmr: 0,
+                cdhipr: 0,
                 pll_arm: 0,
             }
         }
@@ -298,7 +1429,11 @@ pub mod tests {
             Context {
                 caccr: &mut self.caccr,
                 cbcdr: &mut self.cbcdr,
+                cbcmr: &mut self.cbcmr,
+                cdhipr: &self.cdhipr,
                 pll_arm: &mut self.pll_arm,
+                source_hz,
+                periph_clk2_hz,
                 _scope: core::marker::PhantomData,
             }
         }
@@ -307,6 +1442,8 @@ pub mod tests {
                 caccr: timings.div_arm.saturating_sub(1),
                 cbcdr: timings.div_ahb.saturating_sub(1) << 10
                     | timings.div_ipg.saturating_sub(1) << 8,
+                cbcmr: super::PRE_PERIPH_CLK_PLL1 << 18,
+                cdhipr: 0,
                 pll_arm: timings.pll_arm_div_sel,
             }
         }
@@ -331,4 +1468,303 @@ pub mod tests {
         let actual = unsafe { ctx.context().timings() };
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn arm_source_round_trip() {
+        for field in 0..4 {
+            assert_eq!(Source::from_field(field).field(), field);
+        }
+    }
+
+    #[test]
+    fn arm_source_frequency() {
+        let mut ctx = TestContext::new();
+        ctx.cbcdr = 1 << 10 | 1 << 8; // AHB and IPG divide by 2
+        ctx.cbcmr = Source::Pll2.field() << 18;
+        let clocks = unsafe { ctx.context().clocks() };
+        let (arm, ipg) = (clocks.arm, clocks.ipg);
+        assert_eq!(clocks.root, Root::PrePeriph(Source::Pll2));
+        assert_eq!(arm.0, 264_000_000);
+        assert_eq!(ipg.0, 132_000_000);
+
+        #[cfg(feature = "imxrt1060")]
+        {
+            ctx.cbcmr = Source::Pll2Pfd2.field() << 18;
+            let clocks = unsafe { ctx.context().clocks() };
+            let (arm, ipg) = (clocks.arm, clocks.ipg);
+            assert_eq!(arm.0, 198_000_000);
+            assert_eq!(ipg.0, 99_000_000);
+        }
+    }
+
+    #[test]
+    fn arm_low_power_run() {
+        let timings = Timings::target(600_000_000);
+        let mut ctx = TestContext::from_timings(&timings);
+        ctx.cbcdr |= 0b010 << 27; // PERIPH_CLK2 divide by 3
+        ctx.pll_arm |= 1 << 13 | 1 << 31; // Enabled, locked
+        let (cbcdr, cbcmr, pll_arm) = (ctx.cbcdr, ctx.cbcmr, ctx.pll_arm);
+
+        let run = unsafe { ctx.context().enter_low_power_run() };
+        assert_eq!(ctx.cbcdr & (1 << 25 | 0b111 << 27), 1 << 25);
+        assert_eq!(ctx.cbcmr & 0b11 << 12, 1 << 12);
+        #[cfg(feature = "imxrt1060")]
+        assert_eq!(ctx.pll_arm & (1 << 12 | 1 << 13), 1 << 12);
+        let clocks = unsafe { ctx.context().clocks() };
+        let (arm, ipg) = (clocks.arm, clocks.ipg);
+        assert_eq!(clocks.root, Root::PeriphClk2(PeriphClk2::Oscillator));
+        assert_eq!(arm.0, 24_000_000);
+        assert_eq!(ipg.0, 24_000_000 / timings.div_ipg);
+
+        unsafe { ctx.context().exit_low_power_run(run) };
+        assert_eq!(ctx.cbcdr, cbcdr);
+        assert_eq!(ctx.cbcmr, cbcmr);
+        assert_eq!(ctx.pll_arm, pll_arm);
+        #[cfg(feature = "imxrt1060")]
+        assert_eq!(unsafe { ctx.context().clocks() }.arm.0, 600_000_000);
+    }
+
+    #[test]
+    fn arm_pll2_target() {
+        let sources = [
+            (Source::Pll2, 528_000_000),
+            (Source::Pll2, 396_000_000),
+            (Source::Pll2, 352_000_000),
+        ];
+        let target = |hz| SourceTimings::target(hz, sources.iter().copied()).unwrap();
+
+        let timings = target(600_000_000);
+        assert_eq!((timings.arm_hz, timings.div_ahb), (528_000_000, 1));
+        assert_eq!(timings.div_ipg, 4);
+
+        let timings = target(400_000_000);
+        assert_eq!((timings.arm_hz, timings.div_ahb), (396_000_000, 1));
+        assert_eq!(timings.div_ipg, 3);
+
+        let timings = target(264_000_000);
+        assert_eq!((timings.arm_hz, timings.div_ahb), (264_000_000, 2));
+        assert_eq!(timings.div_ipg, 2);
+
+        let timings = target(1_000_000);
+        assert_eq!((timings.arm_hz, timings.div_ahb), (44_000_000, 8));
+        assert_eq!(timings.div_ipg, 1);
+
+        assert_eq!(
+            SourceTimings::target(600_000_000, [(Source::Pll2, 0)].iter().copied()),
+            None
+        );
+    }
+
+    #[cfg(not(feature = "imxrt1060"))]
+    #[test]
+    fn arm_pll2_target_1010() {
+        use super::{pll2_target, MAX_FREQUENCY_HZ};
+        let max = MAX_FREQUENCY_HZ;
+
+        let sources = [(Source::Pll2, 528_000_000), (Source::Pll2Pfd3, 396_000_000)];
+        let target = |hz| pll2_target(hz, max, sources.iter().copied()).unwrap();
+
+        // 528MHz exceeds the 1010's 500MHz maximum
+        let timings = target(600_000_000);
+        assert_eq!(timings.source, Source::Pll2Pfd3);
+        assert_eq!((timings.arm_hz, timings.div_ahb), (396_000_000, 1));
+        assert!(target(u32::MAX).arm_hz <= MAX_FREQUENCY_HZ);
+
+        let timings = target(264_000_000);
+        assert_eq!((timings.arm_hz, timings.div_ahb), (264_000_000, 2));
+    }
+
+    #[test]
+    fn arm_voltage() {
+        assert_eq!(voltage(24_000_000), Millivolts(1_150));
+        assert_eq!(voltage(396_000_000), Millivolts(1_150));
+        #[cfg(feature = "imxrt1060")]
+        assert_eq!(voltage(528_000_000), Millivolts(1_150));
+        assert_eq!(voltage(600_000_000), Millivolts(1_250));
+    }
+
+    #[test]
+    fn arm_overclock_target() {
+        let timings = Timings::target(super::OVERCLOCK_MAX_FREQUENCY_HZ);
+        assert_eq!(timings.arm_hz, 912_000_000);
+        assert_eq!((timings.div_arm, timings.div_ahb), (1, 1));
+        assert_eq!(timings.pll_arm_div_sel, 76);
+    }
+
+    #[test]
+    fn arm_frequency_range() {
+        let timings = Timings::target(super::PLL1_MIN_FREQUENCY_HZ);
+        assert_eq!(timings.arm_hz, super::PLL1_MIN_FREQUENCY_HZ);
+
+        assert_eq!(super::min_frequency(), super::MIN_FREQUENCY_HZ);
+        assert!(check_frequency(super::min_frequency(), 600_000_000).is_ok());
+        assert!(check_frequency(600_000_000, 600_000_000).is_ok());
+        let err = check_frequency(600_000_001, 600_000_000).unwrap_err();
+        assert_eq!(err.requested, 600_000_001);
+        assert_eq!(err.max, 600_000_000);
+        let err = check_frequency(super::min_frequency() - 1, 600_000_000).unwrap_err();
+        assert_eq!(err.min, super::min_frequency());
+    }
+
+    #[test]
+    fn arm_periph_clk2_frequency() {
+        let mut ctx = TestContext::new();
+        ctx.cbcdr = 1 << 25 | 0b011 << 27 | 1 << 10; // PERIPH_CLK2 / 4, AHB / 2
+        ctx.cbcmr = 0; // PLL3
+        let clocks = unsafe { ctx.context().clocks() };
+        assert_eq!(clocks.root, Root::PeriphClk2(PeriphClk2::Pll3));
+        assert_eq!(clocks.arm.0, 60_000_000);
+        assert_eq!(clocks.ipg.0, 60_000_000);
+    }
+
+    #[test]
+    fn arm_periph_clk2_configure() {
+        let mut ctx = TestContext::new();
+        unsafe {
+            let context = ctx.context();
+            context.set_periph_clk2(PeriphClk2::Pll3, 2).unwrap();
+            assert_eq!(context.periph_clk2(), (PeriphClk2::Pll3, 2));
+            context.set_periph_clk2(PeriphClk2::Oscillator, 9).unwrap();
+            assert_eq!(context.periph_clk2(), (PeriphClk2::Oscillator, 8));
+
+            context.select_periph_clk2(true);
+            assert_eq!(
+                context.clocks().root,
+                Root::PeriphClk2(PeriphClk2::Oscillator)
+            );
+            assert_eq!(
+                context.set_periph_clk2(PeriphClk2::Pll3, 1),
+                Err(PeriphClk2Selected)
+            );
+            assert_eq!(context.periph_clk2(), (PeriphClk2::Oscillator, 8));
+
+            context.select_periph_clk2(false);
+            assert_eq!(context.clocks().root, Root::PrePeriph(Source::Pll2));
+        }
+    }
+
+    #[test]
+    fn arm_switch_core() {
+        let mut ctx = TestContext::new();
+        unsafe {
+            let context = ctx.context();
+            context.switch_core(Some(Source::Pll2), 4, 1);
+            let clocks = context.clocks();
+            assert_eq!(clocks.root, Root::PrePeriph(Source::Pll2));
+            assert_eq!((clocks.arm.0, clocks.ipg.0), (132_000_000, 132_000_000));
+
+            context.switch_core(None, 1, 1);
+            let clocks = context.clocks();
+            assert_eq!(clocks.root, Root::PeriphClk2(PeriphClk2::Oscillator));
+            assert_eq!((clocks.arm.0, clocks.ipg.0), (24_000_000, 24_000_000));
+
+            context.switch_core(Some(Source::Pll2), 1, 4);
+            let clocks = context.clocks();
+            assert_eq!(clocks.root, Root::PrePeriph(Source::Pll2));
+            assert_eq!((clocks.arm.0, clocks.ipg.0), (528_000_000, 132_000_000));
+        }
+    }
+
+    #[test]
+    fn arm_switch_core_from_periph_clk2() {
+        let mut ctx = TestContext::new();
+        // PERIPH_CLK2 runs the core from PLL3, divided by 2
+        ctx.cbcdr = 1 << 25 | 0b001 << 27;
+        unsafe {
+            let context = ctx.context();
+            assert_eq!(context.clocks().arm.0, 240_000_000);
+
+            context.switch_core(None, 1, 1);
+            let clocks = context.clocks();
+            assert_eq!(clocks.root, Root::PeriphClk2(PeriphClk2::Oscillator));
+            assert_eq!((clocks.arm.0, clocks.ipg.0), (24_000_000, 24_000_000));
+        }
+        assert_eq!(ctx.cbcdr & 0b111 << 27, 0);
+    }
+
+    #[test]
+    fn arm_low_power_run_from_periph_clk2() {
+        let mut ctx = TestContext::new();
+        // PERIPH_CLK2 runs the core from PLL3, divided by 2; AHB divides by 2
+        ctx.cbcdr = 1 << 25 | 0b001 << 27 | 0b001 << 10;
+        ctx.pll_arm = 1 << 12; // Powered down
+        let (cbcdr, cbcmr) = (ctx.cbcdr, ctx.cbcmr);
+
+        let run = unsafe { ctx.context().enter_low_power_run() };
+        let clocks = unsafe { ctx.context().clocks() };
+        assert_eq!(clocks.root, Root::PeriphClk2(PeriphClk2::Oscillator));
+        assert_eq!(clocks.arm.0, 12_000_000);
+
+        unsafe { ctx.context().exit_low_power_run(run) };
+        assert_eq!((ctx.cbcdr, ctx.cbcmr), (cbcdr, cbcmr));
+        let clocks = unsafe { ctx.context().clocks() };
+        assert_eq!(clocks.root, Root::PeriphClk2(PeriphClk2::Pll3));
+        assert_eq!(clocks.arm.0, 120_000_000);
+    }
+
+    #[test]
+    fn arm_oscillator_target() {
+        let target = |hz| {
+            let timings = OscillatorTimings::target(hz);
+            (timings.div_periph_clk2 * timings.div_ahb, timings.arm_hz)
+        };
+        assert_eq!(target(24_000_000), (1, 24_000_000));
+        assert_eq!(target(16_000_000), (2, 12_000_000));
+        assert_eq!(target(1_000_000), (24, 1_000_000));
+        assert_eq!(target(900_000), (28, 857_142));
+        assert_eq!(target(super::MIN_FREQUENCY_HZ), (64, 375_000));
+        assert_eq!(target(1), (64, 375_000));
+
+        let mut ctx = TestContext::new();
+        unsafe {
+            let context = ctx.context();
+            context.switch_oscillator(&OscillatorTimings::target(1_000_000));
+            let clocks = context.clocks();
+            assert_eq!(clocks.root, Root::PeriphClk2(PeriphClk2::Oscillator));
+            assert_eq!(clocks.arm.0, 1_000_000);
+            assert_eq!(clocks.ipg.0, 1_000_000);
+        }
+    }
+
+    #[test]
+    fn systick_frequency() {
+        let arm = ARMClock(600_000_000);
+        assert_eq!(arm.systick(SysTickSource::Core), 600_000_000);
+        assert_eq!(arm.systick(SysTickSource::External), 100_000);
+    }
+
+    #[test]
+    fn imxrt1060_timings_with_reference() {
+        for hz in [24_000_000, 150_000_000, 600_000_000, 1_000_000_000] {
+            assert_eq!(Timings::with_reference(hz, 24_000_000), Timings::target(hz));
+        }
+        // A faster reference takes bigger PLL steps
+        let timings = Timings::with_reference(600_000_000, 48_000_000);
+        assert_eq!(timings.pll_arm_div_sel(), 54);
+        assert_eq!(timings.arm_hz(), 648_000_000);
+    }
+
+    #[test]
+    fn timings_with_reference_large_frequencies() {
+        let timings = Timings::with_reference(u32::MAX, 24_000_000);
+        assert_eq!(timings.pll_arm_div_sel(), 108);
+        assert_eq!(timings.arm_hz(), 1_296_000_000);
+
+        // The PLL can't run at 54 steps of a huge reference; saturate
+        let timings = Timings::with_reference(u32::MAX, u32::MAX);
+        assert_eq!(timings.pll_arm_div_sel(), 54);
+        assert_eq!(timings.arm_hz(), u32::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "reference frequency is too small")]
+    fn timings_with_reference_zero() {
+        Timings::with_reference(600_000_000, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "reference frequency is too small")]
+    fn timings_with_reference_one() {
+        Timings::with_reference(600_000_000, 1);
+    }
 }
@@ -0,0 +1,384 @@
+//! CCGR clock gate fields, by chip
+//!
+//! Each table lists every clock gate field that the reference manual names
+//! for a chip. Fields that aren't in a table are reserved. Use the tables to
+//! check a [`ClockGateLocation`](crate::ClockGateLocation) against the
+//! reference manual, or to find the gate for a peripheral that this crate
+//! doesn't model:
+//!
+//! ```
+//! use imxrt_ccm::{ccgr, chip::Chip};
+//!
+//! let trng = ccgr::find(Chip::Imxrt1060, "TRNG").unwrap();
+//! assert_eq!((trng.ccgr, trng.cg), (6, 6));
+//! ```
+//!
+//! # References
+//!
+//! i.MX RT 1010 reference manual, i.MX RT 1060 reference manual
+//! - Chapter 14: Clock Control Module (CCM)
+//!   - CCM Memory Map/Register Definition (CCGR0 through CCGR7)
+
+use crate::chip::Chip;
+
+/// The clock gate settings that a field allows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Settings {
+    /// Any [`ClockGate`](crate::ClockGate) setting
+    Any,
+    /// Any setting except [`ClockGate::Off`](crate::ClockGate::Off)
+    ///
+    /// Turning off the gate will likely hang the system, or stop a watchdog.
+    /// See [`ESSENTIAL_CLOCK_GATES`](crate::ESSENTIAL_CLOCK_GATES).
+    Essential,
+}
+
+/// A CCGR clock gate field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GateField {
+    /// CCGR register number
+    ///
+    /// `3` in `CCM_CCGR3[CG7]`
+    pub ccgr: usize,
+    /// Clock gate field number
+    ///
+    /// `7` in `CCM_CCGR3[CG7]`
+    pub cg: usize,
+    /// The reference manual's name for the clock, like `"LPUART1"`
+    pub name: &'static str,
+    /// The allowed settings
+    pub settings: Settings,
+}
+
+const fn field(ccgr: usize, cg: usize, name: &'static str) -> GateField {
+    GateField {
+        ccgr,
+        cg,
+        name,
+        settings: Settings::Any,
+    }
+}
+
+const fn essential(ccgr: usize, cg: usize, name: &'static str) -> GateField {
+    GateField {
+        settings: Settings::Essential,
+        ..field(ccgr, cg, name)
+    }
+}
+
+/// The i.MX RT 1010 clock gate fields
+///
+/// The 1010 doesn't have CCGR7.
+pub const IMXRT1010: &[GateField] = &[
+    essential(0, 0, "AIPS_TZ1"),
+    essential(0, 1, "AIPS_TZ2"),
+    field(0, 2, "MQS"),
+    essential(0, 3, "FLEXSPI_EXSC"),
+    essential(0, 4, "SIM_M_CLK_R"),
+    field(0, 5, "DCP"),
+    field(0, 6, "LPUART3"),
+    field(0, 11, "TRACE"),
+    field(0, 12, "GPT2_BUS"),
+    field(0, 13, "GPT2_SERIAL"),
+    field(0, 14, "LPUART2"),
+    field(0, 15, "GPIO2"),
+    field(1, 0, "LPSPI1"),
+    field(1, 1, "LPSPI2"),
+    field(1, 6, "PIT"),
+    field(1, 8, "ADC1"),
+    field(1, 10, "GPT1_BUS"),
+    field(1, 11, "GPT1_SERIAL"),
+    field(1, 12, "LPUART4"),
+    field(1, 13, "GPIO1"),
+    field(1, 14, "CSU"),
+    field(1, 15, "GPIO5"),
+    essential(2, 0, "OCRAM_EXSC"),
+    field(2, 2, "IOMUXC_SNVS"),
+    field(2, 3, "LPI2C1"),
+    field(2, 4, "LPI2C2"),
+    field(2, 6, "OCOTP"),
+    field(2, 11, "XBAR1"),
+    field(3, 4, "AOI1"),
+    field(3, 7, "EWM"),
+    essential(3, 8, "WDOG1"),
+    essential(3, 9, "FLEXRAM"),
+    essential(3, 14, "OCRAM"),
+    field(3, 15, "IOMUXC_SNVS_GPR"),
+    essential(4, 0, "SIM_M7_CLK_R"),
+    field(4, 1, "IOMUXC"),
+    field(4, 2, "IOMUXC_GPR"),
+    essential(4, 4, "SIM_M7"),
+    essential(4, 6, "SIM_M"),
+    essential(4, 7, "SIM_EMS"),
+    field(4, 8, "PWM1"),
+    field(5, 0, "ROM"),
+    field(5, 1, "FLEXIO1"),
+    essential(5, 2, "WDOG3"),
+    field(5, 3, "DMA"),
+    field(5, 4, "KPP"),
+    essential(5, 5, "WDOG2"),
+    field(5, 7, "SPDIF"),
+    field(5, 9, "SAI1"),
+    field(5, 11, "SAI3"),
+    field(5, 12, "LPUART1"),
+    field(5, 14, "SNVS_HP"),
+    field(5, 15, "SNVS_LP"),
+    field(6, 0, "USBOH3"),
+    field(6, 3, "DCDC"),
+    essential(6, 5, "FLEXSPI"),
+    field(6, 6, "TRNG"),
+    essential(6, 10, "SIM_PER"),
+    essential(6, 11, "ANADIG"),
+];
+
+/// The i.MX RT 1060 clock gate fields
+pub const IMXRT1060: &[GateField] = &[
+    essential(0, 0, "AIPS_TZ1"),
+    essential(0, 1, "AIPS_TZ2"),
+    field(0, 2, "MQS"),
+    essential(0, 3, "FLEXSPI_EXSC"),
+    essential(0, 4, "SIM_M_CLK_R"),
+    field(0, 5, "DCP"),
+    field(0, 6, "LPUART3"),
+    field(0, 7, "CAN1"),
+    field(0, 8, "CAN1_SERIAL"),
+    field(0, 9, "CAN2"),
+    field(0, 10, "CAN2_SERIAL"),
+    field(0, 11, "TRACE"),
+    field(0, 12, "GPT2_BUS"),
+    field(0, 13, "GPT2_SERIAL"),
+    field(0, 14, "LPUART2"),
+    field(0, 15, "GPIO2"),
+    field(1, 0, "LPSPI1"),
+    field(1, 1, "LPSPI2"),
+    field(1, 2, "LPSPI3"),
+    field(1, 3, "LPSPI4"),
+    field(1, 4, "ADC2"),
+    field(1, 5, "ENET"),
+    field(1, 6, "PIT"),
+    field(1, 7, "AOI2"),
+    field(1, 8, "ADC1"),
+    field(1, 9, "SEMC_EXSC"),
+    field(1, 10, "GPT1_BUS"),
+    field(1, 11, "GPT1_SERIAL"),
+    field(1, 12, "LPUART4"),
+    field(1, 13, "GPIO1"),
+    field(1, 14, "CSU"),
+    field(1, 15, "GPIO5"),
+    essential(2, 0, "OCRAM_EXSC"),
+    field(2, 1, "CSI"),
+    field(2, 2, "IOMUXC_SNVS"),
+    field(2, 3, "LPI2C1"),
+    field(2, 4, "LPI2C2"),
+    field(2, 5, "LPI2C3"),
+    field(2, 6, "OCOTP"),
+    field(2, 7, "XBAR3"),
+    field(2, 8, "IPMUX1"),
+    field(2, 9, "IPMUX2"),
+    field(2, 10, "IPMUX3"),
+    field(2, 11, "XBAR1"),
+    field(2, 12, "XBAR2"),
+    field(2, 13, "GPIO3"),
+    field(2, 14, "LCD"),
+    field(2, 15, "PXP"),
+    field(3, 0, "FLEXIO2"),
+    field(3, 1, "LPUART5"),
+    essential(3, 2, "SEMC"),
+    field(3, 3, "LPUART6"),
+    field(3, 4, "AOI1"),
+    field(3, 5, "LCDIF_PIX"),
+    field(3, 6, "GPIO4"),
+    field(3, 7, "EWM"),
+    essential(3, 8, "WDOG1"),
+    essential(3, 9, "FLEXRAM"),
+    field(3, 10, "ACMP1"),
+    field(3, 11, "ACMP2"),
+    field(3, 12, "ACMP3"),
+    field(3, 13, "ACMP4"),
+    essential(3, 14, "OCRAM"),
+    field(3, 15, "IOMUXC_SNVS_GPR"),
+    essential(4, 0, "SIM_M7_CLK_R"),
+    field(4, 1, "IOMUXC"),
+    field(4, 2, "IOMUXC_GPR"),
+    field(4, 3, "BEE"),
+    essential(4, 4, "SIM_M7"),
+    field(4, 5, "TSC_DIG"),
+    essential(4, 6, "SIM_M"),
+    essential(4, 7, "SIM_EMS"),
+    field(4, 8, "PWM1"),
+    field(4, 9, "PWM2"),
+    field(4, 10, "PWM3"),
+    field(4, 11, "PWM4"),
+    field(4, 12, "ENC1"),
+    field(4, 13, "ENC2"),
+    field(4, 14, "ENC3"),
+    field(4, 15, "ENC4"),
+    field(5, 0, "ROM"),
+    field(5, 1, "FLEXIO1"),
+    essential(5, 2, "WDOG3"),
+    field(5, 3, "DMA"),
+    field(5, 4, "KPP"),
+    essential(5, 5, "WDOG2"),
+    essential(5, 6, "AIPS_TZ4"),
+    field(5, 7, "SPDIF"),
+    essential(5, 8, "SIM_MAIN"),
+    field(5, 9, "SAI1"),
+    field(5, 10, "SAI2"),
+    field(5, 11, "SAI3"),
+    field(5, 12, "LPUART1"),
+    field(5, 13, "LPUART7"),
+    field(5, 14, "SNVS_HP"),
+    field(5, 15, "SNVS_LP"),
+    field(6, 0, "USBOH3"),
+    field(6, 1, "USDHC1"),
+    field(6, 2, "USDHC2"),
+    field(6, 3, "DCDC"),
+    field(6, 4, "IPMUX4"),
+    essential(6, 5, "FLEXSPI"),
+    field(6, 6, "TRNG"),
+    field(6, 7, "LPUART8"),
+    field(6, 8, "TIMER4"),
+    essential(6, 9, "AIPS_TZ3"),
+    essential(6, 10, "SIM_PER"),
+    essential(6, 11, "ANADIG"),
+    field(6, 12, "LPI2C4"),
+    field(6, 13, "TIMER1"),
+    field(6, 14, "TIMER2"),
+    field(6, 15, "TIMER3"),
+    field(7, 0, "ENET2"),
+    field(7, 1, "FLEXSPI2"),
+    field(7, 2, "AXBS_L"),
+    field(7, 3, "CAN3"),
+    field(7, 4, "CAN3_SERIAL"),
+    essential(7, 5, "AIPS_LITE"),
+    field(7, 6, "FLEXIO3"),
+];
+
+/// Returns the clock gate fields for `chip`
+pub const fn fields(chip: Chip) -> &'static [GateField] {
+    match chip {
+        Chip::Imxrt1010 => IMXRT1010,
+        Chip::Imxrt1060 => IMXRT1060,
+    }
+}
+
+/// Returns the clock gate field named `name`, or `None` if `chip` doesn't
+/// have the field
+pub fn find(chip: Chip, name: &str) -> Option<&'static GateField> {
+    fields(chip).iter().find(|field| field.name == name)
+}
+
+/// Returns the clock gate field at `CCGR<ccgr>[CG<cg>]`, or `None` if the
+/// field is reserved on `chip`
+pub fn at(chip: Chip, ccgr: usize, cg: usize) -> Option<&'static GateField> {
+    fields(chip)
+        .iter()
+        .find(|field| field.ccgr == ccgr && field.cg == cg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{at, fields, Settings};
+    use crate::{
+        chip::Chip,
+        i2c::I2C,
+        perclock::{GPT, PIT},
+        spi::SPI,
+        uart::UART,
+        usb::USB,
+        ClockGateLocation, ClockGateLocator, ADC, DCDC, DMA, ESSENTIAL_CLOCK_GATES, PWM,
+    };
+
+    const BOTH: &[Chip] = &[Chip::Imxrt1010, Chip::Imxrt1060];
+    const IMXRT1060: &[Chip] = &[Chip::Imxrt1060];
+
+    /// Every modeled instance, its clock gate names, and the chips that have it
+    fn instances() -> Vec<(ClockGateLocation, &'static [&'static str], &'static [Chip])> {
+        vec![
+            (UART::UART1.location(), &["LPUART1"], BOTH),
+            (UART::UART2.location(), &["LPUART2"], BOTH),
+            (UART::UART3.location(), &["LPUART3"], BOTH),
+            (UART::UART4.location(), &["LPUART4"], BOTH),
+            (UART::UART5.location(), &["LPUART5"], IMXRT1060),
+            (UART::UART6.location(), &["LPUART6"], IMXRT1060),
+            (UART::UART7.location(), &["LPUART7"], IMXRT1060),
+            (UART::UART8.location(), &["LPUART8"], IMXRT1060),
+            (SPI::SPI1.location(), &["LPSPI1"], BOTH),
+            (SPI::SPI2.location(), &["LPSPI2"], BOTH),
+            (SPI::SPI3.location(), &["LPSPI3"], IMXRT1060),
+            (SPI::SPI4.location(), &["LPSPI4"], IMXRT1060),
+            (I2C::I2C1.location(), &["LPI2C1"], BOTH),
+            (I2C::I2C2.location(), &["LPI2C2"], BOTH),
+            (I2C::I2C3.location(), &["LPI2C3"], IMXRT1060),
+            (I2C::I2C4.location(), &["LPI2C4"], IMXRT1060),
+            (GPT::GPT1.location(), &["GPT1_BUS", "GPT1_SERIAL"], BOTH),
+            (GPT::GPT2.location(), &["GPT2_BUS", "GPT2_SERIAL"], BOTH),
+            (PIT.location(), &["PIT"], BOTH),
+            (ADC::ADC1.location(), &["ADC1"], BOTH),
+            (ADC::ADC2.location(), &["ADC2"], IMXRT1060),
+            (PWM::PWM1.location(), &["PWM1"], BOTH),
+            (PWM::PWM2.location(), &["PWM2"], IMXRT1060),
+            (PWM::PWM3.location(), &["PWM3"], IMXRT1060),
+            (PWM::PWM4.location(), &["PWM4"], IMXRT1060),
+            (DCDC.location(), &["DCDC"], BOTH),
+            (DMA.location(), &["DMA"], BOTH),
+            (USB::USB1.location(), &["USBOH3"], BOTH),
+            (USB::USB2.location(), &["USBOH3"], IMXRT1060),
+        ]
+    }
+
+    #[test]
+    fn ccgr_tables() {
+        for (chip, ccgr_count) in [(Chip::Imxrt1010, 7), (Chip::Imxrt1060, 8)] {
+            let fields = fields(chip);
+            for (idx, field) in fields.iter().enumerate() {
+                assert!(field.ccgr < ccgr_count && field.cg < 16, "{:?}", field);
+                assert!(
+                    fields[idx + 1..]
+                        .iter()
+                        .all(|other| (other.ccgr, other.cg) != (field.ccgr, field.cg)
+                            && other.name != field.name),
+                    "{:?}",
+                    field
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn ccgr_locations() {
+        for (location, names, chips) in instances() {
+            assert_eq!(location.gates.len(), names.len());
+            for chip in chips {
+                for (cg, name) in location.gates.iter().zip(names.iter()) {
+                    assert_eq!(
+                        at(*chip, location.offset, *cg).map(|field| field.name),
+                        Some(*name),
+                        "{} on {}",
+                        name,
+                        chip
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ccgr_essential() {
+        let chip = Chip::COMPILED.unwrap_or(Chip::Imxrt1010);
+        let essential: Vec<_> = fields(chip)
+            .iter()
+            .filter(|field| field.settings == Settings::Essential)
+            .map(|field| (field.ccgr, field.cg))
+            .collect();
+        assert_eq!(essential.len(), ESSENTIAL_CLOCK_GATES.len());
+        for location in ESSENTIAL_CLOCK_GATES {
+            assert!(location
+                .gates
+                .iter()
+                .all(|cg| essential.contains(&(location.offset, *cg))));
+        }
+    }
+}
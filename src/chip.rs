@@ -0,0 +1,198 @@
+//! Runtime chip identification
+//!
+//! A program that's built for one i.MX RT chip, and runs on another, writes
+//! the wrong CCM registers. Call [`validate`] early in your program, before
+//! you configure any clocks, to check that the chip matches the chip feature
+//! that you enabled:
+//!
+//! ```no_run
+//! if let Err(mismatch) = imxrt_ccm::chip::validate() {
+//!     panic!("{}", mismatch);
+//! }
+//! ```
+//!
+//! Identification reads the DIGPROG silicon version register, which is only
+//! available on the i.MX RT 10xx chips. Don't use this module on an i.MX RT
+//! 11xx chip.
+//!
+//! # References
+//!
+//! i.MX RT 1060 reference manual
+//! - Chapter 42: Universal Serial Bus Controller (USB)
+//!   - USB Analog Memory Map/Register Definition (DIGPROG)
+
+use crate::register::{self, Field};
+use core::fmt;
+
+const USB_ANALOG_DIGPROG: *const u32 = 0x400D_8260 as _;
+
+const MINOR: Field = Field::new(0, 0xFF);
+const MAJOR_LOWER: Field = Field::new(8, 0xFF);
+const MAJOR_UPPER: Field = Field::new(16, 0xFF);
+
+/// An i.MX RT chip family
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Chip {
+    /// An i.MX RT 1010 chip, like the iMXRT1011
+    Imxrt1010,
+    /// An i.MX RT 1060 chip, like the iMXRT1062
+    ///
+    /// The 1050 reports the same chip ID.
+    Imxrt1060,
+}
+
+impl Chip {
+    /// The chip that this crate was built for, or `None` if no chip feature
+    /// is enabled
+    ///
+    /// If you enable more than one chip feature, the crate uses the 1060
+    /// implementation.
+    pub const COMPILED: Option<Chip> = if cfg!(feature = "imxrt1060") {
+        Some(Chip::Imxrt1060)
+    } else if cfg!(feature = "imxrt1010") {
+        Some(Chip::Imxrt1010)
+    } else {
+        None
+    };
+
+    /// Returns the chip that has `chip_id`, or `None` if the ID is unknown
+    ///
+    /// `chip_id` is the DIGPROG MAJOR_UPPER field.
+    pub const fn from_chip_id(chip_id: u32) -> Option<Chip> {
+        match chip_id {
+            0x6A => Some(Chip::Imxrt1060),
+            0x6B => Some(Chip::Imxrt1010),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Chip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Chip::Imxrt1010 => f.write_str("i.MX RT 1010"),
+            Chip::Imxrt1060 => f.write_str("i.MX RT 1060"),
+        }
+    }
+}
+
+/// The silicon version, read from the DIGPROG register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SiliconVersion(u32);
+
+impl SiliconVersion {
+    /// Wrap a raw DIGPROG register value
+    pub const fn from_raw(digprog: u32) -> Self {
+        SiliconVersion(digprog)
+    }
+
+    /// Returns the raw DIGPROG register value
+    pub const fn raw(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the chip ID
+    pub const fn chip_id(self) -> u32 {
+        MAJOR_UPPER.get(self.0)
+    }
+
+    /// Returns the silicon revision, as (major, minor)
+    pub const fn revision(self) -> (u32, u32) {
+        (MAJOR_LOWER.get(self.0), MINOR.get(self.0))
+    }
+
+    /// Returns the chip, or `None` if the chip ID is unknown
+    pub const fn chip(self) -> Option<Chip> {
+        Chip::from_chip_id(self.chip_id())
+    }
+}
+
+/// The chip doesn't match the chip that this crate was built for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ChipMismatch {
+    /// The chip that this crate was built for
+    pub expected: Chip,
+    /// The chip's silicon version
+    pub found: SiliconVersion,
+}
+
+impl fmt::Display for ChipMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "imxrt-ccm was built for an {}, but ", self.expected)?;
+        match self.found.chip() {
+            Some(chip) => write!(f, "the chip is an {}", chip)?,
+            None => write!(f, "the chip ID {:#04X} is unknown", self.found.chip_id())?,
+        }
+        write!(f, " (DIGPROG {:#010X})", self.found.raw())
+    }
+}
+
+/// Returns the silicon version
+#[inline(always)]
+pub fn silicon_version() -> SiliconVersion {
+    // Safety: atomic read of a valid register
+    unsafe { silicon_version_(USB_ANALOG_DIGPROG) }
+}
+
+unsafe fn silicon_version_(digprog: *const u32) -> SiliconVersion {
+    SiliconVersion(register::load(digprog))
+}
+
+/// Returns the chip, or `None` if the chip ID is unknown
+#[inline(always)]
+pub fn identify() -> Option<Chip> {
+    silicon_version().chip()
+}
+
+/// Checks that the chip matches [`Chip::COMPILED`]
+///
+/// Returns the silicon version if the chip matches, or if no chip feature is
+/// enabled.
+#[inline(always)]
+pub fn validate() -> Result<SiliconVersion, ChipMismatch> {
+    check(Chip::COMPILED, silicon_version())
+}
+
+fn check(expected: Option<Chip>, found: SiliconVersion) -> Result<SiliconVersion, ChipMismatch> {
+    match expected {
+        Some(expected) if found.chip() != Some(expected) => Err(ChipMismatch { expected, found }),
+        _ => Ok(found),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, silicon_version_, Chip, ChipMismatch, SiliconVersion};
+
+    #[test]
+    fn chip_validate() {
+        let digprog: u32 = 0x006A_0001;
+        let version = unsafe { silicon_version_(&digprog) };
+        assert_eq!(version.chip(), Some(Chip::Imxrt1060));
+        assert_eq!(version.revision(), (0, 1));
+
+        assert_eq!(check(Some(Chip::Imxrt1060), version), Ok(version));
+        assert_eq!(check(None, version), Ok(version));
+        let mismatch = check(Some(Chip::Imxrt1010), version).unwrap_err();
+        assert_eq!(
+            mismatch,
+            ChipMismatch {
+                expected: Chip::Imxrt1010,
+                found: version
+            }
+        );
+        assert_eq!(
+            format!("{}", mismatch),
+            "imxrt-ccm was built for an i.MX RT 1010, but the chip is an i.MX RT 1060 (DIGPROG 0x006A0001)"
+        );
+
+        let unknown = SiliconVersion::from_raw(0x0011_7000);
+        assert_eq!(
+            format!("{}", check(Some(Chip::Imxrt1060), unknown).unwrap_err()),
+            "imxrt-ccm was built for an i.MX RT 1060, but the chip ID 0x11 is unknown (DIGPROG 0x00117000)"
+        );
+    }
+}
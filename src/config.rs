@@ -0,0 +1,529 @@
+//! Whole clock tree configuration
+
+use crate::{
+    arm,
+    gate::{self, Known},
+    i2c, perclock, pll2,
+    pll3::{self, PFD},
+    spi, uart, ClockGate, ClockGateLocation, GateSnapshot, RootFrequency,
+};
+
+/// A clock tree configuration
+///
+/// `CcmConfig` describes the ARM clock frequency, the source and divider of
+/// each clock root, and the clock gates. Build a configuration, then commit it
+/// with [`CCM::apply`](crate::CCM::apply). Anything that you don't describe is
+/// left unchanged. Use [`CCM::capture`](crate::CCM::capture) to read the
+/// configuration that's in hardware, then change only what you need.
+///
+/// ```no_run
+/// use imxrt_ccm::{
+///     perclock, uart, ClockGate, CcmConfig, ESSENTIAL_CLOCK_GATES,
+/// };
+/// # struct MyClocks;
+/// # impl imxrt_ccm::Clocks for MyClocks {
+/// #   type I2C = ();
+/// #   type SPI = ();
+/// #   type UART = ();
+/// #   type GPT = ();
+/// #   type PIT = ();
+/// # }
+///
+/// const CONFIG: CcmConfig = CcmConfig::new()
+///     .arm_frequency(600_000_000)
+///     .all_gates(ClockGate::Off, ESSENTIAL_CLOCK_GATES)
+///     .perclock(perclock::Selection::Oscillator, 24)
+///     .uart(uart::Selection::Oscillator, 1);
+///
+/// let mut ccm = unsafe { imxrt_ccm::CCM::<MyClocks>::new() };
+/// let applied = ccm.apply(&CONFIG).unwrap();
+/// assert_eq!(applied.perclock.unwrap().hz, 1_000_000);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CcmConfig<'a> {
+    arm_hz: Option<u32>,
+    perclock: Option<(perclock::Selection, u32)>,
+    uart: Option<(uart::Selection, u32)>,
+    spi: Option<(spi::Selection, u32)>,
+    i2c: Option<(i2c::Selection, u32)>,
+    all_gates: Option<(ClockGate, &'a [ClockGateLocation])>,
+    snapshot: Option<GateSnapshot>,
+    gates: &'a [(ClockGateLocation, ClockGate)],
+}
+
+impl<'a> CcmConfig<'a> {
+    /// Create a configuration that changes nothing
+    pub const fn new() -> Self {
+        CcmConfig {
+            arm_hz: None,
+            perclock: None,
+            uart: None,
+            spi: None,
+            i2c: None,
+            all_gates: None,
+            snapshot: None,
+            gates: &[],
+        }
+    }
+
+    /// Create the default clock tree configuration
+    ///
+    /// | Clock    | Source      | Divider | Frequency                            |
+    /// | -------- | ----------- | ------- | ------------------------------------ |
+    /// | ARM      | PLL1        |         | [`arm::MAX_FREQUENCY_HZ`]            |
+    /// | IPG      | ARM         | 4       | 150MHz (1060), 125MHz (1010)         |
+    /// | PERCLK   | Oscillator  | 24      | 1MHz                                 |
+    /// | UART     | Oscillator  | 1       | 24MHz                                |
+    /// | LPSPI    | PLL2        | 5       | 105.6MHz                             |
+    /// | LPI2C    | Oscillator  | 3       | 8MHz                                 |
+    ///
+    /// The ARM clock runs at the fastest frequency that the datasheet supports
+    /// for your chip feature. Without a chip feature, that's the conservative
+    /// 500MHz. The clock root dividers are the same as the handles' `configure()`
+    /// defaults. The configuration doesn't change any clock gates.
+    pub const fn defaults() -> Self {
+        CcmConfig::new()
+            .arm_frequency(arm::MAX_FREQUENCY_HZ)
+            .perclock(perclock::Selection::Oscillator, 24)
+            .uart(uart::Selection::Oscillator, 1)
+            .spi(spi::Selection::Pll2, 5)
+            .i2c(i2c::Selection::Oscillator, 3)
+    }
+
+    /// Set the ARM clock frequency
+    ///
+    /// See [`arm::try_set_frequency`] for the valid range. If the ARM clock
+    /// already runs at `hz`, applying the configuration leaves the ARM clock
+    /// unchanged, even if `hz` is out of range.
+    pub const fn arm_frequency(mut self, hz: u32) -> Self {
+        self.arm_hz = Some(hz);
+        self
+    }
+
+    /// Set the periodic clock source and divider
+    pub const fn perclock(mut self, selection: perclock::Selection, divider: u32) -> Self {
+        self.perclock = Some((selection, divider));
+        self
+    }
+
+    /// Set the UART clock source and divider
+    pub const fn uart(mut self, selection: uart::Selection, divider: u32) -> Self {
+        self.uart = Some((selection, divider));
+        self
+    }
+
+    /// Set the SPI clock source and divider
+    pub const fn spi(mut self, selection: spi::Selection, divider: u32) -> Self {
+        self.spi = Some((selection, divider));
+        self
+    }
+
+    /// Set the I2C clock source and divider
+    pub const fn i2c(mut self, selection: i2c::Selection, divider: u32) -> Self {
+        self.i2c = Some((selection, divider));
+        self
+    }
+
+    /// Set every clock gate to `gate`, except the clock gates in `exclude`
+    ///
+    /// These gates change before any clock root changes. Turn off the
+    /// gates of running peripherals here, so that they don't see a glitch.
+    /// See [`CCM::set_all_clock_gates`](crate::CCM::set_all_clock_gates).
+    pub const fn all_gates(mut self, gate: ClockGate, exclude: &'a [ClockGateLocation]) -> Self {
+        self.all_gates = Some((gate, exclude));
+        self
+    }
+
+    /// Restore all clock gates from `snapshot` after all clock roots change
+    ///
+    /// The individual [`gates`](CcmConfig::gates) change after the snapshot.
+    pub const fn gate_snapshot(mut self, snapshot: GateSnapshot) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+
+    /// Set the clock gates after all clock roots change
+    pub const fn gates(mut self, gates: &'a [(ClockGateLocation, ClockGate)]) -> Self {
+        self.gates = gates;
+        self
+    }
+
+    /// Returns `true` if a clock root selects PLL3, or one of its PFDs
+    fn needs_pll3(&self) -> bool {
+        matches!(self.uart, Some((uart::Selection::Pll3, _)))
+            || matches!(self.i2c, Some((i2c::Selection::Pll3, _)))
+            || matches!(
+                self.spi,
+                Some((spi::Selection::Pll3Pfd0 | spi::Selection::Pll3Pfd1, _))
+            )
+    }
+}
+
+/// The frequencies that result from applying a [`CcmConfig`]
+///
+/// A field is `None` if the configuration did not change that clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Applied {
+    /// The ARM clock
+    pub arm: Option<arm::Achieved>,
+    /// The periodic clock root
+    pub perclock: Option<RootFrequency>,
+    /// The UART clock root
+    pub uart: Option<RootFrequency>,
+    /// The SPI clock root
+    pub spi: Option<RootFrequency>,
+    /// The I2C clock root
+    pub i2c: Option<RootFrequency>,
+}
+
+/// Commit `config` to the CCM
+///
+/// The order is
+///
+/// 1. check the ARM clock frequency. If it's out of range, return an error
+///    without changing any clocks. If the ARM clock already runs at the
+///    frequency, skip the next step.
+/// 2. set the ARM clock frequency, parking the core on the oscillator while
+///    PLL1 locks.
+/// 3. set all clock gates.
+/// 4. enable PLL3, and wait for it to lock, if a clock root selects PLL3, or
+///    one of its PFDs.
+/// 5. ungate the PFD that the SPI clock root selects.
+/// 6. set each clock root's source and divider. The clock gates of the
+///    root's peripherals are off while the root changes, then they return to
+///    their previous settings.
+/// 7. restore the gate snapshot, then set the individual clock gates.
+///
+/// The periodic clock root changes after the ARM clock, since it may run on
+/// the IPG clock.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM and CCM_ANALOG.
+pub(crate) unsafe fn apply(config: &CcmConfig) -> Result<Applied, arm::FrequencyOutOfRange> {
+    let arm = match config.arm_hz {
+        // Already there, perhaps from a clock source that try_set_frequency
+        // wouldn't select. Leave it alone, so that apply(capture()) changes
+        // nothing.
+        Some(hz) if arm::clocks().arm.0 == hz => {
+            let clocks = arm::clocks();
+            Some(arm::Achieved {
+                arm: clocks.arm,
+                ipg: clocks.ipg,
+                error_hz: 0,
+                root: clocks.root,
+            })
+        }
+        Some(hz) => Some(arm::try_set_frequency(hz)?),
+        None => None,
+    };
+
+    if let Some((gate, exclude)) = config.all_gates {
+        crate::gate::set_all(gate as u8, &[exclude]);
+    }
+
+    if config.needs_pll3() {
+        pll3::enable();
+    }
+    match config.spi {
+        Some((spi::Selection::Pll3Pfd0, _)) => pll3::set_pfd_gated(PFD::PFD0, false),
+        Some((spi::Selection::Pll3Pfd1, _)) => pll3::set_pfd_gated(PFD::PFD1, false),
+        Some((spi::Selection::Pll2Pfd2, _)) => pll2::set_pfd_gated(PFD::PFD2, false),
+        _ => {}
+    }
+
+    let perclock = config.perclock.map(|(selection, divider)| {
+        parked(
+            |known| matches!(known, Known::Gpt(_) | Known::Pit),
+            || {
+                perclock::set_selection(selection);
+                perclock::set_divider(divider);
+            },
+        );
+        RootFrequency::exact(perclock::frequency())
+    });
+    let uart = config.uart.map(|(selection, divider)| {
+        parked(
+            |known| matches!(known, Known::Uart(_)),
+            || {
+                uart::set_selection(selection);
+                uart::set_divider(divider);
+            },
+        );
+        RootFrequency::exact(uart::frequency())
+    });
+    let spi = config.spi.map(|(selection, divider)| {
+        parked(
+            |known| matches!(known, Known::Spi(_)),
+            || {
+                spi::set_selection(selection);
+                spi::set_divider(divider);
+            },
+        );
+        RootFrequency::exact(spi::frequency())
+    });
+    let i2c = config.i2c.map(|(selection, divider)| {
+        parked(
+            |known| matches!(known, Known::I2c(_)),
+            || {
+                i2c::set_selection(selection);
+                i2c::set_divider(divider);
+            },
+        );
+        RootFrequency::exact(i2c::frequency())
+    });
+
+    if let Some(snapshot) = &config.snapshot {
+        crate::gate::restore(&snapshot.ccgr);
+    }
+    for (location, gate) in config.gates {
+        crate::gate::set(location, *gate as u8);
+    }
+
+    Ok(Applied {
+        arm,
+        perclock,
+        uart,
+        spi,
+        i2c,
+    })
+}
+
+/// Turn off the known clock gates that `affected` selects while `change`
+/// runs, then return them to their previous settings
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM.
+unsafe fn parked(affected: fn(&Known) -> bool, change: impl FnOnce()) {
+    let mut saved = [None; gate::known_len()];
+    for (idx, saved) in saved.iter_mut().enumerate() {
+        if let Some((_, known)) = gate::known(idx).filter(|(_, known)| affected(known)) {
+            let location = known.location();
+            *saved = Some(gate::get(&location));
+            gate::set(&location, ClockGate::Off as u8);
+        }
+    }
+    change();
+    for (idx, saved) in saved.iter().enumerate() {
+        if let (Some(value), Some((_, known))) = (saved, gate::known(idx)) {
+            gate::set(&known.location(), *value);
+        }
+    }
+}
+
+/// Read the clock tree configuration from hardware
+///
+/// # Safety
+///
+/// Reads multiple CCM registers without synchronization.
+pub(crate) unsafe fn capture() -> CcmConfig<'static> {
+    CcmConfig::new()
+        .arm_frequency(arm::clocks().arm.0)
+        .perclock(perclock::selection(), perclock::divider())
+        .uart(uart::selection(), uart::divider())
+        .spi(spi::selection(), spi::divider())
+        .i2c(i2c::selection(), i2c::divider())
+        .gate_snapshot(GateSnapshot {
+            ccgr: crate::gate::snapshot(),
+        })
+}
+
+/// An expected value that differs from the actual value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mismatch<T> {
+    /// The value in the expected configuration
+    pub expected: T,
+    /// The value in hardware
+    pub actual: T,
+}
+
+/// Compare `expected` and `actual`, returning a mismatch if they differ
+fn mismatch<T: PartialEq>(expected: Option<T>, actual: Option<T>) -> Option<Mismatch<T>> {
+    match (expected, actual) {
+        (Some(expected), Some(actual)) if expected != actual => Some(Mismatch { expected, actual }),
+        _ => None,
+    }
+}
+
+/// The differences between an expected configuration and the hardware
+///
+/// Use [`CCM::validate`](crate::CCM::validate) to compare the hardware against
+/// a configuration. A field is `None` if the hardware matches, or if the
+/// expected configuration doesn't describe that clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mismatches {
+    /// The ARM clock frequency
+    pub arm_hz: Option<Mismatch<u32>>,
+    /// The periodic clock source and divider
+    pub perclock: Option<Mismatch<(perclock::Selection, u32)>>,
+    /// The UART clock source and divider
+    pub uart: Option<Mismatch<(uart::Selection, u32)>>,
+    /// The SPI clock source and divider
+    pub spi: Option<Mismatch<(spi::Selection, u32)>>,
+    /// The I2C clock source and divider
+    pub i2c: Option<Mismatch<(i2c::Selection, u32)>>,
+    /// The first CCGR register that differs from the gate snapshot, and its
+    /// number
+    pub ccgr: Option<(usize, Mismatch<u32>)>,
+    /// The first individual clock gate that differs
+    pub gate: Option<(ClockGateLocation, Mismatch<ClockGate>)>,
+}
+
+impl Mismatches {
+    /// Returns `true` if there are no mismatches
+    pub fn is_empty(&self) -> bool {
+        self.arm_hz.is_none()
+            && self.perclock.is_none()
+            && self.uart.is_none()
+            && self.spi.is_none()
+            && self.i2c.is_none()
+            && self.ccgr.is_none()
+            && self.gate.is_none()
+    }
+}
+
+/// Compare the `expected` configuration against the `actual` configuration
+///
+/// `gate` returns the actual setting of an individual clock gate. The
+/// `all_gates` setting of `expected` describes a transition, not a state,
+/// so it's not compared.
+fn compare(
+    expected: &CcmConfig,
+    actual: &CcmConfig,
+    gate: impl Fn(&ClockGateLocation) -> ClockGate,
+) -> Mismatches {
+    Mismatches {
+        arm_hz: mismatch(expected.arm_hz, actual.arm_hz),
+        perclock: mismatch(expected.perclock, actual.perclock),
+        uart: mismatch(expected.uart, actual.uart),
+        spi: mismatch(expected.spi, actual.spi),
+        i2c: mismatch(expected.i2c, actual.i2c),
+        ccgr: expected
+            .snapshot
+            .zip(actual.snapshot)
+            .and_then(|(expected, actual)| {
+                expected
+                    .ccgr
+                    .iter()
+                    .zip(actual.ccgr.iter())
+                    .enumerate()
+                    .find_map(|(idx, (&expected, &actual))| {
+                        mismatch(Some(expected), Some(actual)).map(|mismatch| (idx, mismatch))
+                    })
+            }),
+        gate: expected
+            .gates
+            .iter()
+            .map(|(location, expected)| {
+                let actual = gate(location);
+                (*location, mismatch(Some(*expected), Some(actual)))
+            })
+            .find_map(|(location, mismatch)| mismatch.map(|mismatch| (location, mismatch))),
+    }
+}
+
+/// Compare the hardware against the `expected` configuration
+///
+/// # Safety
+///
+/// Reads multiple CCM registers without synchronization.
+pub(crate) unsafe fn validate(expected: &CcmConfig) -> Mismatches {
+    compare(expected, &capture(), |location| {
+        ClockGate::from_u8(crate::gate::get(location))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, CcmConfig, Mismatch};
+    use crate::{i2c, perclock, spi, uart, ClockGate, ClockGateLocation, GateSnapshot};
+
+    #[test]
+    fn config_needs_pll3() {
+        const CONFIG: CcmConfig = CcmConfig::new()
+            .perclock(perclock::Selection::Oscillator, 24)
+            .uart(uart::Selection::Oscillator, 1);
+        assert!(!CONFIG.needs_pll3());
+        assert!(CONFIG.i2c(i2c::Selection::Pll3, 8).needs_pll3());
+        assert!(CONFIG.uart(uart::Selection::Pll3, 1).needs_pll3());
+        assert!(CONFIG.spi(spi::Selection::Pll3Pfd0, 4).needs_pll3());
+        assert!(CONFIG.spi(spi::Selection::Pll3Pfd1, 4).needs_pll3());
+        assert!(!CONFIG.spi(spi::Selection::Pll2, 4).needs_pll3());
+        assert!(!CONFIG.spi(spi::Selection::Pll2Pfd2, 4).needs_pll3());
+        assert!(!CcmConfig::new().needs_pll3());
+    }
+
+    #[test]
+    fn config_defaults() {
+        const DEFAULTS: CcmConfig = CcmConfig::defaults();
+        assert_eq!(DEFAULTS.arm_hz, Some(crate::arm::MAX_FREQUENCY_HZ));
+        assert_eq!(
+            DEFAULTS.perclock,
+            Some((perclock::Selection::Oscillator, 24))
+        );
+        assert!(!DEFAULTS.needs_pll3());
+        assert!(DEFAULTS.all_gates.is_none() && DEFAULTS.gates.is_empty());
+    }
+
+    #[test]
+    fn config_compare() {
+        const GATES: &[(ClockGateLocation, ClockGate)] = &[
+            (ClockGateLocation::new(5, &[12]), ClockGate::On),
+            (ClockGateLocation::new(1, &[6]), ClockGate::Off),
+        ];
+        const EXPECTED: CcmConfig = CcmConfig::new()
+            .arm_frequency(600_000_000)
+            .uart(uart::Selection::Oscillator, 1)
+            .gates(GATES);
+        let actual = CcmConfig::new()
+            .arm_frequency(600_000_000)
+            .perclock(perclock::Selection::IPG, 1)
+            .uart(uart::Selection::Pll3, 1);
+
+        let mismatches = compare(&EXPECTED, &actual, |_| ClockGate::On);
+        assert_eq!(
+            mismatches.uart,
+            Some(Mismatch {
+                expected: (uart::Selection::Oscillator, 1),
+                actual: (uart::Selection::Pll3, 1),
+            })
+        );
+        assert_eq!(mismatches.arm_hz, None);
+        // Not described by the expected configuration
+        assert_eq!(mismatches.perclock, None);
+        assert_eq!(
+            mismatches.gate,
+            Some((
+                GATES[1].0,
+                Mismatch {
+                    expected: ClockGate::Off,
+                    actual: ClockGate::On,
+                }
+            ))
+        );
+        assert!(!mismatches.is_empty());
+
+        let actual = actual.uart(uart::Selection::Oscillator, 1);
+        let mismatches = compare(&EXPECTED, &actual, |location| {
+            GATES.iter().find(|(l, _)| l == location).unwrap().1
+        });
+        assert!(mismatches.is_empty());
+
+        let mut ccgr = [u32::MAX; crate::CCGR_COUNT];
+        let expected = EXPECTED.gate_snapshot(GateSnapshot { ccgr });
+        ccgr[3] = 0;
+        let actual = actual.gate_snapshot(GateSnapshot { ccgr });
+        let mismatches = compare(&expected, &actual, |_| ClockGate::Off);
+        assert_eq!(
+            mismatches.ccgr,
+            Some((
+                3,
+                Mismatch {
+                    expected: u32::MAX,
+                    actual: 0,
+                }
+            ))
+        );
+    }
+}
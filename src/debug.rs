@@ -0,0 +1,278 @@
+//! Whole-CCM register snapshots for bug reports
+
+use crate::{
+    arm, i2c, perclock, pll2, pll3, pll4, pll6, raw, spi,
+    tree::{Node, Tree},
+    uart, ClockRoot, ClockSource,
+};
+
+/// Every CCM register, in address order
+const CCM: &[(&str, raw::Reg)] = &[
+    ("CCR", raw::CCR),
+    ("CSR", raw::CSR),
+    ("CCSR", raw::CCSR),
+    ("CACRR", raw::CACRR),
+    ("CBCDR", raw::CBCDR),
+    ("CBCMR", raw::CBCMR),
+    ("CSCMR1", raw::CSCMR1),
+    ("CSCMR2", raw::CSCMR2),
+    ("CSCDR1", raw::CSCDR1),
+    ("CS1CDR", raw::CS1CDR),
+    ("CS2CDR", raw::CS2CDR),
+    ("CDCDR", raw::CDCDR),
+    ("CSCDR2", raw::CSCDR2),
+    ("CSCDR3", raw::CSCDR3),
+    ("CDHIPR", raw::CDHIPR),
+    ("CLPCR", raw::CLPCR),
+    ("CISR", raw::CISR),
+    ("CIMR", raw::CIMR),
+    ("CCOSR", raw::CCOSR),
+    ("CGPR", raw::CGPR),
+    ("CCGR0", raw::CCGR0),
+    ("CCGR1", raw::CCGR1),
+    ("CCGR2", raw::CCGR2),
+    ("CCGR3", raw::CCGR3),
+    ("CCGR4", raw::CCGR4),
+    ("CCGR5", raw::CCGR5),
+    ("CCGR6", raw::CCGR6),
+    ("CCGR7", raw::CCGR7),
+    ("CMEOR", raw::CMEOR),
+];
+
+/// Every CCM_ANALOG register, in address order
+const CCM_ANALOG: &[(&str, raw::Reg)] = &[
+    ("PLL_ARM", raw::PLL_ARM),
+    ("PLL_USB1", raw::PLL_USB1),
+    ("PLL_USB2", raw::PLL_USB2),
+    ("PLL_SYS", raw::PLL_SYS),
+    ("PLL_SYS_SS", raw::PLL_SYS_SS),
+    ("PLL_SYS_NUM", raw::PLL_SYS_NUM),
+    ("PLL_SYS_DENOM", raw::PLL_SYS_DENOM),
+    ("PLL_AUDIO", raw::PLL_AUDIO),
+    ("PLL_AUDIO_NUM", raw::PLL_AUDIO_NUM),
+    ("PLL_AUDIO_DENOM", raw::PLL_AUDIO_DENOM),
+    ("PLL_VIDEO", raw::PLL_VIDEO),
+    ("PLL_VIDEO_NUM", raw::PLL_VIDEO_NUM),
+    ("PLL_VIDEO_DENOM", raw::PLL_VIDEO_DENOM),
+    ("PLL_ENET", raw::PLL_ENET),
+    ("PFD_480", raw::PFD_480),
+    ("PFD_528", raw::PFD_528),
+    ("MISC0", raw::MISC0),
+    ("MISC1", raw::MISC1),
+    ("MISC2", raw::MISC2),
+];
+
+/// The value of every CCM and CCM_ANALOG register, and the clock frequencies
+/// that they describe
+///
+/// Use [`CCM::debug_snapshot`](crate::CCM::debug_snapshot) to capture a snapshot.
+/// The `Debug` output includes every field; use [`registers`](DebugSnapshot::registers)
+/// to print the registers by name:
+///
+/// ```no_run
+/// # use imxrt_ccm as ccm;
+/// # let ccm = unsafe { ccm::CCM::<ccm::SplitClocks>::new() };
+/// # macro_rules! rprintln { ($($arg:tt)*) => {} }
+/// let snapshot = ccm.debug_snapshot();
+/// rprintln!("ARM {} Hz, IPG {} Hz", snapshot.arm_hz, snapshot.ipg_hz);
+/// for (name, value) in snapshot.registers() {
+///     rprintln!("{} = {:#010X}", name, value);
+/// }
+/// ```
+///
+/// A snapshot doesn't need hardware. Use [`from_registers`](DebugSnapshot::from_registers)
+/// to decode register values from elsewhere, like a debugger, on the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DebugSnapshot {
+    /// CCM register values, from CCR through CMEOR
+    pub ccm: [u32; CCM.len()],
+    /// CCM_ANALOG register values, from PLL_ARM through MISC2
+    pub ccm_analog: [u32; CCM_ANALOG.len()],
+    /// ARM clock frequency
+    pub arm_hz: u32,
+    /// IPG clock frequency
+    pub ipg_hz: u32,
+    /// Periodic clock root frequency
+    pub perclock_hz: u32,
+    /// UART clock root frequency
+    pub uart_hz: u32,
+    /// SPI clock root frequency
+    pub spi_hz: u32,
+    /// I2C clock root frequency
+    pub i2c_hz: u32,
+    /// PLL2 (system PLL) frequency
+    pub pll2_hz: u32,
+    /// PLL3 (USB1 PLL) frequency
+    pub pll3_hz: u32,
+    /// PLL4 (audio PLL) frequency
+    pub pll4_hz: u32,
+    /// PLL6 (ENET PLL) frequency
+    pub pll6_hz: u32,
+}
+
+impl DebugSnapshot {
+    /// Decode a snapshot from register values
+    ///
+    /// `read` returns the value of a CCM or CCM_ANALOG register. The
+    /// frequencies are computed from the register values, so this works on
+    /// the host, without any hardware:
+    ///
+    /// ```
+    /// use imxrt_ccm::{raw, DebugSnapshot, FrequencyMap};
+    ///
+    /// // Register values dumped by a debugger
+    /// let dump: &[(usize, u32)] = &[
+    ///     (0x400F_C024, 0x0000_0043), // CSCDR1
+    ///     (0x400D_8010, 0x0000_3000), // PLL_USB1
+    /// ];
+    /// let snapshot = DebugSnapshot::from_registers(|reg| {
+    ///     dump.iter()
+    ///         .find(|(address, _)| *address == reg.address() as usize)
+    ///         .map_or(0, |(_, value)| *value)
+    /// });
+    /// assert_eq!(snapshot.uart_hz, 6_000_000);
+    /// assert_eq!(snapshot.pll3_hz, 480_000_000);
+    ///
+    /// // Decode every clock root
+    /// let map = FrequencyMap::new(&snapshot.tree());
+    /// ```
+    pub fn from_registers(read: impl Fn(raw::Reg) -> u32) -> Self {
+        let snapshot = registers_(read);
+        let tree = snapshot.tree();
+        let hz = |node| tree.frequency(node);
+        DebugSnapshot {
+            arm_hz: hz(Node::Root(ClockRoot::AHB)),
+            ipg_hz: hz(Node::Root(ClockRoot::IPG)),
+            perclock_hz: hz(Node::Root(ClockRoot::PerClock)),
+            uart_hz: hz(Node::Root(ClockRoot::UART)),
+            spi_hz: hz(Node::Root(ClockRoot::SPI)),
+            i2c_hz: hz(Node::Root(ClockRoot::I2C)),
+            pll2_hz: hz(Node::Source(ClockSource::Pll2)),
+            pll3_hz: hz(Node::Source(ClockSource::Pll3)),
+            pll4_hz: hz(Node::Source(ClockSource::AudioPll)),
+            pll6_hz: hz(Node::Source(ClockSource::EnetPll)),
+            ..snapshot
+        }
+    }
+
+    /// Returns the captured value of `reg`
+    ///
+    /// Returns `None` if the snapshot doesn't include the register.
+    pub fn register(&self, reg: raw::Reg) -> Option<u32> {
+        CCM.iter()
+            .chain(CCM_ANALOG)
+            .zip(self.ccm.iter().chain(&self.ccm_analog))
+            .find(|((_, captured), _)| *captured == reg)
+            .map(|(_, value)| *value)
+    }
+
+    /// Returns a clock tree that decodes the captured register values
+    ///
+    /// Registers that the snapshot doesn't include read as zero.
+    pub fn tree(&self) -> Tree<impl Fn(raw::Reg) -> u32 + '_> {
+        Tree::new(move |reg| self.register(reg).unwrap_or(0))
+    }
+
+    /// Returns the register names and values, starting with the CCM registers
+    pub fn registers(&self) -> impl Iterator<Item = (&'static str, u32)> + '_ {
+        CCM.iter()
+            .chain(CCM_ANALOG)
+            .map(|(name, _)| *name)
+            .zip(self.ccm.iter().chain(&self.ccm_analog).copied())
+    }
+}
+
+/// Read all registers, leaving the frequencies zero
+fn registers_(read: impl Fn(raw::Reg) -> u32) -> DebugSnapshot {
+    let mut ccm = [0; CCM.len()];
+    for (value, (_, reg)) in ccm.iter_mut().zip(CCM) {
+        *value = read(*reg);
+    }
+    let mut ccm_analog = [0; CCM_ANALOG.len()];
+    for (value, (_, reg)) in ccm_analog.iter_mut().zip(CCM_ANALOG) {
+        *value = read(*reg);
+    }
+    DebugSnapshot {
+        ccm,
+        ccm_analog,
+        arm_hz: 0,
+        ipg_hz: 0,
+        perclock_hz: 0,
+        uart_hz: 0,
+        spi_hz: 0,
+        i2c_hz: 0,
+        pll2_hz: 0,
+        pll3_hz: 0,
+        pll4_hz: 0,
+        pll6_hz: 0,
+    }
+}
+
+/// Capture a debug snapshot
+///
+/// # Safety
+///
+/// Reads multiple CCM and CCM_ANALOG registers without synchronization.
+pub(crate) unsafe fn snapshot() -> DebugSnapshot {
+    let (arm::ARMClock(arm_hz), arm::IPGClock(ipg_hz)) = arm::frequency();
+    DebugSnapshot {
+        arm_hz,
+        ipg_hz,
+        perclock_hz: perclock::frequency(),
+        uart_hz: uart::frequency(),
+        spi_hz: spi::frequency(),
+        i2c_hz: i2c::frequency(),
+        pll2_hz: pll2::frequency(),
+        pll3_hz: pll3::frequency(),
+        pll4_hz: pll4::frequency(),
+        pll6_hz: pll6::frequency(),
+        ..registers_(|reg| reg.read())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{registers_, DebugSnapshot, CCM, CCM_ANALOG};
+    use crate::raw;
+
+    #[test]
+    fn debug_registers_in_order() {
+        let snapshot = registers_(|reg| reg.address() as u32);
+        let mut registers = snapshot.registers();
+        assert_eq!(registers.next(), Some(("CCR", 0x400F_C000)));
+        assert_eq!(registers.nth(CCM.len() - 2), Some(("CMEOR", 0x400F_C088)));
+        assert_eq!(registers.next(), Some(("PLL_ARM", 0x400D_8000)));
+        assert_eq!(registers.last(), Some(("MISC2", 0x400D_8170)));
+
+        assert_eq!(snapshot.register(raw::CSCDR1), Some(0x400F_C024));
+        assert_eq!(snapshot.register(raw::PFD_528), Some(0x400D_8100));
+
+        for table in [CCM, CCM_ANALOG] {
+            assert!(table
+                .windows(2)
+                .all(|pair| pair[0].1.address() < pair[1].1.address()));
+        }
+    }
+
+    #[test]
+    fn debug_from_registers() {
+        // PLL2 and PLL3 run; AHB divides PLL2 by 2, and IPG divides by 4.
+        // UART runs on pll3_80m.
+        let snapshot = DebugSnapshot::from_registers(|reg| match reg {
+            r if r == raw::PLL_SYS => 1 << 13 | 1,
+            r if r == raw::PLL_USB1 => 1 << 13 | 1 << 12,
+            r if r == raw::CBCDR => 1 << 10 | 3 << 8,
+            _ => 0,
+        });
+        assert_eq!(snapshot.pll2_hz, 528_000_000);
+        assert_eq!(snapshot.pll3_hz, 480_000_000);
+        assert_eq!(snapshot.arm_hz, 264_000_000);
+        assert_eq!(snapshot.ipg_hz, 66_000_000);
+        assert_eq!(snapshot.uart_hz, 80_000_000);
+        assert_eq!(snapshot.register(raw::CBCDR), Some(1 << 10 | 3 << 8));
+
+        let copy = DebugSnapshot::from_registers(|reg| snapshot.register(reg).unwrap_or(0));
+        assert_eq!(copy, snapshot);
+    }
+}
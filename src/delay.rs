@@ -0,0 +1,136 @@
+//! Busy-wait delays that track the ARM clock
+//!
+//! [`Delay`] implements the `embedded-hal` `DelayNs` trait with the DWT cycle
+//! counter, which runs at the ARM clock frequency. Each delay reads the ARM
+//! clock frequency from the CCM before it starts, so delays stay correct after
+//! [`CCM::set_frequency_arm`](crate::CCM::set_frequency_arm), or any other
+//! ARM clock change:
+//!
+//! ```no_run
+//! use embedded_hal::delay::DelayNs;
+//! use imxrt_ccm::delay::Delay;
+//! # let mut ccm = unsafe { imxrt_ccm::CCM::<imxrt_ccm::SplitClocks>::new() };
+//!
+//! let mut cp = cortex_m::Peripherals::take().unwrap();
+//! let mut delay = Delay::new(cp.DWT, &mut cp.DCB);
+//! delay.delay_ms(10);
+//!
+//! ccm.set_frequency_arm(150_000_000);
+//! delay.delay_ms(10); // Still 10ms
+//! ```
+//!
+//! A delay that's running when the ARM clock changes uses the old frequency
+//! until it completes.
+
+use crate::arm;
+use cortex_m::peripheral::{DCB, DWT};
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+const MICROS_PER_SECOND: u64 = 1_000_000;
+const MILLIS_PER_SECOND: u64 = 1_000;
+
+/// A busy-wait delay that counts ARM clock cycles
+///
+/// `Delay` owns the DWT, so that nothing else can disable or reset the cycle
+/// counter.
+pub struct Delay {
+    dwt: DWT,
+}
+
+impl Delay {
+    /// Enable the DWT cycle counter, and create a delay
+    pub fn new(mut dwt: DWT, dcb: &mut DCB) -> Self {
+        dcb.enable_trace();
+        DWT::unlock();
+        dwt.enable_cycle_counter();
+        Delay { dwt }
+    }
+
+    /// Release the DWT
+    ///
+    /// The cycle counter remains enabled.
+    pub fn release(self) -> DWT {
+        self.dwt
+    }
+
+    /// Wait for `amount` units, where there are `per_second` units in a second
+    fn delay(&mut self, amount: u32, per_second: u64) {
+        // Safety: atomic reads of valid registers. If another context changes
+        // the ARM clock during the reads, this delay may be too long or too
+        // short; later delays use the new frequency.
+        let arm_hz = unsafe { arm::frequency() }.0 .0;
+        spin(cycles(amount, per_second, arm_hz), DWT::cycle_count);
+    }
+}
+
+/// Returns the number of ARM clock cycles in `amount` units, rounded up
+fn cycles(amount: u32, per_second: u64, arm_hz: u32) -> u64 {
+    (amount as u64 * arm_hz as u64).div_ceil(per_second)
+}
+
+/// Wait until `now` advances by `cycles`
+///
+/// `now` is a free-running, up-counting, 32-bit counter that may wrap. Long
+/// delays may wrap the counter many times.
+fn spin(mut cycles: u64, mut now: impl FnMut() -> u32) {
+    let mut last = now();
+    while cycles > 0 {
+        let current = now();
+        cycles = cycles.saturating_sub(current.wrapping_sub(last) as u64);
+        last = current;
+    }
+}
+
+impl embedded_hal::delay::DelayNs for Delay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.delay(ns, NANOS_PER_SECOND)
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.delay(us, MICROS_PER_SECOND)
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.delay(ms, MILLIS_PER_SECOND)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cycles, spin, MILLIS_PER_SECOND, NANOS_PER_SECOND};
+    use core::cell::Cell;
+
+    #[test]
+    fn delay_cycles() {
+        assert_eq!(cycles(1_000, NANOS_PER_SECOND, 600_000_000), 600);
+        assert_eq!(cycles(1, NANOS_PER_SECOND, 600_000_000), 1);
+        assert_eq!(cycles(0, NANOS_PER_SECOND, 600_000_000), 0);
+        assert_eq!(
+            cycles(u32::MAX, MILLIS_PER_SECOND, 600_000_000),
+            u32::MAX as u64 * 600_000
+        );
+    }
+
+    #[test]
+    fn delay_spin() {
+        // The counter starts near the wrap, and advances 7 cycles per read
+        let count = Cell::new(u32::MAX - 20);
+        let now = || {
+            count.set(count.get().wrapping_add(7));
+            count.get()
+        };
+        spin(100, now);
+        assert_eq!(count.get(), (u32::MAX - 20).wrapping_add(7 * 16));
+
+        // Longer than one counter period
+        let count = Cell::new(0u32);
+        let reads = Cell::new(0u32);
+        let now = || {
+            reads.set(reads.get() + 1);
+            count.set(count.get().wrapping_add(1 << 30));
+            count.get()
+        };
+        spin(5 << 30, now);
+        assert_eq!(reads.get(), 6);
+    }
+}
@@ -0,0 +1,343 @@
+//! Clock root source and divider selection
+
+/// The frequency achieved by a clock root configuration
+///
+/// Returned when you request a clock root frequency, like
+/// [`UARTClock::configure_frequency`](crate::uart::UARTClock::configure_frequency),
+/// or a clock root divider, like
+/// [`UARTClock::configure_divider`](crate::uart::UARTClock::configure_divider).
+/// When you request a divider, there's no requested frequency, and the error
+/// is zero. Use [`relative_to`](RootFrequency::relative_to) to compare the
+/// frequency with your target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RootFrequency {
+    /// The clock root frequency
+    pub hz: u32,
+    /// The clock root frequency minus the requested frequency, in Hz
+    pub error_hz: i32,
+}
+
+impl RootFrequency {
+    /// A frequency that has no requested frequency
+    pub(crate) const fn exact(hz: u32) -> Self {
+        RootFrequency { hz, error_hz: 0 }
+    }
+
+    /// Returns the same frequency, with the error relative to `target_hz`
+    ///
+    /// ```
+    /// use imxrt_ccm::RootFrequency;
+    ///
+    /// let achieved = RootFrequency { hz: 8_000_000, error_hz: 0 };
+    /// assert_eq!(achieved.relative_to(7_500_000).error_hz, 500_000);
+    /// ```
+    pub const fn relative_to(self, target_hz: u32) -> Self {
+        RootFrequency {
+            hz: self.hz,
+            error_hz: self.hz as i32 - target_hz as i32,
+        }
+    }
+}
+
+/// A clock root divider that's between `MIN` and `MAX`, inclusive
+///
+/// The clock root modules have `Divider` aliases with the correct range, like
+/// [`uart::Divider`](crate::uart::Divider). Convert a `u32` with `TryFrom`, or
+/// with [`new`](Divider::new) in a `const` context:
+///
+/// ```
+/// use core::convert::TryFrom;
+/// use imxrt_ccm::{uart, DividerOutOfRange};
+///
+/// assert_eq!(uart::Divider::try_from(8).unwrap().get(), 8);
+/// assert_eq!(
+///     uart::Divider::try_from(65),
+///     Err(DividerOutOfRange { divider: 65, min: 1, max: 64 })
+/// );
+/// const UART_DIVIDER: uart::Divider = match uart::Divider::new(3) {
+///     Some(divider) => divider,
+///     None => panic!("UART divider out of range"),
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Divider<const MIN: u32, const MAX: u32>(u32);
+
+impl<const MIN: u32, const MAX: u32> Divider<MIN, MAX> {
+    /// The smallest divider
+    pub const MIN: Self = Divider(MIN);
+    /// The largest divider
+    pub const MAX: Self = Divider(MAX);
+
+    /// Returns a divider, or `None` if `divider` is out of range
+    pub const fn new(divider: u32) -> Option<Self> {
+        if MIN <= divider && divider <= MAX {
+            Some(Divider(divider))
+        } else {
+            None
+        }
+    }
+
+    /// Returns a divider, treating anything below `MIN` as `MIN`, and
+    /// anything above `MAX` as `MAX`
+    pub(crate) const fn saturating(divider: u32) -> Self {
+        Divider(clamp(divider, MIN, MAX))
+    }
+
+    /// Returns the divider value
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+}
+
+impl<const MIN: u32, const MAX: u32> core::convert::TryFrom<u32> for Divider<MIN, MAX> {
+    type Error = DividerOutOfRange;
+    fn try_from(divider: u32) -> Result<Self, Self::Error> {
+        Self::new(divider).ok_or(DividerOutOfRange {
+            divider,
+            min: MIN,
+            max: MAX,
+        })
+    }
+}
+
+impl<const MIN: u32, const MAX: u32> From<Divider<MIN, MAX>> for u32 {
+    fn from(divider: Divider<MIN, MAX>) -> u32 {
+        divider.0
+    }
+}
+
+/// Indicates that a clock root divider is out of range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DividerOutOfRange {
+    /// The requested divider
+    pub divider: u32,
+    /// The smallest acceptable divider
+    pub min: u32,
+    /// The largest acceptable divider
+    pub max: u32,
+}
+
+/// An error when configuring a clock root
+///
+/// Returned by the `try_` configuration methods, like
+/// [`UARTClock::try_configure_frequency`](crate::uart::UARTClock::try_configure_frequency).
+/// When a method returns an error, it made no changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockError {
+    /// The divider is out of range
+    DividerOutOfRange(DividerOutOfRange),
+    /// The selected source isn't running, or isn't available on this chip
+    SourceUnavailable,
+    /// No source and divider are within the tolerance of the requested frequency
+    FrequencyUnreachable {
+        /// The requested frequency
+        target_hz: u32,
+        /// The closest achievable frequency, or 0 if no source is running
+        closest_hz: u32,
+    },
+    /// The clock root can't be configured through this interface
+    RootUnsupported,
+}
+
+impl From<DividerOutOfRange> for ClockError {
+    fn from(err: DividerOutOfRange) -> Self {
+        ClockError::DividerOutOfRange(err)
+    }
+}
+
+/// A clock root source selection and divider, and the frequency that they
+/// produce
+///
+/// Returned by the calculations that don't need hardware, like
+/// [`uart::closest`](crate::uart::closest). Each clock root module has a
+/// `Setting` alias with its selection and divider types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RootSetting<S, D> {
+    /// The clock root source
+    pub selection: S,
+    /// The clock root divider
+    pub divider: D,
+    /// The clock root frequency, and its error relative to the target
+    pub frequency: RootFrequency,
+}
+
+/// A clock root source selection and divider
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Choice {
+    /// The clock root multiplexer value
+    pub selection: u32,
+    /// The divider, between 1 and the maximum divider
+    pub divider: u32,
+    /// The clock root frequency
+    pub hz: u32,
+}
+
+impl Choice {
+    /// Returns the achieved frequency relative to `target_hz`
+    pub fn frequency(&self, target_hz: u32) -> RootFrequency {
+        RootFrequency::exact(self.hz).relative_to(target_hz)
+    }
+
+    /// Returns the setting for this choice, with its multiplexer value
+    /// converted to `selection`
+    pub const fn setting<S, const MIN: u32, const MAX: u32>(
+        &self,
+        selection: S,
+        target_hz: u32,
+    ) -> RootSetting<S, Divider<MIN, MAX>> {
+        RootSetting {
+            selection,
+            divider: Divider::saturating(self.divider),
+            frequency: RootFrequency::exact(self.hz).relative_to(target_hz),
+        }
+    }
+
+    /// Returns the choice, or an error if it's more than `tolerance_hz` away
+    /// from `target_hz`
+    pub fn within(self, target_hz: u32, tolerance_hz: u32) -> Result<Self, ClockError> {
+        if self.hz.abs_diff(target_hz) <= tolerance_hz {
+            Ok(self)
+        } else {
+            Err(ClockError::FrequencyUnreachable {
+                target_hz,
+                closest_hz: self.hz,
+            })
+        }
+    }
+}
+
+/// Returns the source and divider that best approximate `target_hz`
+///
+/// `sources` pairs each clock root multiplexer value with its source frequency.
+/// Sources that aren't running, with a frequency of 0, are ignored. The divider
+/// is between [1, `max_divider`]. If two choices are equally close to
+/// `target_hz`, the earlier source wins. Returns `None` if no source is running.
+pub(crate) const fn closest(
+    target_hz: u32,
+    sources: &[(u32, u32)],
+    max_divider: u32,
+) -> Option<Choice> {
+    let mut best: Option<Choice> = None;
+    let mut idx = 0;
+    while idx < sources.len() {
+        let (selection, source_hz) = sources[idx];
+        idx += 1;
+        if source_hz == 0 {
+            continue;
+        }
+        // The two dividers that surround the target
+        let divider = clamp(source_hz / max(target_hz, 1), 1, max_divider);
+        let dividers = [divider, clamp(divider + 1, 1, max_divider)];
+        let mut jdx = 0;
+        while jdx < dividers.len() {
+            let divider = dividers[jdx];
+            jdx += 1;
+            let hz = source_hz / divider;
+            let closer = match &best {
+                Some(best) => hz.abs_diff(target_hz) < best.hz.abs_diff(target_hz),
+                None => true,
+            };
+            if closer {
+                best = Some(Choice {
+                    selection,
+                    divider,
+                    hz,
+                });
+            }
+        }
+    }
+    best
+}
+
+const fn max(a: u32, b: u32) -> u32 {
+    if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+const fn clamp(value: u32, min: u32, max: u32) -> u32 {
+    if value < min {
+        min
+    } else if value > max {
+        max
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{closest, Choice, ClockError, Divider, DividerOutOfRange};
+    use core::convert::TryFrom;
+
+    #[test]
+    fn divider_closest() {
+        let sources = [(1, 24_000_000), (0, 80_000_000)];
+        assert_eq!(
+            closest(8_000_000, &sources, 64),
+            Some(Choice {
+                selection: 1,
+                divider: 3,
+                hz: 8_000_000
+            })
+        );
+        assert_eq!(
+            closest(40_000_000, &sources, 64),
+            Some(Choice {
+                selection: 0,
+                divider: 2,
+                hz: 40_000_000
+            })
+        );
+        // Saturate at the largest frequency
+        assert_eq!(closest(100_000_000, &sources, 64).unwrap().hz, 80_000_000);
+        // Saturate at the smallest frequency
+        assert_eq!(closest(1, &sources, 64).unwrap().hz, 24_000_000 / 64);
+        // Round to the nearest frequency
+        assert_eq!(
+            closest(7_000_000, &[(1, 24_000_000)], 64).unwrap().divider,
+            3
+        );
+        assert_eq!(
+            closest(5_000_000, &[(1, 24_000_000)], 64).unwrap().divider,
+            5
+        );
+
+        assert_eq!(closest(8_000_000, &[(0, 0)], 64), None);
+    }
+
+    #[test]
+    fn divider_within_tolerance() {
+        let choice = closest(7_000_000, &[(1, 24_000_000)], 64).unwrap();
+        assert_eq!(choice.hz, 8_000_000);
+        assert_eq!(choice.within(7_000_000, 1_000_000).unwrap().hz, 8_000_000);
+        let choice = closest(7_000_000, &[(1, 24_000_000)], 64).unwrap();
+        assert_eq!(
+            choice.within(7_000_000, 999_999),
+            Err(ClockError::FrequencyUnreachable {
+                target_hz: 7_000_000,
+                closest_hz: 8_000_000
+            })
+        );
+    }
+
+    #[test]
+    fn divider_bounds() {
+        type D = Divider<1, 8>;
+        assert_eq!(D::new(0), None);
+        assert_eq!(D::new(1), Some(D::MIN));
+        assert_eq!(D::new(8), Some(D::MAX));
+        assert_eq!(D::new(9), None);
+        assert_eq!(u32::from(D::try_from(5).unwrap()), 5);
+        assert_eq!(
+            D::try_from(9),
+            Err(DividerOutOfRange {
+                divider: 9,
+                min: 1,
+                max: 8
+            })
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Record register writes instead of performing them
+//!
+//! Use [`record`] to run configuration calls without touching hardware. Every
+//! CCM and CCM_ANALOG register write that happens while `record` runs is
+//! stored in a buffer, as a [`Write`]. Inspect the resulting [`Plan`], compare
+//! it in a test, or [`replay`](Plan::replay) it later.
+//!
+//! ```no_run
+//! use imxrt_ccm::dryrun::{self, Write};
+//! # let mut ccm = unsafe { imxrt_ccm::CCM::<imxrt_ccm::SplitClocks>::new() };
+//!
+//! let mut buffer = [Write::default(); 32];
+//! let (_, plan) = dryrun::record(&mut buffer, || {
+//!     ccm.set_defaults();
+//! });
+//! assert!(plan.is_complete());
+//! for write in plan.writes() {
+//!     // Print the write...
+//! }
+//! ```
+//!
+//! # Limitations
+//!
+//! A read-modify-write during the recording sees the values recorded earlier
+//! in the same recording. All other reads see the hardware, which didn't
+//! change. Frequency getters return the hardware's frequencies, and calls
+//! that wait on hardware, like enabling a PLL that's off, may never return.
+//!
+//! Only one recording may be active at a time. Any CCM write that happens
+//! during the recording, including a write from an interrupt, is recorded.
+
+use core::{
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+/// A recorded register write
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Write {
+    /// The register address
+    pub address: usize,
+    /// The bits that the write intends to change
+    pub mask: u32,
+    /// The value written to the register
+    pub value: u32,
+}
+
+/// The register writes recorded by [`record`]
+#[derive(Debug, PartialEq, Eq)]
+pub struct Plan<'a> {
+    writes: &'a [Write],
+    dropped: usize,
+}
+
+impl<'a> Plan<'a> {
+    /// Returns the recorded writes, in order
+    pub fn writes(&self) -> &'a [Write] {
+        self.writes
+    }
+
+    /// Returns the number of writes that didn't fit in the buffer
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Returns `true` if the buffer held every write
+    pub fn is_complete(&self) -> bool {
+        self.dropped == 0
+    }
+
+    /// Perform the recorded writes
+    ///
+    /// # Safety
+    ///
+    /// Modifies global, mutable memory that's owned by the CCM. The writes
+    /// don't wait for any PLL lock or divider handshake.
+    pub unsafe fn replay(&self) {
+        for write in self.writes {
+            crate::register::write(write.address as *mut u32, write.mask, write.value);
+        }
+    }
+}
+
+struct Recorder<'a> {
+    buffer: &'a mut [Write],
+    len: usize,
+    dropped: usize,
+}
+
+static ACTIVE: AtomicPtr<Recorder<'static>> = AtomicPtr::new(ptr::null_mut());
+
+/// Stops the recording, even if the recorded function panics
+struct Stop;
+
+impl Drop for Stop {
+    fn drop(&mut self) {
+        ACTIVE.store(ptr::null_mut(), Ordering::Release);
+    }
+}
+
+/// Run `func`, recording CCM register writes into `buffer` instead of
+/// performing them
+///
+/// Returns the result of `func`, and the plan. Writes that don't fit in
+/// `buffer` are counted, but not recorded.
+///
+/// # Panics
+///
+/// Panics if another recording is active.
+pub fn record<R>(buffer: &mut [Write], func: impl FnOnce() -> R) -> (R, Plan<'_>) {
+    let mut recorder = Recorder {
+        buffer,
+        len: 0,
+        dropped: 0,
+    };
+    let previous = ACTIVE.swap((&mut recorder as *mut Recorder).cast(), Ordering::AcqRel);
+    if !previous.is_null() {
+        ACTIVE.store(previous, Ordering::Release);
+        panic!("Another recording is active");
+    }
+    let result = {
+        let _stop = Stop;
+        func()
+    };
+    let Recorder {
+        buffer,
+        len,
+        dropped,
+    } = recorder;
+    (
+        result,
+        Plan {
+            writes: &buffer[..len],
+            dropped,
+        },
+    )
+}
+
+/// Returns `true` if `address` is a CCM or CCM_ANALOG register
+fn is_ccm(address: usize) -> bool {
+    (0x400F_C000..0x400F_C090).contains(&address) || (0x400D_8000..0x400D_8180).contains(&address)
+}
+
+/// Returns `true` if a recording is active
+#[cfg(all(feature = "gate-cache", not(feature = "simulation")))]
+pub(crate) fn active() -> bool {
+    !ACTIVE.load(Ordering::Acquire).is_null()
+}
+
+/// Record the write, returning `true` if it shouldn't reach hardware
+pub(crate) fn intercept(address: *mut u32, mask: u32, value: u32) -> bool {
+    let recorder = ACTIVE.load(Ordering::Acquire);
+    if recorder.is_null() || !is_ccm(address as usize) {
+        return false;
+    }
+    // Safety: the recorder outlives the recording, and nothing else accesses
+    // it while the recording is active.
+    let recorder = unsafe { &mut *recorder };
+    match recorder.buffer.get_mut(recorder.len) {
+        Some(write) => {
+            *write = Write {
+                address: address as usize,
+                mask,
+                value,
+            };
+            recorder.len += 1;
+        }
+        None => recorder.dropped += 1,
+    }
+    true
+}
+
+/// Returns the last value recorded for `address`
+pub(crate) fn recorded(address: *const u32) -> Option<u32> {
+    let recorder = ACTIVE.load(Ordering::Acquire);
+    if recorder.is_null() {
+        return None;
+    }
+    // Safety: see intercept
+    let recorder = unsafe { &*recorder };
+    recorder.buffer[..recorder.len]
+        .iter()
+        .rev()
+        .find(|write| write.address == address as usize)
+        .map(|write| write.value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{record, Write};
+    use crate::raw::{cscmr1, CSCMR1};
+
+    #[test]
+    fn dryrun_record() {
+        let mut buffer = [Write::default(); 2];
+        let (result, plan) = record(&mut buffer, || unsafe {
+            CSCMR1.write(0);
+            cscmr1::FLEXSPI_PODF.write(3);
+            CSCMR1.write(1);
+            42
+        });
+        assert_eq!(result, 42);
+        assert_eq!(
+            plan.writes(),
+            &[
+                Write {
+                    address: 0x400F_C01C,
+                    mask: u32::MAX,
+                    value: 0,
+                },
+                Write {
+                    address: 0x400F_C01C,
+                    mask: 0x7 << 23,
+                    value: 3 << 23,
+                },
+            ]
+        );
+        assert_eq!(plan.dropped(), 1);
+        assert!(!plan.is_complete());
+
+        // Writes outside of the CCM still happen
+        let mut mem = 0u32;
+        let (_, plan) = record(&mut buffer, || unsafe {
+            crate::register::write(&mut mem, u32::MAX, 7)
+        });
+        assert!(plan.writes().is_empty());
+        assert_eq!(mem, 7);
+    }
+}
@@ -0,0 +1,261 @@
+//! Dynamic frequency scaling
+//!
+//! The module switches the ARM clock between a small set of operating points.
+//! Each operating point derives from a PLL that's already running, or from the
+//! 24MHz crystal oscillator. Switching between operating points only changes
+//! the glitchless peripheral clock multiplexer, the PRE_PERIPH_CLK multiplexer
+//! while the core runs on the oscillator, and the AHB and IPG dividers. It
+//! never restarts a PLL, so a switch completes in microseconds.
+//!
+//! Use [`Governor`] to select an operating point based on the core's workload.
+//!
+//! # Requirements
+//!
+//! PLL2 must be running at 528MHz. On the i.MX RT 1060, the 396MHz operating
+//! point also requires PLL2 PFD2 at its reset fraction, and the 600MHz operating
+//! point requires PLL1, divided by the ARM divider, to produce 600MHz; see
+//! [`arm::set_frequency`]. On the i.MX RT 1010, the
+//! 250MHz and 500MHz operating points require PLL6.
+//!
+//! The IPG clock changes with the operating point. Peripherals that use the IPG
+//! clock may not be aware of the new clock frequency.
+
+use crate::arm::{self, ARMClock, IPGClock, Millivolts, Source};
+
+/// An ARM clock operating point
+///
+/// Operating points are ordered from the slowest to the fastest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OperatingPoint {
+    /// 24MHz, from the crystal oscillator
+    Mhz24,
+    /// 132MHz, from PLL2
+    Mhz132,
+    /// 250MHz, from PLL6
+    #[cfg(not(feature = "imxrt1060"))]
+    Mhz250,
+    /// 396MHz, from PLL2 PFD2
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    Mhz396,
+    /// 500MHz, from PLL6
+    #[cfg(not(feature = "imxrt1060"))]
+    Mhz500,
+    /// 528MHz, from PLL2
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    Mhz528,
+    /// 600MHz, from PLL1
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    Mhz600,
+}
+
+/// All operating points, from the slowest to the fastest
+#[cfg(feature = "imxrt1060")]
+pub const OPERATING_POINTS: &[OperatingPoint] = &[
+    OperatingPoint::Mhz24,
+    OperatingPoint::Mhz132,
+    OperatingPoint::Mhz396,
+    OperatingPoint::Mhz528,
+    OperatingPoint::Mhz600,
+];
+/// All operating points, from the slowest to the fastest
+#[cfg(not(feature = "imxrt1060"))]
+pub const OPERATING_POINTS: &[OperatingPoint] = &[
+    OperatingPoint::Mhz24,
+    OperatingPoint::Mhz132,
+    OperatingPoint::Mhz250,
+    OperatingPoint::Mhz500,
+];
+
+impl OperatingPoint {
+    /// Returns the core clock source, and the AHB and IPG dividers
+    ///
+    /// A `None` source is the crystal oscillator.
+    const fn config(self) -> (Option<Source>, u32, u32) {
+        match self {
+            OperatingPoint::Mhz24 => (None, 1, 1),
+            OperatingPoint::Mhz132 => (Some(Source::Pll2), 4, 1),
+            #[cfg(not(feature = "imxrt1060"))]
+            OperatingPoint::Mhz250 => (Some(Source::Pll6), 2, 2),
+            #[cfg(feature = "imxrt1060")]
+            OperatingPoint::Mhz396 => (Some(Source::Pll2Pfd2), 1, 3),
+            #[cfg(not(feature = "imxrt1060"))]
+            OperatingPoint::Mhz500 => (Some(Source::Pll6), 1, 4),
+            #[cfg(feature = "imxrt1060")]
+            OperatingPoint::Mhz528 => (Some(Source::Pll2), 1, 4),
+            #[cfg(feature = "imxrt1060")]
+            OperatingPoint::Mhz600 => (Some(Source::Pll1), 1, 4),
+        }
+    }
+
+    /// Returns the nominal ARM clock frequency
+    pub const fn arm_hz(self) -> u32 {
+        match self {
+            OperatingPoint::Mhz24 => 24_000_000,
+            OperatingPoint::Mhz132 => 132_000_000,
+            #[cfg(not(feature = "imxrt1060"))]
+            OperatingPoint::Mhz250 => 250_000_000,
+            #[cfg(feature = "imxrt1060")]
+            OperatingPoint::Mhz396 => 396_000_000,
+            #[cfg(not(feature = "imxrt1060"))]
+            OperatingPoint::Mhz500 => 500_000_000,
+            #[cfg(feature = "imxrt1060")]
+            OperatingPoint::Mhz528 => 528_000_000,
+            #[cfg(feature = "imxrt1060")]
+            OperatingPoint::Mhz600 => 600_000_000,
+        }
+    }
+
+    /// Returns the nominal IPG clock frequency
+    pub const fn ipg_hz(self) -> u32 {
+        let (_, _, div_ipg) = self.config();
+        self.arm_hz() / div_ipg
+    }
+
+    /// Returns the VDD_SOC voltage required by this operating point
+    ///
+    /// See [`arm::voltage`] for more information.
+    pub const fn voltage(self) -> Millivolts {
+        arm::voltage(self.arm_hz())
+    }
+
+    /// Returns the next faster operating point, or `None` if this is the fastest
+    pub fn faster(self) -> Option<OperatingPoint> {
+        OPERATING_POINTS.iter().copied().find(|&point| point > self)
+    }
+
+    /// Returns the next slower operating point, or `None` if this is the slowest
+    pub fn slower(self) -> Option<OperatingPoint> {
+        OPERATING_POINTS
+            .iter()
+            .rev()
+            .copied()
+            .find(|&point| point < self)
+    }
+}
+
+/// Switch to an operating point, returning the ARM and IPG clock frequencies
+///
+/// See the [module-level documentation](self) for the operating point requirements.
+///
+/// # Safety
+///
+/// Modifies CCM peripheral memory. Users should prefer the safer
+/// [`CCM::set_operating_point`](crate::CCM::set_operating_point) method.
+pub unsafe fn set_operating_point(point: OperatingPoint) -> (ARMClock, IPGClock) {
+    let (source, div_ahb, div_ipg) = point.config();
+    arm::ARM_CONTEXT.switch_core(source, div_ahb, div_ipg);
+    arm::frequency()
+}
+
+/// Returns the current operating point, or `None` if the ARM clock doesn't run
+/// at an operating point
+pub fn operating_point() -> Option<OperatingPoint> {
+    // Safety: reads of valid registers
+    let (ARMClock(arm_hz), _) = unsafe { arm::frequency() };
+    OPERATING_POINTS
+        .iter()
+        .copied()
+        .find(|point| point.arm_hz() == arm_hz)
+}
+
+/// A workload-based operating point governor
+///
+/// The governor steps to a faster operating point when the core is busier than
+/// the upper threshold, and to a slower operating point when the core is less
+/// busy than the lower threshold. The governor only selects operating points;
+/// apply the selection with [`set_operating_point`].
+///
+/// ```
+/// use imxrt_ccm::dvfs::{Governor, OperatingPoint};
+///
+/// let mut governor = Governor::new(OperatingPoint::Mhz132);
+/// assert!(governor.update(95).is_some());
+/// assert!(governor.point() > OperatingPoint::Mhz132);
+/// assert_eq!(governor.update(50), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Governor {
+    point: OperatingPoint,
+    up_percent: u32,
+    down_percent: u32,
+}
+
+impl Governor {
+    /// Create a governor that starts at `point`
+    ///
+    /// The governor steps up above 80% load, and steps down below 30% load.
+    pub const fn new(point: OperatingPoint) -> Self {
+        Governor {
+            point,
+            up_percent: 80,
+            down_percent: 30,
+        }
+    }
+
+    /// Set the load thresholds, in percent
+    pub const fn with_thresholds(self, down_percent: u32, up_percent: u32) -> Self {
+        Governor {
+            up_percent,
+            down_percent,
+            ..self
+        }
+    }
+
+    /// Returns the selected operating point
+    pub const fn point(&self) -> OperatingPoint {
+        self.point
+    }
+
+    /// Update the governor with the core's load, in percent
+    ///
+    /// Returns the new operating point if the governor selects a different
+    /// operating point.
+    pub fn update(&mut self, load_percent: u32) -> Option<OperatingPoint> {
+        let next = if load_percent > self.up_percent {
+            self.point.faster()
+        } else if load_percent < self.down_percent {
+            self.point.slower()
+        } else {
+            None
+        }?;
+        self.point = next;
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Governor, OperatingPoint, OPERATING_POINTS};
+
+    #[test]
+    fn dvfs_operating_points() {
+        assert!(OPERATING_POINTS.windows(2).all(|w| w[0] < w[1]));
+        for point in OPERATING_POINTS {
+            assert!(point.ipg_hz() <= 150_000_000);
+        }
+        assert_eq!(OperatingPoint::Mhz24.slower(), None);
+        assert_eq!(OperatingPoint::Mhz24.faster(), Some(OperatingPoint::Mhz132));
+        let fastest = *OPERATING_POINTS.last().unwrap();
+        assert_eq!(fastest.faster(), None);
+    }
+
+    #[test]
+    fn dvfs_governor() {
+        let mut governor = Governor::new(OperatingPoint::Mhz24);
+        assert_eq!(governor.update(10), None);
+        assert_eq!(governor.update(50), None);
+        assert_eq!(governor.update(90), Some(OperatingPoint::Mhz132));
+        for _ in 0..OPERATING_POINTS.len() {
+            governor.update(100);
+        }
+        assert_eq!(governor.point(), *OPERATING_POINTS.last().unwrap());
+        assert_eq!(governor.update(100), None);
+
+        let mut governor = Governor::new(OperatingPoint::Mhz132).with_thresholds(10, 20);
+        assert_eq!(governor.update(15), None);
+        assert_eq!(governor.update(5), Some(OperatingPoint::Mhz24));
+    }
+}
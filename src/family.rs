@@ -0,0 +1,315 @@
+//! A clock control interface that's common to the i.MX RT families
+//!
+//! The 10xx and 11xx CCMs locate clock gates and clock roots differently, but
+//! drivers need the same few operations: turn on a clock gate, and query a
+//! clock root frequency. [`ClockControl`] describes those operations. The 10xx
+//! [`CCM`] implements `ClockControl`, with [`ClockGateLocation`]
+//! gates and [`ClockRoot`] roots. When you enable an 11xx chip feature, the
+//! `imxrt11xx::CCM` implements `ClockControl`, too.
+//!
+//! Write your driver against `ClockControl`, and let the caller supply the
+//! gate and root for the peripheral:
+//!
+//! ```no_run
+//! use imxrt_ccm::{family::ClockControl, uart::UART, ClockGateLocator, ClockRoot};
+//!
+//! fn enable_uart<C: ClockControl>(ccm: &mut C, gate: C::Gate, root: C::Root) -> Option<u32> {
+//!     ccm.set_clock_gate_enabled(gate, true);
+//!     ccm.root_frequency(root)
+//! }
+//!
+//! let mut ccm = unsafe { imxrt_ccm::CCM::<imxrt_ccm::SplitClocks>::new() };
+//! let uart_hz = enable_uart(&mut ccm, UART::UART2.location(), ClockRoot::UART);
+//! ```
+//!
+//! In your driver's tests, use a [`MockClockControl`] in place of the CCM. The
+//! mock keeps the clock gates and clock root frequencies in memory, so you can
+//! check which gates your driver turned on:
+//!
+//! ```
+//! use imxrt_ccm::{
+//!     family::{ClockControl, MockClockControl},
+//!     uart::UART,
+//!     ClockGateLocator, ClockRoot,
+//! };
+//! # fn enable_uart<C: ClockControl>(ccm: &mut C, gate: C::Gate, root: C::Root) -> Option<u32> {
+//! #     ccm.set_clock_gate_enabled(gate, true);
+//! #     ccm.root_frequency(root)
+//! # }
+//!
+//! let mut ccm: MockClockControl = MockClockControl::new();
+//! ccm.set_source_frequency(ClockRoot::UART, 24_000_000);
+//!
+//! let uart_hz = enable_uart(&mut ccm, UART::UART2.location(), ClockRoot::UART);
+//! assert_eq!(uart_hz, Some(24_000_000));
+//! assert!(ccm.clock_gate_enabled(UART::UART2.location()));
+//! assert!(!ccm.clock_gate_enabled(UART::UART3.location()));
+//! ```
+
+use crate::{
+    gate, i2c, perclock, spi, uart, ClockError, ClockGate, ClockGateLocation, ClockRoot, Clocks,
+    DividerOutOfRange, CCM,
+};
+use core::convert::TryFrom;
+
+/// Clock gate control, clock root configuration, and frequency queries
+pub trait ClockControl {
+    /// Identifies a clock gate
+    type Gate: Copy;
+    /// Identifies a clock root
+    type Root: Copy;
+
+    /// Turn the clock gate on or off
+    fn set_clock_gate_enabled(&mut self, gate: Self::Gate, enabled: bool);
+
+    /// Returns `true` if the clock gate is on in run mode
+    fn clock_gate_enabled(&self, gate: Self::Gate) -> bool;
+
+    /// Divide the source of `root` by `divider`, leaving the source unchanged
+    ///
+    /// `set_root_divider` doesn't change any clock gates. Turn off the gates
+    /// of the peripherals that use `root` before you change the divider.
+    /// Returns an error, and makes no changes, if the divider is out of range,
+    /// or if the implementation can't configure `root`.
+    fn set_root_divider(&mut self, root: Self::Root, divider: u32) -> Result<(), ClockError>;
+
+    /// Returns the frequency of `root`, or `None` if the implementation
+    /// can't compute it
+    fn root_frequency(&self, root: Self::Root) -> Option<u32>;
+}
+
+/// The 10xx CCM
+///
+/// `set_root_divider` configures the UART, SPI, I2C, and periodic clock roots.
+/// It returns [`ClockError::RootUnsupported`] for all other roots.
+impl<C: Clocks> ClockControl for CCM<C> {
+    type Gate = ClockGateLocation;
+    type Root = ClockRoot;
+
+    #[inline(always)]
+    fn set_clock_gate_enabled(&mut self, location: ClockGateLocation, enabled: bool) {
+        let gate = if enabled {
+            ClockGate::On
+        } else {
+            ClockGate::Off
+        };
+        // Safety: we own the CCM peripheral memory
+        unsafe { gate::set(&location, gate as u8) }
+    }
+
+    #[inline(always)]
+    fn clock_gate_enabled(&self, location: ClockGateLocation) -> bool {
+        gate::get(&location) != ClockGate::Off as u8
+    }
+
+    fn set_root_divider(&mut self, root: ClockRoot, divider: u32) -> Result<(), ClockError> {
+        // Safety: we own the CCM peripheral memory
+        unsafe {
+            match root {
+                ClockRoot::UART => uart::set_divider(uart::Divider::try_from(divider)?.get()),
+                ClockRoot::SPI => spi::set_divider(spi::Divider::try_from(divider)?.get()),
+                ClockRoot::I2C => i2c::set_divider(i2c::Divider::try_from(divider)?.get()),
+                ClockRoot::PerClock => {
+                    perclock::set_divider(perclock::Divider::try_from(divider)?.get())
+                }
+                _ => return Err(ClockError::RootUnsupported),
+            }
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn root_frequency(&self, root: ClockRoot) -> Option<u32> {
+        Some(CCM::root_frequency(self, root))
+    }
+}
+
+/// A [`ClockControl`] that keeps clock gates and clock roots in memory
+///
+/// Use `MockClockControl` to test a driver that's generic over
+/// `ClockControl`, without hardware. The defaults mock the 10xx CCM; supply
+/// other `G` and `R` types to mock the 11xx CCM, or your own `ClockControl`.
+///
+/// - A clock gate is off until the driver turns it on.
+/// - A clock root has no frequency until you call
+///   [`set_source_frequency`](MockClockControl::set_source_frequency). Its
+///   frequency is the source frequency, divided by the last divider that the
+///   driver set.
+///
+/// The mock holds up to `N` clock gates and `N` clock roots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockClockControl<G = ClockGateLocation, R = ClockRoot, const N: usize = 16> {
+    gates: [Option<(G, bool)>; N],
+    roots: [Option<MockRoot<R>>; N],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct MockRoot<R> {
+    root: R,
+    source_hz: u32,
+    divider: u32,
+}
+
+impl<G: Copy + PartialEq, R: Copy + PartialEq, const N: usize> MockClockControl<G, R, N> {
+    /// Create a mock with every clock gate off, and no clock roots
+    pub const fn new() -> Self {
+        MockClockControl {
+            gates: [None; N],
+            roots: [None; N],
+        }
+    }
+
+    /// Set the frequency of the source that drives `root`, and reset the
+    /// root's divider to 1
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mock already holds `N` other clock roots.
+    pub fn set_source_frequency(&mut self, root: R, source_hz: u32) {
+        let mock = MockRoot {
+            root,
+            source_hz,
+            divider: 1,
+        };
+        *slot(&mut self.roots, |mock| mock.root == root) = Some(mock);
+    }
+
+    /// Returns the divider of `root`, or `None` if `root` has no source
+    /// frequency
+    pub fn root_divider(&self, root: R) -> Option<u32> {
+        self.root(root).map(|mock| mock.divider)
+    }
+
+    fn root(&self, root: R) -> Option<&MockRoot<R>> {
+        self.roots.iter().flatten().find(|mock| mock.root == root)
+    }
+}
+
+impl<G: Copy + PartialEq, R: Copy + PartialEq, const N: usize> Default
+    for MockClockControl<G, R, N>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the slot that holds the element matching `find`, or the first empty
+/// slot
+///
+/// Panics if there's no matching element, and no empty slot.
+fn slot<T>(slots: &mut [Option<T>], find: impl Fn(&T) -> bool) -> &mut Option<T> {
+    let idx = slots
+        .iter()
+        .position(|slot| slot.as_ref().is_some_and(&find))
+        .or_else(|| slots.iter().position(Option::is_none))
+        .expect("MockClockControl is full");
+    &mut slots[idx]
+}
+
+impl<G: Copy + PartialEq, R: Copy + PartialEq, const N: usize> ClockControl
+    for MockClockControl<G, R, N>
+{
+    type Gate = G;
+    type Root = R;
+
+    /// # Panics
+    ///
+    /// Panics if the mock already holds `N` other clock gates.
+    fn set_clock_gate_enabled(&mut self, gate: G, enabled: bool) {
+        *slot(&mut self.gates, |(other, _)| *other == gate) = Some((gate, enabled));
+    }
+
+    fn clock_gate_enabled(&self, gate: G) -> bool {
+        self.gates
+            .iter()
+            .flatten()
+            .any(|&(other, enabled)| other == gate && enabled)
+    }
+
+    /// Returns [`ClockError::RootUnsupported`] if `root` has no source
+    /// frequency, and [`ClockError::DividerOutOfRange`] if `divider` is zero.
+    fn set_root_divider(&mut self, root: R, divider: u32) -> Result<(), ClockError> {
+        if divider == 0 {
+            return Err(ClockError::DividerOutOfRange(DividerOutOfRange {
+                divider,
+                min: 1,
+                max: u32::MAX,
+            }));
+        }
+        let mock = self
+            .roots
+            .iter_mut()
+            .flatten()
+            .find(|mock| mock.root == root)
+            .ok_or(ClockError::RootUnsupported)?;
+        mock.divider = divider;
+        Ok(())
+    }
+
+    fn root_frequency(&self, root: R) -> Option<u32> {
+        self.root(root).map(|mock| mock.source_hz / mock.divider)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClockControl, MockClockControl};
+    use crate::{ClockError, ClockRoot, DividerOutOfRange, SplitClocks, CCM};
+
+    #[test]
+    fn family_divider_checks() {
+        // Neither call touches a register
+        let mut ccm = unsafe { CCM::<SplitClocks>::new() };
+        assert_eq!(
+            ccm.set_root_divider(ClockRoot::AHB, 2),
+            Err(ClockError::RootUnsupported)
+        );
+        assert_eq!(
+            ccm.set_root_divider(ClockRoot::UART, 65),
+            Err(ClockError::DividerOutOfRange(DividerOutOfRange {
+                divider: 65,
+                min: 1,
+                max: 64
+            }))
+        );
+    }
+
+    #[test]
+    fn family_mock() {
+        let mut ccm: MockClockControl<u8, ClockRoot, 2> = MockClockControl::new();
+        assert!(!ccm.clock_gate_enabled(7));
+        ccm.set_clock_gate_enabled(7, true);
+        ccm.set_clock_gate_enabled(3, false);
+        assert!(ccm.clock_gate_enabled(7));
+        assert!(!ccm.clock_gate_enabled(3));
+        ccm.set_clock_gate_enabled(7, false);
+        assert!(!ccm.clock_gate_enabled(7));
+
+        assert_eq!(ccm.root_frequency(ClockRoot::SPI), None);
+        assert_eq!(
+            ccm.set_root_divider(ClockRoot::SPI, 2),
+            Err(ClockError::RootUnsupported)
+        );
+        ccm.set_source_frequency(ClockRoot::SPI, 528_000_000);
+        ccm.set_root_divider(ClockRoot::SPI, 8).unwrap();
+        assert_eq!(ccm.root_divider(ClockRoot::SPI), Some(8));
+        assert_eq!(ccm.root_frequency(ClockRoot::SPI), Some(66_000_000));
+        assert!(matches!(
+            ccm.set_root_divider(ClockRoot::SPI, 0),
+            Err(ClockError::DividerOutOfRange(_))
+        ));
+
+        // A new source frequency resets the divider
+        ccm.set_source_frequency(ClockRoot::SPI, 480_000_000);
+        assert_eq!(ccm.root_frequency(ClockRoot::SPI), Some(480_000_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "MockClockControl is full")]
+    fn family_mock_full() {
+        let mut ccm: MockClockControl<u8, ClockRoot, 2> = MockClockControl::new();
+        for gate in 0..3 {
+            ccm.set_clock_gate_enabled(gate, true);
+        }
+    }
+}
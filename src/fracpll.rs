@@ -0,0 +1,268 @@
+//! Fractional PLL helpers
+//!
+//! The audio PLL (PLL4) and the video PLL (PLL5) share a register layout. Each
+//! PLL has a control register, a numerator register, and a denominator register.
+//! Each PLL's post divider combines the control register's `POST_DIV_SELECT`
+//! field with a two-bit divider in CCM_ANALOG_MISC2.
+
+use crate::{register::Field, xtalosc::reference_frequency};
+
+const DIV_SELECT: Field = Field::new(0, 0x7F);
+const POWERDOWN: Field = Field::new(12, 1);
+const ENABLE: Field = Field::new(13, 1);
+const BYPASS_CLK_SRC: Field = Field::new(14, 0b11);
+pub(crate) const BYPASS: Field = Field::new(16, 1);
+const POST_DIV_SELECT: Field = Field::new(19, 0b11);
+const LOCK: Field = Field::new(31, 1);
+const MFN: Field = Field::new(0, 0x3FFF_FFFF);
+
+/// The smallest loop divider
+pub const DIV_SELECT_MIN: u32 = 27;
+/// The largest loop divider
+pub const DIV_SELECT_MAX: u32 = 54;
+
+/// The PLL post divider
+///
+/// The post divider divides the PLL VCO frequency to produce
+/// the PLL output frequency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostDivider {
+    Divide1,
+    Divide2,
+    Divide4,
+    Divide8,
+    Divide16,
+}
+
+impl PostDivider {
+    /// Returns the divider value
+    pub const fn divider(self) -> u32 {
+        match self {
+            PostDivider::Divide1 => 1,
+            PostDivider::Divide2 => 2,
+            PostDivider::Divide4 => 4,
+            PostDivider::Divide8 => 8,
+            PostDivider::Divide16 => 16,
+        }
+    }
+
+    /// Returns the `POST_DIV_SELECT` and MISC2 divider field values
+    const fn fields(self) -> (u32, u32) {
+        match self {
+            PostDivider::Divide1 => (0b10, 0b00),
+            PostDivider::Divide2 => (0b01, 0b00),
+            PostDivider::Divide4 => (0b00, 0b00),
+            PostDivider::Divide8 => (0b00, 0b01),
+            PostDivider::Divide16 => (0b00, 0b11),
+        }
+    }
+
+    /// Returns the total divider described by the `POST_DIV_SELECT` and
+    /// MISC2 divider fields
+    pub(crate) const fn from_fields(post_div_select: u32, misc2_div: u32) -> u32 {
+        let post = match post_div_select {
+            0b00 => 4,
+            0b01 => 2,
+            _ => 1,
+        };
+        let misc2 = match misc2_div {
+            0b01 => 2,
+            0b11 => 4,
+            _ => 1,
+        };
+        post * misc2
+    }
+}
+
+/// A fractional PLL configuration
+///
+/// The configuration describes the output frequency
+///
+/// ```text
+/// OSC * (div_select + numerator / denominator) / post_divider
+/// ```
+///
+/// `numerator` must be less than `denominator`, and both must fit in 30 bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    /// Loop divider, between [27, 54]
+    pub div_select: u32,
+    /// Fractional loop divider numerator
+    pub numerator: u32,
+    /// Fractional loop divider denominator
+    pub denominator: u32,
+    /// Post divider
+    pub post_divider: PostDivider,
+}
+
+impl Config {
+    /// Compute a configuration that produces `hz`
+    ///
+    /// `from_frequency` selects the largest post divider that keeps the PLL
+    /// VCO in range, then computes an exact fractional loop divider. Returns
+    /// `None` if `hz` cannot be produced by the PLL.
+    pub fn from_frequency(hz: u32) -> Option<Config> {
+        const DIVIDERS: [PostDivider; 5] = [
+            PostDivider::Divide16,
+            PostDivider::Divide8,
+            PostDivider::Divide4,
+            PostDivider::Divide2,
+            PostDivider::Divide1,
+        ];
+        let osc = reference_frequency() as u64;
+        let (min, max) = (osc * DIV_SELECT_MIN as u64, osc * DIV_SELECT_MAX as u64);
+        DIVIDERS.iter().find_map(|&post_divider| {
+            let vco = hz as u64 * post_divider.divider() as u64;
+            if vco < min || vco >= max {
+                return None;
+            }
+            let div_select = (vco / osc) as u32;
+            let remainder = (vco % osc) as u32;
+            let gcd = gcd(remainder, osc as u32);
+            Some(Config {
+                div_select,
+                numerator: remainder / gcd,
+                denominator: osc as u32 / gcd,
+                post_divider,
+            })
+        })
+    }
+
+    /// Returns the output frequency described by this configuration
+    pub fn frequency(&self) -> u32 {
+        compute_frequency(
+            self.div_select,
+            self.numerator,
+            self.denominator,
+            self.post_divider.divider(),
+        )
+    }
+}
+
+const fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+fn compute_frequency(div_select: u32, num: u32, denom: u32, post_divider: u32) -> u32 {
+    let osc = reference_frequency() as u64;
+    let fraction = (osc * num as u64).checked_div(denom as u64).unwrap_or(0);
+    ((osc * div_select as u64 + fraction) / post_divider.max(1) as u64) as u32
+}
+
+/// Fractional PLL registers
+pub(crate) struct Registers {
+    pub pll: *mut u32,
+    pub num: *mut u32,
+    pub denom: *mut u32,
+    pub misc2: *mut u32,
+    /// The low bit of the MISC2 divider
+    pub misc2_lsb: Field,
+    /// The high bit of the MISC2 divider
+    pub misc2_msb: Field,
+}
+
+impl Registers {
+    /// Configure and enable the PLL, leaving it bypassed
+    ///
+    /// `div_select` is clamped between [27, 54].
+    pub unsafe fn configure(&self, config: &Config) {
+        let (post_div_select, misc2_div) = config.post_divider.fields();
+        BYPASS.modify(self.pll, 1);
+        BYPASS_CLK_SRC.modify(self.pll, 0); // 24MHz oscillator
+        MFN.modify(self.num, config.numerator);
+        MFN.modify(self.denom, config.denominator);
+        DIV_SELECT.modify(
+            self.pll,
+            config.div_select.clamp(DIV_SELECT_MIN, DIV_SELECT_MAX),
+        );
+        POST_DIV_SELECT.modify(self.pll, post_div_select);
+        self.misc2_lsb.modify(self.misc2, misc2_div & 1);
+        self.misc2_msb.modify(self.misc2, misc2_div >> 1);
+        POWERDOWN.modify(self.pll, 0);
+        ENABLE.modify(self.pll, 1);
+    }
+
+    /// Power down and disable the PLL
+    pub unsafe fn disable(&self) {
+        ENABLE.modify(self.pll, 0);
+        POWERDOWN.modify(self.pll, 1);
+    }
+
+    /// Returns `true` if the PLL is powered, enabled, and locked
+    pub unsafe fn is_enabled(&self) -> bool {
+        POWERDOWN.read(self.pll) == 0 && ENABLE.read(self.pll) != 0 && LOCK.read(self.pll) != 0
+    }
+
+    /// Returns the PLL frequency
+    ///
+    /// Returns 0 if the PLL is powered down, or if its output is disabled. If the
+    /// PLL is bypassed, returns the crystal oscillator frequency.
+    pub unsafe fn frequency(&self) -> u32 {
+        if POWERDOWN.read(self.pll) != 0 || ENABLE.read(self.pll) == 0 {
+            return 0;
+        }
+        if BYPASS.read(self.pll) != 0 {
+            return reference_frequency();
+        }
+        let misc2_div = self.misc2_msb.read(self.misc2) << 1 | self.misc2_lsb.read(self.misc2);
+        let post_divider = PostDivider::from_fields(POST_DIV_SELECT.read(self.pll), misc2_div);
+        compute_frequency(
+            DIV_SELECT.read(self.pll),
+            MFN.read(self.num),
+            MFN.read(self.denom),
+            post_divider,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, PostDivider, Registers, BYPASS};
+    use crate::register::Field;
+
+    #[test]
+    fn fracpll_split_divider() {
+        let (mut pll, mut num, mut denom, mut misc2) = (0x1_1000u32, 0, 0, 0);
+        let regs = Registers {
+            pll: &mut pll,
+            num: &mut num,
+            denom: &mut denom,
+            misc2: &mut misc2,
+            misc2_lsb: Field::new(15, 1),
+            misc2_msb: Field::new(23, 1),
+        };
+        let config = Config {
+            div_select: 32,
+            numerator: 96,
+            denominator: 125,
+            post_divider: PostDivider::Divide16,
+        };
+        unsafe {
+            regs.configure(&config);
+            BYPASS.modify(regs.pll, 0);
+            assert_eq!(regs.misc2.read(), 1 << 23 | 1 << 15);
+            assert_eq!(regs.frequency(), 49_152_000);
+
+            regs.configure(&Config {
+                post_divider: PostDivider::Divide8,
+                ..config
+            });
+            BYPASS.modify(regs.pll, 0);
+            assert_eq!(regs.misc2.read(), 1 << 15);
+            assert_eq!(regs.frequency(), 98_304_000);
+
+            regs.configure(&Config {
+                post_divider: PostDivider::Divide1,
+                ..config
+            });
+            BYPASS.modify(regs.pll, 0);
+            assert_eq!(regs.misc2.read(), 0);
+            assert_eq!(regs.frequency(), 786_432_000);
+        }
+    }
+}
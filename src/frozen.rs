@@ -0,0 +1,144 @@
+//! Frozen clock frequencies
+
+use crate::{
+    arm::{ARMClock, IPGClock},
+    i2c, perclock, spi, uart, ClockFor, ClockRoot, FrequencyMap, ADC, DCDC, DMA, PWM,
+};
+
+/// The final clock frequencies of a frozen CCM
+///
+/// Use [`CCM::freeze`](crate::CCM::freeze) to end clock configuration, and
+/// to capture the frequencies. `freeze` consumes the CCM, so no safe code can
+/// change a frequency after you have a `FrozenClocks`. Hand it to drivers by
+/// value:
+///
+/// ```no_run
+/// use imxrt_ccm::{uart::UART, ClockFor, FrozenClocks};
+/// # let mut ccm = unsafe { imxrt_ccm::CCM::<imxrt_ccm::SplitClocks>::new() };
+///
+/// struct Driver {
+///     baud_divider: u32,
+/// }
+///
+/// impl Driver {
+///     fn new(clocks: FrozenClocks, baud: u32) -> Self {
+///         Driver {
+///             baud_divider: ClockFor::<UART>::frequency(&clocks) / baud,
+///         }
+///     }
+/// }
+///
+/// ccm.set_frequency_arm(600_000_000);
+/// let clocks = ccm.freeze();
+/// let driver = Driver::new(clocks, 115_200);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FrozenClocks {
+    arm: u32,
+    ipg: u32,
+    perclock: u32,
+    uart: u32,
+    spi: u32,
+    i2c: u32,
+}
+
+impl FrozenClocks {
+    /// Freeze the frequencies in `map`
+    pub(crate) fn new(map: &FrequencyMap) -> Self {
+        FrozenClocks {
+            arm: map.root(ClockRoot::AHB),
+            ipg: map.root(ClockRoot::IPG),
+            perclock: map.root(ClockRoot::PerClock),
+            uart: map.root(ClockRoot::UART),
+            spi: map.root(ClockRoot::SPI),
+            i2c: map.root(ClockRoot::I2C),
+        }
+    }
+
+    /// Returns the ARM clock frequency
+    #[inline(always)]
+    pub const fn arm(&self) -> ARMClock {
+        ARMClock(self.arm)
+    }
+
+    /// Returns the IPG clock frequency
+    #[inline(always)]
+    pub const fn ipg(&self) -> IPGClock {
+        IPGClock(self.ipg)
+    }
+
+    /// Returns the periodic clock frequency, in Hz
+    #[inline(always)]
+    pub const fn perclock(&self) -> u32 {
+        self.perclock
+    }
+
+    /// Returns the UART clock frequency, in Hz
+    #[inline(always)]
+    pub const fn uart(&self) -> u32 {
+        self.uart
+    }
+
+    /// Returns the SPI clock frequency, in Hz
+    #[inline(always)]
+    pub const fn spi(&self) -> u32 {
+        self.spi
+    }
+
+    /// Returns the I2C clock frequency, in Hz
+    #[inline(always)]
+    pub const fn i2c(&self) -> u32 {
+        self.i2c
+    }
+}
+
+macro_rules! clock_for {
+    ($($inst:ty => $field:ident,)+) => {
+        $(
+            impl ClockFor<$inst> for FrozenClocks {
+                #[inline(always)]
+                fn frequency(&self) -> u32 {
+                    self.$field
+                }
+            }
+        )+
+    };
+}
+
+clock_for! {
+    uart::UART => uart,
+    spi::SPI => spi,
+    i2c::I2C => i2c,
+    perclock::GPT => perclock,
+    perclock::PIT => perclock,
+    ADC => ipg,
+    PWM => ipg,
+    DCDC => ipg,
+    DMA => arm,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FrozenClocks;
+    use crate::{i2c::I2C, raw, tree::Tree, ClockFor, ClockRoot, FrequencyMap, DMA};
+
+    #[test]
+    fn frozen_frequencies() {
+        // LPI2C_CLK_SEL selects the oscillator; LPI2C_CLK_PODF divides by 3
+        let tree = Tree::new(|reg| {
+            if reg == raw::CSCDR2 {
+                1 << 18 | 2 << 19
+            } else {
+                0
+            }
+        });
+        let map = FrequencyMap::new(&tree);
+        let clocks = FrozenClocks::new(&map);
+        assert_eq!(clocks.i2c(), 8_000_000);
+        assert_eq!(ClockFor::<I2C>::frequency(&clocks), 8_000_000);
+        assert_eq!(clocks.uart(), map.root(ClockRoot::UART));
+        assert_eq!((clocks.arm(), clocks.ipg()), map.arm());
+        assert_eq!(ClockFor::<DMA>::frequency(&clocks), clocks.arm().0);
+    }
+}
@@ -1,32 +1,359 @@
 //! Clock gate control
 
 use super::ClockGateLocation;
+use crate::{
+    i2c::I2C,
+    perclock::{GPT, PIT},
+    register,
+    spi::SPI,
+    uart::UART,
+    usb::USB,
+    ClockGateLocator, ADC, CCGR_COUNT, DCDC, DMA, PWM,
+};
 
 const MASK: u32 = 0b11;
 const CCGR_BASE: *mut u32 = 0x400F_C068 as *mut u32;
 
+/// Runs `func` in a critical section, if the `"critical-section"` feature
+/// is enabled
+#[inline(always)]
+pub(crate) fn locked<R>(func: impl FnOnce() -> R) -> R {
+    #[cfg(feature = "critical-section")]
+    {
+        critical_section::with(|_| func())
+    }
+    #[cfg(not(feature = "critical-section"))]
+    {
+        func()
+    }
+}
+
+/// RAM copies of the CCGR registers
+///
+/// The cache is empty until the first clock gate access. Every clock gate
+/// change updates both the register and the cache. A simulation doesn't use
+/// the cache, since each thread simulates its own registers.
+#[cfg(all(feature = "gate-cache", not(feature = "simulation")))]
+mod cache {
+    use crate::CCGR_COUNT;
+    use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+    #[allow(clippy::declare_interior_mutable_const)] // Only used to initialize CCGR
+    const EMPTY: AtomicU32 = AtomicU32::new(0);
+    static CCGR: [AtomicU32; CCGR_COUNT] = [EMPTY; CCGR_COUNT];
+    static VALID: AtomicBool = AtomicBool::new(false);
+
+    /// Returns the cached register at `offset`, filling the cache from `base`
+    /// if it's empty
+    #[inline(always)]
+    pub unsafe fn load(base: *const u32, offset: usize) -> u32 {
+        if !VALID.load(Ordering::Relaxed) {
+            sync(base);
+        }
+        CCGR[offset].load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    pub fn store(offset: usize, value: u32) {
+        // A dry run doesn't change the registers
+        #[cfg(feature = "dry-run")]
+        if crate::dryrun::active() {
+            return;
+        }
+        CCGR[offset].store(value, Ordering::Relaxed);
+    }
+
+    /// Fill the cache from the registers at `base`
+    pub unsafe fn sync(base: *const u32) {
+        for (idx, reg) in CCGR.iter().enumerate() {
+            reg.store(crate::register::load(base.add(idx)), Ordering::Relaxed);
+        }
+        VALID.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Without a cache, every access reads the register
+#[cfg(not(all(feature = "gate-cache", not(feature = "simulation"))))]
+mod cache {
+    #[inline(always)]
+    pub unsafe fn load(base: *const u32, offset: usize) -> u32 {
+        crate::register::load(base.add(offset))
+    }
+
+    #[inline(always)]
+    pub fn store(_: usize, _: u32) {}
+
+    #[inline(always)]
+    pub unsafe fn sync(_: *const u32) {}
+}
+
+/// Refill the clock gate cache from the CCGR registers
+///
+/// Does nothing if the `"gate-cache"` feature is disabled.
+///
 /// # Safety
 ///
-/// Modifies global, mutable memory. The read-modify-write operation is not
-/// atomic.
+/// Reads global, mutable memory. The cache may be incoherent if a clock
+/// gate changes while this runs, unless the `"critical-section"` feature is
+/// enabled.
 #[inline(always)]
+pub unsafe fn sync() {
+    locked(|| cache::sync(CCGR_BASE))
+}
+
+/// # Safety
+///
+/// Modifies global, mutable memory. The read-modify-write operation is not
+/// atomic, unless the `"critical-section"` feature is enabled.
 pub unsafe fn set(location: &ClockGateLocation, value: u8) {
-    let ccgr = CCGR_BASE.add(location.offset);
-    let mut register = ccgr.read_volatile();
-    for gate in location.gates {
-        let shift: usize = gate * 2;
-        register &= !(MASK << shift);
-        register |= (MASK & (value as u32)) << shift;
-    }
-    ccgr.write_volatile(register);
+    locked(|| {
+        let ccgr = CCGR_BASE.add(location.offset);
+        let mut register =
+            register::recorded(ccgr).unwrap_or_else(|| cache::load(CCGR_BASE, location.offset));
+        let mut mask = 0;
+        for gate in location.gates {
+            let shift: usize = gate * 2;
+            mask |= MASK << shift;
+            register &= !(MASK << shift);
+            register |= (MASK & (value as u32)) << shift;
+        }
+        register::write(ccgr, mask, register);
+        cache::store(location.offset, register);
+    })
 }
 
-#[inline(always)]
 pub fn get(location: &ClockGateLocation) -> u8 {
-    // Safety: pointer in range
-    let ccgr = unsafe { CCGR_BASE.add(location.offset) };
-    // Safety: pointer valid
-    let register = unsafe { ccgr.read_volatile() };
+    // Safety: pointer in range, valid for reads
+    let register = unsafe { cache::load(CCGR_BASE, location.offset) };
     let shift = location.gates[0] * 2;
     ((register >> shift) & MASK) as u8
 }
+
+/// Returns the values of all CCGR registers
+#[inline(always)]
+pub fn snapshot() -> [u32; CCGR_COUNT] {
+    // Safety: pointer in range, valid for reads
+    unsafe { snapshot_(CCGR_BASE) }
+}
+
+unsafe fn snapshot_(base: *const u32) -> [u32; CCGR_COUNT] {
+    let mut ccgr = [0; CCGR_COUNT];
+    for (idx, reg) in ccgr.iter_mut().enumerate() {
+        *reg = register::load(base.add(idx));
+    }
+    ccgr
+}
+
+/// Write all CCGR registers
+///
+/// # Safety
+///
+/// Modifies global, mutable memory.
+pub unsafe fn restore(ccgr: &[u32; CCGR_COUNT]) {
+    locked(|| {
+        restore_(CCGR_BASE, ccgr);
+        cache::sync(CCGR_BASE);
+    })
+}
+
+unsafe fn restore_(base: *mut u32, ccgr: &[u32; CCGR_COUNT]) {
+    for (idx, reg) in ccgr.iter().enumerate() {
+        register::write(base.add(idx), u32::MAX, *reg);
+    }
+}
+
+/// Set all clock gates to `value`, except for the clock gates in any of the
+/// `exclude` lists
+///
+/// # Safety
+///
+/// Modifies global, mutable memory. The read-modify-write operation is not
+/// atomic, unless the `"critical-section"` feature is enabled.
+pub unsafe fn set_all(value: u8, exclude: &[&[ClockGateLocation]]) {
+    locked(|| {
+        set_all_(CCGR_BASE, value, exclude);
+        cache::sync(CCGR_BASE);
+    })
+}
+
+unsafe fn set_all_(base: *mut u32, value: u8, exclude: &[&[ClockGateLocation]]) {
+    for offset in 0..CCGR_COUNT {
+        let keep = exclude
+            .iter()
+            .flat_map(|locations| locations.iter())
+            .filter(|location| location.offset == offset)
+            .flat_map(|location| location.gates)
+            .fold(0u32, |keep, gate| keep | MASK << (gate * 2));
+        let fill = u32::from_ne_bytes([(value & 0b11) * 0b0101_0101; 4]);
+
+        let ccgr = base.add(offset);
+        let register = register::read(ccgr);
+        register::write(ccgr, !keep, (register & keep) | (fill & !keep));
+    }
+}
+
+/// A peripheral instance identifier that's modeled by this crate
+#[derive(Clone, Copy)]
+pub enum Known {
+    Adc(ADC),
+    Dcdc,
+    Dma,
+    Gpt(GPT),
+    I2c(I2C),
+    Pit,
+    Pwm(PWM),
+    Spi(SPI),
+    Uart(UART),
+    Usb(USB),
+}
+
+impl Known {
+    pub fn location(&self) -> ClockGateLocation {
+        match self {
+            Known::Adc(adc) => adc.location(),
+            Known::Dcdc => DCDC.location(),
+            Known::Dma => DMA.location(),
+            Known::Gpt(gpt) => gpt.location(),
+            Known::I2c(i2c) => i2c.location(),
+            Known::Pit => PIT.location(),
+            Known::Pwm(pwm) => pwm.location(),
+            Known::Spi(spi) => spi.location(),
+            Known::Uart(uart) => uart.location(),
+            Known::Usb(usb) => usb.location(),
+        }
+    }
+}
+
+/// All clock gates that are available on every i.MX RT chip
+const COMMON: &[(&str, Known)] = &[
+    ("ADC1", Known::Adc(ADC::ADC1)),
+    ("DCDC", Known::Dcdc),
+    ("DMA", Known::Dma),
+    ("GPT1", Known::Gpt(GPT::GPT1)),
+    ("GPT2", Known::Gpt(GPT::GPT2)),
+    ("LPI2C1", Known::I2c(I2C::I2C1)),
+    ("LPI2C2", Known::I2c(I2C::I2C2)),
+    ("PIT", Known::Pit),
+    ("PWM1", Known::Pwm(PWM::PWM1)),
+    ("LPSPI1", Known::Spi(SPI::SPI1)),
+    ("LPSPI2", Known::Spi(SPI::SPI2)),
+    ("LPUART1", Known::Uart(UART::UART1)),
+    ("LPUART2", Known::Uart(UART::UART2)),
+    ("LPUART3", Known::Uart(UART::UART3)),
+    ("LPUART4", Known::Uart(UART::UART4)),
+    ("USBOH3", Known::Usb(USB::USB1)),
+];
+
+/// Clock gates that are only available on the 1060
+#[cfg(feature = "imxrt1060")]
+const CHIP: &[(&str, Known)] = &[
+    ("ADC2", Known::Adc(ADC::ADC2)),
+    ("LPI2C3", Known::I2c(I2C::I2C3)),
+    ("LPI2C4", Known::I2c(I2C::I2C4)),
+    ("PWM2", Known::Pwm(PWM::PWM2)),
+    ("PWM3", Known::Pwm(PWM::PWM3)),
+    ("PWM4", Known::Pwm(PWM::PWM4)),
+    ("LPSPI3", Known::Spi(SPI::SPI3)),
+    ("LPSPI4", Known::Spi(SPI::SPI4)),
+    ("LPUART5", Known::Uart(UART::UART5)),
+    ("LPUART6", Known::Uart(UART::UART6)),
+    ("LPUART7", Known::Uart(UART::UART7)),
+    ("LPUART8", Known::Uart(UART::UART8)),
+];
+
+#[cfg(not(feature = "imxrt1060"))]
+const CHIP: &[(&str, Known)] = &[];
+
+/// Returns the known clock gate at `idx`, or `None` if `idx` is out of range
+///
+/// Indexes start with the common clock gates, and continue into the chip-specific
+/// clock gates.
+pub fn known(idx: usize) -> Option<&'static (&'static str, Known)> {
+    COMMON.get(idx).or_else(|| CHIP.get(idx - COMMON.len()))
+}
+
+/// Returns the number of known clock gates
+pub const fn known_len() -> usize {
+    COMMON.len() + CHIP.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{known, known_len, restore_, set_all_, snapshot_, CCGR_COUNT};
+    use crate::ClockGateLocation;
+
+    #[test]
+    fn set_all_excludes() {
+        let mut ccgr = [0u32; CCGR_COUNT];
+        let exclude = [
+            ClockGateLocation::new(0, &[0, 15]),
+            ClockGateLocation::new(3, &[9]),
+        ];
+        unsafe { set_all_(ccgr.as_mut_ptr(), 0b11, &[&exclude]) };
+        assert_eq!(ccgr[0], 0x3FFF_FFFC);
+        assert_eq!(ccgr[3], !(0b11 << 18));
+        assert!(ccgr[1..3].iter().all(|reg| *reg == u32::MAX));
+
+        unsafe { set_all_(ccgr.as_mut_ptr(), 0b01, &[&exclude[..1], &exclude[1..]]) };
+        assert_eq!(ccgr[0], 0x1555_5554);
+        assert_eq!(ccgr[3], 0x5555_5555 & !(0b11 << 18));
+        assert!(ccgr[4..].iter().all(|reg| *reg == 0x5555_5555));
+    }
+
+    #[test]
+    fn snapshot_restore() {
+        let mut ccgr = [0u32; CCGR_COUNT];
+        for (idx, reg) in ccgr.iter_mut().enumerate() {
+            *reg = 0xFFFF_0000 | idx as u32;
+        }
+        let snapshot = unsafe { snapshot_(ccgr.as_ptr()) };
+        assert_eq!(snapshot, ccgr);
+
+        let mut mem = [0u32; CCGR_COUNT];
+        unsafe { restore_(mem.as_mut_ptr(), &snapshot) };
+        assert_eq!(mem, ccgr);
+    }
+
+    #[cfg(all(feature = "gate-cache", not(feature = "simulation")))]
+    #[test]
+    fn cache_sync_store() {
+        use super::cache;
+
+        let mut ccgr = [0u32; CCGR_COUNT];
+        ccgr[3] = 0xC;
+        unsafe {
+            cache::sync(ccgr.as_ptr());
+            ccgr[3] = 0;
+            assert_eq!(cache::load(ccgr.as_ptr(), 3), 0xC);
+            cache::store(3, 0x30);
+            assert_eq!(cache::load(ccgr.as_ptr(), 3), 0x30);
+        }
+    }
+
+    #[test]
+    fn known_locations_are_unique() {
+        for i in 0..known_len() {
+            let (name_i, inst_i) = known(i).unwrap();
+            for j in (i + 1)..known_len() {
+                let (name_j, inst_j) = known(j).unwrap();
+                assert_ne!(name_i, name_j);
+                let (loc_i, loc_j) = (inst_i.location(), inst_j.location());
+                assert!(
+                    loc_i.offset != loc_j.offset || loc_i.gates != loc_j.gates,
+                    "{} and {} share a clock gate",
+                    name_i,
+                    name_j
+                );
+            }
+        }
+        assert!(known(known_len()).is_none());
+
+        // Both USB controllers share the USBOH3 clock gate
+        let (_, usb) = (0..known_len())
+            .filter_map(known)
+            .find(|(name, _)| *name == "USBOH3")
+            .unwrap();
+        let location = usb.location();
+        assert_eq!((location.offset, location.gates), (6, &[0][..]));
+    }
+}
@@ -0,0 +1,214 @@
+//! Glitch-free clock root switching
+//!
+//! Some CCM clock roots sit behind a glitchless multiplexer. To change the
+//! source or dividers of such a root without glitching its consumers,
+//!
+//! 1. select a safe source on the glitchless multiplexer,
+//! 2. change the dividers, or the source behind the multiplexer,
+//! 3. select the new source on the glitchless multiplexer,
+//!
+//! and wait for the CCM handshakes after each step. [`switch`] implements
+//! that sequence. The [`arm`](crate::arm) module uses it to change the core
+//! clock. You may use it for clock roots that this crate doesn't model, like
+//! the SEMC clock root.
+//!
+//! # Example
+//!
+//! Change the SEMC alternate clock while the SEMC clock root temporarily
+//! runs on PERIPH_CLK.
+//!
+//! ```no_run
+//! use imxrt_ccm::glitchless::{self, GlitchlessMux, Wait};
+//!
+//! const CBCDR: *mut u32 = 0x400F_C014 as _;
+//! // CBCDR[SEMC_CLK_SEL]
+//! const SEMC_CLK_SEL: GlitchlessMux = unsafe { GlitchlessMux::new(CBCDR, 6, 1) };
+//!
+//! unsafe {
+//!     glitchless::switch(&SEMC_CLK_SEL, 0, 1, Wait::Forever, || {
+//!         // Change SEMC_ALT_CLK_SEL, and wait for the handshake...
+//!         Ok(())
+//!     })
+//! }
+//! .unwrap();
+//! ```
+
+use crate::register::{self, Field};
+
+/// CCM divider and multiplexer handshake register
+const CCM_CDHIPR: *const u32 = 0x400F_C048 as _;
+
+/// Indicates that a divider or multiplexer handshake did not complete in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandshakeTimeout;
+
+/// How long to wait for divider and mux handshakes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wait {
+    /// Wait until the handshakes complete
+    Forever,
+    /// Check the handshakes at most this many times
+    Iterations(u32),
+}
+
+impl Wait {
+    /// Wait for all divider and mux handshakes to complete
+    pub(crate) unsafe fn handshake(self, cdhipr: *const u32) -> Result<(), HandshakeTimeout> {
+        match self {
+            Wait::Forever => {
+                while register::load(cdhipr) != 0 {}
+                Ok(())
+            }
+            Wait::Iterations(max_iterations) => {
+                for _ in 0..max_iterations {
+                    if register::load(cdhipr) == 0 {
+                        return Ok(());
+                    }
+                }
+                Err(HandshakeTimeout)
+            }
+        }
+    }
+}
+
+/// Wait for all divider and mux handshakes to complete
+///
+/// Returns [`HandshakeTimeout`] if the handshakes did not complete.
+pub fn wait_for_handshake(wait: Wait) -> Result<(), HandshakeTimeout> {
+    // Safety: atomic reads of a valid register
+    unsafe { wait.handshake(CCM_CDHIPR) }
+}
+
+/// A glitchless clock multiplexer
+///
+/// A `GlitchlessMux` describes the multiplexer's select field in a CCM register.
+pub struct GlitchlessMux {
+    /// Register address
+    address: *mut u32,
+    /// The select field
+    select: Field,
+    /// The handshake register
+    cdhipr: *const u32,
+}
+
+impl GlitchlessMux {
+    /// Describe a glitchless multiplexer at `address`, with a select field
+    /// `mask` bits wide, starting at bit `offset`
+    ///
+    /// Callers should not apply offset to the mask.
+    ///
+    /// # Safety
+    ///
+    /// Caller must ensure that `address` is a valid CCM register, and that the
+    /// field describes a glitchless multiplexer with a handshake in CDHIPR.
+    pub const unsafe fn new(address: *mut u32, offset: u32, mask: u32) -> Self {
+        GlitchlessMux {
+            address,
+            select: Field::new(offset, mask),
+            cdhipr: CCM_CDHIPR,
+        }
+    }
+
+    /// Use `cdhipr` as the handshake register
+    pub(crate) const fn with_handshake(mut self, cdhipr: *const u32) -> Self {
+        self.cdhipr = cdhipr;
+        self
+    }
+
+    /// Select `selection`, then wait for the handshake
+    ///
+    /// # Safety
+    ///
+    /// Caller must ensure that this read-modify-write operation is atomic
+    pub unsafe fn select(&self, selection: u32, wait: Wait) -> Result<(), HandshakeTimeout> {
+        self.select.modify(self.address, selection);
+        wait.handshake(self.cdhipr)
+    }
+
+    /// Returns the multiplexer selection
+    pub fn selection(&self) -> u32 {
+        // Safety: assumed valid through `new`, atomic read
+        unsafe { self.select.read(self.address) }
+    }
+
+    /// Wait for all divider and mux handshakes to complete
+    ///
+    /// Use this after changing a divider, or a multiplexer that's behind the
+    /// glitchless multiplexer.
+    pub fn wait_for_handshake(&self, wait: Wait) -> Result<(), HandshakeTimeout> {
+        // Safety: assumed valid through `new`, atomic reads
+        unsafe { wait.handshake(self.cdhipr) }
+    }
+}
+
+/// Runs `func` while `mux` selects `safe`, then selects `target`
+///
+/// Returns early if a handshake, or the function, times out. In that case,
+/// `mux` may still select `safe`.
+///
+/// # Safety
+///
+/// Modifies CCM register memory. `safe` must be a running clock source that
+/// the root's consumers can tolerate.
+pub unsafe fn switch<R>(
+    mux: &GlitchlessMux,
+    safe: u32,
+    target: u32,
+    wait: Wait,
+    func: impl FnOnce() -> Result<R, HandshakeTimeout>,
+) -> Result<R, HandshakeTimeout> {
+    mux.select(safe, wait)?;
+    let result = func()?;
+    mux.select(target, wait)?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{switch, GlitchlessMux, HandshakeTimeout, Wait};
+
+    #[test]
+    fn glitchless_switch() {
+        let mut mem: u32 = 0;
+        let cdhipr: u32 = 0;
+        unsafe {
+            let mux = GlitchlessMux::new(&mut mem, 25, 1).with_handshake(&cdhipr);
+            let during = switch(&mux, 1, 0, Wait::Forever, || Ok(mux.selection())).unwrap();
+            assert_eq!(during, 1);
+            assert_eq!(mux.selection(), 0);
+        }
+        assert_eq!(mem, 0);
+    }
+
+    #[test]
+    fn glitchless_switch_timeout() {
+        let mut mem: u32 = 0;
+        let cdhipr: u32 = 1 << 16;
+        unsafe {
+            let mux = GlitchlessMux::new(&mut mem, 25, 1).with_handshake(&cdhipr);
+            let result = switch(&mux, 1, 0, Wait::Iterations(10), || -> Result<(), _> {
+                panic!("Function runs after a timeout")
+            });
+            assert_eq!(result, Err(HandshakeTimeout));
+            assert_eq!(mux.selection(), 1);
+        }
+    }
+
+    #[test]
+    fn glitchless_handshake_timeout() {
+        let mut cdhipr: u32 = 1 << 16;
+        unsafe {
+            assert_eq!(
+                Wait::Iterations(10).handshake(&cdhipr),
+                Err(HandshakeTimeout)
+            );
+            assert_eq!(
+                Wait::Iterations(0).handshake(&cdhipr),
+                Err(HandshakeTimeout)
+            );
+            cdhipr = 0;
+            assert_eq!(Wait::Iterations(1).handshake(&cdhipr), Ok(()));
+            assert_eq!(Wait::Forever.handshake(&cdhipr), Ok(()));
+        }
+    }
+}
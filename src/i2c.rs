@@ -1,11 +1,20 @@
 //! I2C clock control
 
-use super::{set_clock_gate, ClockGate, ClockGateLocation, ClockGateLocator, Instance};
-use crate::register::{Field, Register};
-use core::marker::PhantomData;
+use super::{
+    set_clock_gate, ClockFor, ClockGate, ClockGateGuard, ClockGateLocation, ClockGateLocator,
+    ClockRoot, ClockRootLocator, Instance,
+};
+use crate::{
+    divider::Choice,
+    register::{Field, Register},
+    shared::SharedClockGate,
+    xtalosc::reference_frequency,
+    ClockError, ClockSource, RootFrequency,
+};
+use core::{convert::TryFrom, marker::PhantomData};
 
-/// Base I2C clock frequency (Hz)
-const CLOCK_FREQUENCY_HZ: u32 = crate::OSCILLATOR_FREQUENCY_HZ;
+/// The fixed PLL3 divider for `pll3_60m`
+const PLL3_DIVIDER: u32 = 8;
 /// Default I2C peripheral clock divider
 const DEFAULT_CLOCK_DIVIDER: u32 = 3;
 
@@ -29,19 +38,154 @@ where
     /// The divider should be between [1, 64]. The function will treat a 0 as 1,
     /// and anything greater than 64 as 64.
     ///
+    /// Returns the I2C clock root frequency. Since there's no requested
+    /// frequency, the error is zero; see [`RootFrequency::relative_to`].
+    ///
     /// When `configure` returns, all I2C clock gates will be set to off.
     /// Use [`clock_gate`](struct.I2CClock.html#method.clock_gate)
     /// to turn on I2C clock gates.
     #[inline(always)]
-    pub fn configure_divider(&mut self, divider: u32) {
+    pub fn configure_divider(&mut self, divider: u32) -> RootFrequency {
         unsafe {
-            set_clock_gate::<I>(I2C::I2C1, ClockGate::Off);
-            set_clock_gate::<I>(I2C::I2C2, ClockGate::Off);
-            set_clock_gate::<I>(I2C::I2C3, ClockGate::Off);
-            set_clock_gate::<I>(I2C::I2C4, ClockGate::Off);
+            crate::clock_gates_off::<I>(I2CS);
 
             configure(divider)
         };
+        RootFrequency::exact(frequency())
+    }
+
+    /// Configure the I2C clocks with a divider that's known to be in range
+    ///
+    /// Unlike `configure_divider`, the divider can't saturate. See
+    /// [`configure_divider`](I2CClock::configure_divider) for more information.
+    #[inline(always)]
+    pub fn configure_with_divider(&mut self, divider: Divider) -> RootFrequency {
+        self.configure_divider(divider.get())
+    }
+
+    /// Configure the I2C clocks to approximate `target_hz`, returning the
+    /// achieved frequency and its error
+    ///
+    /// See [`configure_frequency`] for more information.
+    ///
+    /// When `configure_frequency` returns, all I2C clock gates will be set to off.
+    /// Use [`clock_gate`](struct.I2CClock.html#method.clock_gate)
+    /// to turn on I2C clock gates.
+    #[inline(always)]
+    pub fn configure_frequency(&mut self, target_hz: u32) -> RootFrequency {
+        unsafe {
+            crate::clock_gates_off::<I>(I2CS);
+
+            configure_frequency(target_hz)
+        }
+    }
+
+    /// Configure the I2C clocks with a clock divider, leaving the I2C clock
+    /// gates untouched
+    ///
+    /// `configure_divider_ungated` behaves like `configure_divider`, but it does
+    /// not turn off any I2C clock gates. Use it when you manage the clock gates
+    /// yourself. If a I2C clock gate is on, the peripheral may see a glitch as
+    /// the clock root changes; you're responsible for tolerating that glitch.
+    #[inline(always)]
+    pub fn configure_divider_ungated(&mut self, divider: u32) -> RootFrequency {
+        // Safety: we own the CCM peripheral memory
+        unsafe { configure(divider) };
+        RootFrequency::exact(frequency())
+    }
+
+    /// Configure the I2C clocks to approximate `target_hz`, leaving the I2C
+    /// clock gates untouched
+    ///
+    /// `configure_frequency_ungated` behaves like `configure_frequency`, but it
+    /// does not turn off any I2C clock gates. See
+    /// [`configure_divider_ungated`](struct.I2CClock.html#method.configure_divider_ungated)
+    /// for the risks.
+    #[inline(always)]
+    pub fn configure_frequency_ungated(&mut self, target_hz: u32) -> RootFrequency {
+        // Safety: we own the CCM peripheral memory
+        unsafe { configure_frequency(target_hz) }
+    }
+
+    /// Set the I2C clock divider, leaving the clock root selection unchanged
+    ///
+    /// See [`set_divider`] for more information. Unlike `configure_divider`,
+    /// `set_divider` does not change the I2C clock gates.
+    #[inline(always)]
+    pub fn set_divider(&mut self, divider: u32) {
+        // Safety: we own the CCM peripheral memory
+        unsafe { set_divider(divider) }
+    }
+
+    /// Select the I2C clock root source, leaving the divider unchanged
+    ///
+    /// When `set_selection` returns, all I2C clock gates will be set to off.
+    /// Use [`clock_gate`](struct.I2CClock.html#method.clock_gate)
+    /// to turn on I2C clock gates.
+    #[inline(always)]
+    pub fn set_selection(&mut self, selection: Selection) {
+        unsafe {
+            crate::clock_gates_off::<I>(I2CS);
+
+            set_selection(selection)
+        }
+    }
+
+    /// Configure the I2C clocks with a clock divider, or return an error if the
+    /// divider is out of range
+    ///
+    /// Unlike [`configure_divider`](Self::configure_divider),
+    /// `try_configure_divider` doesn't saturate the divider. If it returns an
+    /// error, it makes no changes.
+    pub fn try_configure_divider(&mut self, divider: u32) -> Result<RootFrequency, ClockError> {
+        let divider = Divider::try_from(divider)?;
+        Ok(self.configure_with_divider(divider))
+    }
+
+    /// Configure the I2C clocks to within `tolerance_hz` of `target_hz`, or
+    /// return an error if no source and divider are close enough
+    ///
+    /// Unlike [`configure_frequency`](Self::configure_frequency), which settles for
+    /// the closest frequency, `try_configure_frequency` makes no changes if the
+    /// closest frequency isn't within the tolerance.
+    pub fn try_configure_frequency(
+        &mut self,
+        target_hz: u32,
+        tolerance_hz: u32,
+    ) -> Result<RootFrequency, ClockError> {
+        let choice = choose(&Sources::current(), target_hz)
+            // Unwrap OK: the oscillator always runs
+            .unwrap()
+            .within(target_hz, tolerance_hz)?;
+        unsafe {
+            crate::clock_gates_off::<I>(I2CS);
+            CSCDR2.set(choice.divider - 1, choice.selection);
+        }
+        Ok(choice.frequency(target_hz))
+    }
+
+    /// Set the I2C clock divider, or return an error if the divider is out of
+    /// range
+    ///
+    /// Unlike [`set_divider`](Self::set_divider), `try_set_divider` doesn't
+    /// saturate the divider. If it returns an error, it makes no changes.
+    pub fn try_set_divider(&mut self, divider: u32) -> Result<(), ClockError> {
+        let divider = Divider::try_from(divider)?;
+        self.set_divider(divider.get());
+        Ok(())
+    }
+
+    /// Select the I2C clock root source, or return an error if the source
+    /// isn't running
+    ///
+    /// See [`set_selection`](Self::set_selection). If `try_set_selection`
+    /// returns an error, it makes no changes.
+    pub fn try_set_selection(&mut self, selection: Selection) -> Result<(), ClockError> {
+        if source_frequency(selection) == 0 {
+            return Err(ClockError::SourceUnavailable);
+        }
+        self.set_selection(selection);
+        Ok(())
     }
 
     /// Configure the I2C clocks with a default divider
@@ -67,6 +211,22 @@ pub enum I2C {
     I2C4,
 }
 
+/// All I2C instances
+const I2CS: &[I2C] = &[I2C::I2C1, I2C::I2C2, I2C::I2C3, I2C::I2C4];
+
+/// I2C clock root selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Selection {
+    /// PLL3 divided by 8
+    Pll3,
+    /// The crystal oscillator
+    Oscillator,
+}
+
+/// I2C clock root divider, between [1, 64]
+pub type Divider = crate::Divider<1, 64>;
+
 impl ClockGateLocator for I2C {
     #[inline(always)]
     fn location(&self) -> ClockGateLocation {
@@ -91,6 +251,23 @@ impl ClockGateLocator for I2C {
     }
 }
 
+impl ClockRootLocator for I2C {
+    #[inline(always)]
+    fn clock_root(&self) -> ClockRoot {
+        ClockRoot::I2C
+    }
+}
+
+impl<I> ClockFor<I2C> for I2CClock<I>
+where
+    I: Instance<Inst = I2C>,
+{
+    #[inline(always)]
+    fn frequency(&self) -> u32 {
+        frequency()
+    }
+}
+
 impl<I> I2CClock<I>
 where
     I: Instance<Inst = I2C>,
@@ -101,6 +278,23 @@ where
         unsafe { set_clock_gate::<I>(i2c.instance(), gate) }
     }
 
+    /// Turn on the clock gate for the I2C instance, returning a guard
+    /// that restores the previous setting when dropped
+    #[inline(always)]
+    pub fn enable_guard<'a>(&mut self, i2c: &'a mut I) -> ClockGateGuard<'a, I> {
+        // Safety: we have exclusive access to the instance
+        unsafe { ClockGateGuard::new(i2c) }
+    }
+
+    /// Share the clock gate for the I2C instance between multiple users
+    ///
+    /// See [`shared`](crate::shared) for more information.
+    #[inline(always)]
+    pub fn shared_gate(&mut self, i2c: I) -> SharedClockGate<I> {
+        // Safety: we own the instance
+        unsafe { SharedClockGate::new(i2c) }
+    }
+
     /// Returns the clock gate setting for the I2C instance
     #[inline(always)]
     pub fn clock_gate(&self, i2c: &I) -> ClockGate {
@@ -114,6 +308,238 @@ where
     pub fn frequency(&self) -> u32 {
         frequency()
     }
+
+    /// Returns the clock root selection
+    #[inline(always)]
+    pub fn selection(&self) -> Selection {
+        selection()
+    }
+
+    /// Returns `true` if the I2C clock root runs
+    ///
+    /// The clock root runs when its selected source runs. Use this to check
+    /// that the I2C clock is configured before you use a I2C peripheral.
+    #[inline(always)]
+    pub fn is_configured(&self) -> bool {
+        frequency() != 0
+    }
+
+    /// Returns `true` if any I2C clock gate is on
+    ///
+    /// The check only considers the instances that are valid for `I`.
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        crate::any_clock_gate_on::<I>(I2CS)
+    }
+}
+
+/// An I2C bus speed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Mode {
+    /// Standard mode, 100KHz
+    Standard,
+    /// Fast mode, 400KHz
+    Fast,
+    /// Fast mode plus, 1MHz
+    FastPlus,
+}
+
+impl Mode {
+    /// Returns the largest SCL frequency for the mode
+    pub const fn hz(self) -> u32 {
+        match self {
+            Mode::Standard => 100_000,
+            Mode::Fast => 400_000,
+            Mode::FastPlus => 1_000_000,
+        }
+    }
+
+    /// Returns the smallest SCL low and high periods, in nanoseconds
+    const fn min_low_high_ns(self) -> (u32, u32) {
+        match self {
+            Mode::Standard => (4_700, 4_000),
+            Mode::Fast => (1_300, 600),
+            Mode::FastPlus => (500, 260),
+        }
+    }
+}
+
+/// LPI2C master SCL timing
+///
+/// The SCL period is `(clklo + clkhi + 2 + latency) * 2^prescale` I2C clock
+/// cycles, where the latency is `2 / 2^prescale`, rounded down. The timing
+/// assumes that FILTSCL is zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Timing {
+    /// The MCFGR1 PRESCALE field, between 0 and 7
+    pub prescale: u32,
+    /// The MCCR0 CLKLO field, between 3 and 63
+    pub clklo: u32,
+    /// The MCCR0 CLKHI field, between 1 and 63
+    pub clkhi: u32,
+    /// The achieved SCL frequency
+    pub hz: u32,
+}
+
+/// An I2C clock root configuration that satisfies an I2C mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Suggestion {
+    /// The I2C clock root selection
+    pub selection: Selection,
+    /// The I2C clock root divider
+    pub divider: u32,
+    /// The I2C clock root frequency
+    pub root_hz: u32,
+    /// The LPI2C timing at the root frequency
+    pub timing: Timing,
+}
+
+/// The I2C clock root frequency can't satisfy an I2C mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimingUnreachable {
+    /// The requested mode
+    pub mode: Mode,
+    /// The I2C clock root frequency
+    pub i2c_hz: u32,
+    /// The first of the [`suggestions`] for the mode, if any
+    pub suggestion: Option<Suggestion>,
+}
+
+/// The largest LPI2C MCFGR1 PRESCALE value
+const MAX_PRESCALE: u32 = 7;
+/// The smallest, and largest, LPI2C MCCR0 CLKLO values
+const CLKLO: (u32, u32) = (3, 63);
+/// The smallest, and largest, LPI2C MCCR0 CLKHI values
+const CLKHI: (u32, u32) = (1, 63);
+
+/// Returns the LPI2C master timing that satisfies `mode` with the I2C clock
+/// root frequency `i2c_hz`
+///
+/// The timing meets the mode's smallest SCL low and high periods. The SCL
+/// frequency doesn't exceed the mode's frequency, and it's within 10% of the
+/// mode's frequency. Returns `None` if no timing satisfies the mode.
+///
+/// ```
+/// use imxrt_ccm::i2c::{self, Mode};
+///
+/// let timing = i2c::timing(60_000_000, Mode::Fast).unwrap();
+/// assert_eq!(timing.hz, 400_000);
+/// assert!(i2c::timing(3_000_000, Mode::FastPlus).is_none());
+/// ```
+pub const fn timing(i2c_hz: u32, mode: Mode) -> Option<Timing> {
+    let (low_ns, high_ns) = mode.min_low_high_ns();
+    let mut best: Option<Timing> = None;
+    let mut prescale = 0;
+    while prescale <= MAX_PRESCALE {
+        let clock_hz = (i2c_hz >> prescale) as u64;
+        let latency = 2 >> prescale;
+        // Clock cycles in the SCL period, and the smallest low and high periods
+        let period = clock_hz.div_ceil(mode.hz() as u64) as u32;
+        let low = min_cycles(clock_hz * low_ns as u64, CLKLO.0 + 1);
+        let high = min_cycles(clock_hz * high_ns as u64, CLKHI.0 + 1);
+        // Spread the extra cycles over the low and high periods
+        let mut cycles = period.saturating_sub(latency);
+        if cycles < low + high {
+            cycles = low + high;
+        }
+        let extra = cycles - low - high;
+        let clklo = low + extra - extra / 2 - 1;
+        let clkhi = high + extra / 2 - 1;
+        let hz = (clock_hz / (clklo + clkhi + 2 + latency) as u64) as u32;
+        let faster = match best {
+            Some(best) => hz > best.hz,
+            None => true,
+        };
+        if clklo <= CLKLO.1 && clkhi <= CLKHI.1 && hz as u64 * 10 >= mode.hz() as u64 * 9 && faster
+        {
+            best = Some(Timing {
+                prescale,
+                clklo,
+                clkhi,
+                hz,
+            });
+        }
+        prescale += 1;
+    }
+    best
+}
+
+/// Returns the clock cycles in `cycles_ns` (clock Hz times nanoseconds),
+/// rounded up, or `min`, whichever is larger
+const fn min_cycles(cycles_ns: u64, min: u32) -> u32 {
+    let cycles = cycles_ns.div_ceil(1_000_000_000) as u32;
+    if cycles > min {
+        cycles
+    } else {
+        min
+    }
+}
+
+/// Returns every I2C clock root selection and divider that satisfies `mode`
+///
+/// The PLL3 suggestions come first, then the oscillator suggestions. Each
+/// source's suggestions start with the smallest divider. The suggestions
+/// reflect the current PLL3 frequency.
+pub fn suggestions(mode: Mode) -> impl Iterator<Item = Suggestion> {
+    suggestions_(mode, ClockSource::Pll3.frequency())
+}
+
+fn suggestions_(mode: Mode, pll3_hz: u32) -> impl Iterator<Item = Suggestion> {
+    let sources = [
+        (Selection::Pll3, pll3_hz / PLL3_DIVIDER),
+        (Selection::Oscillator, reference_frequency()),
+    ];
+    IntoIterator::into_iter(sources).flat_map(move |(selection, source_hz)| {
+        (1..=64).filter_map(move |divider| {
+            let root_hz = source_hz / divider;
+            timing(root_hz, mode).map(|timing| Suggestion {
+                selection,
+                divider,
+                root_hz,
+                timing,
+            })
+        })
+    })
+}
+
+/// Check that the I2C clock root frequency `i2c_hz` satisfies `mode`
+///
+/// Returns the LPI2C timing, or a clock root configuration that satisfies
+/// the mode. See [`timing`] for the requirements.
+pub fn check_timing(i2c_hz: u32, mode: Mode) -> Result<Timing, TimingUnreachable> {
+    timing(i2c_hz, mode).ok_or_else(|| TimingUnreachable {
+        mode,
+        i2c_hz,
+        suggestion: suggestions(mode).next(),
+    })
+}
+
+impl<I> I2CClock<I>
+where
+    I: Instance<Inst = I2C>,
+{
+    /// Check that the I2C clock root frequency satisfies `mode`
+    ///
+    /// See [`check_timing`] for more information.
+    ///
+    /// ```no_run
+    /// use imxrt_ccm::{i2c::{self, Mode}, Instance};
+    ///
+    /// fn check<I: Instance<Inst = i2c::I2C>>(i2c_clock: &mut i2c::I2CClock<I>) {
+    ///     if let Err(unreachable) = i2c_clock.check_timing(Mode::FastPlus) {
+    ///         let suggestion = unreachable.suggestion.unwrap();
+    ///         i2c_clock.set_selection(suggestion.selection);
+    ///         i2c_clock.set_divider(suggestion.divider);
+    ///     }
+    /// }
+    /// ```
+    pub fn check_timing(&self, mode: Mode) -> Result<Timing, TimingUnreachable> {
+        check_timing(self.frequency(), mode)
+    }
 }
 
 const LPI2C_CLK_PODF: Field = Field::new(19, 0x3F);
@@ -134,35 +560,209 @@ const CSCDR2: Register =
 /// This could be called anywhere, modifying global memory that's owned by
 /// the CCM. Consider using the [`I2CClock`](struct.I2CClock.html) for a
 /// safer interface.
-#[inline(always)]
 pub unsafe fn configure(divider: u32) {
     configure_(divider, &CSCDR2);
 }
 
 #[inline(always)]
 unsafe fn configure_(divider: u32, reg: &Register) {
-    const OSCILLATOR: u32 = 1;
-    reg.set(divider.min(64).max(1).saturating_sub(1), OSCILLATOR);
+    reg.set(divider.clamp(1, 64).saturating_sub(1), OSCILLATOR);
+}
+
+const OSCILLATOR: u32 = 1;
+const PLL3_60M: u32 = 0;
+
+/// Configure the I2C clock root to approximate `target_hz`, returning the
+/// achieved frequency and its error
+///
+/// The function selects the source, either the crystal oscillator or PLL3
+/// divided by 8, and the divider, that produce the frequency closest to
+/// `target_hz`. PLL3 is only considered if it's running. Configure will
+/// **not** disable peripheral clock gates.
+///
+/// # Safety
+///
+/// This could be called anywhere, modifying global memory that's owned by
+/// the CCM. Consider using the [`I2CClock`](struct.I2CClock.html) for a
+/// safer interface.
+pub unsafe fn configure_frequency(target_hz: u32) -> RootFrequency {
+    configure_frequency_(target_hz, &CSCDR2, crate::pll3::frequency())
+}
+
+unsafe fn configure_frequency_(target_hz: u32, reg: &Register, pll3_hz: u32) -> RootFrequency {
+    let sources = Sources {
+        oscillator_hz: reference_frequency(),
+        pll3_hz,
+    };
+    // Unwrap OK: the oscillator always runs
+    let choice = choose(&sources, target_hz).unwrap();
+    reg.set(choice.divider - 1, choice.selection);
+    choice.frequency(target_hz)
+}
+
+/// Returns the frequency of the source selected by `selection`
+fn source_frequency(selection: Selection) -> u32 {
+    match selection {
+        Selection::Pll3 => ClockSource::Pll3.frequency() / PLL3_DIVIDER,
+        Selection::Oscillator => ClockSource::Oscillator.frequency(),
+    }
+}
+
+/// Set the I2C clock divider, leaving the clock root selection unchanged
+///
+/// Unlike [`configure`], which also selects the clock source, `set_divider`
+/// only writes the divider field. Use it to adjust a clock root that you
+/// configured with [`configure_frequency`] or [`set_selection`].
+///
+/// The divider should be between [1, 64]. The function will treat a 0 as 1,
+/// and anything greater than 64 as 64.
+///
+/// # Safety
+///
+/// This could be called anywhere, modifying global memory that's owned by
+/// the CCM. Consider using the [`I2CClock`](struct.I2CClock.html) for a
+/// safer interface.
+pub unsafe fn set_divider(divider: u32) {
+    set_divider_(divider, &CSCDR2);
+}
+
+#[inline(always)]
+unsafe fn set_divider_(divider: u32, reg: &Register) {
+    reg.set_divider(divider.clamp(1, 64) - 1);
+}
+
+/// Select the I2C clock root source, leaving the divider unchanged
+///
+/// Set selection will **not** disable peripheral clock gates. You should disable
+/// clock gates yourself before calling this function.
+///
+/// # Safety
+///
+/// This could be called anywhere, modifying global memory that's owned by
+/// the CCM. Consider using the [`I2CClock`](struct.I2CClock.html) for a
+/// safer interface.
+pub unsafe fn set_selection(selection: Selection) {
+    set_selection_(selection, &CSCDR2);
+}
+
+#[inline(always)]
+unsafe fn set_selection_(selection: Selection, reg: &Register) {
+    reg.set_selection(match selection {
+        Selection::Pll3 => PLL3_60M,
+        Selection::Oscillator => OSCILLATOR,
+    });
 }
 
 /// Returns the I2C clock frequency
+///
+/// If the I2C clock root runs on PLL3, the frequency reflects the
+/// PLL3 frequency.
 #[inline(always)]
 pub fn frequency() -> u32 {
-    frequency_(&CSCDR2)
+    frequency_(&CSCDR2, crate::pll3::frequency())
 }
 
 #[inline(always)]
-fn frequency_(reg: &Register) -> u32 {
+fn frequency_(reg: &Register, pll3_hz: u32) -> u32 {
     let divider = reg.divider() + 1;
-    CLOCK_FREQUENCY_HZ / divider
+    match reg.selection() {
+        0 => pll3_hz / PLL3_DIVIDER / divider,
+        _ => reference_frequency() / divider,
+    }
+}
+
+/// Returns the I2C clock divider
+#[inline(always)]
+pub fn divider() -> u32 {
+    CSCDR2.divider() + 1
+}
+
+/// Returns the I2C clock root selection
+#[inline(always)]
+pub fn selection() -> Selection {
+    selection_(&CSCDR2)
+}
+
+#[inline(always)]
+fn selection_(reg: &Register) -> Selection {
+    from_field(reg.selection())
+}
+
+/// The I2C clock root source frequencies
+///
+/// Use `Sources` with [`closest`] and [`root_frequency`] to compute I2C clock
+/// root settings without the CCM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sources {
+    /// The crystal oscillator frequency
+    pub oscillator_hz: u32,
+    /// The PLL3 frequency, before the fixed divide by 8, or 0 if PLL3 isn't
+    /// running
+    pub pll3_hz: u32,
+}
+
+impl Sources {
+    /// Returns the source frequencies of this system
+    pub fn current() -> Self {
+        Sources {
+            oscillator_hz: reference_frequency(),
+            pll3_hz: crate::pll3::frequency(),
+        }
+    }
+}
+
+/// An I2C clock root selection and divider, and the frequency that they
+/// produce
+pub type Setting = crate::RootSetting<Selection, Divider>;
+
+/// Returns the I2C clock root frequency for `selection` and `divider`
+///
+/// Unlike [`frequency`], `root_frequency` doesn't read the CCM.
+pub const fn root_frequency(sources: &Sources, selection: Selection, divider: Divider) -> u32 {
+    let source_hz = match selection {
+        Selection::Pll3 => sources.pll3_hz / PLL3_DIVIDER,
+        Selection::Oscillator => sources.oscillator_hz,
+    };
+    source_hz / divider.get()
+}
+
+/// Returns the I2C clock root selection and divider that best approximate
+/// `target_hz`
+///
+/// `closest` makes the same choice as [`configure_frequency`], but it doesn't
+/// read or write the CCM. Returns `None` if no source runs.
+pub const fn closest(sources: &Sources, target_hz: u32) -> Option<Setting> {
+    match choose(sources, target_hz) {
+        Some(choice) => Some(choice.setting(from_field(choice.selection), target_hz)),
+        None => None,
+    }
+}
+
+/// Returns the source and divider that best approximate `target_hz`
+const fn choose(sources: &Sources, target_hz: u32) -> Option<Choice> {
+    let sources = [
+        (OSCILLATOR, sources.oscillator_hz),
+        (PLL3_60M, sources.pll3_hz / PLL3_DIVIDER),
+    ];
+    crate::divider::closest(target_hz, &sources, 64)
+}
+
+/// Returns the selection described by a clock root selection value
+const fn from_field(field: u32) -> Selection {
+    match field {
+        PLL3_60M => Selection::Pll3,
+        _ => Selection::Oscillator,
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::{
-        configure_, frequency_, Register, CLOCK_FREQUENCY_HZ, LPI2C_CLK_PODF, LPI2C_CLK_SEL,
+        configure_, configure_frequency_, frequency_, selection_, set_divider_, set_selection_,
+        suggestions_, timing, Mode, Register, Selection, Timing, LPI2C_CLK_PODF, LPI2C_CLK_SEL,
     };
+    use crate::OSCILLATOR_FREQUENCY_HZ as CLOCK_FREQUENCY_HZ;
 
     unsafe fn register(mem: &mut u32) -> Register {
         Register::new(LPI2C_CLK_PODF, LPI2C_CLK_SEL, mem)
@@ -174,7 +774,7 @@ mod tests {
         unsafe {
             let reg = register(&mut mem);
             configure_(65, &reg);
-            assert_eq!(frequency_(&reg), CLOCK_FREQUENCY_HZ / 64);
+            assert_eq!(frequency_(&reg, 0), CLOCK_FREQUENCY_HZ / 64);
         }
     }
 
@@ -184,7 +784,16 @@ mod tests {
         unsafe {
             let reg = register(&mut mem);
             configure_(0, &reg);
-            assert_eq!(frequency_(&reg), CLOCK_FREQUENCY_HZ);
+            assert_eq!(frequency_(&reg, 0), CLOCK_FREQUENCY_HZ);
+        }
+    }
+
+    #[test]
+    fn i2c_pll3() {
+        let mut mem: u32 = 2 << 19;
+        unsafe {
+            let reg = register(&mut mem);
+            assert_eq!(frequency_(&reg, 480_000_000), 60_000_000 / 3);
         }
     }
 
@@ -194,7 +803,94 @@ mod tests {
         unsafe {
             let reg = register(&mut mem);
             configure_(7, &reg);
-            assert_eq!(frequency_(&reg), CLOCK_FREQUENCY_HZ / 7);
+            assert_eq!(frequency_(&reg, 0), CLOCK_FREQUENCY_HZ / 7);
         }
     }
+
+    #[test]
+    fn i2c_configure_frequency() {
+        let mut mem: u32 = 0;
+        unsafe {
+            let reg = register(&mut mem);
+            let achieved = configure_frequency_(8_000_000, &reg, 480_000_000);
+            assert_eq!((achieved.hz, achieved.error_hz), (8_000_000, 0));
+            assert_eq!(frequency_(&reg, 480_000_000), 8_000_000);
+
+            let achieved = configure_frequency_(60_000_000, &reg, 480_000_000);
+            assert_eq!((achieved.hz, achieved.error_hz), (60_000_000, 0));
+            assert_eq!(frequency_(&reg, 480_000_000), 60_000_000);
+
+            let achieved = configure_frequency_(7_000_000, &reg, 0);
+            assert_eq!(achieved.hz, CLOCK_FREQUENCY_HZ / 3);
+            assert_eq!(achieved.error_hz, 1_000_000);
+        }
+    }
+
+    #[test]
+    fn i2c_selection() {
+        let mut mem: u32 = 0;
+        unsafe {
+            let reg = register(&mut mem);
+            assert_eq!(selection_(&reg), Selection::Pll3);
+            configure_(1, &reg);
+            assert_eq!(selection_(&reg), Selection::Oscillator);
+        }
+    }
+
+    #[test]
+    fn i2c_set_divider_selection() {
+        let mut mem: u32 = 0;
+        unsafe {
+            let reg = register(&mut mem);
+            configure_frequency_(60_000_000, &reg, 480_000_000);
+            set_divider_(4, &reg);
+            assert_eq!(selection_(&reg), Selection::Pll3);
+            assert_eq!(frequency_(&reg, 480_000_000), 60_000_000 / 4);
+
+            set_selection_(Selection::Oscillator, &reg);
+            assert_eq!(frequency_(&reg, 480_000_000), CLOCK_FREQUENCY_HZ / 4);
+
+            set_divider_(65, &reg);
+            assert_eq!(frequency_(&reg, 480_000_000), CLOCK_FREQUENCY_HZ / 64);
+        }
+    }
+
+    #[test]
+    fn i2c_timing() {
+        let fast = timing(60_000_000, Mode::Fast).unwrap();
+        assert_eq!(
+            fast,
+            Timing {
+                prescale: 1,
+                clklo: 47,
+                clkhi: 25,
+                hz: 400_000,
+            }
+        );
+        let standard = timing(60_000_000, Mode::Standard).unwrap();
+        assert_eq!(
+            (standard.prescale, standard.clklo, standard.clkhi),
+            (3, 40, 33)
+        );
+        assert_eq!(standard.hz, 100_000);
+
+        // 8MHz is too slow for the fast mode plus low and high periods
+        assert_eq!(timing(8_000_000, Mode::FastPlus), None);
+        assert_eq!(timing(0, Mode::Standard), None);
+    }
+
+    #[test]
+    fn i2c_timing_suggestions() {
+        let mut suggestions = suggestions_(Mode::FastPlus, 480_000_000);
+        let first = suggestions.next().unwrap();
+        assert_eq!((first.selection, first.divider), (Selection::Pll3, 1));
+        assert!(first.timing.hz <= 1_000_000);
+        assert!(suggestions.all(
+            |suggestion| timing(suggestion.root_hz, Mode::FastPlus) == Some(suggestion.timing)
+        ));
+
+        // PLL3 is off
+        let first = suggestions_(Mode::Fast, 0).next().unwrap();
+        assert_eq!((first.selection, first.divider), (Selection::Oscillator, 1));
+    }
 }
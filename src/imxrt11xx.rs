@@ -0,0 +1,721 @@
+//! Clock roots and LPCG clock gates for i.MX RT 11xx processors
+//!
+//! The i.MX RT 1160 and 1170 CCM doesn't have the 10xx CCGR registers, or the
+//! per-peripheral clock root muxes. Instead, each clock root has a generic
+//! control register that selects one of eight sources, and divides the source
+//! by 1 to 256. Each peripheral has its own low-power clock gate (LPCG), which
+//! is either on or off.
+//!
+//! The [`CCM`] in this module controls every clock root and clock gate. A
+//! [`ClockRoot`] locates a clock root's registers, and a [`Gate`] locates an
+//! LPCG's registers. It's independent of the 10xx [`CCM`](crate::CCM); don't use
+//! the 10xx APIs on an 11xx processor.
+//!
+//! ```no_run
+//! use imxrt_ccm::imxrt11xx::{ClockRoot, Gate, Mux, CCM};
+//!
+//! let mut ccm = unsafe { CCM::new() };
+//! // LPUART1 runs at 24MHz from the crystal oscillator
+//! ccm.configure_root(ClockRoot::LPUART1, Mux::Oscillator, 1);
+//! ccm.set_clock_gate(Gate::LPUART1, true);
+//! assert_eq!(ccm.root_frequency(ClockRoot::LPUART1), Some(24_000_000));
+//! ```
+//!
+//! The first four sources of every clock root are the same oscillators. The
+//! other four sources are PLL outputs that differ for each clock root; see
+//! your reference manual's clock root table. This module doesn't configure
+//! the 11xx PLLs.
+//!
+//! Enable `"imxrt1160"` or `"imxrt1170"` to use this module. The display and
+//! camera clock roots and clock gates require `"imxrt1170"`.
+//!
+//! # References
+//!
+//! i.MX RT 1160 reference manual, i.MX RT 1170 reference manual
+//! - Chapter 15: Clock Control Module (CCM)
+
+use crate::{family::ClockControl, register::Field, ClockError, OSCILLATOR_FREQUENCY_HZ};
+use core::convert::TryFrom;
+
+/// A clock root divider
+pub type Divider = crate::Divider<1, 256>;
+
+const CCM_BASE: usize = 0x40CC_0000;
+/// The distance between clock root register sets, in bytes
+const CLOCK_ROOT_STRIDE: usize = 0x80;
+/// The offset of STATUS0 from CONTROL, in words
+const STATUS0: usize = 0x20 / 4;
+
+const LPCG_BASE: usize = 0x40CC_6000;
+/// The distance between LPCG register sets, in bytes
+const LPCG_STRIDE: usize = 0x20;
+
+const DIV: Field = Field::new(0, 0xFF);
+const MUX: Field = Field::new(8, 0b111);
+const OFF: Field = Field::new(24, 1);
+const CHANGING: Field = Field::new(31, 1);
+
+const ON: Field = Field::new(0, 1);
+
+/// An 11xx clock root
+///
+/// The value is the clock root's index in the CCM. The display and camera
+/// clock roots are only available on the i.MX RT 1170.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u32)]
+#[allow(missing_docs)]
+pub enum ClockRoot {
+    M7 = 0,
+    M4 = 1,
+    Bus = 2,
+    BusLPSR = 3,
+    SEMC = 4,
+    CSSYS = 5,
+    CSTrace = 6,
+    M4SysTick = 7,
+    M7SysTick = 8,
+    ADC1 = 9,
+    ADC2 = 10,
+    ACMP = 11,
+    FlexIO1 = 12,
+    FlexIO2 = 13,
+    GPT1 = 14,
+    GPT2 = 15,
+    GPT3 = 16,
+    GPT4 = 17,
+    GPT5 = 18,
+    GPT6 = 19,
+    FlexSPI1 = 20,
+    FlexSPI2 = 21,
+    CAN1 = 22,
+    CAN2 = 23,
+    CAN3 = 24,
+    LPUART1 = 25,
+    LPUART2 = 26,
+    LPUART3 = 27,
+    LPUART4 = 28,
+    LPUART5 = 29,
+    LPUART6 = 30,
+    LPUART7 = 31,
+    LPUART8 = 32,
+    LPUART9 = 33,
+    LPUART10 = 34,
+    LPUART11 = 35,
+    LPUART12 = 36,
+    LPI2C1 = 37,
+    LPI2C2 = 38,
+    LPI2C3 = 39,
+    LPI2C4 = 40,
+    LPI2C5 = 41,
+    LPI2C6 = 42,
+    LPSPI1 = 43,
+    LPSPI2 = 44,
+    LPSPI3 = 45,
+    LPSPI4 = 46,
+    LPSPI5 = 47,
+    LPSPI6 = 48,
+    EMV1 = 49,
+    EMV2 = 50,
+    ENET1 = 51,
+    ENET2 = 52,
+    ENETQoS = 53,
+    ENET25M = 54,
+    ENETTimer1 = 55,
+    ENETTimer2 = 56,
+    ENETTimer3 = 57,
+    USDHC1 = 58,
+    USDHC2 = 59,
+    ASRC = 60,
+    MQS = 61,
+    MIC = 62,
+    SPDIF = 63,
+    SAI1 = 64,
+    SAI2 = 65,
+    SAI3 = 66,
+    SAI4 = 67,
+    #[cfg(feature = "imxrt1170")]
+    GC355 = 68,
+    #[cfg(feature = "imxrt1170")]
+    LCDIF = 69,
+    #[cfg(feature = "imxrt1170")]
+    LCDIFv2 = 70,
+    #[cfg(feature = "imxrt1170")]
+    MIPIRef = 71,
+    #[cfg(feature = "imxrt1170")]
+    MIPIEsc = 72,
+    #[cfg(feature = "imxrt1170")]
+    CSI2 = 73,
+    #[cfg(feature = "imxrt1170")]
+    CSI2Esc = 74,
+    #[cfg(feature = "imxrt1170")]
+    CSI2UI = 75,
+    #[cfg(feature = "imxrt1170")]
+    CSI = 76,
+    CKO1 = 77,
+    CKO2 = 78,
+}
+
+impl ClockRoot {
+    /// Returns the clock gate for the peripheral that uses this clock root
+    ///
+    /// Returns `None` if the clock root doesn't have exactly one clock gate,
+    /// like the bus clock roots, and the clock roots that are shared by
+    /// multiple peripherals. Also returns `None` for the core and memory
+    /// clock roots: M7, M4, SEMC, and FlexSPI. Turning off those gates would
+    /// stop the core that's running, or the memory that it's executing from.
+    pub const fn gate(self) -> Option<Gate> {
+        let gate = match self {
+            ClockRoot::CSTrace => Gate::CSTrace,
+            ClockRoot::FlexIO1 => Gate::FlexIO1,
+            ClockRoot::FlexIO2 => Gate::FlexIO2,
+            ClockRoot::GPT1 => Gate::GPT1,
+            ClockRoot::GPT2 => Gate::GPT2,
+            ClockRoot::GPT3 => Gate::GPT3,
+            ClockRoot::GPT4 => Gate::GPT4,
+            ClockRoot::GPT5 => Gate::GPT5,
+            ClockRoot::GPT6 => Gate::GPT6,
+            ClockRoot::CAN1 => Gate::CAN1,
+            ClockRoot::CAN2 => Gate::CAN2,
+            ClockRoot::CAN3 => Gate::CAN3,
+            ClockRoot::LPUART1 => Gate::LPUART1,
+            ClockRoot::LPUART2 => Gate::LPUART2,
+            ClockRoot::LPUART3 => Gate::LPUART3,
+            ClockRoot::LPUART4 => Gate::LPUART4,
+            ClockRoot::LPUART5 => Gate::LPUART5,
+            ClockRoot::LPUART6 => Gate::LPUART6,
+            ClockRoot::LPUART7 => Gate::LPUART7,
+            ClockRoot::LPUART8 => Gate::LPUART8,
+            ClockRoot::LPUART9 => Gate::LPUART9,
+            ClockRoot::LPUART10 => Gate::LPUART10,
+            ClockRoot::LPUART11 => Gate::LPUART11,
+            ClockRoot::LPUART12 => Gate::LPUART12,
+            ClockRoot::LPI2C1 => Gate::LPI2C1,
+            ClockRoot::LPI2C2 => Gate::LPI2C2,
+            ClockRoot::LPI2C3 => Gate::LPI2C3,
+            ClockRoot::LPI2C4 => Gate::LPI2C4,
+            ClockRoot::LPI2C5 => Gate::LPI2C5,
+            ClockRoot::LPI2C6 => Gate::LPI2C6,
+            ClockRoot::LPSPI1 => Gate::LPSPI1,
+            ClockRoot::LPSPI2 => Gate::LPSPI2,
+            ClockRoot::LPSPI3 => Gate::LPSPI3,
+            ClockRoot::LPSPI4 => Gate::LPSPI4,
+            ClockRoot::LPSPI5 => Gate::LPSPI5,
+            ClockRoot::LPSPI6 => Gate::LPSPI6,
+            ClockRoot::ENET1 => Gate::ENET1,
+            ClockRoot::ENETQoS => Gate::ENETQoS,
+            ClockRoot::USDHC1 => Gate::USDHC1,
+            ClockRoot::USDHC2 => Gate::USDHC2,
+            ClockRoot::ASRC => Gate::ASRC,
+            ClockRoot::MQS => Gate::MQS,
+            ClockRoot::SPDIF => Gate::SPDIF,
+            ClockRoot::SAI1 => Gate::SAI1,
+            ClockRoot::SAI2 => Gate::SAI2,
+            ClockRoot::SAI3 => Gate::SAI3,
+            ClockRoot::SAI4 => Gate::SAI4,
+            #[cfg(feature = "imxrt1170")]
+            ClockRoot::LCDIF => Gate::LCDIF,
+            #[cfg(feature = "imxrt1170")]
+            ClockRoot::LCDIFv2 => Gate::LCDIFv2,
+            #[cfg(feature = "imxrt1170")]
+            ClockRoot::CSI => Gate::CSI,
+            ClockRoot::ADC1 => Gate::LPADC1,
+            ClockRoot::ADC2 => Gate::LPADC2,
+            ClockRoot::EMV1 => Gate::EMVSIM1,
+            ClockRoot::EMV2 => Gate::EMVSIM2,
+            ClockRoot::ENET2 => Gate::ENET1G,
+            ClockRoot::MIC => Gate::PDM,
+            #[cfg(feature = "imxrt1170")]
+            ClockRoot::GC355 => Gate::GPU2D,
+            #[cfg(feature = "imxrt1170")]
+            ClockRoot::CSI2 => Gate::MIPICSI,
+            _ => return None,
+        };
+        Some(gate)
+    }
+
+    #[inline(always)]
+    fn control(self) -> *mut u32 {
+        (CCM_BASE + self as usize * CLOCK_ROOT_STRIDE) as *mut u32
+    }
+}
+
+/// An 11xx low-power clock gate (LPCG)
+///
+/// The value is the gate's index in the CCM. The display and camera clock
+/// gates are only available on the i.MX RT 1170.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u32)]
+#[allow(missing_docs)]
+pub enum Gate {
+    M7 = 0,
+    M4 = 1,
+    SimM7 = 2,
+    SimM = 3,
+    SimDisp = 4,
+    SimPer = 5,
+    SimLPSR = 6,
+    Anadig = 7,
+    DCDC = 8,
+    SRC = 9,
+    CCM = 10,
+    GPC = 11,
+    SSARC = 12,
+    SimR = 13,
+    WDOG1 = 14,
+    WDOG2 = 15,
+    WDOG3 = 16,
+    WDOG4 = 17,
+    EWM = 18,
+    SEMA = 19,
+    MUA = 20,
+    MUB = 21,
+    EDMA = 22,
+    EDMALPSR = 23,
+    ROMCP = 24,
+    OCRAM = 25,
+    FlexRAM = 26,
+    LMEM = 27,
+    FlexSPI1 = 28,
+    FlexSPI2 = 29,
+    RDC = 30,
+    M7XRDC = 31,
+    M4XRDC = 32,
+    SEMC = 33,
+    XECC = 34,
+    IEE = 35,
+    KeyManager = 36,
+    OCOTP = 37,
+    SNVSHP = 38,
+    SNVS = 39,
+    CAAM = 40,
+    JTAGMux = 41,
+    CSTrace = 42,
+    XBAR1 = 43,
+    XBAR2 = 44,
+    XBAR3 = 45,
+    AOI1 = 46,
+    AOI2 = 47,
+    ADCETC = 48,
+    IOMUXC = 49,
+    IOMUXCLPSR = 50,
+    GPIO = 51,
+    KPP = 52,
+    FlexIO1 = 53,
+    FlexIO2 = 54,
+    LPADC1 = 55,
+    LPADC2 = 56,
+    DAC = 57,
+    ACMP1 = 58,
+    ACMP2 = 59,
+    ACMP3 = 60,
+    ACMP4 = 61,
+    PIT1 = 62,
+    PIT2 = 63,
+    GPT1 = 64,
+    GPT2 = 65,
+    GPT3 = 66,
+    GPT4 = 67,
+    GPT5 = 68,
+    GPT6 = 69,
+    QTIMER1 = 70,
+    QTIMER2 = 71,
+    QTIMER3 = 72,
+    QTIMER4 = 73,
+    ENC1 = 74,
+    ENC2 = 75,
+    ENC3 = 76,
+    ENC4 = 77,
+    HRTIMER = 78,
+    PWM1 = 79,
+    PWM2 = 80,
+    PWM3 = 81,
+    PWM4 = 82,
+    CAN1 = 83,
+    CAN2 = 84,
+    CAN3 = 85,
+    LPUART1 = 86,
+    LPUART2 = 87,
+    LPUART3 = 88,
+    LPUART4 = 89,
+    LPUART5 = 90,
+    LPUART6 = 91,
+    LPUART7 = 92,
+    LPUART8 = 93,
+    LPUART9 = 94,
+    LPUART10 = 95,
+    LPUART11 = 96,
+    LPUART12 = 97,
+    LPI2C1 = 98,
+    LPI2C2 = 99,
+    LPI2C3 = 100,
+    LPI2C4 = 101,
+    LPI2C5 = 102,
+    LPI2C6 = 103,
+    LPSPI1 = 104,
+    LPSPI2 = 105,
+    LPSPI3 = 106,
+    LPSPI4 = 107,
+    LPSPI5 = 108,
+    LPSPI6 = 109,
+    EMVSIM1 = 110,
+    EMVSIM2 = 111,
+    ENET1 = 112,
+    ENET1G = 113,
+    ENETQoS = 114,
+    USB = 115,
+    CDOG = 116,
+    USDHC1 = 117,
+    USDHC2 = 118,
+    ASRC = 119,
+    MQS = 120,
+    PDM = 121,
+    SPDIF = 122,
+    SAI1 = 123,
+    SAI2 = 124,
+    SAI3 = 125,
+    SAI4 = 126,
+    #[cfg(feature = "imxrt1170")]
+    PXP = 127,
+    #[cfg(feature = "imxrt1170")]
+    GPU2D = 128,
+    #[cfg(feature = "imxrt1170")]
+    LCDIF = 129,
+    #[cfg(feature = "imxrt1170")]
+    LCDIFv2 = 130,
+    #[cfg(feature = "imxrt1170")]
+    MIPIDSI = 131,
+    #[cfg(feature = "imxrt1170")]
+    MIPICSI = 132,
+    #[cfg(feature = "imxrt1170")]
+    CSI = 133,
+    #[cfg(feature = "imxrt1170")]
+    DCICMIPI = 134,
+    #[cfg(feature = "imxrt1170")]
+    DCICLCD = 135,
+    #[cfg(feature = "imxrt1170")]
+    VideoMux = 136,
+    UniqEdtI = 137,
+}
+
+impl Gate {
+    #[inline(always)]
+    fn direct(self) -> *mut u32 {
+        (LPCG_BASE + self as usize * LPCG_STRIDE) as *mut u32
+    }
+}
+
+/// A clock root source
+///
+/// The first four sources are the same for every clock root. The meaning of
+/// the other sources depends on the clock root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u32)]
+pub enum Mux {
+    /// The 48MHz RC oscillator, divided by 2
+    RC48MDiv2 = 0,
+    /// The 24MHz crystal oscillator
+    Oscillator = 1,
+    /// The 400MHz RC oscillator
+    RC400M = 2,
+    /// The 16MHz RC oscillator
+    RC16M = 3,
+    /// The clock root's fifth source
+    Source4 = 4,
+    /// The clock root's sixth source
+    Source5 = 5,
+    /// The clock root's seventh source
+    Source6 = 6,
+    /// The clock root's eighth source
+    Source7 = 7,
+}
+
+impl Mux {
+    const fn from_field(mux: u32) -> Self {
+        match mux & 0b111 {
+            0 => Mux::RC48MDiv2,
+            1 => Mux::Oscillator,
+            2 => Mux::RC400M,
+            3 => Mux::RC16M,
+            4 => Mux::Source4,
+            5 => Mux::Source5,
+            6 => Mux::Source6,
+            _ => Mux::Source7,
+        }
+    }
+
+    /// Returns the source frequency, or `None` if the source depends on
+    /// the clock root
+    pub const fn frequency(self) -> Option<u32> {
+        match self {
+            Mux::RC48MDiv2 => Some(24_000_000),
+            Mux::Oscillator => Some(OSCILLATOR_FREQUENCY_HZ),
+            Mux::RC400M => Some(400_000_000),
+            Mux::RC16M => Some(16_000_000),
+            _ => None,
+        }
+    }
+}
+
+/// A clock root's configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RootConfig {
+    /// The selected source
+    pub mux: Mux,
+    /// The divider, between 1 and 256
+    pub divider: u32,
+    /// `true` if the clock root is on
+    pub enabled: bool,
+}
+
+impl RootConfig {
+    /// Returns the clock root frequency, or `None` if the frequency depends
+    /// on a PLL
+    ///
+    /// Returns `Some(0)` if the clock root is off.
+    pub const fn frequency(&self) -> Option<u32> {
+        match self.mux.frequency() {
+            Some(_) if !self.enabled => Some(0),
+            Some(hz) => Some(hz / self.divider),
+            None => None,
+        }
+    }
+}
+
+/// The 11xx clock roots and clock gates
+pub struct CCM(());
+
+impl CCM {
+    /// Construct a new CCM
+    ///
+    /// # Safety
+    ///
+    /// There should only be one `CCM` at any time. The CCM must not be used
+    /// on a 10xx processor.
+    pub const unsafe fn new() -> Self {
+        CCM(())
+    }
+
+    /// Select `mux` for `root`, and divide the source by `divider`
+    ///
+    /// The divider should be between [1, 256]. The function will treat a 0 as 1,
+    /// and anything greater than 256 as 256. `configure_root` turns off the
+    /// root's clock gate, if it has one, and turns on the clock root. See
+    /// [`ClockRoot::gate`] for the clock roots that don't have a clock gate.
+    ///
+    /// `configure_root` waits for the clock root to change. If the new source
+    /// doesn't run, the change never completes, and `configure_root` never
+    /// returns.
+    #[inline(always)]
+    pub fn configure_root(&mut self, root: ClockRoot, mux: Mux, divider: u32) {
+        // Safety: we own the CCM peripheral memory
+        unsafe {
+            if let Some(gate) = root.gate() {
+                set_clock_gate_(gate.direct(), false);
+            }
+            configure_root_(root.control(), mux, divider);
+        }
+    }
+
+    /// Divide the source of `root` by `divider`, leaving the source unchanged
+    ///
+    /// The divider should be between [1, 256]. The function will treat a 0 as 1,
+    /// and anything greater than 256 as 256. `set_root_divider` doesn't change
+    /// any clock gates. Like [`configure_root`](CCM::configure_root), it
+    /// never returns if the clock root's source doesn't run.
+    #[inline(always)]
+    pub fn set_root_divider(&mut self, root: ClockRoot, divider: u32) {
+        // Safety: we own the CCM peripheral memory
+        unsafe { set_root_divider_(root.control(), divider) }
+    }
+
+    /// Turn `root` on or off, leaving its source and divider unchanged
+    ///
+    /// Like [`configure_root`](CCM::configure_root), `set_root_enabled`
+    /// never returns if the clock root's source doesn't run.
+    #[inline(always)]
+    pub fn set_root_enabled(&mut self, root: ClockRoot, enabled: bool) {
+        // Safety: we own the CCM peripheral memory
+        unsafe { set_root_enabled_(root.control(), enabled) }
+    }
+
+    /// Returns the configuration of `root`
+    #[inline(always)]
+    pub fn root(&self, root: ClockRoot) -> RootConfig {
+        // Safety: atomic read of a valid register
+        unsafe { root_(root.control()) }
+    }
+
+    /// Returns the frequency of `root`, or `None` if the frequency depends
+    /// on a PLL
+    ///
+    /// See [`RootConfig::frequency`] for more information.
+    #[inline(always)]
+    pub fn root_frequency(&self, root: ClockRoot) -> Option<u32> {
+        self.root(root).frequency()
+    }
+
+    /// Turn the clock gate on or off
+    #[inline(always)]
+    pub fn set_clock_gate(&mut self, gate: Gate, on: bool) {
+        // Safety: we own the CCM peripheral memory
+        unsafe { set_clock_gate_(gate.direct(), on) }
+    }
+
+    /// Returns `true` if the clock gate is on
+    #[inline(always)]
+    pub fn clock_gate(&self, gate: Gate) -> bool {
+        // Safety: atomic read of a valid register
+        unsafe { ON.read(gate.direct()) != 0 }
+    }
+}
+
+/// The 11xx CCM
+///
+/// `set_root_divider` configures every clock root. A clock gate is enabled
+/// if it's on.
+impl ClockControl for CCM {
+    type Gate = Gate;
+    type Root = ClockRoot;
+
+    #[inline(always)]
+    fn set_clock_gate_enabled(&mut self, gate: Gate, enabled: bool) {
+        self.set_clock_gate(gate, enabled)
+    }
+
+    #[inline(always)]
+    fn clock_gate_enabled(&self, gate: Gate) -> bool {
+        self.clock_gate(gate)
+    }
+
+    fn set_root_divider(&mut self, root: ClockRoot, divider: u32) -> Result<(), ClockError> {
+        let divider = Divider::try_from(divider)?;
+        CCM::set_root_divider(self, root, divider.get());
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn root_frequency(&self, root: ClockRoot) -> Option<u32> {
+        CCM::root_frequency(self, root)
+    }
+}
+
+unsafe fn configure_root_(control: *mut u32, mux: Mux, divider: u32) {
+    let divider = divider.clamp(1, 256);
+    // The other CONTROL bits are reserved; OFF is zero, so the root turns on
+    crate::register::write(control, u32::MAX, (mux as u32) << 8 | (divider - 1));
+    wait_(control);
+}
+
+unsafe fn set_root_divider_(control: *mut u32, divider: u32) {
+    DIV.modify(control, divider.clamp(1, 256) - 1);
+    wait_(control);
+}
+
+unsafe fn set_root_enabled_(control: *mut u32, enabled: bool) {
+    OFF.modify(control, !enabled as u32);
+    wait_(control);
+}
+
+/// Wait for a clock root change to take effect
+///
+/// The change only completes if the old and new sources run, so this spins
+/// forever if they don't.
+unsafe fn wait_(control: *const u32) {
+    while CHANGING.read(control.add(STATUS0)) != 0 {}
+}
+
+unsafe fn root_(control: *const u32) -> RootConfig {
+    let value = crate::register::load(control);
+    RootConfig {
+        mux: Mux::from_field(MUX.get(value)),
+        divider: DIV.get(value) + 1,
+        enabled: OFF.get(value) == 0,
+    }
+}
+
+unsafe fn set_clock_gate_(direct: *mut u32, on: bool) {
+    ON.modify(direct, on as u32);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        configure_root_, root_, set_clock_gate_, set_root_divider_, set_root_enabled_, ClockRoot,
+        Gate, Mux, RootConfig, ON,
+    };
+
+    #[test]
+    fn imxrt11xx_root() {
+        // CONTROL, followed by the SET, CLR, TOG, and STATUS0 registers
+        let mut control = [0u32; 9];
+        unsafe {
+            configure_root_(control.as_mut_ptr(), Mux::RC400M, 4);
+            assert_eq!(control[0], 2 << 8 | 3);
+            let config = root_(control.as_ptr());
+            assert_eq!(
+                config,
+                RootConfig {
+                    mux: Mux::RC400M,
+                    divider: 4,
+                    enabled: true
+                }
+            );
+            assert_eq!(config.frequency(), Some(100_000_000));
+
+            set_root_enabled_(control.as_mut_ptr(), false);
+            assert_eq!(control[0], 1 << 24 | 2 << 8 | 3);
+            assert_eq!(root_(control.as_ptr()).frequency(), Some(0));
+
+            configure_root_(control.as_mut_ptr(), Mux::Source5, 0);
+            assert_eq!(control[0], 5 << 8);
+            assert_eq!(root_(control.as_ptr()).frequency(), None);
+            configure_root_(control.as_mut_ptr(), Mux::Oscillator, 1000);
+            assert_eq!(control[0], 1 << 8 | 255);
+            set_root_divider_(control.as_mut_ptr(), 6);
+            assert_eq!(control[0], 1 << 8 | 5);
+        }
+    }
+
+    #[test]
+    fn imxrt11xx_gate() {
+        let mut direct: u32 = 0;
+        unsafe {
+            set_clock_gate_(&mut direct, true);
+            assert_eq!(ON.read(&direct), 1);
+            set_clock_gate_(&mut direct, false);
+        }
+        assert_eq!(direct, 0);
+
+        assert_eq!(ClockRoot::GPT1.gate(), Some(Gate::GPT1));
+        assert_eq!(ClockRoot::GPT6.gate(), Some(Gate::GPT6));
+        assert_eq!(ClockRoot::LPUART12.gate(), Some(Gate::LPUART12));
+        assert_eq!(ClockRoot::LPI2C3.gate(), Some(Gate::LPI2C3));
+        assert_eq!(ClockRoot::LPSPI6.gate(), Some(Gate::LPSPI6));
+        assert_eq!(ClockRoot::ADC2.gate(), Some(Gate::LPADC2));
+        assert_eq!(ClockRoot::ENET2.gate(), Some(Gate::ENET1G));
+        assert_eq!(ClockRoot::SAI4.gate(), Some(Gate::SAI4));
+        assert_eq!(ClockRoot::Bus.gate(), None);
+        assert_eq!(ClockRoot::ACMP.gate(), None);
+        assert_eq!(ClockRoot::CSTrace.gate(), Some(Gate::CSTrace));
+        assert_eq!(ClockRoot::CKO2 as u32, 78);
+        assert_eq!(Gate::UniqEdtI as u32, 137);
+    }
+
+    #[test]
+    fn imxrt11xx_core_memory_roots_ungated() {
+        // Gating these would stop the core, or the memory it executes from
+        for root in [
+            ClockRoot::M7,
+            ClockRoot::M4,
+            ClockRoot::SEMC,
+            ClockRoot::FlexSPI1,
+            ClockRoot::FlexSPI2,
+        ] {
+            assert_eq!(root.gate(), None);
+        }
+    }
+}
@@ -71,13 +71,16 @@
 //!
 //! fn take_ccm() -> Option<CCM> {
 //!   // TODO safety check that ensures
-//!   // CCM only taken once!
+//!   // CCM only taken once! Or, enable the
+//!   // "critical-section" feature, and use CCM::take.
 //!   Some(unsafe { CCM::new() })
 //! }
 //!
 //! let mut ccm = take_ccm().unwrap();
 //! // Enable the clock, which disables all clock gates
-//! let mut i2c_clock = ccm.i2c_clock_mut().configure_divider(8);
+//! let i2c_root = ccm.i2c_clock_mut().configure_divider(8);
+//! // Check the root frequency against the driver's needs
+//! assert!(i2c_root.relative_to(3_000_000).error_hz.abs() < 100_000);
 //! ```
 //!
 //! We recommend that you create driver initialization APIs that require clocks. By requiring an immutable
@@ -148,7 +151,7 @@
 //!
 //! # `imxrt-ral` support
 //!
-//! `imxrt-ccm` provides support for `imxrt-ral`. The feature includes `Instance` implementations on
+//! `imxrt-ccm` provides support for `imxrt-ral` 0.5. The feature includes `Instance` implementations on
 //! all supported `imxrt-ral` peripheral instances. It also include helper functions and types,
 //! which are exported in the `ral` module. Use the `imxrt-ral` support if your HAL already depends on
 //! the `imxrt-ral` crate.
@@ -157,6 +160,13 @@
 //! in your dependency graph enables the correct `imxrt-ral` feature for your processor. See the
 //! `imxrt-ral` documentation for more information.
 //!
+//! # svd2rust support
+//!
+//! If your HAL uses a svd2rust peripheral access crate (PAC), enable the `"svd2rust"` feature.
+//! Wrap a PAC peripheral in a `svd2rust::Peripheral` to use it as an [`Instance`]. The wrapper
+//! identifies the instance by its register block address. Like the `imxrt-ral` support, you
+//! must enable one of the chip features.
+//!
 //! # Chip support
 //!
 //! `imxrt-ccm` does not require you to select a chip. If you do not select a chip, the crate provides
@@ -171,18 +181,151 @@
 //! | `"imxrt1010"` | Support for i.MX RT 1010 processors, like iMXRT1011               |
 //! | `"imxrt1060"` | Support for i.MX RT 1060 processors, like iMXRT1061 and iMXRT1062 |
 //!
+//! The i.MX RT 1160 and 1170 have a different CCM, with generic clock roots and
+//! per-peripheral low-power clock gates (LPCG). Enable the `"imxrt1160"` or `"imxrt1170"`
+//! feature to use the `imxrt11xx` module, which controls the 11xx clock roots and clock
+//! gates. The rest of the crate only supports the 10xx processors.
+//!
 //! If you enable the `imxrt-ral` feature, you **must** enable one of these features.
+//!
+//! Use `chip::validate` to check, at runtime, that your program runs on the chip that
+//! you selected.
+//!
+//! # Custom clock gates
+//!
+//! `imxrt-ccm` only models a subset of all clock gates. To control a clock gate that
+//! this crate doesn't know about, enable the `"unsealed"` feature, and implement
+//! [`ClockGateLocator`] for your own peripheral instance identifier. See the
+//! `ClockGateLocator` documentation for the invariants that you must uphold.
+//!
+//! # Critical sections
+//!
+//! Enable the `"critical-section"` feature to use `CCM::take`, which hands out
+//! the CCM at most once. The feature also makes each clock gate change a
+//! critical section, so that gate changes from thread and interrupt contexts
+//! can't corrupt each other's CCGR read-modify-write. Use the `Sync`
+//! `GateControl` to change clock gates from a `static` that's shared with
+//! interrupts. Your program must provide a
+//! [`critical-section`](https://docs.rs/critical-section) implementation.
+//!
+//! # Clock gate cache
+//!
+//! Enable the `"gate-cache"` feature to keep a copy of the CCGR registers in RAM.
+//! Clock gate getters read the copy, and clock gate setters compute their
+//! read-modify-write from the copy, instead of reading the registers over the
+//! peripheral bus. The cache fills on the first clock gate access. If other code
+//! changes a CCGR register, call `CCM::sync_from_hardware` to refill the cache.
+//!
+//! # Dry runs
+//!
+//! Enable the `"dry-run"` feature to record CCM register writes instead of
+//! performing them. See the `dryrun` module for more information.
+//!
+//! # Host simulation
+//!
+//! Enable the `"simulation"` feature in your tests to run the crate against
+//! in-memory CCM and CCM_ANALOG registers. Use it to test your clock setup on a
+//! development host, or on CI, without hardware. The feature requires `std`, so
+//! don't enable it for an embedded target. See the `simulation` module for more
+//! information.
+//!
+//! # Calculations without hardware
+//!
+//! The clock root modules `perclock`, `uart`, `spi`, and `i2c` each provide a
+//! `closest` function, which chooses a source and divider for a target
+//! frequency, and a `root_frequency` function, which computes the frequency of
+//! a source and divider. `arm::Timings::with_reference` computes the ARM
+//! clock dividers. These functions take the source frequencies as inputs, so
+//! they run on a host, and in `const` contexts.
+//!
+//! # Monotonic timers
+//!
+//! Enable the `"monotonic"` feature to run the periodic clock at a rate that's
+//! known at compile time, for GPT-based monotonic timers, like an RTIC monotonic.
+//! See the `monotonic` module for more information.
+//!
+//! # `fugit` support
+//!
+//! Enable the `"fugit"` feature to use [`fugit`](https://docs.rs/fugit) rates
+//! alongside the frequencies in Hz. Clock roots and PLLs have `rate()` getters, and
+//! clock roots have `configure_rate()` setters. `ARMClock` and `IPGClock` convert into
+//! a `fugit::HertzU32`.
+//!
+//! # Register tracing
+//!
+//! Enable the `"log"` or `"defmt"` feature to trace every CCM register write at the
+//! trace level. Each message includes the register address, the mask of the bits
+//! that the write intends to change, and the register's old and new values. Use
+//! the trace to find the exact sequence of writes that precedes a clock-related
+//! lockup. Tracing reads each register before writing it, so only enable it while
+//! debugging.
 
 #![cfg_attr(not(test), no_std)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+#[cfg(feature = "simulation")]
+extern crate std;
+
 pub mod arm;
+pub mod ccgr;
+pub mod chip;
+mod config;
+mod debug;
+#[cfg(feature = "delay")]
+#[cfg_attr(docsrs, doc(cfg(feature = "delay")))]
+pub mod delay;
+mod divider;
+#[cfg(feature = "dry-run")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dry-run")))]
+pub mod dryrun;
+pub mod dvfs;
+pub mod family;
+mod fracpll;
+mod frozen;
 mod gate;
+pub mod glitchless;
 pub mod i2c;
+#[cfg(any(feature = "imxrt1160", feature = "imxrt1170"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "imxrt1160", feature = "imxrt1170"))))]
+pub mod imxrt11xx;
+pub mod lowpower;
+#[cfg(feature = "imxrt1060")]
+#[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+pub mod lvds;
+mod map;
+#[cfg(feature = "monotonic")]
+#[cfg_attr(docsrs, doc(cfg(feature = "monotonic")))]
+pub mod monotonic;
 pub mod perclock;
+mod pfd;
+pub mod pll;
+pub mod pll2;
+pub mod pll3;
+pub mod pll4;
+#[cfg(feature = "imxrt1060")]
+#[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+pub mod pll5;
+pub mod pll6;
+#[cfg(feature = "fugit")]
+mod rate;
+pub mod raw;
 mod register;
+pub mod shared;
+#[cfg(feature = "simulation")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simulation")))]
+pub mod simulation;
+mod source;
 pub mod spi;
+mod state;
+#[cfg(feature = "svd2rust")]
+#[cfg_attr(docsrs, doc(cfg(feature = "svd2rust")))]
+pub mod svd2rust;
+pub mod tree;
 pub mod uart;
+mod usage;
+pub mod usb;
+pub mod verify;
+pub mod xtalosc;
 
 #[cfg(feature = "imxrt-ral")]
 #[cfg_attr(docsrs, doc(cfg(feature = "imxrt-ral")))]
@@ -192,8 +335,16 @@ use core::marker::PhantomData;
 
 use perclock::PerClock;
 
+pub use config::{Applied, CcmConfig, Mismatch, Mismatches};
+pub use debug::DebugSnapshot;
+pub use divider::{ClockError, Divider, DividerOutOfRange, RootFrequency, RootSetting};
+pub use frozen::FrozenClocks;
+pub use map::FrequencyMap;
+pub use source::ClockSource;
+pub use state::CcmState;
+
 /// Describes the location of a clock gate field
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ClockGateLocation {
     /// CCGR register offset
     ///
@@ -205,15 +356,188 @@ pub struct ClockGateLocation {
     gates: &'static [usize],
 }
 
+/// The number of CCGR registers
+///
+/// The 1010 does not have CCGR7.
+#[cfg(feature = "imxrt1060")]
+const CCGR_COUNT: usize = 8;
+#[cfg(not(feature = "imxrt1060"))]
+const CCGR_COUNT: usize = 7;
+/// The number of clock gate fields in a CCGR register
+const GATES_PER_CCGR: usize = 16;
+
+impl ClockGateLocation {
+    /// Create a clock gate location
+    ///
+    /// `offset` is the CCGR register number, and `gates` are the
+    /// clock gate fields in that register. To describe `CCM_CCGR3[CG7]`,
+    /// use
+    ///
+    /// ```
+    /// # use imxrt_ccm::ClockGateLocation;
+    /// const LOCATION: ClockGateLocation = ClockGateLocation::new(3, &[7]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` does not name a CCGR register (CCGR0 through CCGR7 on a 1060,
+    /// and CCGR0 through CCGR6 on all other chips), if
+    /// `gates` is empty, or if any gate is not a field in a CCGR register (CG0
+    /// through CG15).
+    pub const fn new(offset: usize, gates: &'static [usize]) -> Self {
+        assert!(offset < CCGR_COUNT, "CCGR offset out of range");
+        assert!(!gates.is_empty(), "Clock gate location requires a gate");
+        let mut idx = 0;
+        while idx < gates.len() {
+            assert!(gates[idx] < GATES_PER_CCGR, "Clock gate out of range");
+            idx += 1;
+        }
+        ClockGateLocation { offset, gates }
+    }
+}
+
 /// A type that can locate a clock gate
 ///
 /// `ClockGateLocator` is implemented on all structs and enums
 /// that describe peripheral instances.
+///
+/// # Custom locators
+///
+/// By default, you cannot implement `ClockGateLocator`. Enable the `"unsealed"`
+/// feature to implement `ClockGateLocator` for peripherals that this crate doesn't
+/// model. Your implementation must hold these invariants:
+///
+/// - `location` always returns the same [`ClockGateLocation`] for the same value.
+/// - the location describes the clock gate(s) for your peripheral, and no other
+///   peripheral. Otherwise, changing your clock gate will change the clock gate of
+///   an unrelated peripheral.
+///
+/// ```
+/// # #[cfg(feature = "unsealed")] {
+/// use imxrt_ccm::{ClockGateLocation, ClockGateLocator};
+///
+/// /// FlexIO2 clock gate
+/// #[derive(Clone, Copy, PartialEq)]
+/// struct FlexIO2;
+///
+/// impl ClockGateLocator for FlexIO2 {
+///     fn location(&self) -> ClockGateLocation {
+///         ClockGateLocation::new(3, &[0])
+///     }
+/// }
+/// # }
+/// ```
 pub trait ClockGateLocator: Copy + PartialEq + private::Sealed {
     /// Returns the location of a clock gate
     fn location(&self) -> ClockGateLocation;
 }
 
+/// The clock that drives a peripheral
+///
+/// See [`ClockRootLocator`] and [`CCM::frequency`]. The chip features enable the
+/// clock roots that exist on that chip. For instance, the 1010 has SAI1, SAI3,
+/// FlexSPI, and trace clock roots, but no SAI2, USDHC, SEMC, CAN, or FlexIO2
+/// clock roots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockRoot {
+    /// AHB_CLK_ROOT, which is the same as the ARM clock
+    AHB,
+    /// IPG_CLK_ROOT
+    IPG,
+    /// PERCLK_CLK_ROOT, for the GPT and PIT timers
+    PerClock,
+    /// UART_CLK_ROOT
+    UART,
+    /// LPSPI_CLK_ROOT
+    SPI,
+    /// LPI2C_CLK_ROOT
+    I2C,
+    /// USDHC1_CLK_ROOT
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    USDHC1,
+    /// USDHC2_CLK_ROOT
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    USDHC2,
+    /// SAI1_CLK_ROOT
+    #[cfg(any(feature = "imxrt1060", feature = "imxrt1010"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "imxrt1060", feature = "imxrt1010"))))]
+    SAI1,
+    /// SAI2_CLK_ROOT
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    SAI2,
+    /// SAI3_CLK_ROOT
+    #[cfg(any(feature = "imxrt1060", feature = "imxrt1010"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "imxrt1060", feature = "imxrt1010"))))]
+    SAI3,
+    /// FLEXSPI_CLK_ROOT
+    #[cfg(any(feature = "imxrt1060", feature = "imxrt1010"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "imxrt1060", feature = "imxrt1010"))))]
+    FlexSPI,
+    /// SEMC_CLK_ROOT
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    SEMC,
+    /// TRACE_CLK_ROOT
+    #[cfg(any(feature = "imxrt1060", feature = "imxrt1010"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "imxrt1060", feature = "imxrt1010"))))]
+    Trace,
+    /// CAN_CLK_ROOT
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    CAN,
+    /// FLEXIO2_CLK_ROOT
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    FlexIO2,
+}
+
+/// A type that can locate the clock root that drives a peripheral
+///
+/// `ClockRootLocator` is implemented on the peripheral instance identifiers
+/// that this crate models.
+pub trait ClockRootLocator: ClockGateLocator {
+    /// Returns the clock root that drives the peripheral
+    fn clock_root(&self) -> ClockRoot;
+}
+
+/// A clock that drives peripherals of type `Inst`
+///
+/// Peripheral drivers can accept an `impl ClockFor<Inst>` to learn their clock
+/// frequency, without depending on a concrete clock type. The clock root handles,
+/// like [`uart::UARTClock`], implement `ClockFor` for their peripherals. The
+/// [`arm::ARMClock`] and [`arm::IPGClock`] frequencies implement `ClockFor` for the
+/// peripherals that they drive.
+///
+/// ```
+/// use imxrt_ccm::{uart::UART, ClockFor};
+///
+/// struct UARTDriver {
+///     baud_divider: u32,
+/// }
+///
+/// impl UARTDriver {
+///     pub fn new(clock: &impl ClockFor<UART>, baud: u32) -> Self {
+///         UARTDriver {
+///             baud_divider: clock.frequency() / baud,
+///         }
+///     }
+/// }
+/// ```
+pub trait ClockFor<Inst: ClockGateLocator> {
+    /// Returns the clock frequency, in Hz
+    fn frequency(&self) -> u32;
+}
+
+#[cfg(feature = "unsealed")]
+mod private {
+    pub trait Sealed {}
+    impl<T> Sealed for T {}
+}
+
+#[cfg(not(feature = "unsealed"))]
 mod private {
     pub trait Sealed {}
     impl Sealed for super::ADC {}
@@ -225,6 +549,7 @@ mod private {
     impl Sealed for super::PWM {}
     impl Sealed for super::spi::SPI {}
     impl Sealed for super::uart::UART {}
+    impl Sealed for super::usb::USB {}
 }
 
 /// A peripheral instance that has a clock gate
@@ -312,6 +637,20 @@ impl ClockGateLocator for DCDC {
     }
 }
 
+impl ClockRootLocator for DCDC {
+    #[inline(always)]
+    fn clock_root(&self) -> ClockRoot {
+        ClockRoot::IPG
+    }
+}
+
+impl ClockFor<DCDC> for arm::IPGClock {
+    #[inline(always)]
+    fn frequency(&self) -> u32 {
+        self.0
+    }
+}
+
 /// Peripheral instance identifier for DMA
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct DMA;
@@ -326,9 +665,25 @@ impl ClockGateLocator for DMA {
     }
 }
 
+impl ClockRootLocator for DMA {
+    #[inline(always)]
+    fn clock_root(&self) -> ClockRoot {
+        ClockRoot::AHB
+    }
+}
+
+impl ClockFor<DMA> for arm::ARMClock {
+    #[inline(always)]
+    fn frequency(&self) -> u32 {
+        self.0
+    }
+}
+
 /// Set the clock gate for a peripheral instance
 ///
-/// `set_clock_gate` does nothing if the instance is invalid.
+/// `set_clock_gate` does nothing if the instance is invalid. If you enable the
+/// `"critical-section"` feature, the CCGR read-modify-write happens in a critical
+/// section.
 ///
 /// # Safety
 ///
@@ -352,6 +707,168 @@ pub fn get_clock_gate<I: Instance>(inst: I::Inst) -> Option<ClockGate> {
     })
 }
 
+/// Interrupt-safe clock gate control
+///
+/// `GateControl` is `Sync`, so it can live in a `static` that's shared by
+/// thread and interrupt contexts. Each gate change is a critical section.
+/// Like the [`CCM`] methods, changing a clock gate requires a mutable
+/// reference to the peripheral instance.
+///
+/// ```no_run
+/// use imxrt_ccm::{ClockGate, GateControl, Instance};
+/// # struct GPT1;
+/// # unsafe impl Instance for GPT1 {
+/// #     type Inst = imxrt_ccm::perclock::GPT;
+/// #     fn instance(&self) -> Self::Inst { imxrt_ccm::perclock::GPT::GPT1 }
+/// #     fn is_valid(inst: Self::Inst) -> bool { inst == imxrt_ccm::perclock::GPT::GPT1 }
+/// # }
+///
+/// static GATES: GateControl = GateControl::new();
+///
+/// fn on_interrupt(gpt: &mut GPT1) {
+///     GATES.set_clock_gate(gpt, ClockGate::Off);
+/// }
+/// ```
+#[cfg(feature = "critical-section")]
+#[cfg_attr(docsrs, doc(cfg(feature = "critical-section")))]
+#[derive(Debug)]
+pub struct GateControl(());
+
+#[cfg(feature = "critical-section")]
+impl GateControl {
+    /// Create a clock gate control
+    pub const fn new() -> Self {
+        GateControl(())
+    }
+
+    /// Set the clock gate for a peripheral instance
+    #[inline(always)]
+    pub fn set_clock_gate<I: Instance>(&self, inst: &mut I, gate: ClockGate) {
+        // Safety: we have exclusive access to the instance, and the
+        // read-modify-write happens in a critical section.
+        unsafe { set_clock_gate::<I>(inst.instance(), gate) }
+    }
+
+    /// Returns the clock gate setting for a peripheral instance
+    #[inline(always)]
+    pub fn clock_gate<I: Instance>(&self, inst: &I) -> ClockGate {
+        // Unwrap OK: we have the instance, or the `Instance`
+        // implementation is incorrect.
+        get_clock_gate::<I>(inst.instance()).unwrap()
+    }
+}
+
+#[cfg(feature = "critical-section")]
+impl Default for GateControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Turn off the clock gates of all valid instances
+///
+/// # Safety
+///
+/// See [`set_clock_gate`].
+#[inline(always)]
+unsafe fn clock_gates_off<I: Instance>(insts: &[I::Inst]) {
+    for &inst in insts {
+        set_clock_gate::<I>(inst, ClockGate::Off);
+    }
+}
+
+/// Returns `true` if any valid instance has a clock gate that's not off
+#[inline(always)]
+fn any_clock_gate_on<I: Instance>(insts: &[I::Inst]) -> bool {
+    insts
+        .iter()
+        .filter_map(|&inst| get_clock_gate::<I>(inst))
+        .any(|gate| gate != ClockGate::Off)
+}
+
+/// A clock gate that's on while the guard exists
+///
+/// When the guard drops, the peripheral's clock gate returns to the
+/// setting it had before the guard was created. Use `ClockGateGuard` to
+/// tie a clock gate to the lifetime of a driver:
+///
+/// ```no_run
+/// # use imxrt_ccm as ccm;
+/// # struct I2C;
+/// # unsafe impl ccm::Instance for I2C {
+/// #     type Inst = ccm::i2c::I2C;
+/// #     fn instance(&self) -> Self::Inst { ccm::i2c::I2C::I2C1 }
+/// #     fn is_valid(inst: Self::Inst) -> bool { inst == ccm::i2c::I2C::I2C1 }
+/// # }
+/// # struct MyClocks;
+/// # impl ccm::Clocks for MyClocks {
+/// #   type I2C = I2C;
+/// #   type SPI = ();
+/// #   type UART = ();
+/// #   type GPT = ();
+/// #   type PIT = ();
+/// # }
+/// # let mut ccm = unsafe { ccm::CCM::<MyClocks>::new() };
+/// # let mut i2c1 = I2C;
+/// let i2c_clock = ccm.i2c_clock_mut();
+/// i2c_clock.configure();
+/// {
+///     let i2c = i2c_clock.enable_guard(&mut i2c1);
+///     // Use the I2C peripheral through the guard...
+/// } // Clock gate turns off here
+/// ```
+///
+/// The guard mutably borrows the peripheral instance, but not the clock that
+/// created the guard, so you can keep using the clock while the guard exists.
+/// Use `Deref` and `DerefMut` to access the peripheral instance.
+pub struct ClockGateGuard<'a, I: Instance> {
+    inst: &'a mut I,
+    previous: ClockGate,
+}
+
+impl<'a, I: Instance> ClockGateGuard<'a, I> {
+    /// Turn on the instance's clock gate, remembering its previous setting
+    ///
+    /// # Safety
+    ///
+    /// Caller must make sure that the clock gate can change. Exclusive access
+    /// to the instance prevents other changes to the clock gate for the
+    /// lifetime `'a`.
+    #[inline(always)]
+    pub(crate) unsafe fn new(inst: &'a mut I) -> Self {
+        // Unwrap OK: we have the instance, or the `Instance`
+        // implementation is incorrect.
+        let previous = get_clock_gate::<I>(inst.instance()).unwrap();
+        set_clock_gate::<I>(inst.instance(), ClockGate::On);
+        ClockGateGuard { inst, previous }
+    }
+
+    /// Returns the clock gate setting that the guard will restore
+    pub fn previous(&self) -> ClockGate {
+        self.previous
+    }
+}
+
+impl<I: Instance> core::ops::Deref for ClockGateGuard<'_, I> {
+    type Target = I;
+    fn deref(&self) -> &I {
+        self.inst
+    }
+}
+
+impl<I: Instance> core::ops::DerefMut for ClockGateGuard<'_, I> {
+    fn deref_mut(&mut self) -> &mut I {
+        self.inst
+    }
+}
+
+impl<I: Instance> Drop for ClockGateGuard<'_, I> {
+    fn drop(&mut self) {
+        // Safety: guard holds exclusive access to the instance
+        unsafe { set_clock_gate::<I>(self.inst.instance(), self.previous) };
+    }
+}
+
 /// Peripheral instance identifier for ADCs
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ADC {
@@ -370,6 +887,20 @@ impl ClockGateLocator for ADC {
     }
 }
 
+impl ClockRootLocator for ADC {
+    #[inline(always)]
+    fn clock_root(&self) -> ClockRoot {
+        ClockRoot::IPG
+    }
+}
+
+impl ClockFor<ADC> for arm::IPGClock {
+    #[inline(always)]
+    fn frequency(&self) -> u32 {
+        self.0
+    }
+}
+
 /// Peripheral instance identifier for PWM
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PWM {
@@ -392,10 +923,27 @@ impl ClockGateLocator for PWM {
     }
 }
 
+impl ClockRootLocator for PWM {
+    #[inline(always)]
+    fn clock_root(&self) -> ClockRoot {
+        ClockRoot::IPG
+    }
+}
+
+impl ClockFor<PWM> for arm::IPGClock {
+    #[inline(always)]
+    fn frequency(&self) -> u32 {
+        self.0
+    }
+}
+
 /// Correlates an instance type to a CCM clock root
 ///
 /// If you're usage doesn't require a clock, fill in an empty
 /// tuple, `()`, or any type that _doesn't_ implement [`Instance`].
+///
+/// If you only use one of the periodic clock's timers, use
+/// [`perclock::NoGPT`] or [`perclock::NoPIT`] for the other timer.
 pub trait Clocks {
     /// PIT instance
     type PIT;
@@ -428,6 +976,24 @@ pub struct CCM<C: Clocks> {
     ///
     /// `i2c_clock` is for I2C peripherals.
     i2c_clock: i2c::I2CClock<C::I2C>,
+    /// The system PLL
+    pll2: pll2::PLL2,
+    /// The USB1 PLL
+    pll3: pll3::PLL3,
+    /// The audio PLL
+    pll4: pll4::PLL4,
+    /// The video PLL
+    #[cfg(feature = "imxrt1060")]
+    pll5: pll5::PLL5,
+    /// The ENET PLL
+    pll6: pll6::PLL6,
+    /// The 24MHz oscillator
+    xtalosc: xtalosc::XTALOSC,
+    /// The LVDS1 clock output
+    #[cfg(feature = "imxrt1060")]
+    lvds: lvds::LVDS,
+    /// Low-power mode controls
+    lowpower: lowpower::LowPower,
     /// Marker to prevent default Sync implementation
     _not_sync: PhantomData<*const ()>,
 }
@@ -449,10 +1015,38 @@ impl<C: Clocks> CCM<C> {
             uart_clock: uart::UARTClock::new(),
             spi_clock: spi::SPIClock::new(),
             i2c_clock: i2c::I2CClock::new(),
+            pll2: pll2::PLL2::new(),
+            pll3: pll3::PLL3::new(),
+            pll4: pll4::PLL4::new(),
+            #[cfg(feature = "imxrt1060")]
+            pll5: pll5::PLL5::new(),
+            pll6: pll6::PLL6::new(),
+            xtalosc: xtalosc::XTALOSC::new(),
+            #[cfg(feature = "imxrt1060")]
+            lvds: lvds::LVDS::new(),
+            lowpower: lowpower::LowPower::new(),
             _not_sync: PhantomData,
         }
     }
 
+    /// Take the CCM peripheral
+    ///
+    /// Returns `Some` the first time you call `take`, and `None` every time
+    /// after that. The check is shared by all `CCM` types, so only one `CCM`
+    /// exists, no matter the [`Clocks`]. Note that `take` can't know about
+    /// CCMs constructed with [`new`](CCM::new).
+    #[cfg(feature = "critical-section")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "critical-section")))]
+    pub fn take() -> Option<Self> {
+        use core::cell::Cell;
+        use critical_section::Mutex;
+
+        static TAKEN: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
+        let taken = critical_section::with(|cs| TAKEN.borrow(cs).replace(true));
+        // Safety: the flag ensures that this is the only CCM
+        (!taken).then(|| unsafe { Self::new() })
+    }
+
     /// Returns the clock gate setting for the DCDC buck converter
     #[inline(always)]
     pub fn clock_gate_dcdc<D>(&self, dcdc: &D) -> ClockGate
@@ -473,6 +1067,29 @@ impl<C: Clocks> CCM<C> {
         unsafe { set_clock_gate::<D>(dcdc.instance(), gate) };
     }
 
+    /// Turn on the clock gate for the DCDC buck converter, returning a guard that restores
+    /// the previous setting when dropped
+    #[inline(always)]
+    pub fn enable_guard_dcdc<'a, D>(&mut self, dcdc: &'a mut D) -> ClockGateGuard<'a, D>
+    where
+        D: Instance<Inst = DCDC>,
+    {
+        // Safety: we have exclusive access to the instance
+        unsafe { ClockGateGuard::new(dcdc) }
+    }
+
+    /// Share the clock gate for the DCDC buck converter between multiple users
+    ///
+    /// See [`shared`] for more information.
+    #[inline(always)]
+    pub fn shared_gate_dcdc<D>(&mut self, dcdc: D) -> shared::SharedClockGate<D>
+    where
+        D: Instance<Inst = DCDC>,
+    {
+        // Safety: we own the instance
+        unsafe { shared::SharedClockGate::new(dcdc) }
+    }
+
     /// Returns the clock gate setting for the DMA controller
     #[inline(always)]
     pub fn clock_gate_dma<D>(&self, dma: &D) -> ClockGate
@@ -493,6 +1110,29 @@ impl<C: Clocks> CCM<C> {
         unsafe { set_clock_gate::<D>(dma.instance(), gate) };
     }
 
+    /// Turn on the clock gate for the DMA controller, returning a guard that restores
+    /// the previous setting when dropped
+    #[inline(always)]
+    pub fn enable_guard_dma<'a, D>(&mut self, dma: &'a mut D) -> ClockGateGuard<'a, D>
+    where
+        D: Instance<Inst = DMA>,
+    {
+        // Safety: we have exclusive access to the instance
+        unsafe { ClockGateGuard::new(dma) }
+    }
+
+    /// Share the clock gate for the DMA controller between multiple users
+    ///
+    /// See [`shared`] for more information.
+    #[inline(always)]
+    pub fn shared_gate_dma<D>(&mut self, dma: D) -> shared::SharedClockGate<D>
+    where
+        D: Instance<Inst = DMA>,
+    {
+        // Safety: we own the instance
+        unsafe { shared::SharedClockGate::new(dma) }
+    }
+
     /// Returns the clock gate setting for the ADC
     #[inline(always)]
     pub fn clock_gate_adc<A>(&self, adc: &A) -> ClockGate
@@ -513,6 +1153,29 @@ impl<C: Clocks> CCM<C> {
         unsafe { set_clock_gate::<A>(adc.instance(), gate) }
     }
 
+    /// Turn on the clock gate for the ADC peripheral, returning a guard that restores
+    /// the previous setting when dropped
+    #[inline(always)]
+    pub fn enable_guard_adc<'a, A>(&mut self, adc: &'a mut A) -> ClockGateGuard<'a, A>
+    where
+        A: Instance<Inst = ADC>,
+    {
+        // Safety: we have exclusive access to the instance
+        unsafe { ClockGateGuard::new(adc) }
+    }
+
+    /// Share the clock gate for the ADC peripheral between multiple users
+    ///
+    /// See [`shared`] for more information.
+    #[inline(always)]
+    pub fn shared_gate_adc<A>(&mut self, adc: A) -> shared::SharedClockGate<A>
+    where
+        A: Instance<Inst = ADC>,
+    {
+        // Safety: we own the instance
+        unsafe { shared::SharedClockGate::new(adc) }
+    }
+
     /// Returns the clock gate setting for the ADC
     #[inline(always)]
     pub fn clock_gate_pwm<P>(&self, pwm: &P) -> ClockGate
@@ -533,23 +1196,909 @@ impl<C: Clocks> CCM<C> {
         unsafe { set_clock_gate::<P>(pwm.instance(), gate) }
     }
 
+    /// Turn on the clock gate for the PWM peripheral, returning a guard that restores
+    /// the previous setting when dropped
+    #[inline(always)]
+    pub fn enable_guard_pwm<'a, P>(&mut self, pwm: &'a mut P) -> ClockGateGuard<'a, P>
+    where
+        P: Instance<Inst = PWM>,
+    {
+        // Safety: we have exclusive access to the instance
+        unsafe { ClockGateGuard::new(pwm) }
+    }
+
+    /// Share the clock gate for the PWM peripheral between multiple users
+    ///
+    /// See [`shared`] for more information.
+    #[inline(always)]
+    pub fn shared_gate_pwm<P>(&mut self, pwm: P) -> shared::SharedClockGate<P>
+    where
+        P: Instance<Inst = PWM>,
+    {
+        // Safety: we own the instance
+        unsafe { shared::SharedClockGate::new(pwm) }
+    }
+
     /// Set the ARM clock frequency, returning the new ARM and IPG clock frequency
     //
     /// Changing this at runtime will affect anything that's using the ARM or IPG clocks
     /// as inputs. Keep this in mind when changing the core clock frequency throughout
-    /// your programs.
+    /// your programs. `hz` is clamped to [`arm::MAX_FREQUENCY_HZ`].
     #[inline(always)]
     pub fn set_frequency_arm(&mut self, hz: u32) -> (arm::ARMClock, arm::IPGClock) {
         // Safety: we own the CCM peripheral memory
         unsafe { arm::set_frequency(hz) }
     }
 
+    /// Set the ARM clock frequency, returning the new ARM and IPG clock frequency,
+    /// or an error if a divider or mux handshake times out
+    ///
+    /// See [`arm::set_frequency_bounded`] for more information.
+    #[inline(always)]
+    pub fn set_frequency_arm_bounded(
+        &mut self,
+        hz: u32,
+        max_iterations: u32,
+    ) -> Result<(arm::ARMClock, arm::IPGClock), arm::HandshakeTimeout> {
+        // Safety: we own the CCM peripheral memory
+        unsafe { arm::set_frequency_bounded(hz, max_iterations) }
+    }
+
+    /// Set the ARM clock frequency, returning the achieved frequencies, or an error
+    /// if `hz` is out of range
+    ///
+    /// See [`arm::try_set_frequency`] for more information.
+    #[inline(always)]
+    pub fn try_set_frequency_arm(
+        &mut self,
+        hz: u32,
+    ) -> Result<arm::Achieved, arm::FrequencyOutOfRange> {
+        // Safety: we own the CCM peripheral memory
+        unsafe { arm::try_set_frequency(hz) }
+    }
+
+    /// Set the ARM clock frequency above the datasheet limit, returning the
+    /// achieved frequencies
+    ///
+    /// See [`arm::set_frequency_overclock`] for more information.
+    #[inline(always)]
+    pub fn set_frequency_arm_overclock(
+        &mut self,
+        hz: u32,
+        overclock: &arm::Overclock,
+    ) -> Result<arm::Achieved, arm::FrequencyOutOfRange> {
+        // Safety: we own the CCM peripheral memory
+        unsafe { arm::set_frequency_overclock(hz, overclock) }
+    }
+
+    /// Set the ARM clock frequency, calling `set_voltage` to coordinate the VDD_SOC
+    /// voltage with the frequency change
+    ///
+    /// See [`arm::set_frequency_with_voltage`] for more information.
+    #[inline(always)]
+    pub fn set_frequency_arm_with_voltage(
+        &mut self,
+        hz: u32,
+        set_voltage: impl FnOnce(arm::Millivolts),
+    ) -> (arm::ARMClock, arm::IPGClock) {
+        // Safety: we own the CCM peripheral memory
+        unsafe { arm::set_frequency_with_voltage(hz, set_voltage) }
+    }
+
+    /// Set the ARM clock frequency using PLL2 or its PFDs, returning the new ARM
+    /// and IPG clock frequency
+    ///
+    /// See [`arm::set_frequency_pll2`] for more information.
+    #[inline(always)]
+    pub fn set_frequency_arm_pll2(&mut self, hz: u32) -> (arm::ARMClock, arm::IPGClock) {
+        // Safety: we own the CCM peripheral memory
+        unsafe { arm::set_frequency_pll2(hz) }
+    }
+
     /// Returns the ARM and IPG clock frequencies
     #[inline(always)]
     pub fn frequency_arm(&self) -> (arm::ARMClock, arm::IPGClock) {
         // Safety: we own the CCM peripheral memory
         unsafe { arm::frequency() }
     }
+
+    /// Returns the frequency of the Cortex-M SysTick counter, in Hz
+    ///
+    /// `source` is the SysTick CLKSOURCE selection. Use the frequency to set up
+    /// `cortex-m` delays and monotonic timers:
+    ///
+    /// ```no_run
+    /// # use imxrt_ccm as ccm;
+    /// # let ccm = unsafe { ccm::CCM::<ccm::SplitClocks>::new() };
+    /// use ccm::arm::SysTickSource;
+    ///
+    /// let systick_hz = ccm.frequency_systick(SysTickSource::Core);
+    /// assert_eq!(systick_hz, ccm.frequency_core());
+    /// ```
+    pub fn frequency_systick(&self, source: arm::SysTickSource) -> u32 {
+        self.frequency_arm().0.systick(source)
+    }
+
+    /// Returns the core clock frequency, in Hz
+    ///
+    /// This is the ARM clock frequency, which also drives the DWT cycle counter.
+    pub fn frequency_core(&self) -> u32 {
+        self.frequency_arm().0 .0
+    }
+
+    /// Returns the frequency of the clock root that drives `inst`, in Hz
+    ///
+    /// Use `frequency` when you know a peripheral instance, but not which clock
+    /// root drives it. The frequency is the clock root frequency, independent of
+    /// the instance's clock gate. See [`ClockRootLocator`] for the clock roots.
+    ///
+    /// ```no_run
+    /// # use imxrt_ccm as ccm;
+    /// # struct I2C3;
+    /// # unsafe impl ccm::Instance for I2C3 {
+    /// #     type Inst = ccm::i2c::I2C;
+    /// #     fn instance(&self) -> Self::Inst { ccm::i2c::I2C::I2C3 }
+    /// #     fn is_valid(inst: Self::Inst) -> bool { inst == ccm::i2c::I2C::I2C3 }
+    /// # }
+    /// # let ccm = unsafe { ccm::CCM::<ccm::SplitClocks>::new() };
+    /// let i2c3 = I2C3;
+    /// let hz = ccm.frequency(&i2c3);
+    /// assert_eq!(hz, ccm::i2c::frequency());
+    /// ```
+    pub fn frequency<I>(&self, inst: &I) -> u32
+    where
+        I: Instance,
+        I::Inst: ClockRootLocator,
+    {
+        self.root_frequency(inst.instance().clock_root())
+    }
+
+    /// Returns the frequency of a clock root
+    ///
+    /// The frequency is decoded from the clock root's multiplexer and dividers,
+    /// and from the PLLs and PFDs that the clock root selects. It doesn't
+    /// depend on any clock gate. See [`tree`] for the model.
+    ///
+    /// ```no_run
+    /// # use imxrt_ccm as ccm;
+    /// # let ccm = unsafe { ccm::CCM::<ccm::SplitClocks>::new() };
+    /// use ccm::ClockRoot;
+    ///
+    /// assert_eq!(ccm.root_frequency(ClockRoot::UART), ccm::uart::frequency());
+    /// # #[cfg(any(feature = "imxrt1060", feature = "imxrt1010"))]
+    /// let flexspi_hz = ccm.root_frequency(ClockRoot::FlexSPI);
+    /// ```
+    pub fn root_frequency(&self, root: ClockRoot) -> u32 {
+        self.tree().frequency(tree::Node::Root(root))
+    }
+
+    /// Capture the frequency of every clock root
+    ///
+    /// Call `frequency_map` after you configure the clocks, and hand the map
+    /// to drivers. See [`FrequencyMap`] for more information.
+    pub fn frequency_map(&self) -> FrequencyMap {
+        FrequencyMap::new(&self.tree())
+    }
+
+    /// Recapture `map` if it's stale
+    ///
+    /// Returns `true` if any frequency changed.
+    pub fn refresh_frequency_map(&self, map: &mut FrequencyMap) -> bool {
+        !map.is_current() && map.refresh(&self.tree())
+    }
+
+    /// End clock configuration, and return the final clock frequencies
+    ///
+    /// `freeze` consumes the CCM, so the frequencies in [`FrozenClocks`] can't
+    /// change through safe code. Configure the clock gates before you freeze
+    /// the CCM, or use a `GateControl` afterwards.
+    pub fn freeze(self) -> FrozenClocks {
+        FrozenClocks::new(&self.frequency_map())
+    }
+
+    /// Returns the ARM and IPG clock frequencies, and the clock that drives
+    /// AHB_CLK_ROOT
+    #[inline(always)]
+    pub fn clocks_arm(&self) -> arm::Clocks {
+        // Safety: we own the CCM peripheral memory
+        unsafe { arm::clocks() }
+    }
+
+    /// Returns the ARM, AHB, IPG, and periodic clock dividers, and the frequencies
+    /// they produce
+    pub fn bus_dividers(&self) -> BusDividers {
+        let (_arm, ahb, ipg) = arm::dividers();
+        // Safety: we own the CCM peripheral memory
+        let (clocks, perclock_hz) = unsafe { (arm::clocks(), perclock::frequency()) };
+        BusDividers {
+            #[cfg(feature = "imxrt1060")]
+            arm: _arm,
+            ahb,
+            ipg,
+            perclock: perclock::divider(),
+            clocks,
+            perclock_hz,
+        }
+    }
+
+    /// Select the ARM clock source, returning the new ARM and IPG clock frequency
+    ///
+    /// See [`arm::set_source`] for more information.
+    #[inline(always)]
+    pub fn set_arm_clock_source(&mut self, source: arm::Source) -> (arm::ARMClock, arm::IPGClock) {
+        // Safety: we own the CCM peripheral memory
+        unsafe { arm::set_source(source) }
+    }
+
+    /// Returns the ARM clock source
+    #[inline(always)]
+    pub fn arm_clock_source(&self) -> arm::Source {
+        arm::source()
+    }
+
+    /// Configure the PERIPH_CLK2 source and divider
+    ///
+    /// See [`arm::set_periph_clk2`] for more information.
+    #[inline(always)]
+    pub fn set_periph_clk2(
+        &mut self,
+        source: arm::PeriphClk2,
+        divider: u32,
+    ) -> Result<(), arm::PeriphClk2Selected> {
+        // Safety: we own the CCM peripheral memory
+        unsafe { arm::set_periph_clk2(source, divider) }
+    }
+
+    /// Returns the PERIPH_CLK2 source and divider
+    #[inline(always)]
+    pub fn periph_clk2(&self) -> (arm::PeriphClk2, u32) {
+        arm::periph_clk2()
+    }
+
+    /// Select PERIPH_CLK2 (`true`) or PRE_PERIPH_CLK (`false`) as AHB_CLK_ROOT,
+    /// returning the new clocks
+    ///
+    /// See [`arm::select_periph_clk2`] for more information.
+    #[inline(always)]
+    pub fn select_periph_clk2(&mut self, selected: bool) -> arm::Clocks {
+        // Safety: we own the CCM peripheral memory
+        unsafe { arm::select_periph_clk2(selected) }
+    }
+
+    /// Switch the ARM clock to an operating point, returning the new ARM and IPG
+    /// clock frequency
+    ///
+    /// See [`dvfs::set_operating_point`] for more information.
+    #[inline(always)]
+    pub fn set_operating_point(
+        &mut self,
+        point: dvfs::OperatingPoint,
+    ) -> (arm::ARMClock, arm::IPGClock) {
+        // Safety: we own the CCM peripheral memory
+        unsafe { dvfs::set_operating_point(point) }
+    }
+
+    /// Returns the current operating point, or `None` if the ARM clock doesn't
+    /// run at an operating point
+    #[inline(always)]
+    pub fn operating_point(&self) -> Option<dvfs::OperatingPoint> {
+        dvfs::operating_point()
+    }
+
+    /// Run the ARM core from the 24MHz oscillator, and power down PLL1
+    ///
+    /// See [`arm::enter_low_power_run`] for more information.
+    #[inline(always)]
+    pub fn enter_low_power_run(&mut self) -> arm::LowPowerRun {
+        // Safety: we own the CCM peripheral memory
+        unsafe { arm::enter_low_power_run() }
+    }
+
+    /// Return to the ARM clock frequency that preceded low-power run, returning
+    /// the ARM and IPG clock frequencies
+    ///
+    /// See [`arm::exit_low_power_run`] for more information.
+    #[inline(always)]
+    pub fn exit_low_power_run(&mut self, run: arm::LowPowerRun) -> (arm::ARMClock, arm::IPGClock) {
+        // Safety: we own the CCM peripheral memory
+        unsafe { arm::exit_low_power_run(run) }
+    }
+
+    /// Power down all PLLs, and gate all PFDs, that are not in use
+    ///
+    /// A PLL or PFD is in use if any clock root multiplexer selects it, or if one
+    /// of its dedicated outputs, like the USB PHY clocks, is enabled. See
+    /// [`PllUsage`](pll::PllUsage) for more information. Returns the usage that
+    /// determined which PLLs and PFDs were powered down.
+    ///
+    /// Switch clock roots away from a PLL before calling this method in order to
+    /// power down that PLL. Remember to re-enable a PLL, and ungate its PFDs,
+    /// before selecting it again.
+    pub fn power_down_unused_plls(&mut self) -> pll::PllUsage {
+        let usage = pll::usage();
+        // Safety: we own the CCM and CCM_ANALOG PLL registers
+        unsafe { usage::power_down_unused(&usage) };
+        usage
+    }
+
+    /// Returns a reference to the system PLL (PLL2)
+    pub fn pll2(&self) -> &pll2::PLL2 {
+        &self.pll2
+    }
+    /// Returns a mutable reference to the system PLL (PLL2)
+    pub fn pll2_mut(&mut self) -> &mut pll2::PLL2 {
+        &mut self.pll2
+    }
+
+    /// Returns a reference to the USB1 PLL (PLL3)
+    pub fn pll3(&self) -> &pll3::PLL3 {
+        &self.pll3
+    }
+    /// Returns a mutable reference to the USB1 PLL (PLL3)
+    pub fn pll3_mut(&mut self) -> &mut pll3::PLL3 {
+        &mut self.pll3
+    }
+
+    /// Returns a reference to the audio PLL (PLL4)
+    pub fn pll4(&self) -> &pll4::PLL4 {
+        &self.pll4
+    }
+    /// Returns a mutable reference to the audio PLL (PLL4)
+    pub fn pll4_mut(&mut self) -> &mut pll4::PLL4 {
+        &mut self.pll4
+    }
+
+    /// Returns a reference to the video PLL (PLL5)
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    pub fn pll5(&self) -> &pll5::PLL5 {
+        &self.pll5
+    }
+    /// Returns a mutable reference to the video PLL (PLL5)
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    pub fn pll5_mut(&mut self) -> &mut pll5::PLL5 {
+        &mut self.pll5
+    }
+
+    /// Returns a reference to the ENET PLL (PLL6)
+    pub fn pll6(&self) -> &pll6::PLL6 {
+        &self.pll6
+    }
+    /// Returns a mutable reference to the ENET PLL (PLL6)
+    pub fn pll6_mut(&mut self) -> &mut pll6::PLL6 {
+        &mut self.pll6
+    }
+
+    /// Returns a reference to the 24MHz oscillator
+    pub fn xtalosc(&self) -> &xtalosc::XTALOSC {
+        &self.xtalosc
+    }
+    /// Returns a mutable reference to the 24MHz oscillator
+    pub fn xtalosc_mut(&mut self) -> &mut xtalosc::XTALOSC {
+        &mut self.xtalosc
+    }
+
+    /// Returns a reference to the LVDS1 clock output
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    pub fn lvds(&self) -> &lvds::LVDS {
+        &self.lvds
+    }
+    /// Returns a mutable reference to the LVDS1 clock output
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    pub fn lvds_mut(&mut self) -> &mut lvds::LVDS {
+        &mut self.lvds
+    }
+
+    /// Returns a reference to the low-power mode controls
+    pub fn lowpower(&self) -> &lowpower::LowPower {
+        &self.lowpower
+    }
+    /// Returns a mutable reference to the low-power mode controls
+    pub fn lowpower_mut(&mut self) -> &mut lowpower::LowPower {
+        &mut self.lowpower
+    }
+}
+
+/// The [`Clocks`] of a CCM that's been [`split`](CCM::split)
+///
+/// The clock roots belong to [`Parts`], so a `CCM<SplitClocks>` has no clock
+/// root accessors.
+pub struct SplitClocks;
+
+impl Clocks for SplitClocks {
+    type PIT = ();
+    type GPT = ();
+    type UART = ();
+    type SPI = ();
+    type I2C = ();
+}
+
+/// A CCM, split into independently owned clock roots
+///
+/// Use [`CCM::split`] to acquire the parts. Move each clock root into the task
+/// that owns the corresponding peripherals.
+#[non_exhaustive]
+pub struct Parts<C: Clocks> {
+    /// The periodic clock
+    pub perclock: perclock::PerClock<C::PIT, C::GPT>,
+    /// The UART clock
+    pub uart_clock: uart::UARTClock<C::UART>,
+    /// The SPI clock
+    pub spi_clock: spi::SPIClock<C::SPI>,
+    /// The I2C clock
+    pub i2c_clock: i2c::I2CClock<C::I2C>,
+    /// The rest of the CCM, including the ARM clock, PLLs, and the clock gates
+    /// that aren't associated with a clock root
+    pub ccm: CCM<SplitClocks>,
+}
+
+impl<C: Clocks> CCM<C> {
+    /// Split the CCM into independently owned clock roots
+    ///
+    /// The returned [`CCM`] keeps the ARM clock, the PLLs, and the other
+    /// controls. Note that whole-tree operations on that CCM, like
+    /// [`apply`](CCM::apply) and [`resume`](CCM::resume), still change the
+    /// clock roots. Coordinate those with the owners of the clock roots.
+    ///
+    /// ```no_run
+    /// # use imxrt_ccm as ccm;
+    /// # struct MyClocks;
+    /// # impl ccm::Clocks for MyClocks {
+    /// #   type I2C = ();
+    /// #   type SPI = ();
+    /// #   type UART = ();
+    /// #   type GPT = ();
+    /// #   type PIT = ();
+    /// # }
+    /// let ccm = unsafe { ccm::CCM::<MyClocks>::new() };
+    /// let ccm::Parts {
+    ///     uart_clock,
+    ///     mut ccm,
+    ///     ..
+    /// } = ccm.split();
+    /// ccm.set_frequency_arm(600_000_000);
+    /// // Move uart_clock into the UART task...
+    /// # drop(uart_clock);
+    /// ```
+    pub fn split(self) -> Parts<C> {
+        Parts {
+            perclock: self.perclock,
+            uart_clock: self.uart_clock,
+            spi_clock: self.spi_clock,
+            i2c_clock: self.i2c_clock,
+            ccm: CCM {
+                perclock: perclock::PerClock::new(),
+                uart_clock: uart::UARTClock::new(),
+                spi_clock: spi::SPIClock::new(),
+                i2c_clock: i2c::I2CClock::new(),
+                pll2: self.pll2,
+                pll3: self.pll3,
+                pll4: self.pll4,
+                #[cfg(feature = "imxrt1060")]
+                pll5: self.pll5,
+                pll6: self.pll6,
+                xtalosc: self.xtalosc,
+                #[cfg(feature = "imxrt1060")]
+                lvds: self.lvds,
+                lowpower: self.lowpower,
+                _not_sync: PhantomData,
+            },
+        }
+    }
+}
+
+/// A snapshot of all clock gate settings
+///
+/// Use [`CCM::save_gates`] to capture a snapshot, and [`CCM::restore_gates`] to
+/// restore it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GateSnapshot {
+    ccgr: [u32; CCGR_COUNT],
+}
+
+impl GateSnapshot {
+    /// Returns the raw CCGR register values, starting with CCGR0
+    pub fn registers(&self) -> &[u32] {
+        &self.ccgr
+    }
+}
+
+/// The core clock domain dividers, and the frequencies they produce
+///
+/// Use [`CCM::bus_dividers`] to read the dividers. Each divider is the actual
+/// divide value, not the raw register field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusDividers {
+    /// ARM_PODF, between PLL1 and PRE_PERIPH_CLK
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    pub arm: u32,
+    /// AHB_PODF, which produces the ARM clock
+    pub ahb: u32,
+    /// IPG_PODF, which produces the IPG clock from the ARM clock
+    pub ipg: u32,
+    /// PERCLK_PODF, which produces the periodic clock
+    pub perclock: u32,
+    /// The ARM and IPG clocks, and the clock that drives AHB_CLK_ROOT
+    pub clocks: arm::Clocks,
+    /// The periodic clock frequency
+    pub perclock_hz: u32,
+}
+
+impl<C: Clocks> CCM<C> {
+    /// Capture all clock gate settings
+    ///
+    /// The snapshot includes all CCGR registers, including clock gates that this
+    /// crate does not model.
+    pub fn save_gates(&self) -> GateSnapshot {
+        GateSnapshot {
+            ccgr: gate::snapshot(),
+        }
+    }
+
+    /// Restore all clock gate settings from a snapshot
+    ///
+    /// This overwrites every CCGR register. Any peripheral that's in use while its
+    /// clock gate turns off may stop working.
+    pub fn restore_gates(&mut self, snapshot: &GateSnapshot) {
+        // Safety: we own the CCM peripheral memory
+        unsafe { gate::restore(&snapshot.ccgr) };
+    }
+
+    /// Capture every CCM and CCM_ANALOG register for debugging
+    ///
+    /// The snapshot includes the raw register values, and the clock frequencies
+    /// that they describe. Dump it when you report a clock problem. See
+    /// [`DebugSnapshot`] for an example.
+    pub fn debug_snapshot(&self) -> DebugSnapshot {
+        // Safety: we own the CCM peripheral memory, so the reads are consistent
+        unsafe { debug::snapshot() }
+    }
+
+    /// Returns a model of the clock tree, decoded from the CCM registers
+    ///
+    /// See the [`tree`] module for more information.
+    pub fn tree(&self) -> tree::Tree<tree::Hardware> {
+        // Safety: we own the CCM peripheral memory
+        unsafe { tree::Tree::hardware() }
+    }
+
+    /// Capture the clock tree before entering a low-power mode
+    ///
+    /// The state includes the analog PLL and PFD settings, the post dividers in
+    /// CCM_ANALOG_MISC2, every clock root multiplexer and divider, and all clock
+    /// gates. Pass the state to [`resume`](CCM::resume) after wake.
+    pub fn suspend(&self) -> CcmState {
+        state::capture()
+    }
+
+    /// Restore the clock tree after wake
+    ///
+    /// `resume` restarts any PLL whose settings changed, and waits for it to
+    /// lock, before it restores the clock roots that might select the PLL.
+    /// PLLs that did not change are not disturbed. The clock gates are
+    /// restored last.
+    pub fn resume(&mut self, state: &CcmState) {
+        // Safety: we own the CCM and CCM_ANALOG peripheral memory
+        unsafe { state::restore(state) };
+    }
+
+    /// Prepare the clock tree for deep sleep
+    ///
+    /// `prepare_deep_sleep`
+    ///
+    /// 1. captures the clock tree, like [`suspend`](CCM::suspend).
+    /// 2. moves the core clock, and the AHB and IPG clocks, to the 24MHz oscillator.
+    /// 3. turns off every clock gate, except the [`ESSENTIAL_CLOCK_GATES`] and the
+    ///    clock gates of your `wake` sources.
+    /// 4. powers down every PLL, and gates every PFD, that no peripheral clock
+    ///    root selects. See [`power_down_unused_plls`](CCM::power_down_unused_plls).
+    ///
+    /// To power down more PLLs, switch the clock roots that you don't need in
+    /// deep sleep to the oscillator before calling `prepare_deep_sleep`. PLLs that
+    /// feed essential clocks, like FlexSPI, stay powered.
+    ///
+    /// `prepare_deep_sleep` does not select a low-power mode, and it does not
+    /// execute `WFI`; see [`lowpower`] for those steps. After wake, pass the
+    /// returned token to [`wake_from_deep_sleep`](CCM::wake_from_deep_sleep).
+    ///
+    /// ```no_run
+    /// # use imxrt_ccm as ccm;
+    /// # struct MyClocks;
+    /// # impl ccm::Clocks for MyClocks {
+    /// #   type I2C = ();
+    /// #   type SPI = ();
+    /// #   type UART = ();
+    /// #   type GPT = ();
+    /// #   type PIT = ();
+    /// # }
+    /// # let mut ccm = unsafe { ccm::CCM::<MyClocks>::new() };
+    /// use ccm::{lowpower::Mode, ClockGateLocator, perclock::GPT};
+    ///
+    /// let sleep = ccm.prepare_deep_sleep(&[GPT::GPT1.location()]);
+    /// ccm.lowpower_mut().set_mode(Mode::Wait);
+    /// // Execute WFI...
+    /// ccm.lowpower_mut().set_mode(Mode::Run);
+    /// ccm.wake_from_deep_sleep(sleep);
+    /// ```
+    pub fn prepare_deep_sleep(&mut self, wake: &[ClockGateLocation]) -> lowpower::DeepSleep {
+        let state = state::capture();
+        // Safety: we own the CCM and CCM_ANALOG peripheral memory. The core clock
+        // leaves the PLLs before they power down.
+        unsafe {
+            state::core_to_oscillator();
+            gate::set_all(ClockGate::Off as u8, &[ESSENTIAL_CLOCK_GATES, wake]);
+            usage::power_down_unused(&usage::peripheral_usage());
+        }
+        lowpower::DeepSleep { state }
+    }
+
+    /// Undo [`prepare_deep_sleep`](CCM::prepare_deep_sleep) after wake
+    ///
+    /// See [`resume`](CCM::resume) for more information.
+    pub fn wake_from_deep_sleep(&mut self, sleep: lowpower::DeepSleep) {
+        self.resume(&sleep.state);
+    }
+}
+
+/// Clock gates that are required for the system to keep running
+///
+/// This includes the bus bridges and system interconnect, on-chip memories
+/// (OCRAM, FlexRAM), FlexSPI (for execute-in-place), SEMC (for external SDRAM,
+/// on chips that have it), the analog register interface, and the watchdogs.
+/// Turning off a bus or memory clock gate will likely hang the system. Turning off a watchdog clock gate will stop the
+/// watchdog.
+///
+/// Use `ESSENTIAL_CLOCK_GATES` as the exclusion list for
+/// [`CCM::set_all_clock_gates`].
+pub const ESSENTIAL_CLOCK_GATES: &[ClockGateLocation] = &[
+    ClockGateLocation::new(0, &[0]),  // AIPS_TZ1
+    ClockGateLocation::new(0, &[1]),  // AIPS_TZ2
+    ClockGateLocation::new(0, &[3]),  // FLEXSPI_EXSC
+    ClockGateLocation::new(0, &[4]),  // SIM_M_CLK_R
+    ClockGateLocation::new(2, &[0]),  // OCRAM_EXSC
+    ClockGateLocation::new(3, &[8]),  // WDOG1
+    ClockGateLocation::new(3, &[9]),  // FLEXRAM
+    ClockGateLocation::new(3, &[14]), // OCRAM
+    ClockGateLocation::new(4, &[0]),  // SIM_M7_CLK_R
+    ClockGateLocation::new(4, &[4]),  // SIM_M7
+    ClockGateLocation::new(4, &[6]),  // SIM_M
+    ClockGateLocation::new(4, &[7]),  // SIM_EMS
+    ClockGateLocation::new(5, &[2]),  // WDOG3
+    ClockGateLocation::new(5, &[5]),  // WDOG2
+    ClockGateLocation::new(6, &[5]),  // FLEXSPI
+    ClockGateLocation::new(6, &[10]), // SIM_PER
+    ClockGateLocation::new(6, &[11]), // ANADIG
+    #[cfg(feature = "imxrt1060")]
+    ClockGateLocation::new(3, &[2]), // SEMC
+    #[cfg(feature = "imxrt1060")]
+    ClockGateLocation::new(5, &[6]), // AIPS_TZ4
+    #[cfg(feature = "imxrt1060")]
+    ClockGateLocation::new(5, &[8]), // SIM_MAIN
+    #[cfg(feature = "imxrt1060")]
+    ClockGateLocation::new(6, &[9]), // AIPS_TZ3
+    #[cfg(feature = "imxrt1060")]
+    ClockGateLocation::new(7, &[5]), // AIPS_LITE
+];
+
+impl<C: Clocks> CCM<C> {
+    /// Commit a clock tree configuration
+    ///
+    /// `apply` sequences the changes: it sets the ARM clock, parks clock gates,
+    /// waits for PLLs to lock, ungates PFDs, then changes clock roots. While a
+    /// clock root changes, the clock gates of its peripherals are off; then
+    /// they return to their previous settings. See [`CcmConfig`] for
+    /// an example. Returns an error, without changing any clocks, if the ARM
+    /// clock frequency is out of range.
+    pub fn apply(&mut self, config: &CcmConfig) -> Result<Applied, arm::FrequencyOutOfRange> {
+        // Safety: we own the CCM and CCM_ANALOG peripheral memory
+        unsafe { config::apply(config) }
+    }
+
+    /// Configure the default clock tree
+    ///
+    /// `set_defaults` applies [`CcmConfig::defaults`]: the ARM clock runs at
+    /// [`arm::MAX_FREQUENCY_HZ`], the periodic clock runs at 1MHz, and the UART,
+    /// SPI, and I2C clock roots run at their `configure()` defaults. Clock gates
+    /// don't change. Returns the achieved frequencies.
+    ///
+    /// ```no_run
+    /// # use imxrt_ccm as ccm;
+    /// # let mut ccm = unsafe { ccm::CCM::<ccm::SplitClocks>::new() };
+    /// let applied = ccm.set_defaults();
+    /// assert_eq!(applied.perclock.unwrap().hz, 1_000_000);
+    /// ```
+    pub fn set_defaults(&mut self) -> Applied {
+        // Unwrap OK: the default ARM frequency is always in range
+        self.apply(&CcmConfig::defaults()).unwrap()
+    }
+
+    /// Read the clock tree configuration from hardware
+    ///
+    /// The configuration includes the ARM clock frequency, the source and divider
+    /// of every clock root modeled by [`CcmConfig`], and all clock gates. Use it
+    /// to adopt the clocks that a boot ROM or bootloader configured, changing only
+    /// what you need before you [`apply`](CCM::apply) it.
+    pub fn capture(&self) -> CcmConfig<'static> {
+        // Safety: we own the CCM peripheral memory, so the reads are consistent
+        unsafe { config::capture() }
+    }
+
+    /// Compare the hardware against an expected configuration
+    ///
+    /// Returns the differences between the hardware and `expected`. Only the
+    /// clocks that `expected` describes are compared. Use `validate` to fail fast
+    /// when a boot ROM or bootloader didn't configure the clocks that your
+    /// application expects:
+    ///
+    /// ```no_run
+    /// # use imxrt_ccm as ccm;
+    /// # struct MyClocks;
+    /// # impl ccm::Clocks for MyClocks {
+    /// #   type I2C = ();
+    /// #   type SPI = ();
+    /// #   type UART = ();
+    /// #   type GPT = ();
+    /// #   type PIT = ();
+    /// # }
+    /// # let ccm = unsafe { ccm::CCM::<MyClocks>::new() };
+    /// use ccm::{uart, CcmConfig};
+    ///
+    /// const BOOT: CcmConfig = CcmConfig::new()
+    ///     .arm_frequency(600_000_000)
+    ///     .uart(uart::Selection::Oscillator, 1);
+    ///
+    /// let mismatches = ccm.validate(&BOOT);
+    /// assert!(mismatches.is_empty(), "Unexpected boot clocks: {:?}", mismatches);
+    /// ```
+    pub fn validate(&self, expected: &CcmConfig) -> Mismatches {
+        // Safety: we own the CCM peripheral memory, so the reads are consistent
+        unsafe { config::validate(expected) }
+    }
+
+    /// Set every clock gate, except the clock gates in `exclude`
+    ///
+    /// `set_all_clock_gates` changes all clock gates, including clock gates that
+    /// this crate does not model. Use it during early bring-up, or to establish a
+    /// known baseline for power measurements. Unless you know that your system
+    /// can run without them, you should exclude the
+    /// [`ESSENTIAL_CLOCK_GATES`]:
+    ///
+    /// ```no_run
+    /// # use imxrt_ccm as ccm;
+    /// # struct MyClocks;
+    /// # impl ccm::Clocks for MyClocks {
+    /// #   type I2C = ();
+    /// #   type SPI = ();
+    /// #   type UART = ();
+    /// #   type GPT = ();
+    /// #   type PIT = ();
+    /// # }
+    /// # let mut ccm = unsafe { ccm::CCM::<MyClocks>::new() };
+    /// use ccm::{ClockGate, ESSENTIAL_CLOCK_GATES};
+    /// ccm.set_all_clock_gates(ClockGate::Off, ESSENTIAL_CLOCK_GATES);
+    /// ```
+    pub fn set_all_clock_gates(&mut self, gate: ClockGate, exclude: &[ClockGateLocation]) {
+        // Safety: we own the CCM peripheral memory
+        unsafe { gate::set_all(gate as u8, &[exclude]) };
+    }
+
+    /// Refill the clock gate cache from the CCGR registers
+    ///
+    /// Call `sync_from_hardware` after code outside of this crate, like a
+    /// RAL, changes a CCGR register. Otherwise, the clock gate getters
+    /// return stale values, and the setters undo the external change.
+    #[cfg(feature = "gate-cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "gate-cache")))]
+    pub fn sync_from_hardware(&mut self) {
+        // Safety: we own the CCM peripheral memory
+        unsafe { gate::sync() };
+    }
+}
+
+/// An iterator over all clock gates known to this crate
+///
+/// Each item is the name of a peripheral instance, like `"LPUART3"`, and its
+/// clock gate setting. The iterator only yields clock gates that are available
+/// on the selected chip. Use [`CCM::clock_gates`] to acquire the iterator.
+pub struct ClockGates<'a> {
+    idx: usize,
+    _ccm: PhantomData<&'a ()>,
+}
+
+impl Iterator for ClockGates<'_> {
+    type Item = (&'static str, ClockGate);
+    fn next(&mut self) -> Option<Self::Item> {
+        let (name, inst) = gate::known(self.idx)?;
+        self.idx += 1;
+        let raw = gate::get(&inst.location());
+        Some((name, ClockGate::from_u8(raw)))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = gate::known_len() - self.idx;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for ClockGates<'_> {}
+
+impl<C: Clocks> CCM<C> {
+    /// Returns an iterator over all known clock gates, and their settings
+    ///
+    /// Use `clock_gates` to debug your clock gate configuration.
+    ///
+    /// ```no_run
+    /// # use imxrt_ccm as ccm;
+    /// # struct MyClocks;
+    /// # impl ccm::Clocks for MyClocks {
+    /// #   type I2C = ();
+    /// #   type SPI = ();
+    /// #   type UART = ();
+    /// #   type GPT = ();
+    /// #   type PIT = ();
+    /// # }
+    /// # let ccm = unsafe { ccm::CCM::<MyClocks>::new() };
+    /// for (name, gate) in ccm.clock_gates() {
+    ///     // Print the name and clock gate...
+    /// #   let _ = (name, gate);
+    /// }
+    /// ```
+    pub fn clock_gates(&self) -> ClockGates<'_> {
+        ClockGates {
+            idx: 0,
+            _ccm: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the known peripherals that stay clocked in `mode`
+    ///
+    /// Use `clocked_in` to check your wake sources before entering a low-power
+    /// mode. A peripheral that isn't clocked in WAIT mode cannot wake the core from
+    /// WAIT mode. See [`ClockGate::is_clocked_in`] for the rules.
+    ///
+    /// ```no_run
+    /// # use imxrt_ccm as ccm;
+    /// # struct MyClocks;
+    /// # impl ccm::Clocks for MyClocks {
+    /// #   type I2C = ();
+    /// #   type SPI = ();
+    /// #   type UART = ();
+    /// #   type GPT = ();
+    /// #   type PIT = ();
+    /// # }
+    /// # let ccm = unsafe { ccm::CCM::<MyClocks>::new() };
+    /// use ccm::lowpower::Mode;
+    /// let gpt1_wakes = ccm.clocked_in(Mode::Wait).any(|name| name == "GPT1");
+    /// # let _ = gpt1_wakes;
+    /// ```
+    pub fn clocked_in(&self, mode: lowpower::Mode) -> ClockedIn<'_> {
+        ClockedIn {
+            gates: self.clock_gates(),
+            mode,
+        }
+    }
+}
+
+/// An iterator over the known peripherals that stay clocked in a low-power mode
+///
+/// Each item is the name of a peripheral instance, like `"LPUART3"`. Use
+/// [`CCM::clocked_in`] to acquire the iterator.
+pub struct ClockedIn<'a> {
+    gates: ClockGates<'a>,
+    mode: lowpower::Mode,
+}
+
+impl Iterator for ClockedIn<'_> {
+    type Item = &'static str;
+    fn next(&mut self) -> Option<Self::Item> {
+        let mode = self.mode;
+        self.gates
+            .find(|(_, gate)| gate.is_clocked_in(mode))
+            .map(|(name, _)| name)
+    }
 }
 
 /// Describes a clock gate setting
@@ -576,9 +2125,33 @@ impl ClockGate {
             _ => unreachable!(),
         }
     }
+
+    /// Returns `true` if a peripheral with this clock gate is clocked in `mode`
+    ///
+    /// No clock gate keeps its peripheral clocked in STOP mode.
+    ///
+    /// ```
+    /// use imxrt_ccm::{lowpower::Mode, ClockGate};
+    ///
+    /// assert!(ClockGate::OnlyRun.is_clocked_in(Mode::Run));
+    /// assert!(!ClockGate::OnlyRun.is_clocked_in(Mode::Wait));
+    /// assert!(ClockGate::On.is_clocked_in(Mode::Wait));
+    /// assert!(!ClockGate::On.is_clocked_in(Mode::Stop));
+    /// ```
+    pub const fn is_clocked_in(self, mode: lowpower::Mode) -> bool {
+        match (self, mode) {
+            (ClockGate::Off, _) => false,
+            (ClockGate::OnlyRun, lowpower::Mode::Run) => true,
+            (ClockGate::OnlyRun, _) => false,
+            (ClockGate::On, lowpower::Mode::Stop) => false,
+            (ClockGate::On, _) => true,
+        }
+    }
 }
 
-/// Crystal oscillator frequency
+/// The nominal reference frequency, provided by the 24MHz crystal oscillator
+///
+/// See [`xtalosc::reference_frequency`] for the declared reference frequency.
 const OSCILLATOR_FREQUENCY_HZ: u32 = 24_000_000;
 
 impl<C> CCM<C>
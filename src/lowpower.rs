@@ -0,0 +1,632 @@
+//! Low-power mode configuration
+//!
+//! The CCM decides what happens when the ARM core executes `WFI`. The low-power
+//! mode (LPM) in CCM_CLPCR selects one of three behaviors:
+//!
+//! - [`Mode::Run`]: `WFI` only idles the core. All clocks keep running.
+//! - [`Mode::Wait`]: the ARM core clock stops, if [`LowPower::set_arm_clock_gated`] allows
+//!   it. The bus clocks keep running, and peripherals continue to run unless their
+//!   clock gate is set to `OnlyRun`.
+//! - [`Mode::Stop`]: all CCM root clocks stop, including the bus clocks.
+//!   Peripherals that stay clocked in STOP must be supplied by the oscillator
+//!   or the 32kHz clock.
+//!
+//! The LPM resets to [`Mode::Wait`], but most startup code selects [`Mode::Run`].
+//! Configure the LPM before executing `WFI`, and restore [`Mode::Run`] after
+//! wake:
+//!
+//! ```no_run
+//! use imxrt_ccm::lowpower::Mode;
+//! # struct MyClocks;
+//! # impl imxrt_ccm::Clocks for MyClocks {
+//! #   type I2C = ();
+//! #   type SPI = ();
+//! #   type UART = ();
+//! #   type GPT = ();
+//! #   type PIT = ();
+//! # }
+//! # let mut ccm = unsafe { imxrt_ccm::CCM::<MyClocks>::new() };
+//!
+//! let lowpower = ccm.lowpower_mut();
+//! lowpower.set_arm_clock_gated(true);
+//! lowpower.set_mode(Mode::Wait);
+//! // Execute WFI...
+//! lowpower.set_mode(Mode::Run);
+//! ```
+//!
+//! # References
+//!
+//! i.MX RT 1060 reference manual
+//! - Chapter 14: Clock Control Module (CCM)
+//!   - Low power clock gating
+//!   - CCM Low Power Control Register (CCM_CLPCR)
+
+use crate::{
+    register::{self, Field},
+    CcmState,
+};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+const CCM_CLPCR: *mut u32 = 0x400F_C054 as _;
+const CCM_CMEOR: *mut u32 = 0x400F_C088 as _;
+
+const LPM: Field = Field::new(0, 0b11);
+const ARM_CLK_DIS_ON_LPM: Field = Field::new(5, 1);
+const SBYOS: Field = Field::new(6, 1);
+const VSTBY: Field = Field::new(8, 1);
+const STBY_COUNT: Field = Field::new(9, 0b11);
+const COSC_PWRDOWN: Field = Field::new(11, 1);
+#[cfg(feature = "imxrt1060")]
+const BYPASS_LPM_HS1: Field = Field::new(19, 1);
+#[cfg(feature = "imxrt1060")]
+const BYPASS_LPM_HS0: Field = Field::new(21, 1);
+const MASK_CORE0_WFI: Field = Field::new(22, 1);
+const MASK_L2CC_IDLE: Field = Field::new(27, 1);
+
+/// The handshake fields that must be set before entering STOP mode
+///
+/// The 1060 must bypass its LPM handshakes; the reference manual says that
+/// software should always set them. The 1010 does not have these handshakes.
+/// All chips mask the (absent) L2 cache idle signal.
+const HANDSHAKE_BYPASS: &[Field] = &[
+    #[cfg(feature = "imxrt1060")]
+    BYPASS_LPM_HS1,
+    #[cfg(feature = "imxrt1060")]
+    BYPASS_LPM_HS0,
+    MASK_L2CC_IDLE,
+];
+
+/// A low-power mode
+///
+/// The mode takes effect when the ARM core executes `WFI`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum Mode {
+    /// Remain in RUN mode
+    Run = 0,
+    /// Transfer to WAIT mode
+    Wait = 1,
+    /// Transfer to STOP mode
+    Stop = 2,
+}
+
+impl Mode {
+    fn from_field(lpm: u32) -> Self {
+        match lpm {
+            1 => Mode::Wait,
+            2 => Mode::Stop,
+            // 0b11 is reserved; the CCM treats it like RUN
+            _ => Mode::Run,
+        }
+    }
+}
+
+/// The time between negating the PMIC standby request and checking PMIC ready
+///
+/// The count is in cycles of the 32kHz clock. The CCM only waits when it exits
+/// STOP mode with [`Standby::standby_voltage`] set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum StandbyCount {
+    /// At least 1 cycle
+    Cycles1 = 0,
+    /// At least 3 cycles
+    Cycles3 = 1,
+    /// At least 7 cycles
+    Cycles7 = 2,
+    /// At least 15 cycles
+    Cycles15 = 3,
+}
+
+impl StandbyCount {
+    fn from_field(count: u32) -> Self {
+        match count {
+            0 => StandbyCount::Cycles1,
+            1 => StandbyCount::Cycles3,
+            2 => StandbyCount::Cycles7,
+            _ => StandbyCount::Cycles15,
+        }
+    }
+}
+
+/// STOP mode standby configuration
+///
+/// The configuration decides what the CCM powers down in STOP mode, and how it
+/// waits for power to return on exit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Standby {
+    /// Power down the 24MHz oscillator on STOP entry (`SBYOS`)
+    ///
+    /// When set, the CCM waits for the oscillator to be ready on STOP exit.
+    /// The [`xtalosc`](crate::xtalosc) power-up delay decides how long that
+    /// takes.
+    pub oscillator_off: bool,
+    /// Request standby voltage from the PMIC on STOP entry (`VSTBY`)
+    pub standby_voltage: bool,
+    /// The ramp-up wait after a standby voltage request (`STBY_COUNT`)
+    pub count: StandbyCount,
+}
+
+/// A module whose clock enable can be overridden
+///
+/// Normally, a module's clock enable signal follows the module's own low-power
+/// request. When the override is set, the CCM keeps the module's clock enable
+/// asserted. Use an override when a peripheral stalls in WAIT mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ModuleOverride {
+    /// GPT (`MOD_EN_OV_GPT`)
+    Gpt = 5,
+    /// PIT (`MOD_EN_OV_PIT`)
+    Pit = 6,
+    /// uSDHC (`MOD_EN_USDHC`)
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    Usdhc = 7,
+    /// TRNG (`MOD_EN_OV_TRNG`)
+    Trng = 9,
+    /// CAN3 (`MOD_EN_OV_CANFD_CPI`)
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    Can3 = 10,
+    /// CAN2 (`MOD_EN_OV_CAN2_CPI`)
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    Can2 = 28,
+    /// CAN1 (`MOD_EN_OV_CAN1_CPI`)
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    Can1 = 30,
+}
+
+impl ModuleOverride {
+    const fn field(self) -> Field {
+        Field::new(self as u32, 1)
+    }
+}
+
+/// The clock tree from before [`CCM::prepare_deep_sleep`](crate::CCM::prepare_deep_sleep)
+///
+/// Pass the token to [`CCM::wake_from_deep_sleep`](crate::CCM::wake_from_deep_sleep)
+/// to undo the deep sleep preparation.
+#[must_use = "the clock tree stays prepared for deep sleep until you wake with this token"]
+pub struct DeepSleep {
+    pub(crate) state: CcmState,
+}
+
+/// Low-power mode controls
+///
+/// Use `LowPower` to decide what the CCM does when the ARM core executes `WFI`.
+pub struct LowPower(());
+
+impl LowPower {
+    pub(crate) const fn new() -> Self {
+        LowPower(())
+    }
+
+    /// Set the mode that's entered on the next `WFI`
+    #[inline(always)]
+    pub fn set_mode(&mut self, mode: Mode) {
+        // Safety: we own the CLPCR register
+        unsafe { set_mode(mode) }
+    }
+
+    /// Returns the mode that's entered on the next `WFI`
+    #[inline(always)]
+    pub fn mode(&self) -> Mode {
+        mode()
+    }
+
+    /// Stop (`true`) or keep running (`false`) the ARM core clock in WAIT mode
+    ///
+    /// In STOP mode, the ARM core clock always stops. See
+    /// [`arm::set_clock_gated_in_wfi`](crate::arm::set_clock_gated_in_wfi)
+    /// for more information.
+    #[inline(always)]
+    pub fn set_arm_clock_gated(&mut self, gated: bool) {
+        // Safety: we own the CLPCR register
+        unsafe { crate::arm::set_clock_gated_in_wfi(gated) }
+    }
+
+    /// Returns `true` if the ARM core clock stops in WAIT mode
+    #[inline(always)]
+    pub fn arm_clock_gated(&self) -> bool {
+        crate::arm::clock_gated_in_wfi()
+    }
+
+    /// Set the STOP mode standby configuration
+    ///
+    /// The configuration takes effect on the next STOP mode entry.
+    #[inline(always)]
+    pub fn set_standby(&mut self, standby: &Standby) {
+        // Safety: we own the CLPCR register
+        unsafe { set_standby(standby) }
+    }
+
+    /// Returns the STOP mode standby configuration
+    #[inline(always)]
+    pub fn standby(&self) -> Standby {
+        standby()
+    }
+
+    /// Power down (`true`) or power up (`false`) the 24MHz oscillator right now
+    ///
+    /// Unlike [`Standby::oscillator_off`], this takes effect immediately, in any
+    /// mode. While the oscillator is powered down here, the CCM ignores
+    /// [`Standby::oscillator_off`]. Make sure that nothing is clocked by
+    /// the oscillator before you power it down.
+    #[inline(always)]
+    pub fn set_oscillator_powered_down(&mut self, powered_down: bool) {
+        // Safety: we own the CLPCR register
+        unsafe { set_oscillator_powered_down(powered_down) }
+    }
+
+    /// Returns `true` if the 24MHz oscillator is powered down by
+    /// [`set_oscillator_powered_down`](LowPower::set_oscillator_powered_down)
+    #[inline(always)]
+    pub fn oscillator_powered_down(&self) -> bool {
+        oscillator_powered_down()
+    }
+
+    /// Bypass (`true`) or use (`false`) the low-power mode handshakes
+    ///
+    /// Without the bypass, the CCM may wait forever for a handshake that never
+    /// arrives, and STOP mode entry hangs. Bypass the handshakes before you enter
+    /// STOP mode. The bypassed handshakes depend on the chip.
+    #[inline(always)]
+    pub fn set_handshakes_bypassed(&mut self, bypassed: bool) {
+        // Safety: we own the CLPCR register
+        unsafe { set_handshakes_bypassed(bypassed) }
+    }
+
+    /// Returns `true` if all low-power mode handshakes are bypassed
+    #[inline(always)]
+    pub fn handshakes_bypassed(&self) -> bool {
+        handshakes_bypassed()
+    }
+
+    /// Override (`true`) or follow (`false`) a module's clock enable signal
+    ///
+    /// See [`ModuleOverride`] for more information.
+    #[inline(always)]
+    pub fn set_module_override(&mut self, module: ModuleOverride, overridden: bool) {
+        // Safety: we own the CMEOR register
+        unsafe { set_module_override(module, overridden) }
+    }
+
+    /// Returns `true` if the module's clock enable signal is overridden
+    #[inline(always)]
+    pub fn module_override(&self, module: ModuleOverride) -> bool {
+        module_override(module)
+    }
+
+    /// Enter WAIT mode, and return after wake
+    ///
+    /// `enter_wait` selects [`Mode::Wait`], stops the ARM core clock, unmasks the
+    /// core's `WFI`, bypasses the low-power mode handshakes, and executes `WFI`. Any enabled interrupt wakes
+    /// the core. On wake, `enter_wait` restores the previous low-power mode
+    /// configuration.
+    ///
+    /// In WAIT mode, every peripheral with a [`ClockGate::OnlyRun`](crate::ClockGate::OnlyRun)
+    /// clock gate loses its clock. Set your wake source's clock gate to
+    /// [`ClockGate::On`](crate::ClockGate::On) before you call `enter_wait`.
+    ///
+    /// On targets other than ARM, `enter_wait` configures and restores the CCM,
+    /// but does not execute `WFI`.
+    #[inline(always)]
+    pub fn enter_wait(&mut self) {
+        // Safety: we own the CLPCR register
+        unsafe { enter_wait() }
+    }
+}
+
+/// Set the mode that's entered on the next `WFI`
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`LowPower`] for a safer interface.
+#[inline(always)]
+pub unsafe fn set_mode(mode: Mode) {
+    LPM.modify(CCM_CLPCR, mode as u32)
+}
+
+/// Returns the mode that's entered on the next `WFI`
+#[inline(always)]
+pub fn mode() -> Mode {
+    // Safety: atomic read of a valid register
+    unsafe { mode_(CCM_CLPCR) }
+}
+
+unsafe fn mode_(clpcr: *const u32) -> Mode {
+    Mode::from_field(LPM.read(clpcr))
+}
+
+/// Set the STOP mode standby configuration
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`LowPower`] for a safer interface.
+#[inline(always)]
+pub unsafe fn set_standby(standby: &Standby) {
+    set_standby_(CCM_CLPCR, standby)
+}
+
+unsafe fn set_standby_(clpcr: *mut u32, standby: &Standby) {
+    SBYOS.modify(clpcr, standby.oscillator_off as u32);
+    VSTBY.modify(clpcr, standby.standby_voltage as u32);
+    STBY_COUNT.modify(clpcr, standby.count as u32);
+}
+
+/// Returns the STOP mode standby configuration
+#[inline(always)]
+pub fn standby() -> Standby {
+    // Safety: atomic read of a valid register
+    unsafe { standby_(CCM_CLPCR) }
+}
+
+unsafe fn standby_(clpcr: *const u32) -> Standby {
+    Standby {
+        oscillator_off: SBYOS.read(clpcr) != 0,
+        standby_voltage: VSTBY.read(clpcr) != 0,
+        count: StandbyCount::from_field(STBY_COUNT.read(clpcr)),
+    }
+}
+
+/// Power down (`true`) or power up (`false`) the 24MHz oscillator right now
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Powering down the
+/// oscillator stops every clock that it supplies. Consider using [`LowPower`]
+/// for a safer interface.
+#[inline(always)]
+pub unsafe fn set_oscillator_powered_down(powered_down: bool) {
+    COSC_PWRDOWN.modify(CCM_CLPCR, powered_down as u32)
+}
+
+/// Returns `true` if the 24MHz oscillator is powered down through CLPCR
+#[inline(always)]
+pub fn oscillator_powered_down() -> bool {
+    // Safety: atomic read of a valid register
+    unsafe { COSC_PWRDOWN.read(CCM_CLPCR) != 0 }
+}
+
+/// Bypass (`true`) or use (`false`) the low-power mode handshakes
+///
+/// See [`LowPower::set_handshakes_bypassed`] for more information.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`LowPower`] for a safer interface.
+#[inline(always)]
+pub unsafe fn set_handshakes_bypassed(bypassed: bool) {
+    set_handshakes_bypassed_(CCM_CLPCR, bypassed)
+}
+
+unsafe fn set_handshakes_bypassed_(clpcr: *mut u32, bypassed: bool) {
+    for field in HANDSHAKE_BYPASS {
+        field.modify(clpcr, bypassed as u32);
+    }
+}
+
+/// Returns `true` if all low-power mode handshakes are bypassed
+#[inline(always)]
+pub fn handshakes_bypassed() -> bool {
+    // Safety: atomic read of a valid register
+    unsafe { handshakes_bypassed_(CCM_CLPCR) }
+}
+
+unsafe fn handshakes_bypassed_(clpcr: *const u32) -> bool {
+    HANDSHAKE_BYPASS.iter().all(|field| field.read(clpcr) != 0)
+}
+
+/// Override (`true`) or follow (`false`) a module's clock enable signal
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`LowPower`] for a safer interface.
+#[inline(always)]
+pub unsafe fn set_module_override(module: ModuleOverride, overridden: bool) {
+    module.field().modify(CCM_CMEOR, overridden as u32)
+}
+
+/// Returns `true` if the module's clock enable signal is overridden
+#[inline(always)]
+pub fn module_override(module: ModuleOverride) -> bool {
+    // Safety: atomic read of a valid register
+    unsafe { module.field().read(CCM_CMEOR) != 0 }
+}
+
+/// `true` if gating the ARM core clock unmasked the core's `WFI` signal
+pub(crate) static WFI_UNMASKED: AtomicBool = AtomicBool::new(false);
+
+/// Gate (`true`) or don't gate (`false`) the ARM core clock when the core
+/// executes `WFI` in a low-power mode
+///
+/// Gating the clock also unmasks the core's `WFI` signal, so that `WFI` requests
+/// the low-power mode. `unmasked` remembers if gating unmasked the signal, so
+/// that ungating the clock masks it again.
+pub(crate) unsafe fn set_arm_clock_gated_in_wfi_(
+    clpcr: *mut u32,
+    unmasked: &AtomicBool,
+    gated: bool,
+) {
+    if gated {
+        if MASK_CORE0_WFI.read(clpcr) != 0 {
+            MASK_CORE0_WFI.modify(clpcr, 0);
+            unmasked.store(true, Ordering::Relaxed);
+        }
+        ARM_CLK_DIS_ON_LPM.modify(clpcr, 1);
+    } else {
+        ARM_CLK_DIS_ON_LPM.modify(clpcr, 0);
+        if unmasked.swap(false, Ordering::Relaxed) {
+            MASK_CORE0_WFI.modify(clpcr, 1);
+        }
+    }
+}
+
+/// Returns `true` if `WFI` gates the ARM core clock in a low-power mode
+pub(crate) unsafe fn arm_clock_gated_in_wfi_(clpcr: *const u32) -> bool {
+    ARM_CLK_DIS_ON_LPM.read(clpcr) != 0 && MASK_CORE0_WFI.read(clpcr) == 0
+}
+
+/// Enter WAIT mode, and return after wake
+///
+/// See [`LowPower::enter_wait`] for more information.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`LowPower`] for a safer interface.
+#[inline(always)]
+pub unsafe fn enter_wait() {
+    enter_wait_(CCM_CLPCR, wfi)
+}
+
+unsafe fn enter_wait_(clpcr: *mut u32, wfi: impl FnOnce()) {
+    let previous = register::read(clpcr);
+    set_handshakes_bypassed_(clpcr, true);
+    ARM_CLK_DIS_ON_LPM.modify(clpcr, 1);
+    MASK_CORE0_WFI.modify(clpcr, 0);
+    LPM.modify(clpcr, Mode::Wait as u32);
+    wfi();
+    register::write(clpcr, u32::MAX, previous);
+}
+
+/// Wait for interrupt
+#[inline(always)]
+fn wfi() {
+    // Safety: WFI has no memory effects. The barriers complete any outstanding
+    // CLPCR write before the core sleeps.
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        core::arch::asm!("dsb", "wfi", "isb", options(nostack, preserves_flags));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        arm_clock_gated_in_wfi_, enter_wait_, handshakes_bypassed_, mode_,
+        set_arm_clock_gated_in_wfi_, set_handshakes_bypassed_, set_standby_, standby_, Mode,
+        ModuleOverride, Standby, StandbyCount, ARM_CLK_DIS_ON_LPM, LPM, MASK_CORE0_WFI,
+    };
+    use core::sync::atomic::AtomicBool;
+
+    #[test]
+    fn lowpower_mode() {
+        // Reset value
+        let mut clpcr: u32 = 0x0000_0079;
+        unsafe {
+            assert_eq!(mode_(&clpcr), Mode::Wait);
+            assert_eq!(ARM_CLK_DIS_ON_LPM.read(&clpcr), 1);
+
+            LPM.modify(&mut clpcr, Mode::Stop as u32);
+            assert_eq!(mode_(&clpcr), Mode::Stop);
+            LPM.modify(&mut clpcr, Mode::Run as u32);
+            assert_eq!(mode_(&clpcr), Mode::Run);
+            assert_eq!(clpcr, 0x0000_0078);
+
+            LPM.modify(&mut clpcr, 0b11);
+            assert_eq!(mode_(&clpcr), Mode::Run);
+        }
+    }
+
+    #[test]
+    fn lowpower_standby() {
+        let mut clpcr: u32 = 0x0000_0079;
+        unsafe {
+            assert_eq!(
+                standby_(&clpcr),
+                Standby {
+                    oscillator_off: true,
+                    standby_voltage: false,
+                    count: StandbyCount::Cycles1,
+                }
+            );
+            let standby = Standby {
+                oscillator_off: false,
+                standby_voltage: true,
+                count: StandbyCount::Cycles15,
+            };
+            set_standby_(&mut clpcr, &standby);
+            assert_eq!(clpcr, 0x0000_0039 | 1 << 8 | 0b11 << 9);
+            assert_eq!(standby_(&clpcr), standby);
+        }
+    }
+
+    #[test]
+    fn lowpower_handshakes() {
+        let mut clpcr: u32 = 0x0000_0079;
+        unsafe {
+            assert!(!handshakes_bypassed_(&clpcr));
+            set_handshakes_bypassed_(&mut clpcr, true);
+            assert!(handshakes_bypassed_(&clpcr));
+            #[cfg(feature = "imxrt1060")]
+            assert_eq!(clpcr, 0x0828_0079);
+            #[cfg(not(feature = "imxrt1060"))]
+            assert_eq!(clpcr, 0x0800_0079);
+            set_handshakes_bypassed_(&mut clpcr, false);
+            assert_eq!(clpcr, 0x0000_0079);
+        }
+    }
+
+    #[test]
+    fn lowpower_enter_wait() {
+        let mut clpcr: u32 = 0x0040_0058;
+        let clpcr_ptr: *mut u32 = &mut clpcr;
+        let mut slept = false;
+        unsafe {
+            enter_wait_(clpcr_ptr, || {
+                assert_eq!(mode_(clpcr_ptr), Mode::Wait);
+                assert!(handshakes_bypassed_(clpcr_ptr));
+                assert_eq!(ARM_CLK_DIS_ON_LPM.read(clpcr_ptr), 1);
+                assert_eq!(MASK_CORE0_WFI.read(clpcr_ptr), 0);
+                slept = true;
+            });
+        }
+        assert!(slept);
+        assert_eq!(clpcr, 0x0040_0058);
+    }
+
+    #[test]
+    fn lowpower_module_override() {
+        let mut cmeor: u32 = 0;
+        unsafe {
+            ModuleOverride::Pit.field().modify(&mut cmeor, 1);
+            ModuleOverride::Trng.field().modify(&mut cmeor, 1);
+            assert_eq!(cmeor, 1 << 6 | 1 << 9);
+            assert_eq!(ModuleOverride::Gpt.field().read(&cmeor), 0);
+        }
+    }
+
+    #[test]
+    fn lowpower_arm_clock_gated_in_wfi() {
+        let unmasked = AtomicBool::new(false);
+        let mut clpcr: u32 = 1 << 22;
+        unsafe {
+            assert!(!arm_clock_gated_in_wfi_(&clpcr));
+            set_arm_clock_gated_in_wfi_(&mut clpcr, &unmasked, true);
+            assert_eq!(clpcr, 1 << 5);
+            assert!(arm_clock_gated_in_wfi_(&clpcr));
+            // Gating again doesn't forget the mask
+            set_arm_clock_gated_in_wfi_(&mut clpcr, &unmasked, true);
+            set_arm_clock_gated_in_wfi_(&mut clpcr, &unmasked, false);
+            assert_eq!(clpcr, 1 << 22);
+            assert!(!arm_clock_gated_in_wfi_(&clpcr));
+        }
+
+        // The WFI signal stays unmasked if it was unmasked before gating
+        let mut clpcr: u32 = 0;
+        unsafe {
+            set_arm_clock_gated_in_wfi_(&mut clpcr, &unmasked, true);
+            assert_eq!(clpcr, 1 << 5);
+            set_arm_clock_gated_in_wfi_(&mut clpcr, &unmasked, false);
+            assert_eq!(clpcr, 0);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! LVDS clock output
+//!
+//! The LVDS1 pads (`CCM_CLK1_P` / `CCM_CLK1_N`) can output an internal PLL, PFD,
+//! or the 24MHz reference as a differential clock. Use the LVDS output to provide
+//! a high-frequency clock to an external PHY, or to measure an internal clock.
+//!
+//! The LVDS output is only available on the i.MX RT 1060.
+//!
+//! # References
+//!
+//! i.MX RT 1060 reference manual
+//! - Chapter 14: Clock Control Module (CCM)
+//!   - CCM Analog Memory Map/Register Definition (MISC1)
+
+use crate::register::Field;
+
+const CCM_ANALOG_MISC1: *mut u32 = 0x400D_8160 as _;
+
+const LVDS1_CLK_SEL: Field = Field::new(0, 0x1F);
+const LVDSCLK1_OBEN: Field = Field::new(10, 1);
+const LVDSCLK1_IBEN: Field = Field::new(12, 1);
+
+/// A clock that can be routed to the LVDS1 pads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum Source {
+    /// ARM PLL (PLL1)
+    ArmPll = 0x00,
+    /// System PLL (PLL2)
+    SysPll = 0x01,
+    /// PLL2 PFD0
+    SysPfd0 = 0x02,
+    /// PLL2 PFD1
+    SysPfd1 = 0x03,
+    /// PLL2 PFD2
+    SysPfd2 = 0x04,
+    /// PLL2 PFD3
+    SysPfd3 = 0x05,
+    /// Audio PLL (PLL4)
+    AudioPll = 0x06,
+    /// Video PLL (PLL5)
+    VideoPll = 0x07,
+    /// ENET PLL (PLL6) reference
+    EnetPll = 0x09,
+    /// USB1 PLL (PLL3)
+    Usb1Pll = 0x0C,
+    /// USB2 PLL (PLL7)
+    Usb2Pll = 0x0D,
+    /// PLL3 PFD0
+    Usb1Pfd0 = 0x0E,
+    /// PLL3 PFD1
+    Usb1Pfd1 = 0x0F,
+    /// PLL3 PFD2
+    Usb1Pfd2 = 0x10,
+    /// PLL3 PFD3
+    Usb1Pfd3 = 0x11,
+    /// 24MHz reference
+    Oscillator = 0x12,
+}
+
+impl Source {
+    fn from_field(sel: u32) -> Option<Self> {
+        const SOURCES: [Source; 16] = [
+            Source::ArmPll,
+            Source::SysPll,
+            Source::SysPfd0,
+            Source::SysPfd1,
+            Source::SysPfd2,
+            Source::SysPfd3,
+            Source::AudioPll,
+            Source::VideoPll,
+            Source::EnetPll,
+            Source::Usb1Pll,
+            Source::Usb2Pll,
+            Source::Usb1Pfd0,
+            Source::Usb1Pfd1,
+            Source::Usb1Pfd2,
+            Source::Usb1Pfd3,
+            Source::Oscillator,
+        ];
+        SOURCES.iter().copied().find(|source| *source as u32 == sel)
+    }
+}
+
+/// The LVDS1 clock output
+pub struct LVDS(());
+
+impl LVDS {
+    pub(crate) const fn new() -> Self {
+        LVDS(())
+    }
+
+    /// Route `source` to the LVDS1 pads, or disable the output with `None`
+    ///
+    /// The source must be running for the output to toggle. The pads must
+    /// be configured for the analog clock function.
+    #[inline(always)]
+    pub fn set_output(&mut self, source: Option<Source>) {
+        // Safety: we own the MISC1 LVDS fields
+        unsafe { set_output(source) }
+    }
+
+    /// Returns the clock that's routed to the LVDS1 pads, or `None` if the
+    /// output is disabled
+    #[inline(always)]
+    pub fn output(&self) -> Option<Source> {
+        output()
+    }
+}
+
+/// Route `source` to the LVDS1 pads, or disable the output with `None`
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`LVDS`] for a safer interface.
+#[inline(always)]
+pub unsafe fn set_output(source: Option<Source>) {
+    set_output_(CCM_ANALOG_MISC1, source)
+}
+
+unsafe fn set_output_(misc1: *mut u32, source: Option<Source>) {
+    LVDSCLK1_OBEN.modify(misc1, 0);
+    if let Some(source) = source {
+        LVDSCLK1_IBEN.modify(misc1, 0);
+        LVDS1_CLK_SEL.modify(misc1, source as u32);
+        LVDSCLK1_OBEN.modify(misc1, 1);
+    }
+}
+
+/// Returns the clock that's routed to the LVDS1 pads, or `None` if the
+/// output is disabled
+#[inline(always)]
+pub fn output() -> Option<Source> {
+    // Safety: atomic read of a valid register
+    unsafe { output_(CCM_ANALOG_MISC1) }
+}
+
+unsafe fn output_(misc1: *const u32) -> Option<Source> {
+    if LVDSCLK1_OBEN.read(misc1) == 0 {
+        None
+    } else {
+        Source::from_field(LVDS1_CLK_SEL.read(misc1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{output_, set_output_, Source};
+
+    #[test]
+    fn lvds_output() {
+        let mut misc1: u32 = 1 << 12 | 1 << 16;
+        unsafe {
+            assert_eq!(output_(&misc1), None);
+            set_output_(&mut misc1, Some(Source::Usb1Pfd2));
+            assert_eq!(misc1, 1 << 16 | 1 << 10 | 0x10);
+            assert_eq!(output_(&misc1), Some(Source::Usb1Pfd2));
+            set_output_(&mut misc1, None);
+            assert_eq!(output_(&misc1), None);
+            assert_eq!(misc1, 1 << 16 | 0x10);
+        }
+    }
+}
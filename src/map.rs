@@ -0,0 +1,199 @@
+//! Cached clock root frequencies
+
+use crate::{
+    arm::{self, ARMClock, IPGClock},
+    i2c, perclock, raw, spi,
+    tree::{Node, Tree},
+    uart, ClockFor, ClockRoot, ADC, DCDC, DMA, PWM,
+};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Every clock root, in declaration order
+const ROOTS: &[ClockRoot] = &[
+    ClockRoot::AHB,
+    ClockRoot::IPG,
+    ClockRoot::PerClock,
+    ClockRoot::UART,
+    ClockRoot::SPI,
+    ClockRoot::I2C,
+    #[cfg(feature = "imxrt1060")]
+    ClockRoot::USDHC1,
+    #[cfg(feature = "imxrt1060")]
+    ClockRoot::USDHC2,
+    #[cfg(any(feature = "imxrt1060", feature = "imxrt1010"))]
+    ClockRoot::SAI1,
+    #[cfg(feature = "imxrt1060")]
+    ClockRoot::SAI2,
+    #[cfg(any(feature = "imxrt1060", feature = "imxrt1010"))]
+    ClockRoot::SAI3,
+    #[cfg(any(feature = "imxrt1060", feature = "imxrt1010"))]
+    ClockRoot::FlexSPI,
+    #[cfg(feature = "imxrt1060")]
+    ClockRoot::SEMC,
+    #[cfg(any(feature = "imxrt1060", feature = "imxrt1010"))]
+    ClockRoot::Trace,
+    #[cfg(feature = "imxrt1060")]
+    ClockRoot::CAN,
+    #[cfg(feature = "imxrt1060")]
+    ClockRoot::FlexIO2,
+];
+
+/// The CCGR registers, which don't affect any frequency
+const CCGR: core::ops::Range<usize> = 0x400F_C068..0x400F_C088;
+
+/// Incremented by every CCM and CCM_ANALOG write that could change a frequency
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Note a register write to `address`
+#[inline(always)]
+pub(crate) fn changed(address: usize) {
+    let ccm = 0x400F_C000..0x400F_C090;
+    let ccm_analog = 0x400D_8000..0x400D_8180;
+    if (ccm.contains(&address) && !CCGR.contains(&address)) || ccm_analog.contains(&address) {
+        GENERATION.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// The frequency of every clock root, captured at one point in time
+///
+/// Use [`CCM::frequency_map`](crate::CCM::frequency_map) to capture a map
+/// after you configure the clocks. A `FrequencyMap` is small and `Copy`, so
+/// you can hand it to drivers by value. Looking up a frequency doesn't touch
+/// any CCM register. `FrequencyMap` implements [`ClockFor`] for the modeled
+/// peripherals:
+///
+/// ```no_run
+/// use imxrt_ccm::{uart::UART, ClockFor, ClockRoot};
+/// # let mut ccm = unsafe { imxrt_ccm::CCM::<imxrt_ccm::SplitClocks>::new() };
+///
+/// fn baud_divider(clock: &impl ClockFor<UART>, baud: u32) -> u32 {
+///     clock.frequency() / baud
+/// }
+///
+/// let mut map = ccm.frequency_map();
+/// let divider = baud_divider(&map, 115_200);
+///
+/// // Later, after reconfiguring the clocks...
+/// if ccm.refresh_frequency_map(&mut map) {
+///     // Recompute the divider
+/// }
+/// ```
+///
+/// A map becomes stale after any CCM write that could change a frequency,
+/// including writes from other code. Clock gate changes don't make the map
+/// stale. Check [`is_current`](FrequencyMap::is_current) before you trust an
+/// old map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FrequencyMap {
+    roots: [u32; ROOTS.len()],
+    generation: usize,
+}
+
+impl FrequencyMap {
+    /// Capture the frequency of every clock root in `tree`
+    ///
+    /// The map is current if `tree` models the hardware.
+    pub fn new<R: Fn(raw::Reg) -> u32>(tree: &Tree<R>) -> Self {
+        let generation = GENERATION.load(Ordering::Relaxed);
+        let mut roots = [0; ROOTS.len()];
+        for (hz, root) in roots.iter_mut().zip(ROOTS) {
+            *hz = tree.frequency(Node::Root(*root));
+        }
+        FrequencyMap { roots, generation }
+    }
+
+    /// Returns the frequency of a clock root
+    #[inline(always)]
+    pub fn root(&self, root: ClockRoot) -> u32 {
+        self.roots[root as usize]
+    }
+
+    /// Returns the ARM and IPG clock frequencies
+    #[inline(always)]
+    pub fn arm(&self) -> (ARMClock, IPGClock) {
+        (
+            arm::ARMClock(self.root(ClockRoot::AHB)),
+            arm::IPGClock(self.root(ClockRoot::IPG)),
+        )
+    }
+
+    /// Recapture every clock root frequency in `tree`
+    ///
+    /// Returns `true` if any frequency changed.
+    pub fn refresh<R: Fn(raw::Reg) -> u32>(&mut self, tree: &Tree<R>) -> bool {
+        let previous = self.roots;
+        *self = FrequencyMap::new(tree);
+        previous != self.roots
+    }
+
+    /// Returns `true` if no CCM write changed a frequency since the map was
+    /// captured
+    pub fn is_current(&self) -> bool {
+        self.generation == GENERATION.load(Ordering::Relaxed)
+    }
+}
+
+macro_rules! clock_for {
+    ($($inst:ty => $root:ident,)+) => {
+        $(
+            impl ClockFor<$inst> for FrequencyMap {
+                #[inline(always)]
+                fn frequency(&self) -> u32 {
+                    self.root(ClockRoot::$root)
+                }
+            }
+        )+
+    };
+}
+
+clock_for! {
+    uart::UART => UART,
+    spi::SPI => SPI,
+    i2c::I2C => I2C,
+    perclock::GPT => PerClock,
+    perclock::PIT => PerClock,
+    ADC => IPG,
+    PWM => IPG,
+    DCDC => IPG,
+    DMA => AHB,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{changed, FrequencyMap, ROOTS};
+    use crate::{raw, tree::Tree, uart::UART, ClockFor, ClockRoot};
+
+    #[test]
+    fn map_roots_in_order() {
+        for (idx, root) in ROOTS.iter().enumerate() {
+            assert_eq!(*root as usize, idx);
+        }
+    }
+
+    #[test]
+    fn map_frequency() {
+        // UART_CLK_SEL selects the oscillator; UART_CLK_PODF divides by 4
+        let tree = Tree::new(|reg| if reg == raw::CSCDR1 { 1 << 6 | 3 } else { 0 });
+        let map = FrequencyMap::new(&tree);
+        assert_eq!(map.root(ClockRoot::UART), 6_000_000);
+        assert_eq!(ClockFor::<UART>::frequency(&map), 6_000_000);
+        assert_eq!(map.arm().0 .0, map.root(ClockRoot::AHB));
+
+        let mut other = map;
+        assert!(!other.refresh(&tree));
+        assert!(other.refresh(&Tree::new(|reg| if reg == raw::CSCDR1 {
+            1 << 6
+        } else {
+            0
+        })));
+        assert_eq!(other.root(ClockRoot::UART), 24_000_000);
+
+        assert!(map.is_current());
+        changed(0x400F_C06C); // CCGR1
+        changed(0x2000_0000);
+        assert!(map.is_current());
+        changed(0x400F_C024); // CSCDR1
+        assert!(!map.is_current());
+    }
+}
@@ -0,0 +1,121 @@
+//! Periodic clock setup for GPT monotonic timers
+//!
+//! An RTIC monotonic needs a timer that ticks at a rate that's known at
+//! compile time. [`PerClock::configure_monotonic`] runs the periodic clock
+//! root from the crystal oscillator at `HZ`, and enables a GPT clock gate. The
+//! returned [`MonotonicClock`] exposes the tick rate as an associated
+//! constant. The rate doesn't change when the ARM clock changes.
+//!
+//! ```no_run
+//! use imxrt_ccm::{monotonic::MonotonicClock, perclock::NoPIT, Clocks, Instance, CCM};
+//! # struct GPT1;
+//! # unsafe impl Instance for GPT1 {
+//! #     type Inst = imxrt_ccm::perclock::GPT;
+//! #     fn instance(&self) -> Self::Inst { imxrt_ccm::perclock::GPT::GPT1 }
+//! #     fn is_valid(inst: Self::Inst) -> bool { inst == imxrt_ccm::perclock::GPT::GPT1 }
+//! # }
+//! # struct MyClocks;
+//! # impl Clocks for MyClocks {
+//! #     type PIT = NoPIT;
+//! #     type GPT = GPT1;
+//! #     type UART = ();
+//! #     type SPI = ();
+//! #     type I2C = ();
+//! # }
+//! # let mut ccm = unsafe { CCM::<MyClocks>::new() };
+//! # let mut gpt1 = GPT1;
+//!
+//! type Clock = MonotonicClock<1_000_000>;
+//! let clock: Clock = ccm.perclock_mut().configure_monotonic(&mut gpt1);
+//! assert_eq!(Clock::FREQUENCY_HZ, 1_000_000);
+//! ```
+//!
+//! The 24MHz reference frequency must be a multiple of `HZ`, and the divider must be
+//! between 1 and 64. Other rates don't compile:
+//!
+//! ```compile_fail
+//! # use imxrt_ccm::monotonic::MonotonicClock;
+//! let divider = MonotonicClock::<7>::DIVIDER;
+//! ```
+
+use crate::{
+    perclock::{PerClock, Selection, GPT, PIT},
+    xtalosc::reference_frequency,
+    ClockFor, ClockGate, Instance, OSCILLATOR_FREQUENCY_HZ,
+};
+
+/// A periodic clock root that ticks at `HZ`
+///
+/// Use [`PerClock::configure_monotonic`] to acquire a `MonotonicClock`.
+#[derive(Debug)]
+pub struct MonotonicClock<const HZ: u32>(());
+
+impl<const HZ: u32> MonotonicClock<HZ> {
+    /// The periodic clock frequency, in Hz
+    pub const FREQUENCY_HZ: u32 = HZ;
+
+    /// The periodic clock divider that divides the oscillator to `HZ`
+    pub const DIVIDER: u32 = {
+        assert!(HZ != 0, "HZ must be greater than zero");
+        let divider = OSCILLATOR_FREQUENCY_HZ / HZ;
+        assert!(
+            divider * HZ == OSCILLATOR_FREQUENCY_HZ,
+            "HZ must evenly divide the oscillator frequency"
+        );
+        assert!(divider <= 64, "HZ requires a divider larger than 64");
+        divider
+    };
+
+    /// Returns the periodic clock frequency, in Hz
+    #[inline(always)]
+    pub const fn frequency(&self) -> u32 {
+        HZ
+    }
+}
+
+impl<const HZ: u32> ClockFor<GPT> for MonotonicClock<HZ> {
+    #[inline(always)]
+    fn frequency(&self) -> u32 {
+        HZ
+    }
+}
+
+impl<P, G> PerClock<P, G>
+where
+    P: Instance<Inst = PIT>,
+    G: Instance<Inst = GPT>,
+{
+    /// Run the periodic clock root from the oscillator at `HZ`, and enable the
+    /// clock gate for `gpt`
+    ///
+    /// Like [`configure_selection_divider`](PerClock::configure_selection_divider),
+    /// `configure_monotonic` turns off all other GPT and PIT clock gates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if you declared a reference frequency other than 24MHz with
+    /// [`set_reference_frequency`](crate::xtalosc::XTALOSC::set_reference_frequency).
+    #[cfg_attr(docsrs, doc(cfg(feature = "monotonic")))]
+    pub fn configure_monotonic<const HZ: u32>(&mut self, gpt: &mut G) -> MonotonicClock<HZ> {
+        assert_eq!(
+            reference_frequency(),
+            OSCILLATOR_FREQUENCY_HZ,
+            "MonotonicClock requires a 24MHz reference"
+        );
+        self.configure_selection_divider(Selection::Oscillator, MonotonicClock::<HZ>::DIVIDER);
+        self.set_clock_gate_gpt(gpt, ClockGate::On);
+        MonotonicClock(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MonotonicClock;
+
+    #[test]
+    fn monotonic_divider() {
+        assert_eq!(MonotonicClock::<24_000_000>::DIVIDER, 1);
+        assert_eq!(MonotonicClock::<1_000_000>::DIVIDER, 24);
+        assert_eq!(MonotonicClock::<375_000>::DIVIDER, 64);
+    }
+}
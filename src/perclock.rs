@@ -1,12 +1,18 @@
 //! Periodic clock
 
-use super::{arm, ClockGate, ClockGateLocation, ClockGateLocator, Instance};
+use super::{
+    arm, ClockFor, ClockGate, ClockGateGuard, ClockGateLocation, ClockGateLocator, ClockRoot,
+    ClockRootLocator, Instance,
+};
 use crate::{
+    divider::Choice,
     register::{Field, Register},
-    OSCILLATOR_FREQUENCY_HZ,
+    shared::SharedClockGate,
+    xtalosc::reference_frequency,
+    ClockError, RootFrequency,
 };
 
-use core::marker::PhantomData;
+use core::{convert::TryFrom, marker::PhantomData};
 
 /// The periodic clock root
 ///
@@ -26,6 +32,9 @@ pub enum GPT {
     GPT2,
 }
 
+/// All GPT instances
+const GPTS: &[GPT] = &[GPT::GPT1, GPT::GPT2];
+
 /// Periodic clock selection
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Selection {
@@ -37,6 +46,9 @@ pub enum Selection {
     Oscillator,
 }
 
+/// Periodic clock root divider, between [1, 64]
+pub type Divider = crate::Divider<1, 64>;
+
 impl ClockGateLocator for GPT {
     #[inline(always)]
     fn location(&self) -> ClockGateLocation {
@@ -53,6 +65,20 @@ impl ClockGateLocator for GPT {
     }
 }
 
+impl ClockRootLocator for GPT {
+    #[inline(always)]
+    fn clock_root(&self) -> ClockRoot {
+        ClockRoot::PerClock
+    }
+}
+
+impl<P, G> ClockFor<GPT> for PerClock<P, G> {
+    #[inline(always)]
+    fn frequency(&self) -> u32 {
+        PerClock::frequency(self)
+    }
+}
+
 /// Peripheral instance identifier for PIT
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub struct PIT;
@@ -67,6 +93,87 @@ impl ClockGateLocator for PIT {
     }
 }
 
+impl ClockRootLocator for PIT {
+    #[inline(always)]
+    fn clock_root(&self) -> ClockRoot {
+        ClockRoot::PerClock
+    }
+}
+
+impl<P, G> ClockFor<PIT> for PerClock<P, G> {
+    #[inline(always)]
+    fn frequency(&self) -> u32 {
+        PerClock::frequency(self)
+    }
+}
+
+/// A GPT instance for systems that only use the PIT
+///
+/// Use `NoGPT` as [`Clocks::GPT`](crate::Clocks::GPT) when your HAL doesn't
+/// expose GPT timers, but you still want to use the [`PerClock`] for PIT timers.
+/// There are no `NoGPT` objects, so you can't call the `PerClock` GPT methods.
+/// The configuration methods leave the GPT clock gates untouched.
+///
+/// ```
+/// use imxrt_ccm::{perclock::{NoGPT, PIT}, Clocks, Instance, CCM};
+///
+/// struct MyPIT;
+/// unsafe impl Instance for MyPIT {
+///     type Inst = PIT;
+///     fn instance(&self) -> PIT {
+///         PIT
+///     }
+///     fn is_valid(_: PIT) -> bool {
+///         true
+///     }
+/// }
+///
+/// struct MyClocks;
+/// impl Clocks for MyClocks {
+///     type PIT = MyPIT;
+///     type GPT = NoGPT;
+/// #   type I2C = ();
+/// #   type SPI = ();
+/// #   type UART = ();
+///     // ...
+/// }
+///
+/// let mut ccm = unsafe { CCM::<MyClocks>::new() };
+/// # return; // Don't touch CCM memory
+/// ccm.perclock_mut().configure();
+/// ```
+#[derive(Debug)]
+pub enum NoGPT {}
+
+unsafe impl Instance for NoGPT {
+    type Inst = GPT;
+    fn instance(&self) -> GPT {
+        match *self {}
+    }
+    fn is_valid(_: GPT) -> bool {
+        false
+    }
+}
+
+/// A PIT instance for systems that only use GPTs
+///
+/// Use `NoPIT` as [`Clocks::PIT`](crate::Clocks::PIT) when your HAL doesn't
+/// expose the PIT, but you still want to use the [`PerClock`] for GPT timers.
+/// There are no `NoPIT` objects, so you can't call the `PerClock` PIT methods.
+/// The configuration methods leave the PIT clock gate untouched.
+#[derive(Debug)]
+pub enum NoPIT {}
+
+unsafe impl Instance for NoPIT {
+    type Inst = PIT;
+    fn instance(&self) -> PIT {
+        match *self {}
+    }
+    fn is_valid(_: PIT) -> bool {
+        false
+    }
+}
+
 const DEFAULT_CLOCK_DIVIDER: u32 = 24;
 
 impl<P, G> PerClock<P, G> {
@@ -99,6 +206,28 @@ impl<P, G> PerClock<P, G> {
     pub fn selection(&self) -> Selection {
         selection()
     }
+    /// Returns `true` if the periodic clock runs
+    ///
+    /// Use this to check that the periodic clock is configured before you use
+    /// a GPT or PIT timer.
+    #[inline(always)]
+    pub fn is_configured(&self) -> bool {
+        self.frequency() != 0
+    }
+}
+
+impl<P, G> PerClock<P, G>
+where
+    P: Instance<Inst = PIT>,
+    G: Instance<Inst = GPT>,
+{
+    /// Returns `true` if any GPT or PIT clock gate is on
+    ///
+    /// The check only considers the instances that are valid for `P` and `G`.
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        crate::any_clock_gate_on::<G>(GPTS) || crate::any_clock_gate_on::<P>(&[PIT])
+    }
 }
 
 impl<P, G> PerClock<P, G>
@@ -118,6 +247,23 @@ where
     pub fn set_clock_gate_gpt(&mut self, gpt: &mut G, gate: ClockGate) {
         unsafe { super::set_clock_gate::<G>(gpt.instance(), gate) };
     }
+
+    /// Turn on the clock gate for the GPT, returning a guard that restores
+    /// the previous setting when dropped
+    #[inline(always)]
+    pub fn enable_guard_gpt<'a>(&mut self, gpt: &'a mut G) -> ClockGateGuard<'a, G> {
+        // Safety: we have exclusive access to the instance
+        unsafe { ClockGateGuard::new(gpt) }
+    }
+
+    /// Share the clock gate for the GPT between multiple users
+    ///
+    /// See [`shared`](crate::shared) for more information.
+    #[inline(always)]
+    pub fn shared_gate_gpt(&mut self, gpt: G) -> SharedClockGate<G> {
+        // Safety: we own the instance
+        unsafe { SharedClockGate::new(gpt) }
+    }
 }
 
 impl<P, G> PerClock<P, G>
@@ -137,6 +283,23 @@ where
     pub fn set_clock_gate_pit(&mut self, pit: &mut P, gate: ClockGate) {
         unsafe { super::set_clock_gate::<P>(pit.instance(), gate) };
     }
+
+    /// Turn on the clock gate for the PIT, returning a guard that restores
+    /// the previous setting when dropped
+    #[inline(always)]
+    pub fn enable_guard_pit<'a>(&mut self, pit: &'a mut P) -> ClockGateGuard<'a, P> {
+        // Safety: we have exclusive access to the instance
+        unsafe { ClockGateGuard::new(pit) }
+    }
+
+    /// Share the clock gate for the PIT between multiple users
+    ///
+    /// See [`shared`](crate::shared) for more information.
+    #[inline(always)]
+    pub fn shared_gate_pit(&mut self, pit: P) -> SharedClockGate<P> {
+        // Safety: we own the instance
+        unsafe { SharedClockGate::new(pit) }
+    }
 }
 
 impl<P, G> PerClock<P, G>
@@ -149,16 +312,37 @@ where
     /// The divider should be between [1, 64]. The function will treat a 0 as 1,
     /// and anything greater than 64 as 64.
     ///
+    /// Returns the periodic clock frequency. Since there's no requested
+    /// frequency, the error is zero; see [`RootFrequency::relative_to`].
+    ///
     /// When `configure` returns, all GPT and PIT clock gates will be set to off. To
     /// re-configure clock gates, use the clock gate methods on [`PerClock`](struct.PerClock.html).
     #[inline(always)]
-    pub fn configure_selection_divider(&mut self, selection: Selection, divider: u32) {
+    pub fn configure_selection_divider(
+        &mut self,
+        selection: Selection,
+        divider: u32,
+    ) -> RootFrequency {
         unsafe {
-            super::set_clock_gate::<G>(GPT::GPT1, ClockGate::Off);
-            super::set_clock_gate::<G>(GPT::GPT2, ClockGate::Off);
-            super::set_clock_gate::<P>(PIT, ClockGate::Off);
+            crate::clock_gates_off::<G>(GPTS);
+            crate::clock_gates_off::<P>(&[PIT]);
             configure(selection, divider);
         };
+        RootFrequency::exact(self.frequency())
+    }
+
+    /// Configure the periodic clock root with a divider that's known to be in range
+    ///
+    /// Unlike `configure_selection_divider`, the divider can't saturate. See
+    /// [`configure_selection_divider`](PerClock::configure_selection_divider) for
+    /// more information.
+    #[inline(always)]
+    pub fn configure_with_divider(
+        &mut self,
+        selection: Selection,
+        divider: Divider,
+    ) -> RootFrequency {
+        self.configure_selection_divider(selection, divider.get())
     }
 
     /// Configure the periodic clock root with a default divider. The default divider will result
@@ -168,7 +352,101 @@ where
     /// re-configure clock gates, use the clock gate methods on [`PerClock`](struct.PerClock.html).
     #[inline(always)]
     pub fn configure(&mut self) {
-        self.configure_selection_divider(Selection::Oscillator, DEFAULT_CLOCK_DIVIDER)
+        self.configure_selection_divider(Selection::Oscillator, DEFAULT_CLOCK_DIVIDER);
+    }
+
+    /// Configure the periodic clock root to approximate `target_hz`, returning
+    /// the achieved frequency
+    ///
+    /// See [`configure_frequency`] for more information.
+    ///
+    /// When `configure_frequency` returns, all GPT and PIT clock gates will be set to off. To
+    /// re-configure clock gates, use the clock gate methods on [`PerClock`](struct.PerClock.html).
+    #[inline(always)]
+    pub fn configure_frequency(&mut self, target_hz: u32) -> RootFrequency {
+        unsafe {
+            crate::clock_gates_off::<G>(GPTS);
+            crate::clock_gates_off::<P>(&[PIT]);
+            configure_frequency(target_hz)
+        }
+    }
+
+    /// Set the periodic clock divider, leaving the clock selection unchanged
+    ///
+    /// See [`set_divider`] for more information. Unlike `configure_selection_divider`,
+    /// `set_divider` does not change the GPT and PIT clock gates.
+    #[inline(always)]
+    pub fn set_divider(&mut self, divider: u32) {
+        // Safety: we own the CCM peripheral memory
+        unsafe { set_divider(divider) }
+    }
+
+    /// Select the periodic clock source, leaving the divider unchanged
+    ///
+    /// When `set_selection` returns, all GPT and PIT clock gates will be set to off. To
+    /// re-configure clock gates, use the clock gate methods on [`PerClock`](struct.PerClock.html).
+    #[inline(always)]
+    pub fn set_selection(&mut self, selection: Selection) {
+        unsafe {
+            crate::clock_gates_off::<G>(GPTS);
+            crate::clock_gates_off::<P>(&[PIT]);
+            set_selection(selection);
+        }
+    }
+
+    /// Configure the periodic clock root with a clock divider, or return an
+    /// error if the divider is out of range
+    ///
+    /// Unlike [`configure_selection_divider`](Self::configure_selection_divider),
+    /// `try_configure_selection_divider` doesn't saturate the divider. If it
+    /// returns an error, it makes no changes.
+    pub fn try_configure_selection_divider(
+        &mut self,
+        selection: Selection,
+        divider: u32,
+    ) -> Result<RootFrequency, ClockError> {
+        let divider = Divider::try_from(divider)?;
+        Ok(self.configure_with_divider(selection, divider))
+    }
+
+    /// Configure the periodic clock root to within `tolerance_hz` of `target_hz`,
+    /// or return an error if no source and divider are close enough
+    ///
+    /// Unlike [`configure_frequency`](Self::configure_frequency), which settles for
+    /// the closest frequency, `try_configure_frequency` makes no changes if the
+    /// closest frequency isn't within the tolerance.
+    pub fn try_configure_frequency(
+        &mut self,
+        target_hz: u32,
+        tolerance_hz: u32,
+    ) -> Result<RootFrequency, ClockError> {
+        // Safety: we own the CCM peripheral memory
+        let ipg_hz = unsafe { arm::ARM_CONTEXT.clocks().ipg.0 };
+        let sources = Sources {
+            oscillator_hz: reference_frequency(),
+            ipg_hz,
+        };
+        // Unwrap OK: the oscillator always runs
+        let choice = choose(&sources, target_hz)
+            .unwrap()
+            .within(target_hz, tolerance_hz)?;
+        unsafe {
+            crate::clock_gates_off::<G>(GPTS);
+            crate::clock_gates_off::<P>(&[PIT]);
+            CSCMR1.set(choice.divider - 1, choice.selection);
+        }
+        Ok(choice.frequency(target_hz))
+    }
+
+    /// Set the periodic clock divider, or return an error if the divider is out
+    /// of range
+    ///
+    /// Unlike [`set_divider`](Self::set_divider), `try_set_divider` doesn't
+    /// saturate the divider. If it returns an error, it makes no changes.
+    pub fn try_set_divider(&mut self, divider: u32) -> Result<(), ClockError> {
+        let divider = Divider::try_from(divider)?;
+        self.set_divider(divider.get());
+        Ok(())
     }
 }
 
@@ -189,18 +467,92 @@ const CSCMR1: Register = unsafe { Register::new(PERCLK_PODF, PERCLK_SEL, 0x400F_
 /// This could be called anywhere, modifying global memory that's owned by
 /// the CCM. Consider using the [`PerClock`](struct.PerClock.html) for a
 /// safer interface.
-#[inline(always)]
 pub unsafe fn configure(selection: Selection, divider: u32) {
     configure_(selection, divider, &CSCMR1);
 }
 
 #[inline(always)]
 unsafe fn configure_(selection: Selection, divider: u32, reg: &Register) {
-    let selection: u32 = match selection {
+    reg.set(divider.clamp(1, 64).saturating_sub(1), field(selection));
+}
+
+/// Returns the PERCLK_SEL value for `selection`
+#[inline(always)]
+const fn field(selection: Selection) -> u32 {
+    match selection {
         Selection::Oscillator => 1,
         Selection::IPG => 0,
+    }
+}
+
+/// Set the periodic clock divider, leaving the clock selection unchanged
+///
+/// Unlike [`configure`], which also writes the clock selection, `set_divider`
+/// only writes the divider field.
+///
+/// The divider should be between [1, 64]. The function will treat a 0 as 1,
+/// and anything greater than 64 as 64.
+///
+/// # Safety
+///
+/// This could be called anywhere, modifying global memory that's owned by
+/// the CCM. Consider using the [`PerClock`](struct.PerClock.html) for a
+/// safer interface.
+pub unsafe fn set_divider(divider: u32) {
+    set_divider_(divider, &CSCMR1);
+}
+
+#[inline(always)]
+unsafe fn set_divider_(divider: u32, reg: &Register) {
+    reg.set_divider(divider.clamp(1, 64) - 1);
+}
+
+/// Select the periodic clock source, leaving the divider unchanged
+///
+/// Set selection will **not** disable peripheral clock gates. You should disable
+/// clock gates yourself before calling this function.
+///
+/// # Safety
+///
+/// This could be called anywhere, modifying global memory that's owned by
+/// the CCM. Consider using the [`PerClock`](struct.PerClock.html) for a
+/// safer interface.
+pub unsafe fn set_selection(selection: Selection) {
+    set_selection_(selection, &CSCMR1);
+}
+
+#[inline(always)]
+unsafe fn set_selection_(selection: Selection, reg: &Register) {
+    reg.set_selection(field(selection));
+}
+
+/// Configure the periodic clock root to approximate `target_hz`, returning the
+/// achieved frequency
+///
+/// The function selects the source, either the crystal oscillator or the IPG
+/// clock, and the divider, that produce the frequency closest to `target_hz`.
+/// When both sources can produce the same frequency, the function prefers the
+/// oscillator, since the IPG clock changes with the ARM clock. Configure will
+/// **not** disable peripheral clock gates.
+///
+/// # Safety
+///
+/// This could be called anywhere, modifying global memory that's owned by
+/// the CCM. Consider using the [`PerClock`](struct.PerClock.html) for a
+/// safer interface.
+pub unsafe fn configure_frequency(target_hz: u32) -> RootFrequency {
+    configure_frequency_(target_hz, &CSCMR1, arm::ARM_CONTEXT.clocks().ipg.0)
+}
+
+unsafe fn configure_frequency_(target_hz: u32, reg: &Register, ipg_hz: u32) -> RootFrequency {
+    let sources = Sources {
+        oscillator_hz: reference_frequency(),
+        ipg_hz,
     };
-    reg.set(divider.min(64).max(1).saturating_sub(1), selection);
+    // Unwrap OK: the oscillator always runs
+    let choice = choose(&sources, target_hz).unwrap();
+    reg.set(choice.divider - 1, choice.selection);
+    choice.frequency(target_hz)
 }
 
 /// Returns the periodic clock frequency
@@ -216,11 +568,17 @@ pub unsafe fn frequency() -> u32 {
 unsafe fn frequency_(ctx: &arm::Context, reg: &Register) -> u32 {
     let divider = reg.divider() + 1;
     match selection_(reg) {
-        Selection::IPG => ctx.timings().ipg_hz() / divider,
-        Selection::Oscillator => OSCILLATOR_FREQUENCY_HZ / divider,
+        Selection::IPG => ctx.clocks().ipg.0 / divider,
+        Selection::Oscillator => reference_frequency() / divider,
     }
 }
 
+/// Returns the periodic clock divider
+#[inline(always)]
+pub fn divider() -> u32 {
+    CSCMR1.divider() + 1
+}
+
 /// Returns the periodic clock selection
 #[inline(always)]
 pub fn selection() -> Selection {
@@ -236,13 +594,104 @@ fn selection_(reg: &Register) -> Selection {
     }
 }
 
+/// The periodic clock root source frequencies
+///
+/// Use `Sources` with [`closest`] and [`root_frequency`] to compute periodic
+/// clock root settings without the CCM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sources {
+    /// The crystal oscillator frequency
+    pub oscillator_hz: u32,
+    /// The IPG clock frequency
+    pub ipg_hz: u32,
+}
+
+impl Sources {
+    /// Returns the source frequencies of this system
+    ///
+    /// # Safety
+    ///
+    /// Reads multiple CCM registers without synchronization.
+    pub unsafe fn current() -> Self {
+        Sources {
+            oscillator_hz: reference_frequency(),
+            ipg_hz: arm::ARM_CONTEXT.clocks().ipg.0,
+        }
+    }
+}
+
+/// A periodic clock root selection and divider, and the frequency that they
+/// produce
+pub type Setting = crate::RootSetting<Selection, Divider>;
+
+/// Returns the periodic clock root frequency for `selection` and `divider`
+///
+/// Unlike [`frequency`], `root_frequency` doesn't read the CCM.
+pub const fn root_frequency(sources: &Sources, selection: Selection, divider: Divider) -> u32 {
+    let source_hz = match selection {
+        Selection::IPG => sources.ipg_hz,
+        Selection::Oscillator => sources.oscillator_hz,
+    };
+    source_hz / divider.get()
+}
+
+/// Returns the periodic clock root selection and divider that best approximate
+/// `target_hz`
+///
+/// `closest` makes the same choice as [`configure_frequency`], including the
+/// preference for the oscillator, but it doesn't read or write the CCM.
+/// Returns `None` if no source runs.
+///
+/// ```
+/// use imxrt_ccm::perclock::{self, Selection, Sources};
+///
+/// const SOURCES: Sources = Sources {
+///     oscillator_hz: 24_000_000,
+///     ipg_hz: 150_000_000,
+/// };
+/// const PERCLK: perclock::Setting = match perclock::closest(&SOURCES, 1_000_000) {
+///     Some(setting) => setting,
+///     None => panic!("no periodic clock source"),
+/// };
+///
+/// assert_eq!(PERCLK.selection, Selection::Oscillator);
+/// assert_eq!(PERCLK.divider.get(), 24);
+/// assert_eq!(
+///     perclock::root_frequency(&SOURCES, PERCLK.selection, PERCLK.divider),
+///     PERCLK.frequency.hz
+/// );
+/// ```
+pub const fn closest(sources: &Sources, target_hz: u32) -> Option<Setting> {
+    match choose(sources, target_hz) {
+        Some(choice) => {
+            let selection = if choice.selection == field(Selection::Oscillator) {
+                Selection::Oscillator
+            } else {
+                Selection::IPG
+            };
+            Some(choice.setting(selection, target_hz))
+        }
+        None => None,
+    }
+}
+
+/// Returns the source and divider that best approximate `target_hz`
+const fn choose(sources: &Sources, target_hz: u32) -> Option<Choice> {
+    let sources = [
+        (field(Selection::Oscillator), sources.oscillator_hz),
+        (field(Selection::IPG), sources.ipg_hz),
+    ];
+    crate::divider::closest(target_hz, &sources, 64)
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::{
-        arm::tests::TestContext, configure_, frequency_, Register, Selection,
-        OSCILLATOR_FREQUENCY_HZ, PERCLK_PODF, PERCLK_SEL,
+        arm::tests::TestContext, configure_, configure_frequency_, frequency_, set_divider_,
+        set_selection_, Register, Selection, PERCLK_PODF, PERCLK_SEL,
     };
+    use crate::OSCILLATOR_FREQUENCY_HZ;
 
     unsafe fn register(mem: &mut u32) -> Register {
         Register::new(PERCLK_PODF, PERCLK_SEL, mem)
@@ -294,7 +743,57 @@ mod tests {
             let reg = register(&mut mem);
             configure_(Selection::IPG, 2, &reg);
             let mut ctx = TestContext::from_timings(&crate::arm::Timings::target(600_000_000));
+            #[cfg(feature = "imxrt1060")]
             assert_eq!(frequency_(&ctx.context(), &reg), 150_000_000 / 2);
+            // No PLL1; the same selection is PLL6
+            #[cfg(not(feature = "imxrt1060"))]
+            assert_eq!(frequency_(&ctx.context(), &reg), 125_000_000 / 2);
+        }
+    }
+
+    #[test]
+    fn perclk_configure_frequency() {
+        let mut mem: u32 = 0;
+        unsafe {
+            let reg = register(&mut mem);
+            let achieved = configure_frequency_(1_000_000, &reg, 150_000_000);
+            assert_eq!((achieved.hz, achieved.error_hz), (1_000_000, 0));
+            assert_eq!(frequency_(&TestContext::new().context(), &reg), 1_000_000);
+
+            let achieved = configure_frequency_(75_000_000, &reg, 150_000_000);
+            assert_eq!((achieved.hz, achieved.error_hz), (75_000_000, 0));
+            assert_eq!(reg.selection(), 0);
+
+            // Smaller than the slowest periodic clock
+            let achieved = configure_frequency_(150_000, &reg, 150_000_000);
+            assert_eq!(achieved.hz, OSCILLATOR_FREQUENCY_HZ / 64);
+        }
+    }
+
+    #[test]
+    fn perclk_set_divider_selection() {
+        let mut mem: u32 = 0;
+        unsafe {
+            let reg = register(&mut mem);
+            configure_(Selection::Oscillator, 24, &reg);
+            set_divider_(12, &reg);
+            assert_eq!(
+                frequency_(&TestContext::new().context(), &reg),
+                OSCILLATOR_FREQUENCY_HZ / 12
+            );
+
+            set_selection_(Selection::IPG, &reg);
+            assert_eq!(reg.selection(), 0);
+            assert_eq!(reg.divider() + 1, 12);
         }
     }
+
+    #[test]
+    fn perclk_no_instances() {
+        use super::{NoGPT, NoPIT, GPT, PIT};
+        use crate::Instance;
+        assert!(!NoGPT::is_valid(GPT::GPT1));
+        assert!(!NoGPT::is_valid(GPT::GPT2));
+        assert!(!NoPIT::is_valid(PIT));
+    }
 }
@@ -0,0 +1,136 @@
+//! Phase fractional divider (PFD) helpers
+//!
+//! The CCM_ANALOG_PFD_528 and CCM_ANALOG_PFD_480 registers share a
+//! layout. Each register has four PFDs. Each PFD has a fractional divider,
+//! a stable flag, and a clock gate.
+
+use crate::register::Field;
+
+/// Phase fractional divider (PFD) identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PFD {
+    PFD0,
+    PFD1,
+    PFD2,
+    PFD3,
+}
+
+/// The smallest PFD fractional divider
+pub const FRAC_MIN: u32 = 12;
+/// The largest PFD fractional divider
+pub const FRAC_MAX: u32 = 35;
+
+const fn frac(pfd: PFD) -> Field {
+    Field::new(8 * pfd as u32, 0x3F)
+}
+
+const fn clkgate(pfd: PFD) -> Field {
+    Field::new(8 * pfd as u32 + 7, 1)
+}
+
+/// Set the PFD fractional divider
+///
+/// The PFD is gated while the divider changes, then ungated.
+/// `fraction` should be between [12, 35]. The function will treat anything
+/// less than 12 as 12, and anything greater than 35 as 35.
+///
+/// # Safety
+///
+/// Unsynchronized read-modify-write of `reg`. Caller must ensure that `reg` is valid.
+pub unsafe fn set_fraction(reg: *mut u32, pfd: PFD, fraction: u32) {
+    clkgate(pfd).modify(reg, 1);
+    frac(pfd).modify(reg, fraction.clamp(FRAC_MIN, FRAC_MAX));
+    clkgate(pfd).modify(reg, 0);
+}
+
+/// Returns the PFD fractional divider
+///
+/// # Safety
+///
+/// Caller must ensure that `reg` is valid.
+pub unsafe fn fraction(reg: *const u32, pfd: PFD) -> u32 {
+    frac(pfd).read(reg)
+}
+
+/// Gate (`true`) or ungate (`false`) the PFD output
+///
+/// # Safety
+///
+/// Unsynchronized read-modify-write of `reg`. Caller must ensure that `reg` is valid.
+pub unsafe fn set_gated(reg: *mut u32, pfd: PFD, gated: bool) {
+    clkgate(pfd).modify(reg, gated as u32);
+}
+
+/// Returns `true` if the PFD output is gated
+///
+/// # Safety
+///
+/// Caller must ensure that `reg` is valid.
+pub unsafe fn is_gated(reg: *const u32, pfd: PFD) -> bool {
+    clkgate(pfd).read(reg) != 0
+}
+
+/// Returns the PFD output frequency, given the PLL frequency `pll_hz`
+///
+/// Returns 0 if the PFD is gated.
+///
+/// # Safety
+///
+/// Caller must ensure that `reg` is valid.
+pub unsafe fn frequency(reg: *const u32, pfd: PFD, pll_hz: u32) -> u32 {
+    if is_gated(reg, pfd) {
+        0
+    } else {
+        compute_frequency(pll_hz, fraction(reg, pfd))
+    }
+}
+
+/// Compute the PFD output frequency, given the PLL frequency and the fractional divider
+#[inline(always)]
+pub fn compute_frequency(pll_hz: u32, fraction: u32) -> u32 {
+    (pll_hz as u64 * 18 / fraction.max(1) as u64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fraction, frequency, is_gated, set_fraction, set_gated, PFD};
+
+    #[test]
+    fn pfd_fraction() {
+        let mut reg: u32 = 0;
+        unsafe {
+            set_fraction(&mut reg, PFD::PFD2, 24);
+            assert_eq!(reg, 24 << 16);
+            assert_eq!(fraction(&reg, PFD::PFD2), 24);
+            assert_eq!(frequency(&reg, PFD::PFD2, 528_000_000), 396_000_000);
+        }
+    }
+
+    #[test]
+    fn pfd_fraction_bounds() {
+        let mut reg: u32 = 0;
+        unsafe {
+            set_fraction(&mut reg, PFD::PFD0, 0);
+            assert_eq!(fraction(&reg, PFD::PFD0), 12);
+            set_fraction(&mut reg, PFD::PFD3, 36);
+            assert_eq!(fraction(&reg, PFD::PFD3), 35);
+            assert_eq!(fraction(&reg, PFD::PFD0), 12);
+        }
+    }
+
+    #[test]
+    fn pfd_gate() {
+        let mut reg: u32 = 0;
+        unsafe {
+            set_fraction(&mut reg, PFD::PFD1, 16);
+            set_gated(&mut reg, PFD::PFD1, true);
+            assert!(is_gated(&reg, PFD::PFD1));
+            assert!(!is_gated(&reg, PFD::PFD0));
+            assert_eq!(reg, 1 << 15 | 16 << 8);
+            assert_eq!(frequency(&reg, PFD::PFD1, 528_000_000), 0);
+            set_gated(&mut reg, PFD::PFD1, false);
+            assert_eq!(frequency(&reg, PFD::PFD1, 528_000_000), 594_000_000);
+        }
+    }
+}
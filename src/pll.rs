@@ -0,0 +1,237 @@
+//! PLL lock status
+//!
+//! Each analog PLL reports when it's locked to its reference. Use [`is_locked`]
+//! to query a PLL's lock status, and [`wait_lock`] to wait for a PLL to lock,
+//! without risking an infinite loop:
+//!
+//! ```no_run
+//! use imxrt_ccm::pll::{self, Pll};
+//!
+//! if pll::wait_lock(Pll::Usb1, 100_000).is_err() {
+//!     // PLL3 never locked; fall back to the oscillator...
+//! }
+//! ```
+
+use crate::register::Field;
+
+pub use crate::pfd::PFD;
+
+const LOCK: Field = Field::new(31, 1);
+const POWERDOWN: Field = Field::new(12, 1);
+const POWER: Field = Field::new(12, 1);
+
+/// An analog PLL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pll {
+    /// The ARM PLL, PLL1
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    Arm,
+    /// The system PLL, PLL2
+    Sys,
+    /// The USB1 PLL, PLL3
+    Usb1,
+    /// The audio PLL, PLL4
+    Audio,
+    /// The video PLL, PLL5
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    Video,
+    /// The ENET PLL, PLL6
+    Enet,
+    /// The USB2 PLL, PLL7
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    Usb2,
+}
+
+impl Pll {
+    /// All PLLs available on this chip
+    pub(crate) const ALL: &'static [Pll] = &[
+        #[cfg(feature = "imxrt1060")]
+        Pll::Arm,
+        Pll::Sys,
+        Pll::Usb1,
+        Pll::Audio,
+        #[cfg(feature = "imxrt1060")]
+        Pll::Video,
+        Pll::Enet,
+        #[cfg(feature = "imxrt1060")]
+        Pll::Usb2,
+    ];
+
+    /// Returns the bit that represents this PLL in a [`PllUsage`]
+    const fn bit(self) -> u8 {
+        1 << match self {
+            #[cfg(feature = "imxrt1060")]
+            Pll::Arm => 0,
+            Pll::Sys => 1,
+            Pll::Usb1 => 2,
+            Pll::Audio => 3,
+            #[cfg(feature = "imxrt1060")]
+            Pll::Video => 4,
+            Pll::Enet => 5,
+            #[cfg(feature = "imxrt1060")]
+            Pll::Usb2 => 6,
+        }
+    }
+
+    /// Returns the address of the PLL's PFD register, if the PLL has PFDs
+    pub(crate) const fn pfd_register(self) -> Option<*mut u32> {
+        match self {
+            Pll::Sys => Some(0x400D_8100 as _),
+            Pll::Usb1 => Some(0x400D_80F0 as _),
+            _ => None,
+        }
+    }
+
+    /// Power down the PLL
+    ///
+    /// # Safety
+    ///
+    /// Modifies global, mutable memory that's owned by the CCM.
+    pub(crate) unsafe fn power_down(self) {
+        match self {
+            // USB PLLs have an active-high POWER bit
+            Pll::Usb1 => POWER.modify(self.register(), 0),
+            #[cfg(feature = "imxrt1060")]
+            Pll::Usb2 => POWER.modify(self.register(), 0),
+            _ => POWERDOWN.modify(self.register(), 1),
+        }
+    }
+
+    /// Returns the address of the PLL's control register
+    const fn register(self) -> *mut u32 {
+        let address: usize = match self {
+            #[cfg(feature = "imxrt1060")]
+            Pll::Arm => 0x400D_8000,
+            Pll::Sys => 0x400D_8030,
+            Pll::Usb1 => 0x400D_8010,
+            Pll::Audio => 0x400D_8070,
+            #[cfg(feature = "imxrt1060")]
+            Pll::Video => 0x400D_80A0,
+            Pll::Enet => 0x400D_80E0,
+            #[cfg(feature = "imxrt1060")]
+            Pll::Usb2 => 0x400D_8020,
+        };
+        address as _
+    }
+}
+
+/// Indicates that a PLL did not lock in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockTimeout {
+    /// The PLL that did not lock
+    pub pll: Pll,
+}
+
+/// Describes the PLLs and PFDs that are in use
+///
+/// A PLL or PFD is in use if a clock root multiplexer selects it, even if the
+/// clock root is not running. A PLL is also in use if any of its PFDs are in
+/// use, or if one of its dedicated outputs, like the USB PHY clocks or the ENET
+/// references, is enabled. The CLKO1 and CLKO2 clock outputs are not
+/// considered.
+///
+/// Use [`usage`] to compute the current usage.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PllUsage {
+    plls: u8,
+    /// PLL2 PFDs, then PLL3 PFDs
+    pfds: [u8; 2],
+}
+
+impl PllUsage {
+    /// Returns `true` if the PLL is in use
+    pub fn is_used(&self, pll: Pll) -> bool {
+        self.plls & pll.bit() != 0
+    }
+
+    /// Returns `true` if the PLL's PFD is in use
+    ///
+    /// Returns `false` if the PLL does not have PFDs.
+    pub fn is_pfd_used(&self, pll: Pll, pfd: PFD) -> bool {
+        match Self::pfd_index(pll) {
+            Some(idx) => self.pfds[idx] & (1 << pfd as u8) != 0,
+            None => false,
+        }
+    }
+
+    pub(crate) fn use_pll(&mut self, pll: Pll) {
+        self.plls |= pll.bit();
+    }
+
+    pub(crate) fn use_pfd(&mut self, pll: Pll, pfd: PFD) {
+        if let Some(idx) = Self::pfd_index(pll) {
+            self.pfds[idx] |= 1 << pfd as u8;
+            self.use_pll(pll);
+        }
+    }
+
+    fn pfd_index(pll: Pll) -> Option<usize> {
+        match pll {
+            Pll::Sys => Some(0),
+            Pll::Usb1 => Some(1),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the PLLs and PFDs that are in use
+///
+/// See [`PllUsage`] for more information.
+#[inline(always)]
+pub fn usage() -> PllUsage {
+    crate::usage::usage()
+}
+
+/// Returns `true` if the PLL is locked
+#[inline(always)]
+pub fn is_locked(pll: Pll) -> bool {
+    // Safety: atomic read of a valid register
+    unsafe { LOCK.read(pll.register()) != 0 }
+}
+
+/// Wait for the PLL to lock
+///
+/// `wait_lock` polls the PLL lock status up to `max_iterations` times. If the
+/// PLL is not locked after `max_iterations` polls, `wait_lock` returns
+/// [`LockTimeout`].
+#[inline(always)]
+pub fn wait_lock(pll: Pll, max_iterations: u32) -> Result<(), LockTimeout> {
+    // Safety: reads of a valid register
+    unsafe { wait_lock_(pll.register(), max_iterations) }.map_err(|_| LockTimeout { pll })
+}
+
+/// Wait forever for the PLL to lock
+#[inline(always)]
+pub(crate) fn spin_lock(pll: Pll) {
+    while !is_locked(pll) {}
+}
+
+unsafe fn wait_lock_(reg: *const u32, max_iterations: u32) -> Result<(), ()> {
+    for _ in 0..max_iterations {
+        if LOCK.read(reg) != 0 {
+            return Ok(());
+        }
+    }
+    Err(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::wait_lock_;
+
+    #[test]
+    fn pll_locked() {
+        let reg: u32 = 1 << 31;
+        assert_eq!(unsafe { wait_lock_(&reg, 1) }, Ok(()));
+    }
+
+    #[test]
+    fn pll_lock_timeout() {
+        let reg: u32 = 0x2000;
+        assert_eq!(unsafe { wait_lock_(&reg, 10) }, Err(()));
+        assert_eq!(unsafe { wait_lock_(&reg, 0) }, Err(()));
+    }
+}
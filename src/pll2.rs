@@ -0,0 +1,353 @@
+//! System PLL (PLL2) control
+//!
+//! PLL2, also known as the "system PLL" or the "528 PLL," typically runs at
+//! 528MHz. PLL2 has four phase fractional dividers (PFD). Each PFD output is
+//!
+//! ```text
+//! PLL2 * 18 / FRAC
+//! ```
+//!
+//! where `FRAC` is between [12, 35]. Clock roots, like the SPI clock and the ARM
+//! clock, may select PLL2 or a PLL2 PFD as their source.
+//!
+//! PLL2 supports spread spectrum modulation. See [`SpreadSpectrum`] for more
+//! information.
+//!
+//! The module does not power or re-lock PLL2. It assumes that PLL2 was enabled
+//! by the boot ROM.
+//!
+//! # References
+//!
+//! i.MX RT 1060 reference manual
+//! - Chapter 14: Clock Control Module (CCM)
+//!   - CCM Analog Memory Map/Register Definition
+
+use crate::{pfd, register::Field, xtalosc::reference_frequency};
+
+pub use crate::pfd::PFD;
+
+const CCM_ANALOG_PLL_SYS: *mut u32 = 0x400D_8030 as _;
+const CCM_ANALOG_PLL_SYS_SS: *mut u32 = 0x400D_8040 as _;
+const CCM_ANALOG_PLL_SYS_NUM: *mut u32 = 0x400D_8050 as _;
+const CCM_ANALOG_PLL_SYS_DENOM: *mut u32 = 0x400D_8060 as _;
+const CCM_ANALOG_PFD_528: *mut u32 = 0x400D_8100 as _;
+
+const DIV_SELECT: Field = Field::new(0, 1);
+const POWERDOWN: Field = Field::new(12, 1);
+const ENABLE: Field = Field::new(13, 1);
+const BYPASS: Field = Field::new(16, 1);
+const MFN: Field = Field::new(0, 0x3FFF_FFFF);
+const SS_STEP: Field = Field::new(0, 0x7FFF);
+const SS_ENABLE: Field = Field::new(15, 1);
+const SS_STOP: Field = Field::new(16, 0xFFFF);
+
+/// PLL2 spread spectrum settings
+///
+/// Spread spectrum modulation sweeps the PLL2 frequency down from its nominal
+/// frequency, then back up. Given the PLL2 denominator `DENOM`,
+///
+/// ```text
+/// range      = OSC * stop / DENOM
+/// step       = OSC * step / DENOM
+/// modulation = OSC * step / (2 * stop)
+/// ```
+///
+/// where `OSC` is the 24MHz crystal oscillator. Use [`parameters`](SpreadSpectrum::parameters)
+/// to compute these values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpreadSpectrum {
+    /// Frequency change step, 15 bits
+    pub step: u16,
+    /// Frequency change limit, 16 bits
+    pub stop: u16,
+}
+
+/// The modulation produced by a [`SpreadSpectrum`] setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpreadSpectrumParameters {
+    /// The total frequency change, in Hz
+    pub range_hz: u32,
+    /// The frequency change per step, in Hz
+    pub step_hz: u32,
+    /// The modulation frequency, in Hz
+    pub modulation_hz: u32,
+}
+
+impl SpreadSpectrum {
+    /// Compute the modulation parameters, given the PLL2 denominator
+    ///
+    /// Returns 0 for any parameter that would divide by zero.
+    pub fn parameters(&self, denominator: u32) -> SpreadSpectrumParameters {
+        let osc = reference_frequency() as u64;
+        let (step, stop) = (self.step as u64, self.stop as u64);
+        let scale = |value: u64| (osc * value).checked_div(denominator as u64).unwrap_or(0) as u32;
+        SpreadSpectrumParameters {
+            range_hz: scale(stop),
+            step_hz: scale(step),
+            modulation_hz: (osc * step).checked_div(2 * stop).unwrap_or(0) as u32,
+        }
+    }
+}
+
+/// The system PLL, PLL2
+///
+/// Use `PLL2` to control and query the PLL2 phase fractional dividers.
+pub struct PLL2(());
+
+impl PLL2 {
+    pub(crate) const fn new() -> Self {
+        PLL2(())
+    }
+
+    /// Returns the PLL2 frequency
+    ///
+    /// Returns 0 if PLL2 is powered down, or if its output is disabled. If PLL2 is
+    /// bypassed, returns the crystal oscillator frequency.
+    #[inline(always)]
+    pub fn frequency(&self) -> u32 {
+        frequency()
+    }
+
+    /// Set the fractional divider for a PLL2 PFD
+    ///
+    /// The PFD is gated while the divider changes. When this method returns, the
+    /// PFD is ungated.
+    ///
+    /// `fraction` should be between [12, 35]. The function will treat anything
+    /// less than 12 as 12, and anything greater than 35 as 35.
+    ///
+    /// Changing a PFD frequency affects all clock roots that use the PFD. Make sure
+    /// that those roots are not in use, or can tolerate the change.
+    #[inline(always)]
+    pub fn set_pfd_fraction(&mut self, pfd: PFD, fraction: u32) {
+        // Safety: we own the PFD_528 register
+        unsafe { set_pfd_fraction(pfd, fraction) }
+    }
+
+    /// Returns the fractional divider for a PLL2 PFD
+    #[inline(always)]
+    pub fn pfd_fraction(&self, pfd: PFD) -> u32 {
+        // Safety: atomic read of a valid register
+        unsafe { pfd::fraction(CCM_ANALOG_PFD_528, pfd) }
+    }
+
+    /// Gate (`true`) or ungate (`false`) a PLL2 PFD
+    #[inline(always)]
+    pub fn set_pfd_gated(&mut self, pfd: PFD, gated: bool) {
+        // Safety: we own the PFD_528 register
+        unsafe { set_pfd_gated(pfd, gated) }
+    }
+
+    /// Returns `true` if the PLL2 PFD is gated
+    #[inline(always)]
+    pub fn pfd_is_gated(&self, pfd: PFD) -> bool {
+        // Safety: atomic read of a valid register
+        unsafe { pfd::is_gated(CCM_ANALOG_PFD_528, pfd) }
+    }
+
+    /// Returns the PLL2 PFD frequency
+    ///
+    /// Returns 0 if the PFD is gated.
+    #[inline(always)]
+    pub fn pfd_frequency(&self, pfd: PFD) -> u32 {
+        pfd_frequency(pfd)
+    }
+
+    /// Enable spread spectrum modulation with the provided settings, or disable
+    /// modulation with `None`
+    #[inline(always)]
+    pub fn set_spread_spectrum(&mut self, ss: Option<SpreadSpectrum>) {
+        // Safety: we own the PLL_SYS_SS register
+        unsafe { set_spread_spectrum(ss) }
+    }
+
+    /// Returns the spread spectrum settings, or `None` if modulation is disabled
+    #[inline(always)]
+    pub fn spread_spectrum(&self) -> Option<SpreadSpectrum> {
+        // Safety: atomic read of a valid register
+        unsafe { spread_spectrum_(CCM_ANALOG_PLL_SYS_SS) }
+    }
+
+    /// Returns the active spread spectrum modulation parameters, or `None` if
+    /// modulation is disabled
+    ///
+    /// The parameters account for the current PLL2 denominator.
+    #[inline(always)]
+    pub fn spread_spectrum_parameters(&self) -> Option<SpreadSpectrumParameters> {
+        // Safety: atomic read of a valid register
+        let denominator = unsafe { MFN.read(CCM_ANALOG_PLL_SYS_DENOM) };
+        self.spread_spectrum().map(|ss| ss.parameters(denominator))
+    }
+}
+
+/// Enable spread spectrum modulation with the provided settings, or disable
+/// modulation with `None`
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL2`] for a safer interface.
+#[inline(always)]
+pub unsafe fn set_spread_spectrum(ss: Option<SpreadSpectrum>) {
+    set_spread_spectrum_(CCM_ANALOG_PLL_SYS_SS, ss)
+}
+
+unsafe fn set_spread_spectrum_(pll_sys_ss: *mut u32, ss: Option<SpreadSpectrum>) {
+    match ss {
+        Some(ss) => {
+            SS_STEP.modify(pll_sys_ss, ss.step as u32);
+            SS_STOP.modify(pll_sys_ss, ss.stop as u32);
+            SS_ENABLE.modify(pll_sys_ss, 1);
+        }
+        None => SS_ENABLE.modify(pll_sys_ss, 0),
+    }
+}
+
+unsafe fn spread_spectrum_(pll_sys_ss: *const u32) -> Option<SpreadSpectrum> {
+    if SS_ENABLE.read(pll_sys_ss) == 0 {
+        None
+    } else {
+        Some(SpreadSpectrum {
+            step: SS_STEP.read(pll_sys_ss) as u16,
+            stop: SS_STOP.read(pll_sys_ss) as u16,
+        })
+    }
+}
+
+/// Set the fractional divider for a PLL2 PFD
+///
+/// See [`PLL2::set_pfd_fraction`] for more information.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL2`] for a safer interface.
+#[inline(always)]
+pub unsafe fn set_pfd_fraction(pfd: PFD, fraction: u32) {
+    pfd::set_fraction(CCM_ANALOG_PFD_528, pfd, fraction)
+}
+
+/// Gate (`true`) or ungate (`false`) a PLL2 PFD
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL2`] for a safer interface.
+#[inline(always)]
+pub unsafe fn set_pfd_gated(pfd: PFD, gated: bool) {
+    pfd::set_gated(CCM_ANALOG_PFD_528, pfd, gated)
+}
+
+/// Returns the PLL2 PFD frequency
+///
+/// Returns 0 if the PFD is gated.
+#[inline(always)]
+pub fn pfd_frequency(pfd: PFD) -> u32 {
+    // Safety: atomic read of a valid register
+    unsafe { pfd::frequency(CCM_ANALOG_PFD_528, pfd, frequency()) }
+}
+
+/// Returns the PLL2 frequency
+///
+/// Returns 0 if PLL2 is powered down, or if its output is disabled. If PLL2 is
+/// bypassed, returns the crystal oscillator frequency.
+#[inline(always)]
+pub fn frequency() -> u32 {
+    // Safety: reads of valid registers
+    unsafe {
+        frequency_(
+            CCM_ANALOG_PLL_SYS,
+            CCM_ANALOG_PLL_SYS_NUM,
+            CCM_ANALOG_PLL_SYS_DENOM,
+        )
+    }
+}
+
+unsafe fn frequency_(pll_sys: *const u32, num: *const u32, denom: *const u32) -> u32 {
+    if POWERDOWN.read(pll_sys) != 0 || ENABLE.read(pll_sys) == 0 {
+        return 0;
+    }
+    let osc = reference_frequency();
+    if BYPASS.read(pll_sys) != 0 {
+        return osc;
+    }
+    let div_select = 20 + 2 * DIV_SELECT.read(pll_sys);
+    let (num, denom) = (MFN.read(num) as u64, MFN.read(denom) as u64);
+    let fraction = (osc as u64 * num).checked_div(denom).unwrap_or(0);
+    (osc as u64 * div_select as u64 + fraction) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        frequency_, set_spread_spectrum_, spread_spectrum_, SpreadSpectrum,
+        SpreadSpectrumParameters,
+    };
+
+    const ENABLE: u32 = 1 << 13;
+
+    #[test]
+    fn pll2_528() {
+        let (pll_sys, num, denom) = (ENABLE | 1, 0, 0x12);
+        assert_eq!(unsafe { frequency_(&pll_sys, &num, &denom) }, 528_000_000);
+    }
+
+    #[test]
+    fn pll2_480() {
+        let (pll_sys, num, denom) = (ENABLE, 0, 0x12);
+        assert_eq!(unsafe { frequency_(&pll_sys, &num, &denom) }, 480_000_000);
+    }
+
+    #[test]
+    fn pll2_fractional() {
+        let (pll_sys, num, denom) = (ENABLE | 1, 1, 2);
+        assert_eq!(unsafe { frequency_(&pll_sys, &num, &denom) }, 540_000_000);
+    }
+
+    #[test]
+    fn pll2_off() {
+        let (num, denom) = (0, 0x12);
+        let powered_down = ENABLE | 1 << 12 | 1;
+        assert_eq!(unsafe { frequency_(&powered_down, &num, &denom) }, 0);
+        let disabled = 1;
+        assert_eq!(unsafe { frequency_(&disabled, &num, &denom) }, 0);
+        let bypassed = ENABLE | 1 << 16 | 1;
+        assert_eq!(
+            unsafe { frequency_(&bypassed, &num, &denom) },
+            crate::OSCILLATOR_FREQUENCY_HZ
+        );
+    }
+
+    #[test]
+    fn pll2_spread_spectrum() {
+        let mut pll_sys_ss: u32 = 0;
+        let ss = SpreadSpectrum { step: 1, stop: 250 };
+        unsafe {
+            assert_eq!(spread_spectrum_(&pll_sys_ss), None);
+            set_spread_spectrum_(&mut pll_sys_ss, Some(ss));
+            assert_eq!(pll_sys_ss, 250 << 16 | 1 << 15 | 1);
+            assert_eq!(spread_spectrum_(&pll_sys_ss), Some(ss));
+            set_spread_spectrum_(&mut pll_sys_ss, None);
+            assert_eq!(spread_spectrum_(&pll_sys_ss), None);
+        }
+    }
+
+    #[test]
+    fn pll2_spread_spectrum_parameters() {
+        let ss = SpreadSpectrum { step: 1, stop: 250 };
+        assert_eq!(
+            ss.parameters(1000),
+            SpreadSpectrumParameters {
+                range_hz: 6_000_000,
+                step_hz: 24_000,
+                modulation_hz: 48_000,
+            }
+        );
+        let zeros = SpreadSpectrumParameters {
+            range_hz: 0,
+            step_hz: 0,
+            modulation_hz: 0,
+        };
+        assert_eq!(SpreadSpectrum { step: 0, stop: 0 }.parameters(0), zeros);
+    }
+}
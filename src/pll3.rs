@@ -0,0 +1,346 @@
+//! USB1 PLL (PLL3) control
+//!
+//! PLL3, also known as the "USB1 PLL" or the "480 PLL," runs at 480MHz. Besides
+//! the USB1 PHY, PLL3 drives fixed-divided clocks that other clock roots select:
+//!
+//! - `pll3_80m` is PLL3 / 6, an option for the UART clock root.
+//! - `pll3_60m` is PLL3 / 8, an option for the I2C clock root.
+//! - `pll3_sw_clk` is PLL3, an option for the peripheral clock 2 path.
+//!
+//! PLL3 also has four phase fractional dividers (PFD). Each PFD output is
+//!
+//! ```text
+//! PLL3 * 18 / FRAC
+//! ```
+//!
+//! where `FRAC` is between [12, 35]. The SPI, FlexSPI, and trace clock roots may
+//! select a PLL3 PFD.
+//!
+//! # References
+//!
+//! i.MX RT 1060 reference manual
+//! - Chapter 14: Clock Control Module (CCM)
+//!   - CCM Analog Memory Map/Register Definition
+
+use crate::{
+    pfd,
+    pll::{self, LockTimeout, Pll},
+    register::{self, Field},
+    xtalosc::reference_frequency,
+};
+
+pub use crate::pfd::PFD;
+
+const CCM_ANALOG_PLL_USB1: *mut u32 = 0x400D_8010 as _;
+const CCM_ANALOG_PFD_480: *mut u32 = 0x400D_80F0 as _;
+
+const DIV_SELECT: Field = Field::new(1, 1);
+const EN_USB_CLKS: Field = Field::new(6, 1);
+const POWER: Field = Field::new(12, 1);
+const ENABLE: Field = Field::new(13, 1);
+const BYPASS_CLK_SRC: Field = Field::new(14, 0b11);
+const BYPASS: Field = Field::new(16, 1);
+const LOCK: Field = Field::new(31, 1);
+
+/// The USB1 PLL, PLL3
+///
+/// Use `PLL3` to power, enable, and bypass PLL3, and to control the PLL3
+/// phase fractional dividers.
+pub struct PLL3(());
+
+impl PLL3 {
+    pub(crate) const fn new() -> Self {
+        PLL3(())
+    }
+
+    /// Power up and enable PLL3 at 480MHz
+    ///
+    /// `enable` bypasses PLL3 while it locks. When `enable` returns, PLL3 is locked,
+    /// not bypassed, and the USB PHY clocks are enabled. If PLL3 is already enabled
+    /// and locked, `enable` doesn't restart PLL3, but it still removes the bypass,
+    /// and enables the USB PHY clocks.
+    #[inline(always)]
+    pub fn enable(&mut self) {
+        // Safety: we own the PLL_USB1 register
+        unsafe { enable() }
+    }
+
+    /// Power up and enable PLL3 at 480MHz, waiting at most `max_iterations`
+    /// for PLL3 to lock
+    ///
+    /// If PLL3 does not lock in time, `try_enable` returns [`LockTimeout`], and
+    /// PLL3 remains bypassed. Otherwise, `try_enable` behaves like
+    /// [`enable`](PLL3::enable).
+    #[inline(always)]
+    pub fn try_enable(&mut self, max_iterations: u32) -> Result<(), LockTimeout> {
+        // Safety: we own the PLL_USB1 register
+        unsafe { try_enable(max_iterations) }
+    }
+
+    /// Power down and disable PLL3
+    ///
+    /// Any clock root that uses PLL3, or one of its PFDs, will stop.
+    #[inline(always)]
+    pub fn disable(&mut self) {
+        // Safety: we own the PLL_USB1 register
+        unsafe { disable() }
+    }
+
+    /// Returns `true` if PLL3 is powered, enabled, and locked
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        is_enabled()
+    }
+
+    /// Bypass (`true`) or don't bypass (`false`) PLL3
+    ///
+    /// When bypassed, PLL3 outputs the crystal oscillator frequency.
+    #[inline(always)]
+    pub fn set_bypass(&mut self, bypass: bool) {
+        // Safety: we own the PLL_USB1 register
+        unsafe { BYPASS.modify(CCM_ANALOG_PLL_USB1, bypass as u32) }
+    }
+
+    /// Enable (`true`) or disable (`false`) the PLL3 clock outputs to the USB PHY
+    #[inline(always)]
+    pub fn set_usb_clocks(&mut self, enable: bool) {
+        // Safety: we own the PLL_USB1 register
+        unsafe { EN_USB_CLKS.modify(CCM_ANALOG_PLL_USB1, enable as u32) }
+    }
+
+    /// Returns the PLL3 frequency
+    ///
+    /// Returns 0 if PLL3 is powered down, or if its output is disabled. If PLL3
+    /// is bypassed, returns the crystal oscillator frequency.
+    #[inline(always)]
+    pub fn frequency(&self) -> u32 {
+        frequency()
+    }
+
+    /// Set the fractional divider for a PLL3 PFD
+    ///
+    /// The PFD is gated while the divider changes. When this method returns, the
+    /// PFD is ungated.
+    ///
+    /// `fraction` should be between [12, 35]. The function will treat anything
+    /// less than 12 as 12, and anything greater than 35 as 35.
+    ///
+    /// Changing a PFD frequency affects all clock roots that use the PFD. Make sure
+    /// that those roots are not in use, or can tolerate the change.
+    #[inline(always)]
+    pub fn set_pfd_fraction(&mut self, pfd: PFD, fraction: u32) {
+        // Safety: we own the PFD_480 register
+        unsafe { set_pfd_fraction(pfd, fraction) }
+    }
+
+    /// Returns the fractional divider for a PLL3 PFD
+    #[inline(always)]
+    pub fn pfd_fraction(&self, pfd: PFD) -> u32 {
+        // Safety: atomic read of a valid register
+        unsafe { pfd::fraction(CCM_ANALOG_PFD_480, pfd) }
+    }
+
+    /// Gate (`true`) or ungate (`false`) a PLL3 PFD
+    #[inline(always)]
+    pub fn set_pfd_gated(&mut self, pfd: PFD, gated: bool) {
+        // Safety: we own the PFD_480 register
+        unsafe { set_pfd_gated(pfd, gated) }
+    }
+
+    /// Returns `true` if the PLL3 PFD is gated
+    #[inline(always)]
+    pub fn pfd_is_gated(&self, pfd: PFD) -> bool {
+        // Safety: atomic read of a valid register
+        unsafe { pfd::is_gated(CCM_ANALOG_PFD_480, pfd) }
+    }
+
+    /// Returns the PLL3 PFD frequency
+    ///
+    /// Returns 0 if the PFD is gated.
+    #[inline(always)]
+    pub fn pfd_frequency(&self, pfd: PFD) -> u32 {
+        pfd_frequency(pfd)
+    }
+}
+
+/// Set the fractional divider for a PLL3 PFD
+///
+/// See [`PLL3::set_pfd_fraction`] for more information.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL3`] for a safer interface.
+#[inline(always)]
+pub unsafe fn set_pfd_fraction(pfd: PFD, fraction: u32) {
+    pfd::set_fraction(CCM_ANALOG_PFD_480, pfd, fraction)
+}
+
+/// Gate (`true`) or ungate (`false`) a PLL3 PFD
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL3`] for a safer interface.
+#[inline(always)]
+pub unsafe fn set_pfd_gated(pfd: PFD, gated: bool) {
+    pfd::set_gated(CCM_ANALOG_PFD_480, pfd, gated)
+}
+
+/// Returns the PLL3 PFD frequency
+///
+/// Returns 0 if the PFD is gated.
+#[inline(always)]
+pub fn pfd_frequency(pfd: PFD) -> u32 {
+    // Safety: atomic read of a valid register
+    unsafe { pfd::frequency(CCM_ANALOG_PFD_480, pfd, frequency()) }
+}
+
+/// Power up and enable PLL3 at 480MHz
+///
+/// See [`PLL3::enable`] for more information.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL3`] for a safer interface.
+pub unsafe fn enable() {
+    // Never times out
+    let _ = enable_usb_pll(CCM_ANALOG_PLL_USB1, Pll::Usb1, None);
+}
+
+/// Power up and enable PLL3 at 480MHz, waiting at most `max_iterations`
+/// for PLL3 to lock
+///
+/// See [`PLL3::try_enable`] for more information.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL3`] for a safer interface.
+pub unsafe fn try_enable(max_iterations: u32) -> Result<(), LockTimeout> {
+    enable_usb_pll(CCM_ANALOG_PLL_USB1, Pll::Usb1, Some(max_iterations))
+}
+
+/// Power up and enable a USB PLL at 480MHz, waiting at most `max_iterations`
+/// for the PLL to lock, or forever if `None`
+///
+/// The USB2 PLL, PLL7, has the same register layout as PLL3.
+pub(crate) unsafe fn enable_usb_pll(
+    pll_usb: *mut u32,
+    pll: Pll,
+    max_iterations: Option<u32>,
+) -> Result<(), LockTimeout> {
+    if !is_enabled_(pll_usb) {
+        enable_(pll_usb);
+        match max_iterations {
+            Some(max_iterations) => pll::wait_lock(pll, max_iterations)?,
+            None => pll::spin_lock(pll),
+        }
+    }
+    // A bootloader may leave the PLL locked, but bypassed, or without its
+    // USB clocks
+    EN_USB_CLKS.modify(pll_usb, 1);
+    BYPASS.modify(pll_usb, 0);
+    Ok(())
+}
+
+unsafe fn enable_(pll_usb1: *mut u32) {
+    BYPASS.modify(pll_usb1, 1);
+    DIV_SELECT.modify(pll_usb1, 0); // 480MHz
+    BYPASS_CLK_SRC.modify(pll_usb1, 0); // 24MHz oscillator
+    POWER.modify(pll_usb1, 1);
+    ENABLE.modify(pll_usb1, 1);
+    EN_USB_CLKS.modify(pll_usb1, 1);
+}
+
+/// Power down and disable PLL3
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL3`] for a safer interface.
+pub unsafe fn disable() {
+    register::write(CCM_ANALOG_PLL_USB1, u32::MAX, 0);
+}
+
+/// Returns `true` if PLL3 is powered, enabled, and locked
+#[inline(always)]
+pub fn is_enabled() -> bool {
+    // Safety: atomic read of a valid register
+    unsafe { is_enabled_(CCM_ANALOG_PLL_USB1) }
+}
+
+unsafe fn is_enabled_(pll_usb1: *const u32) -> bool {
+    POWER.read(pll_usb1) != 0 && ENABLE.read(pll_usb1) != 0 && LOCK.read(pll_usb1) != 0
+}
+
+/// Returns the PLL3 frequency
+///
+/// Returns 0 if PLL3 is powered down, or if its output is disabled. If PLL3
+/// is bypassed, returns the crystal oscillator frequency.
+#[inline(always)]
+pub fn frequency() -> u32 {
+    // Safety: atomic read of a valid register
+    unsafe { frequency_(CCM_ANALOG_PLL_USB1) }
+}
+
+unsafe fn frequency_(pll_usb1: *const u32) -> u32 {
+    if POWER.read(pll_usb1) == 0 || ENABLE.read(pll_usb1) == 0 {
+        0
+    } else if BYPASS.read(pll_usb1) != 0 {
+        reference_frequency()
+    } else {
+        reference_frequency() * (20 + 2 * DIV_SELECT.read(pll_usb1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        enable_, enable_usb_pll, frequency_, is_enabled_, BYPASS, ENABLE, EN_USB_CLKS, LOCK, POWER,
+    };
+    use crate::pll::Pll;
+
+    #[test]
+    fn pll3_enable() {
+        let mut pll_usb1: u32 = 0;
+        unsafe {
+            enable_(&mut pll_usb1);
+            assert_eq!(frequency_(&pll_usb1), crate::OSCILLATOR_FREQUENCY_HZ);
+            assert!(!is_enabled_(&pll_usb1));
+
+            LOCK.modify(&mut pll_usb1, 1);
+            BYPASS.modify(&mut pll_usb1, 0);
+            assert!(is_enabled_(&pll_usb1));
+            assert_eq!(frequency_(&pll_usb1), 480_000_000);
+        }
+        assert_eq!(pll_usb1, 0x8000_3040);
+    }
+
+    #[test]
+    fn pll3_disabled() {
+        let pll_usb1: u32 = 0;
+        assert_eq!(unsafe { frequency_(&pll_usb1) }, 0);
+    }
+
+    #[test]
+    fn pll3_enable_locked_bypassed() {
+        // A bootloader left PLL3 locked, but bypassed, without USB clocks
+        let mut pll_usb1: u32 = 0;
+        unsafe {
+            POWER.modify(&mut pll_usb1, 1);
+            ENABLE.modify(&mut pll_usb1, 1);
+            LOCK.modify(&mut pll_usb1, 1);
+            BYPASS.modify(&mut pll_usb1, 1);
+            assert!(is_enabled_(&pll_usb1));
+            assert_eq!(frequency_(&pll_usb1), crate::OSCILLATOR_FREQUENCY_HZ);
+
+            enable_usb_pll(&mut pll_usb1, Pll::Usb1, Some(0)).unwrap();
+            assert_eq!(BYPASS.read(&pll_usb1), 0);
+            assert_eq!(EN_USB_CLKS.read(&pll_usb1), 1);
+            assert_eq!(frequency_(&pll_usb1), 480_000_000);
+        }
+        assert_eq!(pll_usb1, 0x8000_3040);
+    }
+}
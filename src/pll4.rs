@@ -0,0 +1,318 @@
+//! Audio PLL (PLL4) control
+//!
+//! PLL4, also known as the "audio PLL," is a fractional PLL that typically
+//! sources the SAI master clocks. PLL4 shares its structure with the video PLL;
+//! the output frequency is
+//!
+//! ```text
+//! OSC * (DIV_SELECT + NUM / DENOM) / POST_DIVIDER
+//! ```
+//!
+//! where `OSC` is the 24MHz crystal oscillator, and `DIV_SELECT` is between
+//! [27, 54]. The post divider combines the PLL4 `POST_DIV_SELECT` field and the
+//! `AUDIO_DIV` fields in CCM_ANALOG_MISC2.
+//!
+//! Audio codecs expect a master clock that's a multiple of the sample rate. Use
+//! a [`Preset`] to select a verified configuration for the 48kHz or 44.1kHz
+//! sample rate families, then divide the PLL4 output with the SAI clock root
+//! dividers:
+//!
+//! ```no_run
+//! use imxrt_ccm::pll4::Preset;
+//! # struct MyClocks;
+//! # impl imxrt_ccm::Clocks for MyClocks {
+//! #   type I2C = ();
+//! #   type SPI = ();
+//! #   type UART = ();
+//! #   type GPT = ();
+//! #   type PIT = ();
+//! # }
+//! # let mut ccm = unsafe { imxrt_ccm::CCM::<MyClocks>::new() };
+//!
+//! // 786.432MHz = 32 * 24.576MHz, for 48kHz, 96kHz, and 192kHz streams
+//! ccm.pll4_mut().configure(&Preset::Mhz786_432.config());
+//! assert_eq!(ccm.pll4().frequency(), 786_432_000);
+//! ```
+//!
+//! Use [`Config::from_frequency`] to compute any other configuration.
+//!
+//! # References
+//!
+//! i.MX RT 1060 reference manual
+//! - Chapter 14: Clock Control Module (CCM)
+//!   - CCM Analog Memory Map/Register Definition
+
+use crate::{
+    fracpll::{Registers, BYPASS},
+    pll::{self, LockTimeout, Pll},
+    register::Field,
+};
+
+pub use crate::fracpll::{Config, PostDivider, DIV_SELECT_MAX, DIV_SELECT_MIN};
+
+const CCM_ANALOG_PLL_AUDIO: *mut u32 = 0x400D_8070 as _;
+const CCM_ANALOG_PLL_AUDIO_NUM: *mut u32 = 0x400D_8080 as _;
+const CCM_ANALOG_PLL_AUDIO_DENOM: *mut u32 = 0x400D_8090 as _;
+const CCM_ANALOG_MISC2: *mut u32 = 0x400D_8170 as _;
+
+const fn registers(
+    pll_audio: *mut u32,
+    num: *mut u32,
+    denom: *mut u32,
+    misc2: *mut u32,
+) -> Registers {
+    Registers {
+        pll: pll_audio,
+        num,
+        denom,
+        misc2,
+        misc2_lsb: Field::new(15, 1),
+        misc2_msb: Field::new(23, 1),
+    }
+}
+
+const PLL_AUDIO: Registers = registers(
+    CCM_ANALOG_PLL_AUDIO,
+    CCM_ANALOG_PLL_AUDIO_NUM,
+    CCM_ANALOG_PLL_AUDIO_DENOM,
+    CCM_ANALOG_MISC2,
+);
+
+/// Verified PLL4 configurations for audio master clocks
+///
+/// The 48kHz family runs the VCO at 786.432MHz, which is 32 * 24.576MHz. The
+/// 44.1kHz family runs the VCO at 722.5344MHz, which is 32 * 22.5792MHz. Each
+/// preset's frequency is an exact multiple of its family's base master clock,
+/// so there's no fractional error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// 786.432MHz, 32 * 24.576MHz
+    Mhz786_432,
+    /// 393.216MHz, 16 * 24.576MHz
+    Mhz393_216,
+    /// 196.608MHz, 8 * 24.576MHz
+    Mhz196_608,
+    /// 98.304MHz, 4 * 24.576MHz
+    Mhz98_304,
+    /// 49.152MHz, 2 * 24.576MHz
+    Mhz49_152,
+    /// 722.5344MHz, 32 * 22.5792MHz
+    Mhz722_5344,
+    /// 361.2672MHz, 16 * 22.5792MHz
+    Mhz361_2672,
+    /// 180.6336MHz, 8 * 22.5792MHz
+    Mhz180_6336,
+    /// 90.3168MHz, 4 * 22.5792MHz
+    Mhz90_3168,
+    /// 45.1584MHz, 2 * 22.5792MHz
+    Mhz45_1584,
+}
+
+impl Preset {
+    /// Returns the PLL4 configuration for this preset
+    pub const fn config(self) -> Config {
+        // 24MHz * (32 + 96/125) = 786.432MHz
+        const FAMILY_48K: Config = Config {
+            div_select: 32,
+            numerator: 96,
+            denominator: 125,
+            post_divider: PostDivider::Divide1,
+        };
+        // 24MHz * (30 + 66/625) = 722.5344MHz
+        const FAMILY_44K1: Config = Config {
+            div_select: 30,
+            numerator: 66,
+            denominator: 625,
+            post_divider: PostDivider::Divide1,
+        };
+        let (family, post_divider) = match self {
+            Preset::Mhz786_432 => (FAMILY_48K, PostDivider::Divide1),
+            Preset::Mhz393_216 => (FAMILY_48K, PostDivider::Divide2),
+            Preset::Mhz196_608 => (FAMILY_48K, PostDivider::Divide4),
+            Preset::Mhz98_304 => (FAMILY_48K, PostDivider::Divide8),
+            Preset::Mhz49_152 => (FAMILY_48K, PostDivider::Divide16),
+            Preset::Mhz722_5344 => (FAMILY_44K1, PostDivider::Divide1),
+            Preset::Mhz361_2672 => (FAMILY_44K1, PostDivider::Divide2),
+            Preset::Mhz180_6336 => (FAMILY_44K1, PostDivider::Divide4),
+            Preset::Mhz90_3168 => (FAMILY_44K1, PostDivider::Divide8),
+            Preset::Mhz45_1584 => (FAMILY_44K1, PostDivider::Divide16),
+        };
+        Config {
+            post_divider,
+            ..family
+        }
+    }
+
+    /// Returns the PLL4 output frequency for this preset
+    pub fn frequency(self) -> u32 {
+        self.config().frequency()
+    }
+}
+
+/// The audio PLL, PLL4
+///
+/// Use `PLL4` to configure, enable, and disable PLL4.
+pub struct PLL4(());
+
+impl PLL4 {
+    pub(crate) const fn new() -> Self {
+        PLL4(())
+    }
+
+    /// Configure and enable PLL4
+    ///
+    /// `configure` bypasses PLL4 while it locks. When `configure` returns, PLL4 is
+    /// locked and not bypassed.
+    ///
+    /// `div_select` is clamped between [27, 54].
+    #[inline(always)]
+    pub fn configure(&mut self, config: &Config) {
+        // Safety: we own the PLL_AUDIO registers, and the MISC2 audio divider
+        unsafe { configure(config) }
+    }
+
+    /// Configure and enable PLL4, waiting at most `max_iterations` for PLL4 to lock
+    ///
+    /// If PLL4 does not lock in time, `try_configure` returns [`LockTimeout`], and
+    /// PLL4 remains bypassed. Otherwise, `try_configure` behaves like
+    /// [`configure`](PLL4::configure).
+    #[inline(always)]
+    pub fn try_configure(
+        &mut self,
+        config: &Config,
+        max_iterations: u32,
+    ) -> Result<(), LockTimeout> {
+        // Safety: we own the PLL_AUDIO registers, and the MISC2 audio divider
+        unsafe { try_configure(config, max_iterations) }
+    }
+
+    /// Power down and disable PLL4
+    ///
+    /// Any clock root that uses PLL4 will stop.
+    #[inline(always)]
+    pub fn disable(&mut self) {
+        // Safety: we own the PLL_AUDIO register
+        unsafe { disable() }
+    }
+
+    /// Returns `true` if PLL4 is powered, enabled, and locked
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        is_enabled()
+    }
+
+    /// Bypass (`true`) or don't bypass (`false`) PLL4
+    ///
+    /// When bypassed, PLL4 outputs the crystal oscillator frequency.
+    #[inline(always)]
+    pub fn set_bypass(&mut self, bypass: bool) {
+        // Safety: we own the PLL_AUDIO register
+        unsafe { BYPASS.modify(CCM_ANALOG_PLL_AUDIO, bypass as u32) }
+    }
+
+    /// Returns the PLL4 frequency
+    ///
+    /// Returns 0 if PLL4 is powered down, or if its output is disabled. If PLL4
+    /// is bypassed, returns the crystal oscillator frequency.
+    #[inline(always)]
+    pub fn frequency(&self) -> u32 {
+        frequency()
+    }
+}
+
+/// Configure and enable PLL4
+///
+/// See [`PLL4::configure`] for more information.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL4`] for a safer interface.
+pub unsafe fn configure(config: &Config) {
+    PLL_AUDIO.configure(config);
+    pll::spin_lock(Pll::Audio);
+    BYPASS.modify(CCM_ANALOG_PLL_AUDIO, 0);
+}
+
+/// Configure and enable PLL4, waiting at most `max_iterations` for PLL4 to lock
+///
+/// See [`PLL4::try_configure`] for more information.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL4`] for a safer interface.
+pub unsafe fn try_configure(config: &Config, max_iterations: u32) -> Result<(), LockTimeout> {
+    PLL_AUDIO.configure(config);
+    pll::wait_lock(Pll::Audio, max_iterations)?;
+    BYPASS.modify(CCM_ANALOG_PLL_AUDIO, 0);
+    Ok(())
+}
+
+/// Power down and disable PLL4
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL4`] for a safer interface.
+pub unsafe fn disable() {
+    PLL_AUDIO.disable()
+}
+
+/// Returns `true` if PLL4 is powered, enabled, and locked
+#[inline(always)]
+pub fn is_enabled() -> bool {
+    // Safety: atomic read of a valid register
+    unsafe { PLL_AUDIO.is_enabled() }
+}
+
+/// Returns the PLL4 frequency
+///
+/// Returns 0 if PLL4 is powered down, or if its output is disabled. If PLL4
+/// is bypassed, returns the crystal oscillator frequency.
+#[inline(always)]
+pub fn frequency() -> u32 {
+    // Safety: reads of valid registers
+    unsafe { PLL_AUDIO.frequency() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{registers, Preset, BYPASS};
+
+    const PRESETS: [(Preset, u32); 10] = [
+        (Preset::Mhz786_432, 786_432_000),
+        (Preset::Mhz393_216, 393_216_000),
+        (Preset::Mhz196_608, 196_608_000),
+        (Preset::Mhz98_304, 98_304_000),
+        (Preset::Mhz49_152, 49_152_000),
+        (Preset::Mhz722_5344, 722_534_400),
+        (Preset::Mhz361_2672, 361_267_200),
+        (Preset::Mhz180_6336, 180_633_600),
+        (Preset::Mhz90_3168, 90_316_800),
+        (Preset::Mhz45_1584, 45_158_400),
+    ];
+
+    #[test]
+    fn pll4_presets() {
+        for &(preset, hz) in PRESETS.iter() {
+            assert_eq!(preset.frequency(), hz, "{:?}", preset);
+            // Master clocks divide evenly into 256 * fs
+            let fs = if hz % 48_000 == 0 { 48_000 } else { 44_100 };
+            assert_eq!(hz % (256 * fs), 0, "{:?}", preset);
+        }
+    }
+
+    #[test]
+    fn pll4_configure_preset() {
+        for &(preset, hz) in PRESETS.iter() {
+            let (mut pll_audio, mut num, mut denom, mut misc2) = (0x1_1000u32, 0, 0, 0);
+            let regs = registers(&mut pll_audio, &mut num, &mut denom, &mut misc2);
+            unsafe {
+                regs.configure(&preset.config());
+                BYPASS.modify(regs.pll, 0);
+                assert_eq!(regs.frequency(), hz, "{:?}", preset);
+            }
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! Video PLL (PLL5) control
+//!
+//! PLL5, also known as the "video PLL," is a fractional PLL that typically
+//! sources the LCDIF pixel clock. The PLL5 output frequency is
+//!
+//! ```text
+//! OSC * (DIV_SELECT + NUM / DENOM) / POST_DIVIDER
+//! ```
+//!
+//! where `OSC` is the 24MHz crystal oscillator, and `DIV_SELECT` is between
+//! [27, 54]. The VCO therefore runs between 648MHz and 1296MHz, and the output
+//! is between 40.5MHz and 1296MHz. Slower pixel clocks need the LCDIF clock root
+//! dividers. The post divider combines the PLL5 `POST_DIV_SELECT` field and the
+//! `VIDEO_DIV` field in CCM_ANALOG_MISC2.
+//!
+//! Use [`Config::from_frequency`] to compute a configuration for a display's
+//! pixel clock, then [`PLL5::configure`] to apply it:
+//!
+//! ```no_run
+//! use imxrt_ccm::pll5;
+//! # struct MyClocks;
+//! # impl imxrt_ccm::Clocks for MyClocks {
+//! #   type I2C = ();
+//! #   type SPI = ();
+//! #   type UART = ();
+//! #   type GPT = ();
+//! #   type PIT = ();
+//! # }
+//! # let mut ccm = unsafe { imxrt_ccm::CCM::<MyClocks>::new() };
+//!
+//! // 74.25MHz pixel clock for a 1280x720 display
+//! let config = pll5::Config::from_frequency(74_250_000).unwrap();
+//! ccm.pll5_mut().configure(&config);
+//! assert_eq!(ccm.pll5().frequency(), 74_250_000);
+//! ```
+//!
+//! # References
+//!
+//! i.MX RT 1060 reference manual
+//! - Chapter 14: Clock Control Module (CCM)
+//!   - CCM Analog Memory Map/Register Definition
+
+use crate::{
+    fracpll::{Registers, BYPASS},
+    pll::{self, LockTimeout, Pll},
+    register::Field,
+};
+
+pub use crate::fracpll::{Config, PostDivider, DIV_SELECT_MAX, DIV_SELECT_MIN};
+
+const CCM_ANALOG_PLL_VIDEO: *mut u32 = 0x400D_80A0 as _;
+const CCM_ANALOG_PLL_VIDEO_NUM: *mut u32 = 0x400D_80B0 as _;
+const CCM_ANALOG_PLL_VIDEO_DENOM: *mut u32 = 0x400D_80C0 as _;
+const CCM_ANALOG_MISC2: *mut u32 = 0x400D_8170 as _;
+
+const fn registers(
+    pll_video: *mut u32,
+    num: *mut u32,
+    denom: *mut u32,
+    misc2: *mut u32,
+) -> Registers {
+    Registers {
+        pll: pll_video,
+        num,
+        denom,
+        misc2,
+        misc2_lsb: Field::new(30, 1),
+        misc2_msb: Field::new(31, 1),
+    }
+}
+
+const PLL_VIDEO: Registers = registers(
+    CCM_ANALOG_PLL_VIDEO,
+    CCM_ANALOG_PLL_VIDEO_NUM,
+    CCM_ANALOG_PLL_VIDEO_DENOM,
+    CCM_ANALOG_MISC2,
+);
+
+/// The video PLL, PLL5
+///
+/// Use `PLL5` to configure, enable, and disable PLL5.
+pub struct PLL5(());
+
+impl PLL5 {
+    pub(crate) const fn new() -> Self {
+        PLL5(())
+    }
+
+    /// Configure and enable PLL5
+    ///
+    /// `configure` bypasses PLL5 while it locks. When `configure` returns, PLL5 is
+    /// locked and not bypassed.
+    ///
+    /// `div_select` is clamped between [27, 54].
+    #[inline(always)]
+    pub fn configure(&mut self, config: &Config) {
+        // Safety: we own the PLL_VIDEO registers, and the MISC2 video divider
+        unsafe { configure(config) }
+    }
+
+    /// Configure and enable PLL5, waiting at most `max_iterations` for PLL5 to lock
+    ///
+    /// If PLL5 does not lock in time, `try_configure` returns [`LockTimeout`], and
+    /// PLL5 remains bypassed. Otherwise, `try_configure` behaves like
+    /// [`configure`](PLL5::configure).
+    #[inline(always)]
+    pub fn try_configure(
+        &mut self,
+        config: &Config,
+        max_iterations: u32,
+    ) -> Result<(), LockTimeout> {
+        // Safety: we own the PLL_VIDEO registers, and the MISC2 video divider
+        unsafe { try_configure(config, max_iterations) }
+    }
+
+    /// Power down and disable PLL5
+    ///
+    /// Any clock root that uses PLL5 will stop.
+    #[inline(always)]
+    pub fn disable(&mut self) {
+        // Safety: we own the PLL_VIDEO register
+        unsafe { disable() }
+    }
+
+    /// Returns `true` if PLL5 is powered, enabled, and locked
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        is_enabled()
+    }
+
+    /// Bypass (`true`) or don't bypass (`false`) PLL5
+    ///
+    /// When bypassed, PLL5 outputs the crystal oscillator frequency.
+    #[inline(always)]
+    pub fn set_bypass(&mut self, bypass: bool) {
+        // Safety: we own the PLL_VIDEO register
+        unsafe { BYPASS.modify(CCM_ANALOG_PLL_VIDEO, bypass as u32) }
+    }
+
+    /// Returns the PLL5 frequency
+    ///
+    /// Returns 0 if PLL5 is powered down, or if its output is disabled. If PLL5
+    /// is bypassed, returns the crystal oscillator frequency.
+    #[inline(always)]
+    pub fn frequency(&self) -> u32 {
+        frequency()
+    }
+}
+
+/// Configure and enable PLL5
+///
+/// See [`PLL5::configure`] for more information.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL5`] for a safer interface.
+pub unsafe fn configure(config: &Config) {
+    PLL_VIDEO.configure(config);
+    pll::spin_lock(Pll::Video);
+    BYPASS.modify(CCM_ANALOG_PLL_VIDEO, 0);
+}
+
+/// Configure and enable PLL5, waiting at most `max_iterations` for PLL5 to lock
+///
+/// See [`PLL5::try_configure`] for more information.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL5`] for a safer interface.
+pub unsafe fn try_configure(config: &Config, max_iterations: u32) -> Result<(), LockTimeout> {
+    PLL_VIDEO.configure(config);
+    pll::wait_lock(Pll::Video, max_iterations)?;
+    BYPASS.modify(CCM_ANALOG_PLL_VIDEO, 0);
+    Ok(())
+}
+
+/// Power down and disable PLL5
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL5`] for a safer interface.
+pub unsafe fn disable() {
+    PLL_VIDEO.disable()
+}
+
+/// Returns `true` if PLL5 is powered, enabled, and locked
+#[inline(always)]
+pub fn is_enabled() -> bool {
+    // Safety: atomic read of a valid register
+    unsafe { PLL_VIDEO.is_enabled() }
+}
+
+/// Returns the PLL5 frequency
+///
+/// Returns 0 if PLL5 is powered down, or if its output is disabled. If PLL5
+/// is bypassed, returns the crystal oscillator frequency.
+#[inline(always)]
+pub fn frequency() -> u32 {
+    // Safety: reads of valid registers
+    unsafe { PLL_VIDEO.frequency() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{registers, Config, PostDivider, BYPASS};
+
+    #[test]
+    fn pll5_from_frequency() {
+        let config = Config::from_frequency(74_250_000).unwrap();
+        assert_eq!(config.post_divider, PostDivider::Divide16);
+        assert_eq!(config.div_select, 49);
+        assert_eq!((config.numerator, config.denominator), (1, 2));
+        assert_eq!(config.frequency(), 74_250_000);
+    }
+
+    #[test]
+    fn pll5_from_frequency_range() {
+        assert!(Config::from_frequency(40_500_000).is_some());
+        assert!(Config::from_frequency(1_200_000_000).is_some());
+        assert!(Config::from_frequency(40_000_000).is_none());
+        assert!(Config::from_frequency(1_300_000_000).is_none());
+    }
+
+    #[test]
+    fn pll5_configure() {
+        let (mut pll_video, mut num, mut denom, mut misc2) = (0x1_1000u32, 0, 0, 0);
+        let regs = registers(&mut pll_video, &mut num, &mut denom, &mut misc2);
+        let config = Config::from_frequency(74_250_000).unwrap();
+        unsafe {
+            regs.configure(&config);
+            assert_eq!(regs.frequency(), crate::OSCILLATOR_FREQUENCY_HZ);
+            BYPASS.modify(regs.pll, 0);
+            assert_eq!(regs.frequency(), 74_250_000);
+        }
+        assert_eq!(misc2, 0b11 << 30);
+    }
+
+    #[test]
+    fn pll5_off() {
+        let (mut num, mut denom, mut misc2) = (0, 1, 0);
+        let mut powered_down = 1 << 12 | 1 << 13 | 30;
+        let regs = registers(&mut powered_down, &mut num, &mut denom, &mut misc2);
+        assert_eq!(unsafe { regs.frequency() }, 0);
+    }
+}
@@ -0,0 +1,429 @@
+//! ENET PLL (PLL6) control
+//!
+//! PLL6, also known as the "ENET PLL," runs at 500MHz. Besides the 500MHz
+//! reference, PLL6 produces divided clocks for the Ethernet MAC and IEEE 1588
+//! timers:
+//!
+//! - ENET1 reference, selectable between 25MHz, 50MHz, 100MHz, and 125MHz.
+//! - ENET2 reference, with the same options as ENET1.
+//! - A fixed 25MHz reference, typically routed to an external PHY.
+//!
+//! The ENET1, ENET2, and 25MHz references are only available on the i.MX RT 1060.
+//! The i.MX RT 1010 provides only the 500MHz reference.
+//!
+//! # References
+//!
+//! i.MX RT 1060 reference manual
+//! - Chapter 14: Clock Control Module (CCM)
+//!   - CCM Analog Memory Map/Register Definition
+
+use crate::{
+    pll::{self, LockTimeout, Pll},
+    register::Field,
+    xtalosc, OSCILLATOR_FREQUENCY_HZ,
+};
+
+const CCM_ANALOG_PLL_ENET: *mut u32 = 0x400D_80E0 as _;
+
+/// The PLL6 frequency, when enabled and not bypassed
+pub const PLL6_FREQUENCY_HZ: u32 = 500_000_000;
+
+#[cfg(feature = "imxrt1060")]
+const DIV_SELECT: Field = Field::new(0, 0b11);
+#[cfg(feature = "imxrt1060")]
+const ENET2_DIV_SELECT: Field = Field::new(2, 0b11);
+const POWERDOWN: Field = Field::new(12, 1);
+#[cfg(feature = "imxrt1060")]
+const ENABLE: Field = Field::new(13, 1);
+const BYPASS_CLK_SRC: Field = Field::new(14, 0b11);
+const BYPASS: Field = Field::new(16, 1);
+#[cfg(feature = "imxrt1060")]
+const ENET2_REF_EN: Field = Field::new(20, 1);
+#[cfg(feature = "imxrt1060")]
+const ENET_25M_REF_EN: Field = Field::new(21, 1);
+const ENET_500M_REF_EN: Field = Field::new(22, 1);
+const LOCK: Field = Field::new(31, 1);
+
+/// ENET reference clock frequencies
+#[cfg(feature = "imxrt1060")]
+#[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum EnetFrequency {
+    /// 25MHz
+    MHz25 = 0,
+    /// 50MHz
+    MHz50 = 1,
+    /// 100MHz
+    MHz100 = 2,
+    /// 125MHz
+    MHz125 = 3,
+}
+
+#[cfg(feature = "imxrt1060")]
+impl EnetFrequency {
+    /// Returns the frequency, in Hz
+    pub const fn hz(self) -> u32 {
+        match self {
+            EnetFrequency::MHz25 => 25_000_000,
+            EnetFrequency::MHz50 => 50_000_000,
+            EnetFrequency::MHz100 => 100_000_000,
+            EnetFrequency::MHz125 => 125_000_000,
+        }
+    }
+
+    const fn from_field(div_select: u32) -> Self {
+        match div_select & 0b11 {
+            0 => EnetFrequency::MHz25,
+            1 => EnetFrequency::MHz50,
+            2 => EnetFrequency::MHz100,
+            _ => EnetFrequency::MHz125,
+        }
+    }
+}
+
+/// The ENET PLL, PLL6
+///
+/// Use `PLL6` to power PLL6, and to select its ENET reference outputs.
+pub struct PLL6(());
+
+impl PLL6 {
+    pub(crate) const fn new() -> Self {
+        PLL6(())
+    }
+
+    /// Power up PLL6, and wait for it to lock
+    ///
+    /// `enable` bypasses PLL6 while it locks. When `enable` returns, PLL6 is locked
+    /// and not bypassed. `enable` does not change the reference outputs.
+    #[inline(always)]
+    pub fn enable(&mut self) {
+        // Safety: we own the PLL_ENET register
+        unsafe { enable() }
+    }
+
+    /// Power up PLL6, waiting at most `max_iterations` for PLL6 to lock
+    ///
+    /// If PLL6 does not lock in time, `try_enable` returns [`LockTimeout`], and
+    /// PLL6 remains bypassed. Otherwise, `try_enable` behaves like
+    /// [`enable`](PLL6::enable).
+    #[inline(always)]
+    pub fn try_enable(&mut self, max_iterations: u32) -> Result<(), LockTimeout> {
+        // Safety: we own the PLL_ENET register
+        unsafe { try_enable(max_iterations) }
+    }
+
+    /// Power down PLL6
+    ///
+    /// Any clock root that uses PLL6, or one of its references, will stop.
+    #[inline(always)]
+    pub fn disable(&mut self) {
+        // Safety: we own the PLL_ENET register
+        unsafe { disable() }
+    }
+
+    /// Returns `true` if PLL6 is powered and locked
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        is_enabled()
+    }
+
+    /// Bypass (`true`) or don't bypass (`false`) PLL6
+    ///
+    /// When bypassed, PLL6 outputs the crystal oscillator frequency.
+    #[inline(always)]
+    pub fn set_bypass(&mut self, bypass: bool) {
+        // Safety: we own the PLL_ENET register
+        unsafe { BYPASS.modify(CCM_ANALOG_PLL_ENET, bypass as u32) }
+    }
+
+    /// Enable (`true`) or disable (`false`) the 500MHz reference output
+    #[inline(always)]
+    pub fn set_500m_enabled(&mut self, enable: bool) {
+        // Safety: we own the PLL_ENET register
+        unsafe { ENET_500M_REF_EN.modify(CCM_ANALOG_PLL_ENET, enable as u32) }
+    }
+
+    /// Set the ENET1 reference frequency
+    ///
+    /// `None` disables the ENET1 reference output.
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    #[inline(always)]
+    pub fn set_enet1(&mut self, freq: Option<EnetFrequency>) {
+        // Safety: we own the PLL_ENET register
+        unsafe { set_enet1(freq) }
+    }
+
+    /// Set the ENET2 reference frequency
+    ///
+    /// `None` disables the ENET2 reference output.
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    #[inline(always)]
+    pub fn set_enet2(&mut self, freq: Option<EnetFrequency>) {
+        // Safety: we own the PLL_ENET register
+        unsafe { set_enet2(freq) }
+    }
+
+    /// Enable (`true`) or disable (`false`) the 25MHz reference output
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    #[inline(always)]
+    pub fn set_25m_enabled(&mut self, enable: bool) {
+        // Safety: we own the PLL_ENET register
+        unsafe { ENET_25M_REF_EN.modify(CCM_ANALOG_PLL_ENET, enable as u32) }
+    }
+
+    /// Returns the PLL6 frequency
+    ///
+    /// See [`frequency`] for more information.
+    #[inline(always)]
+    pub fn frequency(&self) -> u32 {
+        frequency()
+    }
+
+    /// Returns the ENET1 reference frequency
+    ///
+    /// See [`enet1_frequency`] for more information.
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    #[inline(always)]
+    pub fn enet1_frequency(&self) -> u32 {
+        enet1_frequency()
+    }
+
+    /// Returns the ENET2 reference frequency
+    ///
+    /// See [`enet2_frequency`] for more information.
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    #[inline(always)]
+    pub fn enet2_frequency(&self) -> u32 {
+        enet2_frequency()
+    }
+}
+
+/// Power up PLL6, and wait for it to lock
+///
+/// See [`PLL6::enable`] for more information.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL6`] for a safer interface.
+pub unsafe fn enable() {
+    if is_enabled() {
+        return;
+    }
+    enable_(CCM_ANALOG_PLL_ENET);
+    pll::spin_lock(Pll::Enet);
+    BYPASS.modify(CCM_ANALOG_PLL_ENET, 0);
+}
+
+/// Power up PLL6, waiting at most `max_iterations` for PLL6 to lock
+///
+/// See [`PLL6::try_enable`] for more information.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL6`] for a safer interface.
+pub unsafe fn try_enable(max_iterations: u32) -> Result<(), LockTimeout> {
+    if is_enabled() {
+        return Ok(());
+    }
+    enable_(CCM_ANALOG_PLL_ENET);
+    pll::wait_lock(Pll::Enet, max_iterations)?;
+    BYPASS.modify(CCM_ANALOG_PLL_ENET, 0);
+    Ok(())
+}
+
+unsafe fn enable_(pll_enet: *mut u32) {
+    BYPASS.modify(pll_enet, 1);
+    BYPASS_CLK_SRC.modify(pll_enet, 0); // 24MHz oscillator
+    POWERDOWN.modify(pll_enet, 0);
+}
+
+/// Power down PLL6
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL6`] for a safer interface.
+pub unsafe fn disable() {
+    POWERDOWN.modify(CCM_ANALOG_PLL_ENET, 1);
+}
+
+/// Set the ENET1 reference frequency
+///
+/// `None` disables the ENET1 reference output.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL6`] for a safer interface.
+#[cfg(feature = "imxrt1060")]
+#[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+pub unsafe fn set_enet1(freq: Option<EnetFrequency>) {
+    set_reference(CCM_ANALOG_PLL_ENET, DIV_SELECT, ENABLE, freq)
+}
+
+/// Set the ENET2 reference frequency
+///
+/// `None` disables the ENET2 reference output.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`PLL6`] for a safer interface.
+#[cfg(feature = "imxrt1060")]
+#[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+pub unsafe fn set_enet2(freq: Option<EnetFrequency>) {
+    set_reference(CCM_ANALOG_PLL_ENET, ENET2_DIV_SELECT, ENET2_REF_EN, freq)
+}
+
+#[cfg(feature = "imxrt1060")]
+unsafe fn set_reference(
+    pll_enet: *mut u32,
+    div_select: Field,
+    enable: Field,
+    freq: Option<EnetFrequency>,
+) {
+    match freq {
+        Some(freq) => {
+            div_select.modify(pll_enet, freq as u32);
+            enable.modify(pll_enet, 1);
+        }
+        None => enable.modify(pll_enet, 0),
+    }
+}
+
+/// Returns `true` if PLL6 is powered and locked
+#[inline(always)]
+pub fn is_enabled() -> bool {
+    // Safety: atomic read of a valid register
+    unsafe { is_enabled_(CCM_ANALOG_PLL_ENET) }
+}
+
+unsafe fn is_enabled_(pll_enet: *const u32) -> bool {
+    POWERDOWN.read(pll_enet) == 0 && LOCK.read(pll_enet) != 0
+}
+
+/// Returns the PLL6 frequency
+///
+/// Returns 0 if PLL6 is powered down. If PLL6 is bypassed, returns the crystal
+/// oscillator frequency.
+#[inline(always)]
+pub fn frequency() -> u32 {
+    // Safety: atomic read of a valid register
+    unsafe { frequency_(CCM_ANALOG_PLL_ENET) }
+}
+
+unsafe fn frequency_(pll_enet: *const u32) -> u32 {
+    if POWERDOWN.read(pll_enet) != 0 {
+        0
+    } else if BYPASS.read(pll_enet) != 0 {
+        xtalosc::reference_frequency()
+    } else {
+        // PLL6 multiplies the reference by a fixed ratio
+        let reference_hz = xtalosc::reference_frequency() as u64;
+        (PLL6_FREQUENCY_HZ as u64 * reference_hz / OSCILLATOR_FREQUENCY_HZ as u64) as u32
+    }
+}
+
+/// Returns the ENET1 reference frequency
+///
+/// Returns 0 if PLL6 is powered down, or if the ENET1 reference is disabled.
+/// If PLL6 is bypassed, returns the crystal oscillator frequency.
+#[cfg(feature = "imxrt1060")]
+#[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+#[inline(always)]
+pub fn enet1_frequency() -> u32 {
+    // Safety: atomic read of a valid register
+    unsafe { reference_frequency(CCM_ANALOG_PLL_ENET, DIV_SELECT, ENABLE) }
+}
+
+/// Returns the ENET2 reference frequency
+///
+/// Returns 0 if PLL6 is powered down, or if the ENET2 reference is disabled.
+/// If PLL6 is bypassed, returns the crystal oscillator frequency.
+#[cfg(feature = "imxrt1060")]
+#[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+#[inline(always)]
+pub fn enet2_frequency() -> u32 {
+    // Safety: atomic read of a valid register
+    unsafe { reference_frequency(CCM_ANALOG_PLL_ENET, ENET2_DIV_SELECT, ENET2_REF_EN) }
+}
+
+#[cfg(feature = "imxrt1060")]
+unsafe fn reference_frequency(pll_enet: *const u32, div_select: Field, enable: Field) -> u32 {
+    match frequency_(pll_enet) {
+        0 => 0,
+        _ if enable.read(pll_enet) == 0 => 0,
+        PLL6_FREQUENCY_HZ => EnetFrequency::from_field(div_select.read(pll_enet)).hz(),
+        bypassed => bypassed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{enable_, frequency_, is_enabled_, BYPASS, LOCK};
+
+    #[test]
+    fn pll6_enable() {
+        let mut pll_enet: u32 = 1 << 12;
+        unsafe {
+            enable_(&mut pll_enet);
+            assert_eq!(frequency_(&pll_enet), crate::OSCILLATOR_FREQUENCY_HZ);
+            assert!(!is_enabled_(&pll_enet));
+
+            LOCK.modify(&mut pll_enet, 1);
+            BYPASS.modify(&mut pll_enet, 0);
+            assert!(is_enabled_(&pll_enet));
+            assert_eq!(frequency_(&pll_enet), 500_000_000);
+        }
+        assert_eq!(pll_enet, 1 << 31);
+    }
+
+    #[test]
+    fn pll6_powered_down() {
+        let pll_enet: u32 = 1 << 12;
+        assert_eq!(unsafe { frequency_(&pll_enet) }, 0);
+    }
+
+    #[cfg(feature = "imxrt1060")]
+    #[test]
+    fn pll6_references() {
+        use super::{reference_frequency, set_reference, EnetFrequency};
+        use super::{DIV_SELECT, ENABLE, ENET2_DIV_SELECT, ENET2_REF_EN};
+
+        let mut pll_enet: u32 = 1 << 31;
+        unsafe {
+            set_reference(
+                &mut pll_enet,
+                DIV_SELECT,
+                ENABLE,
+                Some(EnetFrequency::MHz50),
+            );
+            set_reference(
+                &mut pll_enet,
+                ENET2_DIV_SELECT,
+                ENET2_REF_EN,
+                Some(EnetFrequency::MHz125),
+            );
+            assert_eq!(pll_enet, 1 << 31 | 1 << 20 | 1 << 13 | 0b11 << 2 | 0b01);
+            assert_eq!(
+                reference_frequency(&pll_enet, DIV_SELECT, ENABLE),
+                50_000_000
+            );
+            assert_eq!(
+                reference_frequency(&pll_enet, ENET2_DIV_SELECT, ENET2_REF_EN),
+                125_000_000
+            );
+
+            set_reference(&mut pll_enet, DIV_SELECT, ENABLE, None);
+            assert_eq!(reference_frequency(&pll_enet, DIV_SELECT, ENABLE), 0);
+        }
+    }
+}
@@ -2,60 +2,86 @@
 //!
 //! Use [`CCM::from_ral`](../struct.CCM.html#from_ral) to safely
 //! acquire the CCM handle and clock roots.
+//!
+//! `imxrt-ral` identifies each peripheral instance by a const-generic instance
+//! number, like `lpuart::Instance<3>`. The clock handles control the clock gate
+//! of one instance type, so [`Clocks`] takes the GPT, UART, SPI, and I2C
+//! instance numbers as const generics. The instance numbers default to 1.
+//! Use [`set_clock_gate`](crate::set_clock_gate) to control the clock gates of
+//! the other instances.
 
 use crate::{
     i2c::I2C,
     perclock::{GPT, PIT},
     spi::SPI,
     uart::UART,
+    usb::USB,
     Instance, ADC, DCDC, DMA, PWM,
 };
 use imxrt_ral as ral;
 
+#[cfg(feature = "imxrt1060")]
+#[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+pub mod clock_tree;
+
 /// Pairs the RAL instances to CCM clocks
-pub struct Clocks;
-
-impl crate::Clocks for Clocks {
-    type PIT = ral::pit::Instance;
-    type GPT = ral::gpt::Instance;
-    type UART = ral::lpuart::Instance;
-    type SPI = ral::lpspi::Instance;
-    type I2C = ral::lpi2c::Instance;
+///
+/// `GPT_N`, `UART_N`, `SPI_N`, and `I2C_N` are the instance numbers of the
+/// peripherals that the clock handles control.
+pub struct Clocks<
+    const GPT_N: u8 = 1,
+    const UART_N: u8 = 1,
+    const SPI_N: u8 = 1,
+    const I2C_N: u8 = 1,
+>;
+
+impl<const GPT_N: u8, const UART_N: u8, const SPI_N: u8, const I2C_N: u8> crate::Clocks
+    for Clocks<GPT_N, UART_N, SPI_N, I2C_N>
+{
+    type PIT = ral::pit::PIT;
+    type GPT = ral::gpt::Instance<GPT_N>;
+    type UART = ral::lpuart::Instance<UART_N>;
+    type SPI = ral::lpspi::Instance<SPI_N>;
+    type I2C = ral::lpi2c::Instance<I2C_N>;
 }
 
 /// Helper for a clock control module designed to the
 /// RAL interface.
-pub type CCM = crate::CCM<Clocks>;
+pub type CCM<const GPT_N: u8 = 1, const UART_N: u8 = 1, const SPI_N: u8 = 1, const I2C_N: u8 = 1> =
+    crate::CCM<Clocks<GPT_N, UART_N, SPI_N, I2C_N>>;
 
 /// A periodic clock that controls RAL PIT and GPT timings
-pub type PerClock = crate::perclock::PerClock<ral::pit::Instance, ral::gpt::Instance>;
+pub type PerClock<const N: u8 = 1> =
+    crate::perclock::PerClock<ral::pit::PIT, ral::gpt::Instance<N>>;
 /// A UART clock that controls RAL LPUART timing
-pub type UARTClock = crate::uart::UARTClock<ral::lpuart::Instance>;
+pub type UARTClock<const N: u8 = 1> = crate::uart::UARTClock<ral::lpuart::Instance<N>>;
 /// A SPI clock that controls RAL LPSPI timing
-pub type SPIClock = crate::spi::SPIClock<ral::lpspi::Instance>;
+pub type SPIClock<const N: u8 = 1> = crate::spi::SPIClock<ral::lpspi::Instance<N>>;
 /// An I2C clock that contorls RAL LPI2C timing
-pub type I2CClock = crate::i2c::I2CClock<ral::lpi2c::Instance>;
+pub type I2CClock<const N: u8 = 1> = crate::i2c::I2CClock<ral::lpi2c::Instance<N>>;
 
-impl CCM {
+impl<const GPT_N: u8, const UART_N: u8, const SPI_N: u8, const I2C_N: u8>
+    CCM<GPT_N, UART_N, SPI_N, I2C_N>
+{
     /// Converts the `imxrt-ral` CCM instance into the `CCM` driver
     ///
     /// This is safer than using `new()`, since we take ownership of the
     /// only other CCM instance in the system.
     ///
     /// ```no_run
-    /// use imxrt_ccm::CCM;
+    /// use imxrt_ccm::ral;
     /// use imxrt_ral::ccm;
     ///
-    /// let ccm = ccm::CCM::take().map(CCM::from_ral).unwrap();
+    /// let ccm: ral::CCM = ral::CCM::from_ral(unsafe { ccm::CCM::instance() });
     /// ```
-    pub fn from_ral(_: ral::ccm::Instance) -> Self {
+    pub fn from_ral(_: ral::ccm::CCM) -> Self {
         // Safety: we "own" the CCM instance, so no one
         // else can (safely) access it.
         unsafe { crate::CCM::new() }
     }
 }
 
-unsafe impl Instance for ral::dcdc::Instance {
+unsafe impl Instance for ral::dcdc::DCDC {
     type Inst = DCDC;
     #[inline(always)]
     fn instance(&self) -> DCDC {
@@ -68,19 +94,18 @@ unsafe impl Instance for ral::dcdc::Instance {
 }
 
 /// ```no_run
-/// use imxrt_ccm::{CCM, ClockGate};
-/// use imxrt_ral::ccm;
-/// use imxrt_ral::dcdc::DCDC;
+/// use imxrt_ccm::{ral, ClockGate};
+/// use imxrt_ral::{ccm, dcdc::DCDC};
 ///
-/// let mut handle = ccm::CCM::take().map(CCM::from_ral).unwrap();
-/// let mut dcdc = DCDC::take().unwrap();
+/// let mut handle: ral::CCM = ral::CCM::from_ral(unsafe { ccm::CCM::instance() });
+/// let mut dcdc = unsafe { DCDC::instance() };
 /// handle.set_clock_gate_dcdc(&mut dcdc, ClockGate::On);
 /// handle.clock_gate_dcdc(&dcdc);
 /// ```
 #[cfg(doctest)]
 struct DCDCClockGate;
 
-unsafe impl Instance for ral::dma0::Instance {
+unsafe impl Instance for ral::dma::DMA {
     type Inst = DMA;
     #[inline(always)]
     fn instance(&self) -> DMA {
@@ -93,12 +118,11 @@ unsafe impl Instance for ral::dma0::Instance {
 }
 
 /// ```no_run
-/// use imxrt_ccm::{CCM, ClockGate};
-/// use imxrt_ral::ccm;
-/// use imxrt_ral::dma0::DMA0;
+/// use imxrt_ccm::{ral, ClockGate};
+/// use imxrt_ral::{ccm, dma::DMA};
 ///
-/// let mut handle = ccm::CCM::take().map(CCM::from_ral).unwrap();
-/// let mut dma = DMA0::take().unwrap();
+/// let mut handle: ral::CCM = ral::CCM::from_ral(unsafe { ccm::CCM::instance() });
+/// let mut dma = unsafe { DMA::instance() };
 /// handle.set_clock_gate_dma(&mut dma, ClockGate::On);
 /// handle.clock_gate_dma(&dma);
 /// ```
@@ -107,17 +131,20 @@ struct DMAClockGate;
 
 #[cfg(not(any(feature = "imxrt1010", feature = "imxrt1060")))]
 compile_error!("Ensure that LPI2C instances are correct");
-unsafe impl Instance for ral::lpi2c::Instance {
+unsafe impl<const N: u8> Instance for ral::lpi2c::Instance<N>
+where
+    Self: ral::Valid,
+{
     type Inst = I2C;
     #[inline(always)]
     fn instance(&self) -> I2C {
-        match &**self as *const _ {
-            ral::lpi2c::LPI2C1 => I2C::I2C1,
-            ral::lpi2c::LPI2C2 => I2C::I2C2,
+        match N {
+            1 => I2C::I2C1,
+            2 => I2C::I2C2,
             #[cfg(feature = "imxrt1060")]
-            ral::lpi2c::LPI2C3 => I2C::I2C3,
+            3 => I2C::I2C3,
             #[cfg(feature = "imxrt1060")]
-            ral::lpi2c::LPI2C4 => I2C::I2C4,
+            4 => I2C::I2C4,
             _ => unreachable!(),
         }
     }
@@ -130,27 +157,29 @@ unsafe impl Instance for ral::lpi2c::Instance {
 }
 
 /// ```no_run
-/// use imxrt_ccm::{CCM, ClockGate};
-/// use imxrt_ral::ccm;
-/// use imxrt_ral::lpi2c::LPI2C2;
+/// use imxrt_ccm::{ral, ClockGate};
+/// use imxrt_ral::{ccm, lpi2c::LPI2C2};
 ///
-/// let mut ccm = ccm::CCM::take().map(CCM::from_ral).unwrap();
+/// let mut ccm: ral::CCM<1, 1, 1, 2> = ral::CCM::from_ral(unsafe { ccm::CCM::instance() });
 /// let mut i2c_clock = ccm.i2c_clock_mut();
 /// i2c_clock.configure();
-/// let mut i2c = LPI2C2::take().unwrap();
+/// let mut i2c = unsafe { LPI2C2::instance() };
 /// i2c_clock.set_clock_gate(&mut i2c, ClockGate::On);
 /// i2c_clock.clock_gate(&i2c);
 /// ```
 #[cfg(doctest)]
 struct I2CClockGate;
 
-unsafe impl Instance for ral::gpt::Instance {
+unsafe impl<const N: u8> Instance for ral::gpt::Instance<N>
+where
+    Self: ral::Valid,
+{
     type Inst = GPT;
     #[inline(always)]
     fn instance(&self) -> GPT {
-        match &**self as *const _ {
-            ral::gpt::GPT1 => GPT::GPT1,
-            ral::gpt::GPT2 => GPT::GPT2,
+        match N {
+            1 => GPT::GPT1,
+            2 => GPT::GPT2,
             _ => unreachable!(),
         }
     }
@@ -164,21 +193,20 @@ unsafe impl Instance for ral::gpt::Instance {
 }
 
 /// ```no_run
-/// use imxrt_ccm::{CCM, ClockGate};
-/// use imxrt_ral::ccm;
-/// use imxrt_ral::gpt::GPT2;
+/// use imxrt_ccm::{ral, ClockGate};
+/// use imxrt_ral::{ccm, gpt::GPT2};
 ///
-/// let mut ccm = ccm::CCM::take().map(CCM::from_ral).unwrap();
+/// let mut ccm: ral::CCM<2> = ral::CCM::from_ral(unsafe { ccm::CCM::instance() });
 /// let mut perclock = ccm.perclock_mut();
 /// perclock.configure();
-/// let mut gpt = GPT2::take().unwrap();
+/// let mut gpt = unsafe { GPT2::instance() };
 /// perclock.set_clock_gate_gpt(&mut gpt, ClockGate::On);
 /// perclock.clock_gate_gpt(&gpt);
 /// ```
 #[cfg(doctest)]
 struct GPTClockGate;
 
-unsafe impl Instance for ral::pit::Instance {
+unsafe impl Instance for ral::pit::PIT {
     type Inst = PIT;
     #[inline(always)]
     fn instance(&self) -> PIT {
@@ -191,14 +219,13 @@ unsafe impl Instance for ral::pit::Instance {
 }
 
 /// ```no_run
-/// use imxrt_ccm::{CCM, ClockGate};
-/// use imxrt_ral::ccm;
-/// use imxrt_ral::pit::PIT;
+/// use imxrt_ccm::{ral, ClockGate};
+/// use imxrt_ral::{ccm, pit::PIT};
 ///
-/// let mut ccm = ccm::CCM::take().map(CCM::from_ral).unwrap();
+/// let mut ccm: ral::CCM = ral::CCM::from_ral(unsafe { ccm::CCM::instance() });
 /// let mut perclock = ccm.perclock_mut();
 /// perclock.configure();
-/// let mut pit = PIT::take().unwrap();
+/// let mut pit = unsafe { PIT::instance() };
 /// perclock.set_clock_gate_pit(&mut pit, ClockGate::On);
 /// perclock.clock_gate_pit(&pit);
 /// ```
@@ -207,17 +234,20 @@ struct PITClockGate;
 
 #[cfg(not(any(feature = "imxrt1010", feature = "imxrt1060")))]
 compile_error!("Ensure that LPSPI instances are correct");
-unsafe impl Instance for ral::lpspi::Instance {
+unsafe impl<const N: u8> Instance for ral::lpspi::Instance<N>
+where
+    Self: ral::Valid,
+{
     type Inst = SPI;
     #[inline(always)]
     fn instance(&self) -> SPI {
-        match &**self as *const _ {
-            ral::lpspi::LPSPI1 => SPI::SPI1,
-            ral::lpspi::LPSPI2 => SPI::SPI2,
+        match N {
+            1 => SPI::SPI1,
+            2 => SPI::SPI2,
             #[cfg(feature = "imxrt1060")]
-            ral::lpspi::LPSPI3 => SPI::SPI3,
+            3 => SPI::SPI3,
             #[cfg(feature = "imxrt1060")]
-            ral::lpspi::LPSPI4 => SPI::SPI4,
+            4 => SPI::SPI4,
             _ => unreachable!(),
         }
     }
@@ -229,14 +259,13 @@ unsafe impl Instance for ral::lpspi::Instance {
 }
 
 /// ```no_run
-/// use imxrt_ccm::{CCM, ClockGate};
-/// use imxrt_ral::ccm;
-/// use imxrt_ral::lpspi::LPSPI1;
+/// use imxrt_ccm::{ral, ClockGate};
+/// use imxrt_ral::{ccm, lpspi::LPSPI1};
 ///
-/// let mut ccm = ccm::CCM::take().map(CCM::from_ral).unwrap();
+/// let mut ccm: ral::CCM = ral::CCM::from_ral(unsafe { ccm::CCM::instance() });
 /// let mut spi_clock = ccm.spi_clock_mut();
 /// spi_clock.configure();
-/// let mut spi = LPSPI1::take().unwrap();
+/// let mut spi = unsafe { LPSPI1::instance() };
 /// spi_clock.set_clock_gate(&mut spi, ClockGate::On);
 /// spi_clock.clock_gate(&spi);
 /// ```
@@ -245,23 +274,26 @@ struct SPIClockGate;
 
 #[cfg(not(any(feature = "imxrt1010", feature = "imxrt1060")))]
 compile_error!("Ensure that LPUART instances are correct");
-unsafe impl Instance for ral::lpuart::Instance {
+unsafe impl<const N: u8> Instance for ral::lpuart::Instance<N>
+where
+    Self: ral::Valid,
+{
     type Inst = UART;
     #[inline(always)]
     fn instance(&self) -> UART {
-        match &**self as *const _ {
-            ral::lpuart::LPUART1 => UART::UART1,
-            ral::lpuart::LPUART2 => UART::UART2,
-            ral::lpuart::LPUART3 => UART::UART3,
-            ral::lpuart::LPUART4 => UART::UART4,
+        match N {
+            1 => UART::UART1,
+            2 => UART::UART2,
+            3 => UART::UART3,
+            4 => UART::UART4,
             #[cfg(feature = "imxrt1060")]
-            ral::lpuart::LPUART5 => UART::UART5,
+            5 => UART::UART5,
             #[cfg(feature = "imxrt1060")]
-            ral::lpuart::LPUART6 => UART::UART6,
+            6 => UART::UART6,
             #[cfg(feature = "imxrt1060")]
-            ral::lpuart::LPUART7 => UART::UART7,
+            7 => UART::UART7,
             #[cfg(feature = "imxrt1060")]
-            ral::lpuart::LPUART8 => UART::UART8,
+            8 => UART::UART8,
             _ => unreachable!(),
         }
     }
@@ -274,35 +306,33 @@ unsafe impl Instance for ral::lpuart::Instance {
 }
 
 /// ```no_run
-/// use imxrt_ccm::{CCM, ClockGate};
-/// use imxrt_ral::ccm;
-/// use imxrt_ral::lpuart::LPUART4;
+/// use imxrt_ccm::{ral, ClockGate};
+/// use imxrt_ral::{ccm, lpuart::LPUART4};
 ///
-/// let mut ccm = ccm::CCM::take().map(CCM::from_ral).unwrap();
+/// let mut ccm: ral::CCM<1, 4> = ral::CCM::from_ral(unsafe { ccm::CCM::instance() });
 /// let mut uart_clock = ccm.uart_clock_mut();
 /// uart_clock.configure();
-/// let mut uart = LPUART4::take().unwrap();
+/// let mut uart = unsafe { LPUART4::instance() };
 /// uart_clock.set_clock_gate(&mut uart, ClockGate::On);
 /// uart_clock.clock_gate(&uart);
 /// ```
 #[cfg(doctest)]
 struct UARTClockGate;
 
-#[cfg(feature = "imxrt1060")]
-use ral::adc;
-#[cfg(feature = "imxrt1010")]
-use ral::adc1 as adc;
-
 #[cfg(not(any(feature = "imxrt1010", feature = "imxrt1060")))]
 compile_error!("Ensure that ADC instances are correct");
-unsafe impl Instance for adc::Instance {
+unsafe impl<const N: u8> Instance for ral::adc::Instance<N>
+where
+    Self: ral::Valid,
+{
     type Inst = ADC;
     #[inline(always)]
     fn instance(&self) -> ADC {
-        match &**self as *const _ {
-            adc::ADC1 => ADC::ADC1,
+        match N {
+            // The 1010's only ADC is the sole instance
+            ral::SOLE_INSTANCE | 1 => ADC::ADC1,
             #[cfg(feature = "imxrt1060")]
-            adc::ADC2 => ADC::ADC2,
+            2 => ADC::ADC2,
             _ => unreachable!(),
         }
     }
@@ -313,40 +343,39 @@ unsafe impl Instance for adc::Instance {
 }
 
 /// ```no_run
-/// use imxrt_ccm::{CCM, ClockGate};
+/// use imxrt_ccm::{ral, ClockGate};
 /// use imxrt_ral::ccm;
 /// #[cfg(feature = "imxrt1060")]
-/// use imxrt_ral::adc::ADC1;
-/// #[cfg(feature = "imxrt1010")]
-/// use imxrt_ral::adc1::ADC1;
+/// use imxrt_ral::adc::ADC1 as ADC;
+/// #[cfg(not(feature = "imxrt1060"))]
+/// use imxrt_ral::adc::ADC;
 ///
-/// let mut handle = ccm::CCM::take().map(CCM::from_ral).unwrap();
-/// let mut adc = ADC1::take().unwrap();
+/// let mut handle: ral::CCM = ral::CCM::from_ral(unsafe { ccm::CCM::instance() });
+/// let mut adc = unsafe { ADC::instance() };
 /// handle.set_clock_gate_adc(&mut adc, ClockGate::On);
 /// handle.clock_gate_adc(&adc);
 /// ```
 #[cfg(doctest)]
 struct ADCClockGate;
 
-#[cfg(feature = "imxrt1060")]
-use ral::pwm;
-#[cfg(feature = "imxrt1010")]
-use ral::pwm1 as pwm;
-
 #[cfg(not(any(feature = "imxrt1010", feature = "imxrt1060")))]
 compile_error!("Ensure that PWM instances are correct");
-unsafe impl Instance for pwm::Instance {
+unsafe impl<const N: u8> Instance for ral::pwm::Instance<N>
+where
+    Self: ral::Valid,
+{
     type Inst = PWM;
     #[inline(always)]
     fn instance(&self) -> PWM {
-        match &**self as *const _ {
-            pwm::PWM1 => PWM::PWM1,
+        match N {
+            // The 1010's only PWM is the sole instance
+            ral::SOLE_INSTANCE | 1 => PWM::PWM1,
             #[cfg(feature = "imxrt1060")]
-            pwm::PWM2 => PWM::PWM2,
+            2 => PWM::PWM2,
             #[cfg(feature = "imxrt1060")]
-            pwm::PWM3 => PWM::PWM3,
+            3 => PWM::PWM3,
             #[cfg(feature = "imxrt1060")]
-            pwm::PWM4 => PWM::PWM4,
+            4 => PWM::PWM4,
             _ => unreachable!(),
         }
     }
@@ -358,21 +387,59 @@ unsafe impl Instance for pwm::Instance {
 }
 
 /// ```no_run
-/// use imxrt_ccm::{CCM, ClockGate};
+/// use imxrt_ccm::{ral, ClockGate};
 /// use imxrt_ral::ccm;
 /// #[cfg(feature = "imxrt1060")]
-/// use imxrt_ral::pwm::PWM1;
-/// #[cfg(feature = "imxrt1010")]
-/// use imxrt_ral::pwm1::PWM1;
+/// use imxrt_ral::pwm::PWM1 as PWM;
+/// #[cfg(not(feature = "imxrt1060"))]
+/// use imxrt_ral::pwm::PWM;
 ///
-/// let mut handle = ccm::CCM::take().map(CCM::from_ral).unwrap();
-/// let mut pwm = PWM1::take().unwrap();
+/// let mut handle: ral::CCM = ral::CCM::from_ral(unsafe { ccm::CCM::instance() });
+/// let mut pwm = unsafe { PWM::instance() };
 /// handle.set_clock_gate_pwm(&mut pwm, ClockGate::On);
 /// handle.clock_gate_pwm(&pwm);
 /// ```
 #[cfg(doctest)]
 struct PWMClockGate;
 
+#[cfg(not(any(feature = "imxrt1010", feature = "imxrt1060")))]
+compile_error!("Ensure that USB instances are correct");
+unsafe impl<const N: u8> Instance for ral::usb::Instance<N>
+where
+    Self: ral::Valid,
+{
+    type Inst = USB;
+    #[inline(always)]
+    fn instance(&self) -> USB {
+        match N {
+            // The 1010's only USB controller is the sole instance
+            ral::SOLE_INSTANCE | 1 => USB::USB1,
+            #[cfg(feature = "imxrt1060")]
+            2 => USB::USB2,
+            _ => unreachable!(),
+        }
+    }
+    #[inline(always)]
+    fn is_valid(usb: USB) -> bool {
+        matches!(usb, USB::USB1) || (cfg!(feature = "imxrt1060") && matches!(usb, USB::USB2))
+    }
+}
+
+/// ```no_run
+/// use imxrt_ccm::ral;
+/// use imxrt_ral::ccm;
+/// #[cfg(feature = "imxrt1060")]
+/// use imxrt_ral::usb::USB1 as USB;
+/// #[cfg(not(feature = "imxrt1060"))]
+/// use imxrt_ral::usb::USB;
+///
+/// let mut handle: ral::CCM = ral::CCM::from_ral(unsafe { ccm::CCM::instance() });
+/// let mut usb = unsafe { USB::instance() };
+/// handle.enable_usb(&mut usb);
+/// ```
+#[cfg(doctest)]
+struct USBClockGate;
+
 #[cfg(test)]
 mod tests {
 
@@ -0,0 +1,61 @@
+//! A known-good clock tree for i.MX RT 1062 boards
+//!
+//! [`configure`] applies the run-mode clock tree from the NXP SDK's
+//! `BOARD_BootClockRUN`. It works on any i.MX RT 1062 board with a 24MHz
+//! crystal, like the i.MX RT 1060 EVK and the Teensy 4:
+//!
+//! | Clock    | Source      | Divider | Frequency |
+//! | -------- | ----------- | ------- | --------- |
+//! | ARM      | PLL1        |         | 600MHz    |
+//! | IPG      | ARM         | 4       | 150MHz    |
+//! | PERCLK   | IPG         | 2       | 75MHz     |
+//! | UART     | PLL3 / 6    | 1       | 80MHz     |
+//! | LPSPI    | PLL2        | 5       | 105.6MHz  |
+//! | LPI2C    | PLL3 / 8    | 1       | 60MHz     |
+//!
+//! The configuration doesn't change any clock gates. Use [`CONFIG`] as the
+//! starting point for your own configuration:
+//!
+//! ```no_run
+//! use imxrt_ccm::{ral::{self, clock_tree}, uart};
+//! use imxrt_ral::ccm;
+//!
+//! let mut ccm: ral::CCM = ral::CCM::from_ral(unsafe { ccm::CCM::instance() });
+//! let applied = clock_tree::configure(&mut ccm);
+//! assert_eq!(applied.uart.unwrap().hz, clock_tree::UART_FREQUENCY_HZ);
+//!
+//! // Or, run the UART clock from the oscillator
+//! ccm.apply(&clock_tree::CONFIG.uart(uart::Selection::Oscillator, 1)).unwrap();
+//! ```
+
+use super::CCM;
+use crate::{i2c, perclock, spi, uart, Applied, CcmConfig};
+
+/// The ARM clock frequency
+pub const ARM_FREQUENCY_HZ: u32 = 600_000_000;
+/// The periodic clock frequency
+pub const PERCLOCK_FREQUENCY_HZ: u32 = 75_000_000;
+/// The UART clock frequency
+pub const UART_FREQUENCY_HZ: u32 = 80_000_000;
+/// The SPI clock frequency
+pub const SPI_FREQUENCY_HZ: u32 = 105_600_000;
+/// The I2C clock frequency
+pub const I2C_FREQUENCY_HZ: u32 = 60_000_000;
+
+/// The NXP SDK run-mode clock tree
+pub const CONFIG: CcmConfig<'static> = CcmConfig::new()
+    .arm_frequency(ARM_FREQUENCY_HZ)
+    .perclock(perclock::Selection::IPG, 2)
+    .uart(uart::Selection::Pll3, 1)
+    .spi(spi::Selection::Pll2, 5)
+    .i2c(i2c::Selection::Pll3, 1);
+
+/// Apply [`CONFIG`], and return the achieved frequencies
+///
+/// `configure` enables PLL3 for the UART and I2C clock roots.
+pub fn configure<const GPT_N: u8, const UART_N: u8, const SPI_N: u8, const I2C_N: u8>(
+    ccm: &mut CCM<GPT_N, UART_N, SPI_N, I2C_N>,
+) -> Applied {
+    // Unwrap OK: 600MHz is in range for the i.MX RT 1060
+    ccm.apply(&CONFIG).unwrap()
+}
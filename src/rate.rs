@@ -0,0 +1,117 @@
+//! `fugit` rate support
+//!
+//! Each method mirrors a method that uses frequencies in Hz, and only
+//! converts the units.
+
+use crate::{
+    arm, i2c::I2CClock, perclock::PerClock, pll2::PLL2, pll3::PLL3, pll4::PLL4, pll6::PLL6,
+    spi::SPIClock, uart::UARTClock, Clocks, Instance, RootFrequency, CCM,
+};
+use fugit::HertzU32;
+
+impl From<arm::ARMClock> for HertzU32 {
+    fn from(arm: arm::ARMClock) -> Self {
+        HertzU32::from_raw(arm.0)
+    }
+}
+
+impl From<arm::IPGClock> for HertzU32 {
+    fn from(ipg: arm::IPGClock) -> Self {
+        HertzU32::from_raw(ipg.0)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "fugit")))]
+impl RootFrequency {
+    /// Returns the clock root frequency as a rate
+    pub const fn rate(&self) -> HertzU32 {
+        HertzU32::from_raw(self.hz)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "fugit")))]
+impl<C: Clocks> CCM<C> {
+    /// Set the ARM clock frequency, returning the achieved frequencies, or an error
+    /// if `rate` is out of range
+    ///
+    /// See [`try_set_frequency_arm`](CCM::try_set_frequency_arm).
+    #[inline(always)]
+    pub fn try_set_rate_arm(
+        &mut self,
+        rate: HertzU32,
+    ) -> Result<arm::Achieved, arm::FrequencyOutOfRange> {
+        self.try_set_frequency_arm(rate.raw())
+    }
+}
+
+macro_rules! root_rate {
+    ($clock:ident<$($param:ident),+>, $($bound:ident: $inst:path),+) => {
+        #[cfg_attr(docsrs, doc(cfg(feature = "fugit")))]
+        impl<$($param),+> $clock<$($param),+>
+        where
+            $($bound: Instance<Inst = $inst>),+
+        {
+            /// Configure the clock root to approximate `target`, returning the
+            /// achieved frequency
+            ///
+            /// See `configure_frequency`.
+            #[inline(always)]
+            pub fn configure_rate(&mut self, target: HertzU32) -> RootFrequency {
+                self.configure_frequency(target.raw())
+            }
+
+            /// Returns the clock root frequency as a rate
+            #[inline(always)]
+            pub fn rate(&self) -> HertzU32 {
+                HertzU32::from_raw(self.frequency())
+            }
+        }
+    };
+}
+
+root_rate!(UARTClock<U>, U: crate::uart::UART);
+root_rate!(SPIClock<S>, S: crate::spi::SPI);
+root_rate!(I2CClock<I>, I: crate::i2c::I2C);
+root_rate!(PerClock<P, G>, P: crate::perclock::PIT, G: crate::perclock::GPT);
+
+macro_rules! pll_rate {
+    ($($pll:ty),+) => {
+        $(
+            #[cfg_attr(docsrs, doc(cfg(feature = "fugit")))]
+            impl $pll {
+                /// Returns the PLL frequency as a rate
+                #[inline(always)]
+                pub fn rate(&self) -> HertzU32 {
+                    HertzU32::from_raw(self.frequency())
+                }
+            }
+        )+
+    };
+}
+
+pll_rate!(PLL2, PLL3, PLL4, PLL6);
+#[cfg(feature = "imxrt1060")]
+pll_rate!(crate::pll5::PLL5);
+
+#[cfg(test)]
+mod tests {
+    use crate::{arm, RootFrequency};
+    use fugit::{HertzU32, RateExtU32};
+
+    #[test]
+    fn rate_conversions() {
+        let root = RootFrequency {
+            hz: 8_000_000,
+            error_hz: 0,
+        };
+        assert_eq!(root.rate(), 8.MHz::<1, 1>());
+        assert_eq!(
+            HertzU32::from(arm::ARMClock(600_000_000)),
+            600.MHz::<1, 1>()
+        );
+        assert_eq!(
+            HertzU32::from(arm::IPGClock(150_000_000)),
+            150.MHz::<1, 1>()
+        );
+    }
+}
@@ -0,0 +1,402 @@
+//! Raw CCM and CCM_ANALOG registers
+//!
+//! Use `raw` to control clocks that this crate doesn't model, like the SEMC or
+//! FlexSPI clock roots, without repeating address math. The module describes
+//! every CCM and CCM_ANALOG register, and the fields of clock roots that
+//! don't have a clock handle. Writes go through the same path as the rest of
+//! the crate, so they appear in register traces.
+//!
+//! The field descriptions follow the i.MX RT 1060 reference manual. Some
+//! fields don't exist on other chips; check your reference manual before you
+//! use them.
+//!
+//! All accessors are `unsafe`. You're responsible for
+//!
+//! - making sure that you don't race with this crate, or with other code, when
+//!   modifying a register
+//! - waiting for any divider or multiplexer handshake; see
+//!   [`glitchless::wait_for_handshake`](crate::glitchless::wait_for_handshake)
+//! - turning off the clock gates of peripherals whose clocks change
+//!
+//! # Example
+//!
+//! Run the FlexSPI clock root from PLL3 PFD0, divided by 4.
+//!
+//! ```no_run
+//! use imxrt_ccm::raw::cscmr1;
+//!
+//! unsafe {
+//!     cscmr1::FLEXSPI_CLK_SEL.write(3);
+//!     cscmr1::FLEXSPI_PODF.write(3);
+//! }
+//! ```
+
+use crate::register::{self, Field};
+
+/// A CCM or CCM_ANALOG register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reg {
+    address: *mut u32,
+}
+
+impl Reg {
+    /// Describe a register at `address`
+    ///
+    /// # Safety
+    ///
+    /// `address` must be valid for volatile reads and writes.
+    pub const unsafe fn new(address: *mut u32) -> Self {
+        Reg { address }
+    }
+
+    /// Returns the register address
+    pub const fn address(self) -> *mut u32 {
+        self.address
+    }
+
+    /// Read the register
+    ///
+    /// # Safety
+    ///
+    /// Reads global, mutable memory.
+    #[inline(always)]
+    pub unsafe fn read(self) -> u32 {
+        register::load(self.address)
+    }
+
+    /// Write `value` to the register
+    ///
+    /// # Safety
+    ///
+    /// Modifies global, mutable memory.
+    #[inline(always)]
+    pub unsafe fn write(self, value: u32) {
+        register::write(self.address, u32::MAX, value)
+    }
+
+    /// Read the register, change the value with `func`, then write it back
+    ///
+    /// # Safety
+    ///
+    /// Modifies global, mutable memory. The read-modify-write operation is not
+    /// atomic.
+    #[inline(always)]
+    pub unsafe fn modify(self, func: impl FnOnce(u32) -> u32) {
+        self.write(func(self.read()))
+    }
+}
+
+/// A field in a [`Reg`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegField {
+    reg: Reg,
+    offset: u32,
+    mask: u32,
+}
+
+impl RegField {
+    /// Describe a field `mask` bits wide, starting at bit `offset`
+    ///
+    /// Callers should not apply offset to the mask.
+    pub const fn new(reg: Reg, offset: u32, mask: u32) -> Self {
+        RegField { reg, offset, mask }
+    }
+
+    /// Returns the register that holds this field
+    pub const fn reg(self) -> Reg {
+        self.reg
+    }
+
+    const fn field(self) -> Field {
+        Field::new(self.offset, self.mask)
+    }
+
+    /// Read the field
+    ///
+    /// # Safety
+    ///
+    /// Reads global, mutable memory.
+    #[inline(always)]
+    pub unsafe fn read(self) -> u32 {
+        self.field().read(self.reg.address)
+    }
+
+    /// Returns the field from the register value `value`
+    ///
+    /// `get` doesn't read the register. Use it to decode a value that you
+    /// read earlier, like a value from a [`DebugSnapshot`](crate::DebugSnapshot).
+    pub const fn get(self, value: u32) -> u32 {
+        self.field().get(value)
+    }
+
+    /// Write `value` into the field, leaving all other fields unchanged
+    ///
+    /// # Safety
+    ///
+    /// Modifies global, mutable memory. The read-modify-write operation is not
+    /// atomic.
+    #[inline(always)]
+    pub unsafe fn write(self, value: u32) {
+        self.field().modify(self.reg.address, value)
+    }
+}
+
+macro_rules! regs {
+    ($base:expr, $($(#[$attr:meta])* $name:ident = $offset:expr),+ $(,)?) => {
+        $(
+            $(#[$attr])*
+            pub const $name: Reg = unsafe { Reg::new(($base + $offset) as *mut u32) };
+        )+
+    };
+}
+
+macro_rules! fields {
+    ($reg:ident, $($(#[$attr:meta])* $name:ident = ($offset:expr, $mask:expr)),+ $(,)?) => {
+        $(
+            $(#[$attr])*
+            pub const $name: RegField = RegField::new(super::$reg, $offset, $mask);
+        )+
+    };
+}
+
+const CCM: usize = 0x400F_C000;
+const CCM_ANALOG: usize = 0x400D_8000;
+
+regs!(CCM,
+    /// CCM control register
+    CCR = 0x00,
+    /// CCM status register
+    CSR = 0x08,
+    /// CCM clock switcher register
+    CCSR = 0x0C,
+    /// CCM ARM clock root register
+    CACRR = 0x10,
+    /// CCM bus clock divider register
+    CBCDR = 0x14,
+    /// CCM bus clock multiplexer register
+    CBCMR = 0x18,
+    /// CCM serial clock multiplexer register 1
+    CSCMR1 = 0x1C,
+    /// CCM serial clock multiplexer register 2
+    CSCMR2 = 0x20,
+    /// CCM serial clock divider register 1
+    CSCDR1 = 0x24,
+    /// CCM clock divider register 1
+    CS1CDR = 0x28,
+    /// CCM clock divider register 2
+    CS2CDR = 0x2C,
+    /// CCM D1 clock divider register
+    CDCDR = 0x30,
+    /// CCM serial clock divider register 2
+    CSCDR2 = 0x38,
+    /// CCM serial clock divider register 3
+    CSCDR3 = 0x3C,
+    /// CCM divider handshake in-process register
+    CDHIPR = 0x48,
+    /// CCM low power control register
+    CLPCR = 0x54,
+    /// CCM interrupt status register
+    CISR = 0x58,
+    /// CCM interrupt mask register
+    CIMR = 0x5C,
+    /// CCM clock output source register
+    CCOSR = 0x60,
+    /// CCM general purpose register
+    CGPR = 0x64,
+    /// CCM clock gating register 0
+    CCGR0 = 0x68,
+    /// CCM clock gating register 1
+    CCGR1 = 0x6C,
+    /// CCM clock gating register 2
+    CCGR2 = 0x70,
+    /// CCM clock gating register 3
+    CCGR3 = 0x74,
+    /// CCM clock gating register 4
+    CCGR4 = 0x78,
+    /// CCM clock gating register 5
+    CCGR5 = 0x7C,
+    /// CCM clock gating register 6
+    CCGR6 = 0x80,
+    /// CCM clock gating register 7
+    CCGR7 = 0x84,
+    /// CCM module enable override register
+    CMEOR = 0x88,
+);
+
+regs!(CCM_ANALOG,
+    /// Analog ARM PLL control register
+    PLL_ARM = 0x00,
+    /// Analog USB1 480MHz PLL control register
+    PLL_USB1 = 0x10,
+    /// Analog USB2 480MHz PLL control register
+    PLL_USB2 = 0x20,
+    /// Analog system PLL control register
+    PLL_SYS = 0x30,
+    /// 528MHz system PLL spread spectrum register
+    PLL_SYS_SS = 0x40,
+    /// Numerator of the 528MHz system PLL fractional loop divider
+    PLL_SYS_NUM = 0x50,
+    /// Denominator of the 528MHz system PLL fractional loop divider
+    PLL_SYS_DENOM = 0x60,
+    /// Analog audio PLL control register
+    PLL_AUDIO = 0x70,
+    /// Numerator of the audio PLL fractional loop divider
+    PLL_AUDIO_NUM = 0x80,
+    /// Denominator of the audio PLL fractional loop divider
+    PLL_AUDIO_DENOM = 0x90,
+    /// Analog video PLL control register
+    PLL_VIDEO = 0xA0,
+    /// Numerator of the video PLL fractional loop divider
+    PLL_VIDEO_NUM = 0xB0,
+    /// Denominator of the video PLL fractional loop divider
+    PLL_VIDEO_DENOM = 0xC0,
+    /// Analog ENET PLL control register
+    PLL_ENET = 0xE0,
+    /// 480MHz clock (PLL3) phase fractional divider control register
+    PFD_480 = 0xF0,
+    /// 528MHz clock (PLL2) phase fractional divider control register
+    PFD_528 = 0x100,
+    /// Miscellaneous register 0
+    MISC0 = 0x150,
+    /// Miscellaneous register 1
+    MISC1 = 0x160,
+    /// Miscellaneous register 2
+    MISC2 = 0x170,
+);
+
+/// [`CBCDR`] fields
+pub mod cbcdr {
+    use super::RegField;
+    fields!(CBCDR,
+        /// SEMC clock source select
+        SEMC_CLK_SEL = (6, 0x1),
+        /// SEMC alternate clock source select
+        SEMC_ALT_CLK_SEL = (7, 0x1),
+        /// Post divider for the SEMC clock
+        SEMC_PODF = (16, 0x7),
+    );
+}
+
+/// [`CSCMR1`] fields
+pub mod cscmr1 {
+    use super::RegField;
+    fields!(CSCMR1,
+        /// Selector for the SAI1 clock multiplexer
+        SAI1_CLK_SEL = (10, 0x3),
+        /// Selector for the SAI2 clock multiplexer
+        SAI2_CLK_SEL = (12, 0x3),
+        /// Selector for the SAI3 clock multiplexer
+        SAI3_CLK_SEL = (14, 0x3),
+        /// Selector for the USDHC1 clock multiplexer
+        USDHC1_CLK_SEL = (16, 0x1),
+        /// Selector for the USDHC2 clock multiplexer
+        USDHC2_CLK_SEL = (17, 0x1),
+        /// Divider for the FlexSPI clock root
+        FLEXSPI_PODF = (23, 0x7),
+        /// Selector for the FlexSPI clock multiplexer
+        FLEXSPI_CLK_SEL = (29, 0x3),
+    );
+}
+
+/// [`CSCMR2`] fields
+pub mod cscmr2 {
+    use super::RegField;
+    fields!(CSCMR2,
+        /// Divider for the CAN clock root
+        CAN_CLK_PODF = (2, 0x3F),
+        /// Selector for the CAN clock multiplexer
+        CAN_CLK_SEL = (8, 0x3),
+        /// Selector for the FlexIO2 clock multiplexer
+        FLEXIO2_CLK_SEL = (19, 0x3),
+    );
+}
+
+/// [`CSCDR1`] fields
+pub mod cscdr1 {
+    use super::RegField;
+    fields!(CSCDR1,
+        /// Divider for the USDHC1 clock root
+        USDHC1_PODF = (11, 0x7),
+        /// Divider for the USDHC2 clock root
+        USDHC2_PODF = (16, 0x7),
+        /// Divider for the trace clock root
+        TRACE_PODF = (25, 0x7),
+    );
+}
+
+/// [`CS1CDR`] fields
+pub mod cs1cdr {
+    use super::RegField;
+    fields!(CS1CDR,
+        /// Divider for the SAI1 clock root
+        SAI1_CLK_PODF = (0, 0x3F),
+        /// Pre-divider for the SAI1 clock root
+        SAI1_CLK_PRED = (6, 0x7),
+        /// Divider for the FlexIO2 clock root
+        FLEXIO2_CLK_PODF = (9, 0x7),
+        /// Divider for the SAI3 clock root
+        SAI3_CLK_PODF = (16, 0x3F),
+        /// Pre-divider for the SAI3 clock root
+        SAI3_CLK_PRED = (22, 0x7),
+        /// Pre-divider for the FlexIO2 clock root
+        FLEXIO2_CLK_PRED = (25, 0x7),
+    );
+}
+
+/// [`CS2CDR`] fields
+pub mod cs2cdr {
+    use super::RegField;
+    fields!(CS2CDR,
+        /// Divider for the SAI2 clock root
+        SAI2_CLK_PODF = (0, 0x3F),
+        /// Pre-divider for the SAI2 clock root
+        SAI2_CLK_PRED = (6, 0x7),
+    );
+}
+
+/// [`CCOSR`] fields
+pub mod ccosr {
+    use super::RegField;
+    fields!(CCOSR,
+        /// Selector for the CCM_CLKO1 output
+        CLKO1_SEL = (0, 0xF),
+        /// Divider for the CCM_CLKO1 output
+        CLKO1_DIV = (4, 0x7),
+        /// Enable the CCM_CLKO1 output
+        CLKO1_EN = (7, 0x1),
+        /// Route CCM_CLKO2 to the CCM_CLKO1 pad
+        CLK_OUT_SEL = (8, 0x1),
+        /// Selector for the CCM_CLKO2 output
+        CLKO2_SEL = (16, 0x1F),
+        /// Divider for the CCM_CLKO2 output
+        CLKO2_DIV = (21, 0x7),
+        /// Enable the CCM_CLKO2 output
+        CLKO2_EN = (24, 0x1),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Reg, RegField, CSCMR1};
+
+    #[test]
+    fn raw_addresses() {
+        assert_eq!(CSCMR1.address() as usize, 0x400F_C01C);
+        assert_eq!(super::CCGR7.address() as usize, 0x400F_C084);
+        assert_eq!(super::MISC2.address() as usize, 0x400D_8170);
+        assert_eq!(super::cscmr1::FLEXSPI_PODF.reg(), CSCMR1);
+    }
+
+    #[test]
+    fn raw_field_read_write() {
+        let mut mem: u32 = u32::MAX;
+        unsafe {
+            let reg = Reg::new(&mut mem);
+            let field = RegField::new(reg, 23, 0x7);
+            field.write(2);
+            assert_eq!(field.read(), 2);
+            reg.modify(|value| value & !1);
+        }
+        assert_eq!(mem, 0xFD7F_FFFE);
+    }
+}
@@ -1,5 +1,89 @@
 //! Helpers for clock registers
 
+/// Write `value` to the register at `mem`
+///
+/// `mask` describes the bits that the write intends to change. Use
+/// `u32::MAX` when writing the whole register.
+#[inline(always)]
+pub unsafe fn write(mem: *mut u32, mask: u32, value: u32) {
+    #[cfg(feature = "dry-run")]
+    if crate::dryrun::intercept(mem, mask, value) {
+        return;
+    }
+    trace(mem, mask, value);
+    crate::map::changed(mem as usize);
+    #[cfg(feature = "simulation")]
+    if crate::simulation::store(mem as usize, value) {
+        return;
+    }
+    mem.write_volatile(value);
+}
+
+/// Read the register at `mem` before modifying it
+///
+/// During a dry run, this returns the last value recorded for `mem`, if
+/// any. Use it for read-modify-write operations.
+#[inline(always)]
+pub unsafe fn read(mem: *const u32) -> u32 {
+    match recorded(mem) {
+        Some(value) => value,
+        None => load(mem),
+    }
+}
+
+/// Read the register at `mem`
+///
+/// In a simulation, this returns the simulated register.
+#[inline(always)]
+pub unsafe fn load(mem: *const u32) -> u32 {
+    #[cfg(feature = "simulation")]
+    if let Some(value) = crate::simulation::load(mem as usize) {
+        return value;
+    }
+    mem.read_volatile()
+}
+
+/// Returns the last value recorded for `mem` during a dry run
+#[inline(always)]
+pub fn recorded(mem: *const u32) -> Option<u32> {
+    #[cfg(feature = "dry-run")]
+    {
+        crate::dryrun::recorded(mem)
+    }
+    #[cfg(not(feature = "dry-run"))]
+    {
+        let _ = mem;
+        None
+    }
+}
+
+/// Log a register write before it happens
+#[cfg(any(feature = "log", feature = "defmt"))]
+#[inline(never)]
+unsafe fn trace(mem: *mut u32, mask: u32, value: u32) {
+    let old = read(mem);
+    #[cfg(feature = "log")]
+    log::trace!(
+        "CCM write {:#010X} mask {:#010X}: {:#010X} -> {:#010X}",
+        mem as usize,
+        mask,
+        old,
+        value
+    );
+    #[cfg(feature = "defmt")]
+    defmt::trace!(
+        "CCM write {=u32:#010X} mask {=u32:#010X}: {=u32:#010X} -> {=u32:#010X}",
+        mem as u32,
+        mask,
+        old,
+        value
+    );
+}
+
+#[cfg(not(any(feature = "log", feature = "defmt")))]
+#[inline(always)]
+unsafe fn trace(_: *mut u32, _: u32, _: u32) {}
+
 /// A field in a CCM register
 #[derive(Clone, Copy)]
 pub struct Field {
@@ -22,22 +106,28 @@ impl Field {
     /// Clear the field in `mem`, and write `value` in its place
     #[inline(always)]
     pub unsafe fn modify(&self, mem: *mut u32, value: u32) {
-        let mut v = mem.read_volatile();
+        let mut v = read(mem);
         v &= !self.mask;
         v |= (value << self.offset) & self.mask;
-        mem.write_volatile(v);
+        write(mem, self.mask, v);
     }
 
     /// Write `value` into `mem`, setting all other fields to zero
     #[inline(always)]
     pub unsafe fn write_zero(&self, mem: *mut u32, value: u32) {
-        mem.write_volatile((value << self.offset) & self.mask)
+        write(mem, u32::MAX, (value << self.offset) & self.mask)
     }
 
     /// Read the field from `mem`
     #[inline(always)]
     pub unsafe fn read(&self, mem: *const u32) -> u32 {
-        (mem.read_volatile() & self.mask) >> self.offset
+        self.get(load(mem))
+    }
+
+    /// Returns the field from the register value `value`
+    #[inline(always)]
+    pub const fn get(&self, value: u32) -> u32 {
+        (value & self.mask) >> self.offset
     }
 }
 
@@ -67,11 +157,29 @@ impl Register {
     /// Caller must ensure that this read-modify-write operation is atomic
     #[inline(always)]
     pub unsafe fn set(&self, divider: u32, select: u32) {
-        let mut reg = self.address.read_volatile();
+        let mut reg = read(self.address);
         reg &= !(self.divider.mask | self.select.mask);
         reg |= (divider << self.divider.offset) & self.divider.mask;
         reg |= (select << self.select.offset) & self.select.mask;
-        self.address.write_volatile(reg);
+        write(self.address, self.divider.mask | self.select.mask, reg);
+    }
+    /// Set only the clock divider, leaving the selection unchanged
+    ///
+    /// # Safety
+    ///
+    /// Caller must ensure that this read-modify-write operation is atomic
+    #[inline(always)]
+    pub unsafe fn set_divider(&self, divider: u32) {
+        self.divider.modify(self.address, divider);
+    }
+    /// Set only the clock selection, leaving the divider unchanged
+    ///
+    /// # Safety
+    ///
+    /// Caller must ensure that this read-modify-write operation is atomic
+    #[inline(always)]
+    pub unsafe fn set_selection(&self, select: u32) {
+        self.select.modify(self.address, select);
     }
     /// Returns the clock divider
     #[inline(always)]
@@ -99,7 +207,7 @@ mod tests {
         let mut reg = 0;
         unsafe {
             let reg = Register::new(LPI2C_CLK_PODF, LPI2C_CLK_SEL, &mut reg);
-            reg.set(u32::max_value(), u32::max_value());
+            reg.set(u32::MAX, u32::MAX);
         }
         assert_eq!(reg, 0x01FC_0000);
         unsafe {
@@ -107,7 +215,7 @@ mod tests {
             reg.set(0, 0);
         }
         assert_eq!(reg, 0);
-        reg = u32::max_value();
+        reg = u32::MAX;
         unsafe {
             let reg = Register::new(LPI2C_CLK_PODF, LPI2C_CLK_SEL, &mut reg);
             reg.set(3, 1);
@@ -117,7 +225,7 @@ mod tests {
 
     #[test]
     fn divider() {
-        let mut reg = u32::max_value();
+        let mut reg = u32::MAX;
         unsafe {
             let reg = Register::new(LPI2C_CLK_PODF, LPI2C_CLK_SEL, &mut reg);
             reg.set(3, 1);
@@ -127,7 +235,7 @@ mod tests {
 
     #[test]
     fn selection() {
-        let mut reg = u32::max_value();
+        let mut reg = u32::MAX;
         unsafe {
             let reg = Register::new(LPI2C_CLK_PODF, LPI2C_CLK_SEL, &mut reg);
             reg.set(3, 1);
@@ -135,13 +243,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn set_divider_selection() {
+        let mut reg = u32::MAX;
+        unsafe {
+            let reg = Register::new(LPI2C_CLK_PODF, LPI2C_CLK_SEL, &mut reg);
+            reg.set(3, 1);
+            reg.set_divider(5);
+            assert_eq!((reg.divider(), reg.selection()), (5, 1));
+            reg.set_selection(0);
+            assert_eq!((reg.divider(), reg.selection()), (5, 0));
+        }
+        assert_eq!(reg, 0xFE2B_FFFF);
+    }
+
     #[test]
     fn modify() {
         let mut mem = 0;
-        unsafe { LPI2C_CLK_PODF.modify(&mut mem, u32::max_value()) };
+        unsafe { LPI2C_CLK_PODF.modify(&mut mem, u32::MAX) };
         assert_eq!(mem, 0x3f << 19);
         mem = 0;
-        unsafe { LPI2C_CLK_SEL.modify(&mut mem, u32::max_value()) };
+        unsafe { LPI2C_CLK_SEL.modify(&mut mem, u32::MAX) };
         assert!(mem.is_power_of_two());
     }
 }
@@ -0,0 +1,173 @@
+//! Reference-counted clock gates
+//!
+//! Use a [`SharedClockGate`] when more than one driver depends on the same
+//! clock gate. Each driver acquires a [`ClockGateLease`]. The clock gate turns
+//! on when the first driver acquires a lease, and it turns off when the last
+//! lease drops.
+//!
+//! Acquire a `SharedClockGate` from the clock that controls the peripheral's
+//! clock gate. For example, a PWM module shared by two drivers might look like
+//!
+//! ```no_run
+//! # use imxrt_ccm as ccm;
+//! # struct PWM;
+//! # unsafe impl ccm::Instance for PWM {
+//! #     type Inst = ccm::PWM;
+//! #     fn instance(&self) -> Self::Inst { ccm::PWM::PWM2 }
+//! #     fn is_valid(inst: Self::Inst) -> bool { inst == ccm::PWM::PWM2 }
+//! # }
+//! # struct MyClocks;
+//! # impl ccm::Clocks for MyClocks {
+//! #   type I2C = ();
+//! #   type SPI = ();
+//! #   type UART = ();
+//! #   type GPT = ();
+//! #   type PIT = ();
+//! # }
+//! # let mut ccm = unsafe { ccm::CCM::<MyClocks>::new() };
+//! # let mut pwm2 = PWM;
+//! let pwm = ccm.shared_gate_pwm(pwm2);
+//!
+//! let channel_a = pwm.acquire(); // Clock gate turns on
+//! let channel_b = pwm.acquire();
+//! drop(channel_a);
+//! assert_eq!(pwm.users(), 1);
+//! drop(channel_b); // Clock gate turns off
+//! ```
+//!
+//! A `SharedClockGate` owns its peripheral instance, and it's `Sync` when the
+//! instance is `Sync`. Use [`SharedClockGate::new`] to put it in a `static`
+//! that's shared by thread and interrupt contexts:
+//!
+//! ```no_run
+//! # use imxrt_ccm as ccm;
+//! use ccm::shared::SharedClockGate;
+//! # struct PWM;
+//! # unsafe impl ccm::Instance for PWM {
+//! #     type Inst = ccm::PWM;
+//! #     fn instance(&self) -> Self::Inst { ccm::PWM::PWM2 }
+//! #     fn is_valid(inst: Self::Inst) -> bool { inst == ccm::PWM::PWM2 }
+//! # }
+//!
+//! // Safety: nothing else controls the PWM2 clock gate
+//! static PWM2: SharedClockGate<PWM> = unsafe { SharedClockGate::new(PWM) };
+//!
+//! fn on_interrupt() {
+//!     let _lease = PWM2.acquire();
+//!     // Use PWM2...
+//! }
+//! ```
+//!
+//! Enable the `"critical-section"` feature if you acquire or drop leases in
+//! more than one execution context. Each count change and clock gate change
+//! is then one critical section.
+
+use crate::{gate, get_clock_gate, set_clock_gate, ClockGate, Instance};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// A clock gate shared by multiple users
+///
+/// See the [module-level documentation](index.html) for more information.
+///
+/// The clock gate is on as long as there is at least one [`ClockGateLease`].
+/// `SharedClockGate` does not change the clock gate when it's created. If the
+/// clock gate was on before you created the `SharedClockGate`, it stays on until
+/// the first lease is acquired, then released.
+pub struct SharedClockGate<I: Instance> {
+    inst: I,
+    users: AtomicUsize,
+}
+
+impl<I: Instance> SharedClockGate<I> {
+    /// Share the clock gate of `inst`
+    ///
+    /// Prefer the `shared_gate` methods of the [`CCM`](crate::CCM), and of
+    /// the clock root handles. Use `new` to create a `SharedClockGate` in a
+    /// `static`.
+    ///
+    /// # Safety
+    ///
+    /// Caller must make sure that nothing else changes the instance's clock
+    /// gate while the `SharedClockGate` exists.
+    #[inline(always)]
+    pub const unsafe fn new(inst: I) -> Self {
+        SharedClockGate {
+            inst,
+            users: AtomicUsize::new(0),
+        }
+    }
+
+    /// Acquire a lease on the clock gate
+    ///
+    /// If this is the first lease, the clock gate turns on.
+    pub fn acquire(&self) -> ClockGateLease<'_, I> {
+        gate::locked(|| {
+            if self.users.fetch_add(1, Ordering::AcqRel) == 0 {
+                // Safety: we own the instance
+                unsafe { set_clock_gate::<I>(self.inst.instance(), ClockGate::On) };
+            }
+        });
+        ClockGateLease { shared: self }
+    }
+
+    /// Returns the number of active leases
+    pub fn users(&self) -> usize {
+        self.users.load(Ordering::Acquire)
+    }
+
+    /// Returns the clock gate setting
+    pub fn clock_gate(&self) -> ClockGate {
+        // Unwrap OK: we have the instance, or the `Instance`
+        // implementation is incorrect.
+        get_clock_gate::<I>(self.inst.instance()).unwrap()
+    }
+
+    /// Returns a reference to the peripheral instance
+    pub fn instance(&self) -> &I {
+        &self.inst
+    }
+
+    /// Returns the peripheral instance
+    ///
+    /// There are no leases, since each lease borrows the `SharedClockGate`.
+    /// The clock gate keeps its setting.
+    pub fn into_inner(self) -> I {
+        self.inst
+    }
+
+    fn release(&self) {
+        gate::locked(|| {
+            if self.users.fetch_sub(1, Ordering::AcqRel) == 1 {
+                // Safety: we own the instance
+                unsafe { set_clock_gate::<I>(self.inst.instance(), ClockGate::Off) };
+            }
+        });
+    }
+}
+
+/// A lease on a shared clock gate
+///
+/// When the last `ClockGateLease` drops, the shared clock gate turns off.
+pub struct ClockGateLease<'s, I: Instance> {
+    shared: &'s SharedClockGate<I>,
+}
+
+impl<I: Instance> ClockGateLease<'_, I> {
+    /// Returns a reference to the peripheral instance
+    pub fn instance(&self) -> &I {
+        self.shared.instance()
+    }
+}
+
+impl<I: Instance> Clone for ClockGateLease<'_, I> {
+    /// Acquires another lease on the same clock gate
+    fn clone(&self) -> Self {
+        self.shared.acquire()
+    }
+}
+
+impl<I: Instance> Drop for ClockGateLease<'_, I> {
+    fn drop(&mut self) {
+        self.shared.release();
+    }
+}
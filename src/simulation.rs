@@ -0,0 +1,334 @@
+//! Simulated clock registers for host tests
+//!
+//! The `"simulation"` feature routes every CCM and CCM_ANALOG register access
+//! to an in-memory register file, instead of hardware. Use it to unit test your
+//! clock setup on a development host, or on CI, without a board. Enable the
+//! feature for your tests, not for your firmware:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! imxrt-ccm = { version = "0.1", features = ["imxrt1060", "simulation"] }
+//! ```
+//!
+//! Then, use the crate like you would on hardware:
+//!
+//! ```
+//! use imxrt_ccm::{perclock, simulation, CcmConfig, SplitClocks, CCM};
+//!
+//! simulation::reset();
+//! let mut ccm = unsafe { CCM::<SplitClocks>::new() };
+//! let config = CcmConfig::new().perclock(perclock::Selection::Oscillator, 24);
+//! let applied = ccm.apply(&config).unwrap();
+//! assert_eq!(applied.perclock.unwrap().hz, 1_000_000);
+//!
+//! // Inspect the registers that your clock setup wrote
+//! let cscmr1 = simulation::read(0x400F_C01C);
+//! assert_eq!(cscmr1 & 0x7F, 1 << 6 | 23); // PERCLK_SEL, PERCLK_PODF
+//! ```
+//!
+//! Each thread has its own register file, so tests that run in parallel don't
+//! share clock state. A thread's register file starts at the reset values from
+//! the i.MX RT 1060 reference manual. Call [`reset`] at the start of each test
+//! to restore the reset values.
+//!
+//! After reset, PLL2 and PLL3 are powered down. On hardware, the boot ROM
+//! starts them before your firmware runs. If your clock setup expects the
+//! boot ROM's state, [`write()`] it before the test:
+//!
+//! ```
+//! use imxrt_ccm::{pll::{self, Pll}, pll3, simulation};
+//!
+//! simulation::reset();
+//! simulation::write(0x400D_8030, 0x0000_2001); // PLL2 at 528MHz
+//! simulation::write(0x400D_8010, 0x0000_3040); // PLL3 at 480MHz
+//! assert!(pll::is_locked(Pll::Sys));
+//! assert!(pll3::is_enabled());
+//! ```
+//!
+//! # Simulated hardware
+//!
+//! The register file covers
+//!
+//! - the CCM.
+//! - the CCM_ANALOG, PMU, XTALOSC24M, and USB_ANALOG registers. Writes to their
+//!   SET, CLR, and TOG addresses modify the register.
+//! - the USB PHYs, which [`CCM::enable_usb`](crate::CCM::enable_usb) writes.
+//! - the i.MX RT 11xx clock roots and LPCGs. These registers start at zero.
+//!
+//! A PLL locks as soon as it's powered, and every divider and multiplexer
+//! handshake completes immediately. Accesses to any other address, like a
+//! buffer in RAM, reach memory.
+//!
+//! The simulation doesn't model clock behavior beyond the registers. It doesn't
+//! reject register values that hardware wouldn't accept, and clock gates have
+//! no effect. With the simulation, the `"gate-cache"` feature doesn't cache the
+//! clock gates.
+
+#[cfg(target_os = "none")]
+compile_error!(
+    "The \"simulation\" feature is for host targets. Don't enable it for your firmware."
+);
+
+use core::{cell::RefCell, ops::Range};
+use std::{collections::HashMap, thread_local};
+
+/// A block of simulated registers
+struct Block {
+    addresses: Range<usize>,
+    /// The block's registers have SET, CLR, and TOG addresses
+    aliases: bool,
+}
+
+const BLOCKS: &[Block] = &[
+    // CCM
+    Block {
+        addresses: 0x400F_C000..0x400F_C090,
+        aliases: false,
+    },
+    // CCM_ANALOG, PMU, XTALOSC24M, and USB_ANALOG
+    Block {
+        addresses: 0x400D_8000..0x400D_8300,
+        aliases: true,
+    },
+    // USBPHY1 and USBPHY2
+    Block {
+        addresses: 0x400D_9000..0x400D_B000,
+        aliases: true,
+    },
+    // 11xx clock roots and LPCGs
+    Block {
+        addresses: 0x40CC_0000..0x40CC_8000,
+        aliases: false,
+    },
+];
+
+/// Register values after reset
+///
+/// Registers that aren't listed reset to zero.
+const RESET: &[(usize, u32)] = &[
+    (0x400F_C000, 0x0401_107E), // CCR
+    (0x400F_C008, 0x0000_0010), // CSR
+    (0x400F_C00C, 0x0000_0100), // CCSR
+    (0x400F_C010, 0x0000_0001), // CACRR
+    (0x400F_C014, 0x000A_8300), // CBCDR
+    (0x400F_C018, 0x2DAE_8324), // CBCMR
+    (0x400F_C01C, 0x0490_0080), // CSCMR1
+    (0x400F_C020, 0x1319_2F06), // CSCMR2
+    (0x400F_C024, 0x0649_0B00), // CSCDR1
+    (0x400F_C028, 0x0EC1_02C1), // CS1CDR
+    (0x400F_C02C, 0x0007_36C1), // CS2CDR
+    (0x400F_C030, 0x33F7_1F92), // CDCDR
+    (0x400F_C038, 0x0002_9150), // CSCDR2
+    (0x400F_C03C, 0x0001_4841), // CSCDR3
+    (0x400F_C054, 0x0000_0079), // CLPCR
+    (0x400F_C060, 0x000A_0001), // CCOSR
+    (0x400F_C064, 0x0000_FE62), // CGPR
+    (0x400F_C068, 0xFFFF_FFFF), // CCGR0
+    (0x400F_C06C, 0xFFFF_FFFF), // CCGR1
+    (0x400F_C070, 0xFFFF_FFFF), // CCGR2
+    (0x400F_C074, 0xFFFF_FFFF), // CCGR3
+    (0x400F_C078, 0xFFFF_FFFF), // CCGR4
+    (0x400F_C07C, 0xFFFF_FFFF), // CCGR5
+    (0x400F_C080, 0xFFFF_FFFF), // CCGR6
+    (0x400F_C088, 0xFFFF_FFFF), // CMEOR
+    (0x400D_8000, 0x0001_3063), // PLL_ARM
+    (0x400D_8010, 0x0001_2000), // PLL_USB1
+    (0x400D_8020, 0x0001_2000), // PLL_USB2
+    (0x400D_8030, 0x0001_3001), // PLL_SYS
+    (0x400D_8060, 0x0000_0012), // PLL_SYS_DENOM
+    (0x400D_8070, 0x0001_1006), // PLL_AUDIO
+    (0x400D_8080, 0x05F5_E100), // PLL_AUDIO_NUM
+    (0x400D_8090, 0x2964_619C), // PLL_AUDIO_DENOM
+    (0x400D_80A0, 0x0001_100C), // PLL_VIDEO
+    (0x400D_80B0, 0x05F5_E100), // PLL_VIDEO_NUM
+    (0x400D_80C0, 0x10A2_4447), // PLL_VIDEO_DENOM
+    (0x400D_80E0, 0x0001_1001), // PLL_ENET
+    (0x400D_80F0, 0x0F1A_230D), // PFD_480
+    (0x400D_8100, 0x1018_101B), // PFD_528
+    (0x400D_8150, 0x0400_0000), // MISC0
+    (0x400D_8170, 0x0027_2727), // MISC2
+    (0x400D_8260, DIGPROG),     // DIGPROG
+    (0x400D_9000, 0x001E_1C00), // USBPHY1_PWD
+    (0x400D_9030, 0xC020_0000), // USBPHY1_CTRL
+    (0x400D_A000, 0x001E_1C00), // USBPHY2_PWD
+    (0x400D_A030, 0xC020_0000), // USBPHY2_CTRL
+];
+
+/// The silicon version of the chip that this crate was built for
+const DIGPROG: u32 = if cfg!(feature = "imxrt1060") {
+    0x006A_0001
+} else {
+    0x006B_0000
+};
+
+/// PLL control registers, and `true` if the PLL's bit 12 is POWER instead of
+/// POWERDOWN
+const PLLS: &[(usize, bool)] = &[
+    (0x400D_8000, false), // PLL_ARM
+    (0x400D_8010, true),  // PLL_USB1
+    (0x400D_8020, true),  // PLL_USB2
+    (0x400D_8030, false), // PLL_SYS
+    (0x400D_8070, false), // PLL_AUDIO
+    (0x400D_80A0, false), // PLL_VIDEO
+    (0x400D_80E0, false), // PLL_ENET
+];
+
+/// Read-only PLL lock status; the same field in every PLL
+const LOCK: u32 = 1 << 31;
+/// PLL POWER or POWERDOWN; the same field in every PLL
+const POWER: u32 = 1 << 12;
+
+thread_local! {
+    /// Registers written since the last reset
+    static REGISTERS: RefCell<HashMap<usize, u32>> = RefCell::new(HashMap::new());
+}
+
+/// Restore this thread's simulated registers to their reset values
+pub fn reset() {
+    REGISTERS.with(|registers| registers.borrow_mut().clear());
+}
+
+/// Read the simulated register at `address`
+///
+/// # Panics
+///
+/// Panics if `address` isn't a simulated register.
+pub fn read(address: usize) -> u32 {
+    load(address).expect("not a simulated register")
+}
+
+/// Write `value` to the simulated register at `address`
+///
+/// Use `write` to set up a clock state before a test, like the state that your
+/// bootloader leaves behind.
+///
+/// # Panics
+///
+/// Panics if `address` isn't a simulated register.
+pub fn write(address: usize, value: u32) {
+    crate::map::changed(address);
+    assert!(store(address, value), "not a simulated register");
+}
+
+/// Returns the block that holds `address`, or `None` if the address isn't
+/// simulated
+fn block(address: usize) -> Option<&'static Block> {
+    BLOCKS
+        .iter()
+        .find(|block| block.addresses.contains(&address))
+}
+
+fn reset_value(address: usize) -> u32 {
+    RESET
+        .iter()
+        .find(|(reset, _)| *reset == address)
+        .map_or(0, |(_, value)| *value)
+}
+
+/// Returns the register at `address`, ignoring its read-only fields
+fn get(registers: &HashMap<usize, u32>, address: usize) -> u32 {
+    registers
+        .get(&address)
+        .copied()
+        .unwrap_or_else(|| reset_value(address))
+}
+
+/// Returns the register address that an access to `address` reaches, and the
+/// offset of the SET, CLR, or TOG alias
+fn resolve(block: &Block, address: usize) -> (usize, usize) {
+    if block.aliases {
+        (address & !0xF, address & 0xF)
+    } else {
+        (address, 0)
+    }
+}
+
+/// Returns the simulated register at `address`, or `None` if the address
+/// isn't simulated
+pub(crate) fn load(address: usize) -> Option<u32> {
+    let (address, _) = resolve(block(address)?, address);
+    let value = REGISTERS.with(|registers| get(&registers.borrow(), address));
+    Some(match PLLS.iter().find(|(pll, _)| *pll == address) {
+        Some(&(_, active_high)) if (value & POWER != 0) == active_high => value | LOCK,
+        Some(_) => value & !LOCK,
+        None => value,
+    })
+}
+
+/// Write `value` to the simulated register at `address`
+///
+/// Returns `false` if the address isn't simulated.
+pub(crate) fn store(address: usize, value: u32) -> bool {
+    let (address, alias) = match block(address) {
+        Some(block) => resolve(block, address),
+        None => return false,
+    };
+    REGISTERS.with(|registers| {
+        let mut registers = registers.borrow_mut();
+        let current = get(&registers, address);
+        let value = match alias {
+            0x4 => current | value,
+            0x8 => current & !value,
+            0xC => current ^ value,
+            _ => value,
+        };
+        registers.insert(address, value);
+    });
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read, reset, write};
+    use crate::pll::{self, Pll};
+
+    const CBCDR: usize = 0x400F_C014;
+    const PLL_USB1: usize = 0x400D_8010;
+    const PLL_USB1_SET: usize = 0x400D_8014;
+    const PLL_USB1_CLR: usize = 0x400D_8018;
+    const PLL_USB1_TOG: usize = 0x400D_801C;
+
+    #[test]
+    fn simulation_registers() {
+        reset();
+        assert_eq!(read(CBCDR), 0x000A_8300);
+        write(CBCDR, 0x0002_8300);
+        assert_eq!(read(CBCDR), 0x0002_8300);
+
+        // Another thread has its own registers
+        std::thread::spawn(|| assert_eq!(read(CBCDR), 0x000A_8300))
+            .join()
+            .unwrap();
+
+        reset();
+        assert_eq!(read(CBCDR), 0x000A_8300);
+    }
+
+    #[test]
+    fn simulation_aliases_and_lock() {
+        reset();
+        assert_eq!(read(PLL_USB1), 0x0001_2000);
+        assert!(!pll::is_locked(Pll::Usb1));
+
+        // Powering the PLL locks it
+        write(PLL_USB1_SET, 1 << 12);
+        assert_eq!(read(PLL_USB1), 0x8001_3000);
+        assert!(pll::is_locked(Pll::Usb1));
+        assert_eq!(read(PLL_USB1_CLR), read(PLL_USB1));
+
+        write(PLL_USB1_CLR, 1 << 16);
+        assert_eq!(read(PLL_USB1), 0x8000_3000);
+        write(PLL_USB1_TOG, 1 << 12 | 1 << 13);
+        assert_eq!(read(PLL_USB1), 0);
+
+        // LOCK is read-only
+        write(PLL_USB1, 1 << 31);
+        assert!(!pll::is_locked(Pll::Usb1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn simulation_outside_registers() {
+        read(0x2020_0000);
+    }
+}
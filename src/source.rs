@@ -0,0 +1,215 @@
+//! Clock sources shared by the clock roots
+
+use crate::pfd::PFD;
+use core::fmt;
+
+/// A clock source
+///
+/// Each clock root module has its own `Selection` (or `Source`), which only
+/// describes the sources that its multiplexer can select. Convert any of
+/// them into a `ClockSource` to compare sources across clock roots, or to
+/// print the source:
+///
+/// ```
+/// use imxrt_ccm::{pll2::PFD, spi, uart, ClockSource};
+///
+/// assert_eq!(ClockSource::from(uart::Selection::Pll3), ClockSource::Pll3);
+/// let spi = ClockSource::from(spi::Selection::Pll2Pfd2);
+/// assert_eq!(spi, ClockSource::Pll2Pfd(PFD::PFD2));
+/// # extern crate std;
+/// # use std::string::ToString;
+/// assert_eq!(spi.to_string(), "PLL2 PFD2");
+/// ```
+///
+/// Use `TryFrom` to convert a `ClockSource` back into a clock root's
+/// selection. The conversion fails with
+/// [`ClockError::SourceUnavailable`](crate::ClockError::SourceUnavailable)
+/// if the clock root can't select the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ClockSource {
+    /// The 24MHz reference, usually the crystal oscillator
+    Oscillator,
+    /// PLL1, the ARM PLL
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    Pll1,
+    /// PLL2, the system PLL
+    Pll2,
+    /// The PLL2 bypass clock
+    Pll2Bypass,
+    /// A PLL2 PFD
+    Pll2Pfd(PFD),
+    /// PLL3, the USB1 PLL
+    Pll3,
+    /// A PLL3 PFD
+    Pll3Pfd(PFD),
+    /// PLL4, the audio PLL
+    AudioPll,
+    /// PLL5, the video PLL
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    VideoPll,
+    /// PLL6, the ENET PLL
+    EnetPll,
+    /// The IPG clock root
+    IPG,
+}
+
+impl ClockSource {
+    /// Returns the source frequency
+    ///
+    /// Returns 0 if the source isn't running. The frequency doesn't include
+    /// any fixed divider between the source and a clock root, like the PLL3
+    /// divider for the UART clock root.
+    pub fn frequency(self) -> u32 {
+        use crate::{pll2, pll3, pll4, pll6};
+        match self {
+            ClockSource::Oscillator | ClockSource::Pll2Bypass => {
+                crate::xtalosc::reference_frequency()
+            }
+            #[cfg(feature = "imxrt1060")]
+            ClockSource::Pll1 => crate::arm::pll1_frequency(),
+            ClockSource::Pll2 => pll2::frequency(),
+            ClockSource::Pll2Pfd(pfd) => pll2::pfd_frequency(pfd),
+            ClockSource::Pll3 => pll3::frequency(),
+            ClockSource::Pll3Pfd(pfd) => pll3::pfd_frequency(pfd),
+            ClockSource::AudioPll => pll4::frequency(),
+            #[cfg(feature = "imxrt1060")]
+            ClockSource::VideoPll => crate::pll5::frequency(),
+            ClockSource::EnetPll => pll6::frequency(),
+            // Safety: reads of valid registers
+            ClockSource::IPG => unsafe { crate::arm::frequency().1 .0 },
+        }
+    }
+}
+
+impl fmt::Display for ClockSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClockSource::Oscillator => f.write_str("OSC"),
+            #[cfg(feature = "imxrt1060")]
+            ClockSource::Pll1 => f.write_str("PLL1"),
+            ClockSource::Pll2 => f.write_str("PLL2"),
+            ClockSource::Pll2Bypass => f.write_str("PLL2 bypass"),
+            ClockSource::Pll2Pfd(pfd) => write!(f, "PLL2 PFD{}", *pfd as u32),
+            ClockSource::Pll3 => f.write_str("PLL3"),
+            ClockSource::Pll3Pfd(pfd) => write!(f, "PLL3 PFD{}", *pfd as u32),
+            ClockSource::AudioPll => f.write_str("PLL4"),
+            #[cfg(feature = "imxrt1060")]
+            ClockSource::VideoPll => f.write_str("PLL5"),
+            ClockSource::EnetPll => f.write_str("PLL6"),
+            ClockSource::IPG => f.write_str("IPG"),
+        }
+    }
+}
+
+/// Implements `From<$selection> for ClockSource`, and the `TryFrom` inverse
+macro_rules! sources {
+    ($selection:path { $($(#[$attr:meta])* $variant:ident => $source:expr,)+ }) => {
+        impl From<$selection> for ClockSource {
+            fn from(selection: $selection) -> Self {
+                use $selection as S;
+                match selection {
+                    $($(#[$attr])* S::$variant => $source,)+
+                }
+            }
+        }
+
+        impl core::convert::TryFrom<ClockSource> for $selection {
+            type Error = crate::ClockError;
+            fn try_from(source: ClockSource) -> Result<Self, Self::Error> {
+                use $selection as S;
+                $(
+                    $(#[$attr])*
+                    if source == $source {
+                        return Ok(S::$variant);
+                    }
+                )+
+                Err(crate::ClockError::SourceUnavailable)
+            }
+        }
+    };
+}
+
+sources!(crate::uart::Selection {
+    Pll3 => ClockSource::Pll3,
+    Oscillator => ClockSource::Oscillator,
+});
+
+sources!(crate::i2c::Selection {
+    Pll3 => ClockSource::Pll3,
+    Oscillator => ClockSource::Oscillator,
+});
+
+sources!(crate::spi::Selection {
+    Pll3Pfd1 => ClockSource::Pll3Pfd(PFD::PFD1),
+    Pll3Pfd0 => ClockSource::Pll3Pfd(PFD::PFD0),
+    Pll2 => ClockSource::Pll2,
+    Pll2Pfd2 => ClockSource::Pll2Pfd(PFD::PFD2),
+});
+
+sources!(crate::perclock::Selection {
+    IPG => ClockSource::IPG,
+    Oscillator => ClockSource::Oscillator,
+});
+
+sources!(crate::arm::Source {
+    Pll2 => ClockSource::Pll2,
+    #[cfg(feature = "imxrt1060")]
+    Pll2Pfd2 => ClockSource::Pll2Pfd(PFD::PFD2),
+    #[cfg(feature = "imxrt1060")]
+    Pll2Pfd0 => ClockSource::Pll2Pfd(PFD::PFD0),
+    #[cfg(feature = "imxrt1060")]
+    Pll1 => ClockSource::Pll1,
+    #[cfg(not(feature = "imxrt1060"))]
+    Pll3Pfd3 => ClockSource::Pll3Pfd(PFD::PFD3),
+    #[cfg(not(feature = "imxrt1060"))]
+    Pll2Pfd3 => ClockSource::Pll2Pfd(PFD::PFD3),
+    #[cfg(not(feature = "imxrt1060"))]
+    Pll6 => ClockSource::EnetPll,
+});
+
+sources!(crate::arm::PeriphClk2 {
+    Pll3 => ClockSource::Pll3,
+    Oscillator => ClockSource::Oscillator,
+    Pll2Bypass => ClockSource::Pll2Bypass,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::ClockSource;
+    use crate::{pfd::PFD, spi, uart, ClockError};
+    use core::convert::TryFrom;
+
+    #[test]
+    fn source_conversions() {
+        for selection in [
+            spi::Selection::Pll3Pfd1,
+            spi::Selection::Pll3Pfd0,
+            spi::Selection::Pll2,
+            spi::Selection::Pll2Pfd2,
+        ] {
+            assert_eq!(
+                spi::Selection::try_from(ClockSource::from(selection)),
+                Ok(selection)
+            );
+        }
+        assert_eq!(
+            uart::Selection::try_from(ClockSource::Pll2),
+            Err(ClockError::SourceUnavailable)
+        );
+        assert_eq!(
+            ClockSource::from(crate::arm::PeriphClk2::Pll2Bypass),
+            ClockSource::Pll2Bypass
+        );
+    }
+
+    #[test]
+    fn source_display() {
+        use std::string::ToString;
+        assert_eq!(ClockSource::Oscillator.to_string(), "OSC");
+        assert_eq!(ClockSource::Pll3Pfd(PFD::PFD1).to_string(), "PLL3 PFD1");
+        assert_eq!(ClockSource::EnetPll.to_string(), "PLL6");
+    }
+}
@@ -1,16 +1,21 @@
 //! SPI clock control
 
-use super::{ClockGate, ClockGateLocation, ClockGateLocator, Instance};
-use crate::register::{Field, Register};
-use core::marker::PhantomData;
+use super::{
+    ClockFor, ClockGate, ClockGateGuard, ClockGateLocation, ClockGateLocator, ClockRoot,
+    ClockRootLocator, Instance,
+};
+use crate::{
+    register::{Field, Register},
+    shared::SharedClockGate,
+    ClockError, ClockSource, RootFrequency,
+};
+use core::{convert::TryFrom, marker::PhantomData};
 
 const DEFAULT_CLOCK_DIVIDER: u32 = 5;
-/// SPI clock frequency (Hz)
-const CLOCK_FREQUENCY_HZ: u32 = 528_000_000;
 
 /// The SPI clock
 ///
-/// The SPI clock is based on PLL2.
+/// When configured by this crate, the SPI clock is based on PLL2.
 pub struct SPIClock<S>(PhantomData<S>);
 
 impl<S> SPIClock<S> {
@@ -31,19 +36,156 @@ where
     ///
     /// **1010 only:** the divider range is [1, 16].
     ///
+    /// Returns the SPI clock root frequency. Since there's no requested
+    /// frequency, the error is zero; see [`RootFrequency::relative_to`].
+    ///
     /// When `configure_divider` returns, all SPI clock gates will be set to off.
     /// Use [`clock_gate`](struct.SPIClock.html#method.clock_gate)
     /// to turn on SPI clock gates.
     #[inline(always)]
-    pub fn configure_divider(&mut self, divider: u32) {
+    pub fn configure_divider(&mut self, divider: u32) -> RootFrequency {
         unsafe {
-            super::set_clock_gate::<S>(SPI::SPI1, ClockGate::Off);
-            super::set_clock_gate::<S>(SPI::SPI2, ClockGate::Off);
-            super::set_clock_gate::<S>(SPI::SPI3, ClockGate::Off);
-            super::set_clock_gate::<S>(SPI::SPI4, ClockGate::Off);
+            crate::clock_gates_off::<S>(SPIS);
 
             configure(divider)
         };
+        RootFrequency::exact(frequency())
+    }
+
+    /// Configure the SPI clocks with a divider that's known to be in range
+    ///
+    /// Unlike `configure_divider`, the divider can't saturate. See
+    /// [`configure_divider`](SPIClock::configure_divider) for more information.
+    #[inline(always)]
+    pub fn configure_with_divider(&mut self, divider: Divider) -> RootFrequency {
+        self.configure_divider(divider.get())
+    }
+
+    /// Configure the SPI clocks to approximate `target_hz`, returning the
+    /// achieved frequency
+    ///
+    /// See [`configure_frequency`] for more information.
+    ///
+    /// When `configure_frequency` returns, all SPI clock gates will be set to off.
+    /// Use [`clock_gate`](struct.SPIClock.html#method.clock_gate)
+    /// to turn on SPI clock gates.
+    #[inline(always)]
+    pub fn configure_frequency(&mut self, target_hz: u32) -> RootFrequency {
+        unsafe {
+            crate::clock_gates_off::<S>(SPIS);
+
+            configure_frequency(target_hz)
+        }
+    }
+
+    /// Configure the SPI clocks with a clock divider, leaving the SPI clock
+    /// gates untouched
+    ///
+    /// `configure_divider_ungated` behaves like `configure_divider`, but it does
+    /// not turn off any SPI clock gates. Use it when you manage the clock gates
+    /// yourself. If a SPI clock gate is on, the peripheral may see a glitch as
+    /// the clock root changes; you're responsible for tolerating that glitch.
+    #[inline(always)]
+    pub fn configure_divider_ungated(&mut self, divider: u32) -> RootFrequency {
+        // Safety: we own the CCM peripheral memory
+        unsafe { configure(divider) };
+        RootFrequency::exact(frequency())
+    }
+
+    /// Configure the SPI clocks to approximate `target_hz`, leaving the SPI
+    /// clock gates untouched
+    ///
+    /// `configure_frequency_ungated` behaves like `configure_frequency`, but it
+    /// does not turn off any SPI clock gates. See
+    /// [`configure_divider_ungated`](struct.SPIClock.html#method.configure_divider_ungated)
+    /// for the risks.
+    #[inline(always)]
+    pub fn configure_frequency_ungated(&mut self, target_hz: u32) -> RootFrequency {
+        // Safety: we own the CCM peripheral memory
+        unsafe { configure_frequency(target_hz) }
+    }
+
+    /// Set the SPI clock divider, leaving the clock root selection unchanged
+    ///
+    /// See [`set_divider`] for more information. Unlike `configure_divider`,
+    /// `set_divider` does not change the SPI clock gates.
+    #[inline(always)]
+    pub fn set_divider(&mut self, divider: u32) {
+        // Safety: we own the CCM peripheral memory
+        unsafe { set_divider(divider) }
+    }
+
+    /// Select the SPI clock root source, leaving the divider unchanged
+    ///
+    /// When `set_selection` returns, all SPI clock gates will be set to off.
+    /// Use [`clock_gate`](struct.SPIClock.html#method.clock_gate)
+    /// to turn on SPI clock gates.
+    #[inline(always)]
+    pub fn set_selection(&mut self, selection: Selection) {
+        unsafe {
+            crate::clock_gates_off::<S>(SPIS);
+
+            set_selection(selection)
+        }
+    }
+
+    /// Configure the SPI clocks with a clock divider, or return an error if the
+    /// divider is out of range
+    ///
+    /// Unlike [`configure_divider`](Self::configure_divider),
+    /// `try_configure_divider` doesn't saturate the divider. If it returns an
+    /// error, it makes no changes.
+    pub fn try_configure_divider(&mut self, divider: u32) -> Result<RootFrequency, ClockError> {
+        let divider = Divider::try_from(divider)?;
+        Ok(self.configure_with_divider(divider))
+    }
+
+    /// Configure the SPI clocks to within `tolerance_hz` of `target_hz`, or
+    /// return an error if no source and divider are close enough
+    ///
+    /// Unlike [`configure_frequency`](Self::configure_frequency), which settles for
+    /// the closest frequency, `try_configure_frequency` makes no changes if the
+    /// closest frequency isn't within the tolerance.
+    pub fn try_configure_frequency(
+        &mut self,
+        target_hz: u32,
+        tolerance_hz: u32,
+    ) -> Result<RootFrequency, ClockError> {
+        let choice = crate::divider::closest(target_hz, &Sources::current().table(), MAX_DIVIDER)
+            .ok_or(ClockError::FrequencyUnreachable {
+                target_hz,
+                closest_hz: 0,
+            })?
+            .within(target_hz, tolerance_hz)?;
+        unsafe {
+            crate::clock_gates_off::<S>(SPIS);
+            CBCMR.set(choice.divider - 1, choice.selection);
+        }
+        Ok(choice.frequency(target_hz))
+    }
+
+    /// Set the SPI clock divider, or return an error if the divider is out of
+    /// range
+    ///
+    /// Unlike [`set_divider`](Self::set_divider), `try_set_divider` doesn't
+    /// saturate the divider. If it returns an error, it makes no changes.
+    pub fn try_set_divider(&mut self, divider: u32) -> Result<(), ClockError> {
+        let divider = Divider::try_from(divider)?;
+        self.set_divider(divider.get());
+        Ok(())
+    }
+
+    /// Select the SPI clock root source, or return an error if the source
+    /// isn't running
+    ///
+    /// See [`set_selection`](Self::set_selection). If `try_set_selection`
+    /// returns an error, it makes no changes.
+    pub fn try_set_selection(&mut self, selection: Selection) -> Result<(), ClockError> {
+        if source_frequency(field(selection)) == 0 {
+            return Err(ClockError::SourceUnavailable);
+        }
+        self.set_selection(selection);
+        Ok(())
     }
 
     /// Configure the SPI clocks with a default divider
@@ -66,6 +208,26 @@ pub enum SPI {
     SPI4,
 }
 
+/// All SPI instances
+const SPIS: &[SPI] = &[SPI::SPI1, SPI::SPI2, SPI::SPI3, SPI::SPI4];
+
+/// SPI clock root selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Selection {
+    /// PLL3 PFD1
+    Pll3Pfd1,
+    /// PLL3 PFD0
+    Pll3Pfd0,
+    /// PLL2
+    Pll2,
+    /// PLL2 PFD2
+    Pll2Pfd2,
+}
+
+/// SPI clock root divider, between [1, 8], or [1, 16] on the 1010
+pub type Divider = crate::Divider<1, MAX_DIVIDER>;
+
 impl<S> SPIClock<S>
 where
     S: Instance<Inst = SPI>,
@@ -86,11 +248,51 @@ where
         unsafe { super::set_clock_gate::<S>(spi.instance(), gate) }
     }
 
+    /// Turn on the clock gate for the SPI instance, returning a guard
+    /// that restores the previous setting when dropped
+    #[inline(always)]
+    pub fn enable_guard<'a>(&mut self, spi: &'a mut S) -> ClockGateGuard<'a, S> {
+        // Safety: we have exclusive access to the instance
+        unsafe { ClockGateGuard::new(spi) }
+    }
+
+    /// Share the clock gate for the SPI instance between multiple users
+    ///
+    /// See [`shared`](crate::shared) for more information.
+    #[inline(always)]
+    pub fn shared_gate(&mut self, spi: S) -> SharedClockGate<S> {
+        // Safety: we own the instance
+        unsafe { SharedClockGate::new(spi) }
+    }
+
     /// Returns the SPI clock frequency
     #[inline(always)]
     pub fn frequency(&self) -> u32 {
         frequency()
     }
+
+    /// Returns the clock root selection
+    #[inline(always)]
+    pub fn selection(&self) -> Selection {
+        selection()
+    }
+
+    /// Returns `true` if the SPI clock root runs
+    ///
+    /// The clock root runs when its selected source runs. Use this to check
+    /// that the SPI clock is configured before you use a SPI peripheral.
+    #[inline(always)]
+    pub fn is_configured(&self) -> bool {
+        frequency() != 0
+    }
+
+    /// Returns `true` if any SPI clock gate is on
+    ///
+    /// The check only considers the instances that are valid for `S`.
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        crate::any_clock_gate_on::<S>(SPIS)
+    }
 }
 
 impl ClockGateLocator for SPI {
@@ -106,6 +308,160 @@ impl ClockGateLocator for SPI {
     }
 }
 
+impl ClockRootLocator for SPI {
+    #[inline(always)]
+    fn clock_root(&self) -> ClockRoot {
+        ClockRoot::SPI
+    }
+}
+
+impl<S> ClockFor<SPI> for SPIClock<S>
+where
+    S: Instance<Inst = SPI>,
+{
+    #[inline(always)]
+    fn frequency(&self) -> u32 {
+        frequency()
+    }
+}
+
+/// LPSPI SCK settings
+///
+/// Use [`sck`] or [`SPIClock::sck`] to compute the settings for a target SCK
+/// frequency. The LPSPI SCK frequency is the SPI clock root frequency divided
+/// by `2^prescale * (sckdiv + 2)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Sck {
+    /// The TCR PRESCALE field, between 0 and 7
+    pub prescale: u32,
+    /// The CCR SCKDIV field, between 0 and 255
+    pub sckdiv: u32,
+    /// The achieved SCK frequency
+    pub hz: u32,
+    /// The achieved SCK frequency minus the target SCK frequency
+    ///
+    /// The error is never positive.
+    pub error_hz: i32,
+}
+
+/// The largest LPSPI TCR PRESCALE value
+const MAX_PRESCALE: u32 = 7;
+/// The largest LPSPI CCR SCKDIV value
+const MAX_SCKDIV: u32 = 255;
+
+/// Returns the LPSPI SCK settings that produce the fastest SCK that doesn't
+/// exceed `target_hz`, given the SPI clock root frequency `spi_hz`
+///
+/// Returns `None` if `target_hz` is zero, or if even the slowest SCK exceeds
+/// `target_hz`.
+///
+/// ```
+/// use imxrt_ccm::spi;
+///
+/// let sck = spi::sck(132_000_000, 30_000_000).unwrap();
+/// assert_eq!(sck.hz, 26_400_000);
+/// assert_eq!(sck.error_hz, -3_600_000);
+/// ```
+pub const fn sck(spi_hz: u32, target_hz: u32) -> Option<Sck> {
+    if target_hz == 0 {
+        return None;
+    }
+    let mut best: Option<Sck> = None;
+    let mut prescale = 0;
+    while prescale <= MAX_PRESCALE {
+        let prescaled_hz = spi_hz >> prescale;
+        // The smallest divider that doesn't exceed the target
+        let mut divider = prescaled_hz.div_ceil(target_hz);
+        if divider < 2 {
+            divider = 2;
+        }
+        if divider <= MAX_SCKDIV + 2 {
+            let hz = prescaled_hz / divider;
+            let faster = match best {
+                Some(best) => hz > best.hz,
+                None => true,
+            };
+            if faster {
+                best = Some(Sck {
+                    prescale,
+                    sckdiv: divider - 2,
+                    hz,
+                    error_hz: hz as i32 - target_hz as i32,
+                });
+            }
+        }
+        prescale += 1;
+    }
+    best
+}
+
+/// A SPI clock root configuration, and the best SCK that it produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SckOption {
+    /// The SPI clock root selection
+    pub selection: Selection,
+    /// The SPI clock root divider
+    pub divider: u32,
+    /// The SPI clock root frequency
+    pub root_hz: u32,
+    /// The fastest SCK that doesn't exceed the target
+    pub sck: Sck,
+}
+
+/// Returns every SPI clock root selection and divider, and the fastest SCK
+/// that each produces without exceeding `target_hz`
+///
+/// Options that can't produce an SCK at, or below, `target_hz` aren't included.
+/// The options reflect the current PLL and PFD frequencies. Use them to find
+/// a clock root configuration that hits your SCK frequency exactly:
+///
+/// ```
+/// use imxrt_ccm::{spi, Instance};
+///
+/// fn configure_for_display<S: Instance<Inst = spi::SPI>>(spi_clock: &mut spi::SPIClock<S>) {
+///     let option = spi::sck_options(30_000_000)
+///         .find(|option| option.sck.error_hz == 0)
+///         .unwrap();
+///     spi_clock.set_selection(option.selection);
+///     spi_clock.set_divider(option.divider);
+/// }
+/// ```
+pub fn sck_options(target_hz: u32) -> impl Iterator<Item = SckOption> {
+    sck_options_(target_hz, Sources::current().table())
+}
+
+fn sck_options_(target_hz: u32, sources: [(u32, u32); 4]) -> impl Iterator<Item = SckOption> {
+    IntoIterator::into_iter(sources)
+        .filter(|&(_, source_hz)| source_hz != 0)
+        .flat_map(move |(selection, source_hz)| {
+            (1..=MAX_DIVIDER).filter_map(move |divider| {
+                let root_hz = source_hz / divider;
+                sck(root_hz, target_hz).map(|sck| SckOption {
+                    selection: from_field(selection),
+                    divider,
+                    root_hz,
+                    sck,
+                })
+            })
+        })
+}
+
+impl<S> SPIClock<S>
+where
+    S: Instance<Inst = SPI>,
+{
+    /// Returns the LPSPI SCK settings that produce the fastest SCK that
+    /// doesn't exceed `target_hz` at the current SPI clock frequency
+    ///
+    /// See [`sck`] for more information.
+    #[inline(always)]
+    pub fn sck(&self, target_hz: u32) -> Option<Sck> {
+        sck(self.frequency(), target_hz)
+    }
+}
+
 const LPSPI_PODF: Field = Field::new(
     26,
     #[cfg(not(feature = "imxrt1010"))]
@@ -132,7 +488,6 @@ const CBCMR: Register = unsafe { Register::new(LPSPI_PODF, LPSPI_SEL, 0x400F_C01
 /// This could be called anywhere, modifying global memory that's owned by
 /// the CCM. Consider using the [`SPIClock`](struct.SPIClock.html) for a
 /// safer interface.
-#[inline(always)]
 pub unsafe fn configure(divider: u32) {
     configure_(divider, &CBCMR);
 }
@@ -140,30 +495,237 @@ pub unsafe fn configure(divider: u32) {
 #[inline(always)]
 unsafe fn configure_(divider: u32, reg: &Register) {
     const PLL2: u32 = 2; // Consistent for 1062, 1011 chips
-    #[cfg(not(feature = "imxrt1010"))]
-    const MAX_DIVIDER: u32 = 8;
-    #[cfg(feature = "imxrt1010")]
-    const MAX_DIVIDER: u32 = 16;
+    reg.set(divider.clamp(1, MAX_DIVIDER).saturating_sub(1), PLL2);
+}
+
+#[cfg(not(feature = "imxrt1010"))]
+const MAX_DIVIDER: u32 = 8;
+#[cfg(feature = "imxrt1010")]
+const MAX_DIVIDER: u32 = 16;
+
+/// Configure the SPI clock root to approximate `target_hz`, returning the
+/// achieved frequency
+///
+/// The function selects the source, either PLL2, or a PLL2 or PLL3 PFD, and the
+/// divider, that produce the frequency closest to `target_hz`. The function
+/// considers the PFDs at their current fractions, and ignores sources that
+/// aren't running. If none of the sources are running, the function makes no
+/// changes, and returns a frequency of 0. Configure will **not** disable
+/// peripheral clock gates.
+///
+/// # Safety
+///
+/// This could be called anywhere, modifying global memory that's owned by
+/// the CCM. Consider using the [`SPIClock`](struct.SPIClock.html) for a
+/// safer interface.
+pub unsafe fn configure_frequency(target_hz: u32) -> RootFrequency {
+    configure_frequency_(target_hz, &CBCMR, &Sources::current().table())
+}
+
+unsafe fn configure_frequency_(
+    target_hz: u32,
+    reg: &Register,
+    sources: &[(u32, u32)],
+) -> RootFrequency {
+    match crate::divider::closest(target_hz, sources, MAX_DIVIDER) {
+        Some(choice) => {
+            reg.set(choice.divider - 1, choice.selection);
+            choice.frequency(target_hz)
+        }
+        None => RootFrequency {
+            hz: 0,
+            error_hz: -(target_hz as i32),
+        },
+    }
+}
+
+/// Returns the frequency of the source selected by `selection`
+fn source_frequency(selection: u32) -> u32 {
+    ClockSource::from(from_field(selection)).frequency()
+}
+
+/// Set the SPI clock divider, leaving the clock root selection unchanged
+///
+/// Unlike [`configure`], which also selects the clock source, `set_divider`
+/// only writes the divider field. Use it to adjust a clock root that you
+/// configured with [`configure_frequency`] or [`set_selection`].
+///
+/// The divider should be between [1, 8]. If you supply a divider
+/// outside of that closed range, the implementation will saturate the
+/// divider at the nearest extreme.
+///
+/// **1010 only:** the divider range is [1, 16].
+///
+/// # Safety
+///
+/// This could be called anywhere, modifying global memory that's owned by
+/// the CCM. Consider using the [`SPIClock`](struct.SPIClock.html) for a
+/// safer interface.
+pub unsafe fn set_divider(divider: u32) {
+    set_divider_(divider, &CBCMR);
+}
 
-    reg.set(divider.min(MAX_DIVIDER).max(1).saturating_sub(1), PLL2);
+#[inline(always)]
+unsafe fn set_divider_(divider: u32, reg: &Register) {
+    reg.set_divider(divider.clamp(1, MAX_DIVIDER) - 1);
+}
+
+/// Select the SPI clock root source, leaving the divider unchanged
+///
+/// Set selection will **not** disable peripheral clock gates. You should disable
+/// clock gates yourself before calling this function.
+///
+/// # Safety
+///
+/// This could be called anywhere, modifying global memory that's owned by
+/// the CCM. Consider using the [`SPIClock`](struct.SPIClock.html) for a
+/// safer interface.
+pub unsafe fn set_selection(selection: Selection) {
+    set_selection_(selection, &CBCMR);
+}
+
+#[inline(always)]
+unsafe fn set_selection_(selection: Selection, reg: &Register) {
+    reg.set_selection(field(selection));
+}
+
+/// Returns the LPSPI_SEL value for `selection`
+#[inline(always)]
+const fn field(selection: Selection) -> u32 {
+    match selection {
+        Selection::Pll3Pfd1 => 0,
+        Selection::Pll3Pfd0 => 1,
+        Selection::Pll2 => 2,
+        Selection::Pll2Pfd2 => 3,
+    }
 }
 
 /// Returns the SPI clock frequency
+///
+/// The frequency reflects the SPI clock root's source, which may be PLL2,
+/// or a PLL2 or PLL3 PFD.
 #[inline(always)]
 pub fn frequency() -> u32 {
-    frequency_(&CBCMR)
+    frequency_(&CBCMR, source_frequency(CBCMR.selection()))
 }
 
 #[inline(always)]
-fn frequency_(reg: &Register) -> u32 {
+fn frequency_(reg: &Register, source_hz: u32) -> u32 {
     let divider = reg.divider() + 1;
-    CLOCK_FREQUENCY_HZ / divider
+    source_hz / divider
+}
+
+/// Returns the SPI clock divider
+#[inline(always)]
+pub fn divider() -> u32 {
+    CBCMR.divider() + 1
+}
+
+/// Returns the SPI clock root selection
+#[inline(always)]
+pub fn selection() -> Selection {
+    selection_(&CBCMR)
+}
+
+#[inline(always)]
+fn selection_(reg: &Register) -> Selection {
+    from_field(reg.selection())
+}
+
+/// Returns the selection described by a LPSPI_SEL value
+#[inline(always)]
+pub(crate) const fn from_field(field: u32) -> Selection {
+    match field {
+        0 => Selection::Pll3Pfd1,
+        1 => Selection::Pll3Pfd0,
+        2 => Selection::Pll2,
+        _ => Selection::Pll2Pfd2,
+    }
+}
+
+/// The SPI clock root source frequencies
+///
+/// Use `Sources` with [`closest`] and [`root_frequency`] to compute SPI clock
+/// root settings without the CCM. A source that isn't running has a frequency
+/// of 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sources {
+    /// The PLL3 PFD1 frequency
+    pub pll3_pfd1_hz: u32,
+    /// The PLL3 PFD0 frequency
+    pub pll3_pfd0_hz: u32,
+    /// The PLL2 frequency
+    pub pll2_hz: u32,
+    /// The PLL2 PFD2 frequency
+    pub pll2_pfd2_hz: u32,
+}
+
+impl Sources {
+    /// Returns the source frequencies of this system
+    pub fn current() -> Self {
+        Sources {
+            pll3_pfd1_hz: source_frequency(field(Selection::Pll3Pfd1)),
+            pll3_pfd0_hz: source_frequency(field(Selection::Pll3Pfd0)),
+            pll2_hz: source_frequency(field(Selection::Pll2)),
+            pll2_pfd2_hz: source_frequency(field(Selection::Pll2Pfd2)),
+        }
+    }
+
+    /// Returns the frequency of the source selected by `selection`
+    const fn get(&self, selection: Selection) -> u32 {
+        match selection {
+            Selection::Pll3Pfd1 => self.pll3_pfd1_hz,
+            Selection::Pll3Pfd0 => self.pll3_pfd0_hz,
+            Selection::Pll2 => self.pll2_hz,
+            Selection::Pll2Pfd2 => self.pll2_pfd2_hz,
+        }
+    }
+
+    /// Returns each clock root multiplexer value, and its source frequency
+    const fn table(&self) -> [(u32, u32); 4] {
+        [
+            (0, self.get(from_field(0))),
+            (1, self.get(from_field(1))),
+            (2, self.get(from_field(2))),
+            (3, self.get(from_field(3))),
+        ]
+    }
+}
+
+/// An SPI clock root selection and divider, and the frequency that they
+/// produce
+pub type Setting = crate::RootSetting<Selection, Divider>;
+
+/// Returns the SPI clock root frequency for `selection` and `divider`
+///
+/// Unlike [`frequency`], `root_frequency` doesn't read the CCM.
+pub const fn root_frequency(sources: &Sources, selection: Selection, divider: Divider) -> u32 {
+    sources.get(selection) / divider.get()
+}
+
+/// Returns the SPI clock root selection and divider that best approximate
+/// `target_hz`
+///
+/// `closest` makes the same choice as [`configure_frequency`], but it doesn't
+/// read or write the CCM. Returns `None` if no source runs.
+pub const fn closest(sources: &Sources, target_hz: u32) -> Option<Setting> {
+    match crate::divider::closest(target_hz, &sources.table(), MAX_DIVIDER) {
+        Some(choice) => Some(choice.setting(from_field(choice.selection), target_hz)),
+        None => None,
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use super::{configure_, frequency_, Register, CLOCK_FREQUENCY_HZ, LPSPI_PODF, LPSPI_SEL};
+    use super::{
+        closest, configure_, configure_frequency_, frequency_, root_frequency, sck, sck_options_,
+        selection_, set_divider_, set_selection_, Register, Selection, Sources, LPSPI_PODF,
+        LPSPI_SEL,
+    };
+
+    /// SPI clock frequency (Hz)
+    const CLOCK_FREQUENCY_HZ: u32 = 528_000_000;
 
     unsafe fn register(mem: &mut u32) -> Register {
         Register::new(LPSPI_PODF, LPSPI_SEL, mem)
@@ -176,7 +738,7 @@ mod tests {
         unsafe {
             let reg = register(&mut mem);
             configure_(9, &reg);
-            assert_eq!(frequency_(&reg), CLOCK_FREQUENCY_HZ / 8);
+            assert_eq!(frequency_(&reg, CLOCK_FREQUENCY_HZ), CLOCK_FREQUENCY_HZ / 8);
         }
     }
 
@@ -187,7 +749,10 @@ mod tests {
         unsafe {
             let reg = register(&mut mem);
             configure_(17, &reg);
-            assert_eq!(frequency_(&reg), CLOCK_FREQUENCY_HZ / 16);
+            assert_eq!(
+                frequency_(&reg, CLOCK_FREQUENCY_HZ),
+                CLOCK_FREQUENCY_HZ / 16
+            );
         }
     }
 
@@ -197,7 +762,7 @@ mod tests {
         unsafe {
             let reg = register(&mut mem);
             configure_(0, &reg);
-            assert_eq!(frequency_(&reg), CLOCK_FREQUENCY_HZ);
+            assert_eq!(frequency_(&reg, CLOCK_FREQUENCY_HZ), CLOCK_FREQUENCY_HZ);
         }
     }
 
@@ -207,7 +772,132 @@ mod tests {
         unsafe {
             let reg = register(&mut mem);
             configure_(7, &reg);
-            assert_eq!(frequency_(&reg), CLOCK_FREQUENCY_HZ / 7);
+            assert_eq!(frequency_(&reg, CLOCK_FREQUENCY_HZ), CLOCK_FREQUENCY_HZ / 7);
+        }
+    }
+
+    #[test]
+    fn spi_configure_frequency() {
+        let sources = [
+            (0, 664_615_384),
+            (1, 720_000_000),
+            (2, CLOCK_FREQUENCY_HZ),
+            (3, 396_000_000),
+        ];
+        let mut mem: u32 = 0;
+        unsafe {
+            let reg = register(&mut mem);
+            let achieved = configure_frequency_(132_000_000, &reg, &sources);
+            assert_eq!((achieved.hz, achieved.error_hz), (132_000_000, 0));
+            assert_eq!(reg.selection(), 2);
+            assert_eq!(frequency_(&reg, CLOCK_FREQUENCY_HZ), 132_000_000);
+
+            let achieved = configure_frequency_(90_000_000, &reg, &sources);
+            assert_eq!((achieved.hz, achieved.error_hz), (90_000_000, 0));
+            assert_eq!(reg.selection(), 1);
+
+            let achieved = configure_frequency_(90_000_000, &reg, &[(0, 0)]);
+            assert_eq!(achieved.hz, 0);
+            assert_eq!(reg.selection(), 1);
         }
     }
+
+    #[test]
+    fn spi_selection() {
+        let mut mem: u32 = 0;
+        unsafe {
+            let reg = register(&mut mem);
+            assert_eq!(selection_(&reg), Selection::Pll3Pfd1);
+            configure_(1, &reg);
+            assert_eq!(selection_(&reg), Selection::Pll2);
+        }
+        mem |= 0b11 << 4;
+        unsafe {
+            assert_eq!(selection_(&register(&mut mem)), Selection::Pll2Pfd2);
+        }
+    }
+
+    #[test]
+    fn spi_set_divider_selection() {
+        let mut mem: u32 = 0;
+        unsafe {
+            let reg = register(&mut mem);
+            set_selection_(Selection::Pll2Pfd2, &reg);
+            set_divider_(4, &reg);
+            assert_eq!(selection_(&reg), Selection::Pll2Pfd2);
+            assert_eq!(frequency_(&reg, 396_000_000), 396_000_000 / 4);
+
+            set_selection_(Selection::Pll3Pfd0, &reg);
+            assert_eq!(reg.selection(), 1);
+            assert_eq!(reg.divider() + 1, 4);
+
+            set_divider_(0, &reg);
+            assert_eq!(reg.divider(), 0);
+        }
+    }
+
+    #[test]
+    fn spi_sck() {
+        let settings = sck(132_000_000, 30_000_000).unwrap();
+        assert_eq!((settings.prescale, settings.sckdiv), (0, 3));
+        assert_eq!(settings.hz, 26_400_000);
+
+        let settings = sck(132_000_000, 100_000).unwrap();
+        assert_eq!((settings.prescale, settings.sckdiv), (3, 163));
+        assert_eq!(settings.hz, 100_000);
+
+        assert_eq!(sck(132_000_000, 0), None);
+        assert_eq!(sck(132_000_000, 1_000), None);
+    }
+
+    #[test]
+    fn spi_sck_options() {
+        // PLL3 PFD0 runs at 720MHz; the other sources are off
+        let sources = [(0, 0), (1, 720_000_000), (2, 0), (3, 0)];
+        let mut exact = sck_options_(30_000_000, sources).filter(|option| option.sck.error_hz == 0);
+        let option = exact.next().unwrap();
+        assert_eq!(option.selection, Selection::Pll3Pfd0);
+        assert_eq!(option.divider, 1);
+        assert_eq!((option.sck.prescale, option.sck.sckdiv), (0, 22));
+        assert_eq!(option.root_hz, 720_000_000);
+        assert!(exact.all(|option| option.selection == Selection::Pll3Pfd0));
+    }
+
+    #[test]
+    fn spi_closest() {
+        const SOURCES: Sources = Sources {
+            pll3_pfd1_hz: 664_615_384,
+            pll3_pfd0_hz: 720_000_000,
+            pll2_hz: CLOCK_FREQUENCY_HZ,
+            pll2_pfd2_hz: 396_000_000,
+        };
+        for target_hz in [132_000_000, 90_000_000, 33_000_000] {
+            let setting = closest(&SOURCES, target_hz).unwrap();
+            assert_eq!(
+                root_frequency(&SOURCES, setting.selection, setting.divider),
+                setting.frequency.hz
+            );
+
+            // Same choice as the hardware path
+            let mut mem: u32 = 0;
+            unsafe {
+                let reg = register(&mut mem);
+                let achieved = configure_frequency_(target_hz, &reg, &SOURCES.table());
+                assert_eq!(achieved, setting.frequency);
+                assert_eq!(selection_(&reg), setting.selection);
+            }
+        }
+        assert_eq!(
+            closest(&SOURCES, 90_000_000).unwrap().selection,
+            Selection::Pll3Pfd0
+        );
+
+        let stopped = Sources {
+            pll3_pfd1_hz: 0,
+            pll3_pfd0_hz: 0,
+            pll2_hz: 0,
+            pll2_pfd2_hz: 0,
+        };
+        assert_eq!(closest(&stopped, 90_000_000), None);
+    }
 }
@@ -0,0 +1,351 @@
+//! Capture and restore the CCM clock tree
+//!
+//! A [`CcmState`] holds the raw values of the registers that describe the clock
+//! tree: the analog PLLs and PFDs, the clock root multiplexers and dividers, and
+//! the clock gates. Restoring a state moves the core clock to the oscillator,
+//! brings the PLLs back before any clock root selects them, and then restores
+//! the core clock.
+
+use crate::{register, GateSnapshot, CCGR_COUNT};
+
+/// Bypass the PLL output; the same field in every PLL
+const BYPASS: u32 = 1 << 16;
+/// Read-only PLL lock status; the same field in every PLL
+const LOCK: u32 = 1 << 31;
+
+/// MISC2 AUDIO_DIV_LSB, AUDIO_DIV_MSB, and VIDEO_DIV
+///
+/// The remaining MISC2 fields control the regulators, so they're not restored.
+const MISC2_DIVIDERS: u32 = 0b11 << 30 | 1 << 23 | 1 << 15;
+
+#[cfg(feature = "imxrt1060")]
+const CCM_CACRR: usize = 0x400F_C010;
+const CCM_CBCDR: usize = 0x400F_C014;
+const CCM_CBCMR: usize = 0x400F_C018;
+const CCM_CDHIPR: usize = 0x400F_C048;
+const CCGR_BASE: usize = 0x400F_C068;
+const CCM_ANALOG_PFD_480: usize = 0x400D_80F0;
+const CCM_ANALOG_PFD_528: usize = 0x400D_8100;
+const CCM_ANALOG_MISC2: usize = 0x400D_8170;
+
+/// An analog PLL's control register, and its other configuration registers
+struct Analog {
+    control: usize,
+    extras: &'static [usize],
+}
+
+/// The most configuration registers that a PLL has
+const MAX_EXTRAS: usize = 3;
+
+const PLLS: &[Analog] = &[
+    #[cfg(feature = "imxrt1060")]
+    Analog {
+        control: 0x400D_8000, // PLL_ARM
+        extras: &[],
+    },
+    Analog {
+        control: 0x400D_8010, // PLL_USB1
+        extras: &[],
+    },
+    #[cfg(feature = "imxrt1060")]
+    Analog {
+        control: 0x400D_8020, // PLL_USB2
+        extras: &[],
+    },
+    Analog {
+        control: 0x400D_8030, // PLL_SYS
+        extras: &[0x400D_8040, 0x400D_8050, 0x400D_8060],
+    },
+    Analog {
+        control: 0x400D_8070, // PLL_AUDIO
+        extras: &[0x400D_8080, 0x400D_8090],
+    },
+    #[cfg(feature = "imxrt1060")]
+    Analog {
+        control: 0x400D_80A0, // PLL_VIDEO
+        extras: &[0x400D_80B0, 0x400D_80C0],
+    },
+    Analog {
+        control: 0x400D_80E0, // PLL_ENET
+        extras: &[],
+    },
+];
+
+/// CBCDR[PERIPH_CLK_SEL]; set to run the core clock from PERIPH_CLK2
+const PERIPH_CLK_SEL: u32 = 1 << 25;
+/// CBCDR[PERIPH_CLK2_PODF]
+const PERIPH_CLK2_PODF: u32 = 0b111 << 27;
+/// CBCMR[PERIPH_CLK2_SEL]
+const PERIPH_CLK2_SEL: u32 = 0b11 << 12;
+/// CBCMR[PERIPH_CLK2_SEL] selecting the oscillator
+const PERIPH_CLK2_SEL_OSC: u32 = 1 << 12;
+
+/// Clock root multiplexer and divider registers that don't affect the core clock
+///
+/// None of these registers have a handshake.
+const ROOTS: &[usize] = &[
+    0x400F_C00C, // CCSR
+    0x400F_C01C, // CSCMR1
+    0x400F_C020, // CSCMR2
+    0x400F_C024, // CSCDR1
+    0x400F_C028, // CS1CDR
+    #[cfg(feature = "imxrt1060")]
+    0x400F_C02C, // CS2CDR
+    0x400F_C030, // CDCDR
+    0x400F_C038, // CSCDR2
+    #[cfg(feature = "imxrt1060")]
+    0x400F_C03C, // CSCDR3
+];
+
+/// A snapshot of the clock tree
+///
+/// Use [`CCM::suspend`](crate::CCM::suspend) to capture the clock tree before
+/// entering a low-power mode, and [`CCM::resume`](crate::CCM::resume) to restore
+/// it after wake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CcmState {
+    plls: [[u32; 1 + MAX_EXTRAS]; PLLS.len()],
+    pfds: [u32; 2],
+    misc2: u32,
+    #[cfg(feature = "imxrt1060")]
+    cacrr: u32,
+    cbcdr: u32,
+    cbcmr: u32,
+    roots: [u32; ROOTS.len()],
+    gates: GateSnapshot,
+}
+
+impl CcmState {
+    /// Returns the clock gates captured in this state
+    pub fn gates(&self) -> &GateSnapshot {
+        &self.gates
+    }
+}
+
+pub(crate) fn capture_(read: impl Fn(usize) -> u32) -> CcmState {
+    let mut plls = [[0; 1 + MAX_EXTRAS]; PLLS.len()];
+    for (values, pll) in plls.iter_mut().zip(PLLS) {
+        values[0] = read(pll.control);
+        for (value, &extra) in values[1..].iter_mut().zip(pll.extras) {
+            *value = read(extra);
+        }
+    }
+    let mut roots = [0; ROOTS.len()];
+    for (value, &register) in roots.iter_mut().zip(ROOTS) {
+        *value = read(register);
+    }
+    let mut ccgr = [0; CCGR_COUNT];
+    for (idx, value) in ccgr.iter_mut().enumerate() {
+        *value = read(CCGR_BASE + idx * 4);
+    }
+    CcmState {
+        plls,
+        pfds: [read(CCM_ANALOG_PFD_480), read(CCM_ANALOG_PFD_528)],
+        misc2: read(CCM_ANALOG_MISC2) & MISC2_DIVIDERS,
+        #[cfg(feature = "imxrt1060")]
+        cacrr: read(CCM_CACRR),
+        cbcdr: read(CCM_CBCDR),
+        cbcmr: read(CCM_CBCMR),
+        roots,
+        gates: GateSnapshot { ccgr },
+    }
+}
+
+/// Captures the clock tree
+pub(crate) fn capture() -> CcmState {
+    // Safety: reads of valid registers
+    capture_(|address| unsafe { register::load(address as *const u32) })
+}
+
+pub(crate) fn restore_(
+    state: &CcmState,
+    read: impl Fn(usize) -> u32,
+    mut write: impl FnMut(usize, u32),
+) {
+    // Move the core clock to the oscillator before any PLL changes, so that
+    // restarting a PLL can't glitch the core. PERIPH_CLK2 keeps selecting the
+    // oscillator until the core clock leaves it.
+    core_to_oscillator_(&read, &mut write);
+
+    // PLLs next, so that they're locked before any root selects them. Leave
+    // unchanged PLLs alone, so that they don't need to relock.
+    for (values, pll) in state.plls.iter().zip(PLLS) {
+        let control = values[0] & !LOCK;
+        let extras = &values[1..=pll.extras.len()];
+        let unchanged = read(pll.control) & !LOCK == control
+            && pll
+                .extras
+                .iter()
+                .zip(extras)
+                .all(|(&register, &value)| read(register) == value);
+        if unchanged {
+            continue;
+        }
+        write(pll.control, read(pll.control) | BYPASS);
+        for (&register, &value) in pll.extras.iter().zip(extras) {
+            write(register, value);
+        }
+        write(pll.control, control | BYPASS);
+        if values[0] & LOCK != 0 {
+            while read(pll.control) & LOCK == 0 {}
+        }
+        write(pll.control, control);
+    }
+
+    write(CCM_ANALOG_PFD_480, state.pfds[0]);
+    write(CCM_ANALOG_PFD_528, state.pfds[1]);
+    let misc2 = read(CCM_ANALOG_MISC2) & !MISC2_DIVIDERS;
+    write(CCM_ANALOG_MISC2, misc2 | state.misc2);
+
+    // Restore the core clock dividers while the core runs from the
+    // oscillator, then switch PERIPH_CLK_SEL in its own handshake.
+    #[cfg(feature = "imxrt1060")]
+    {
+        write(CCM_CACRR, state.cacrr);
+        wait_handshake(&read);
+    }
+    write(
+        CCM_CBCMR,
+        (state.cbcmr & !PERIPH_CLK2_SEL) | PERIPH_CLK2_SEL_OSC,
+    );
+    wait_handshake(&read);
+    write(CCM_CBCDR, state.cbcdr | PERIPH_CLK_SEL);
+    wait_handshake(&read);
+    write(CCM_CBCDR, state.cbcdr);
+    wait_handshake(&read);
+    write(CCM_CBCMR, state.cbcmr);
+    wait_handshake(&read);
+
+    for (&value, &register) in state.roots.iter().zip(ROOTS) {
+        write(register, value);
+    }
+
+    for (idx, &value) in state.gates.ccgr.iter().enumerate() {
+        write(CCGR_BASE + idx * 4, value);
+    }
+}
+
+/// Wait for all divider and multiplexer handshakes to complete
+fn wait_handshake(read: impl Fn(usize) -> u32) {
+    while read(CCM_CDHIPR) != 0 {}
+}
+
+/// Run the core clock from the 24MHz oscillator, through PERIPH_CLK2
+///
+/// The switch uses the glitchless PERIPH_CLK_SEL multiplexer.
+pub(crate) fn core_to_oscillator_(read: impl Fn(usize) -> u32, mut write: impl FnMut(usize, u32)) {
+    write(CCM_CBCDR, read(CCM_CBCDR) & !PERIPH_CLK2_PODF);
+    write(
+        CCM_CBCMR,
+        (read(CCM_CBCMR) & !PERIPH_CLK2_SEL) | PERIPH_CLK2_SEL_OSC,
+    );
+    wait_handshake(&read);
+    write(CCM_CBCDR, read(CCM_CBCDR) | PERIPH_CLK_SEL);
+    wait_handshake(&read);
+}
+
+/// Run the core clock from the 24MHz oscillator
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM.
+pub(crate) unsafe fn core_to_oscillator() {
+    core_to_oscillator_(
+        |address| register::read(address as *const u32),
+        |address, value| register::write(address as *mut u32, u32::MAX, value),
+    )
+}
+
+/// Restores the clock tree
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM.
+pub(crate) unsafe fn restore(state: &CcmState) {
+    restore_(
+        state,
+        |address| register::read(address as *const u32),
+        |address, value| register::write(address as *mut u32, u32::MAX, value),
+    );
+    crate::gate::sync();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{capture_, restore_, LOCK};
+    use std::{cell::RefCell, collections::HashMap};
+
+    const PLL_SYS: usize = 0x400D_8030;
+    const PLL_SYS_NUM: usize = 0x400D_8050;
+    const PLL_USB1: usize = 0x400D_8010;
+    const CBCDR: usize = 0x400F_C014;
+    const CBCMR: usize = 0x400F_C018;
+    const CCGR3: usize = 0x400F_C074;
+    const MISC2: usize = 0x400D_8170;
+
+    #[test]
+    fn state_round_trip() {
+        let memory = RefCell::new(HashMap::new());
+        let read = |address| *memory.borrow().get(&address).unwrap_or(&0);
+        memory.borrow_mut().extend([
+            (PLL_SYS, LOCK | 1 << 13 | 1),
+            (PLL_SYS_NUM, 7),
+            (PLL_USB1, LOCK | 1 << 12 | 1 << 13),
+            (CBCDR, 0x000A_8300),
+            (CCGR3, 0xFFFF_FFFF),
+            (MISC2, 0x0027_2727 | 1 << 15),
+        ]);
+        let state = capture_(read);
+
+        // Deep sleep: power down PLL2, move the AHB clock, gate peripherals,
+        // and let the regulators settle somewhere else.
+        memory.borrow_mut().extend([
+            (PLL_SYS, 1 << 12 | 1),
+            (PLL_SYS_NUM, 0),
+            (CBCDR, 0x0200_8300),
+            (CCGR3, 0),
+            (MISC2, 0x0012_1212),
+        ]);
+
+        let writes = RefCell::new(Vec::new());
+        restore_(
+            &state,
+            |address| match address {
+                // PLLs lock as soon as they're powered
+                PLL_SYS => read(address) | LOCK,
+                _ => read(address),
+            },
+            |address, value| {
+                writes.borrow_mut().push((address, value));
+                memory.borrow_mut().insert(address, value);
+            },
+        );
+
+        assert_eq!(read(PLL_SYS), 1 << 13 | 1);
+        assert_eq!(read(PLL_SYS_NUM), 7);
+        assert_eq!(read(CBCDR), 0x000A_8300);
+        assert_eq!(read(CBCMR), 0);
+        assert_eq!(read(CCGR3), 0xFFFF_FFFF);
+        assert_eq!(read(MISC2), 0x0012_1212 | 1 << 15);
+
+        let writes = writes.into_inner();
+        // The unchanged PLL is not touched
+        assert!(!writes.iter().any(|&(address, _)| address == PLL_USB1));
+        let position = |address, value| {
+            writes
+                .iter()
+                .position(|&write| write == (address, value))
+                .unwrap()
+        };
+        let first = |address| writes.iter().position(|&(a, _)| a == address).unwrap();
+        // The core moves to the oscillator before PLL2 restarts
+        assert!(position(CBCDR, 0x0200_8300) < first(PLL_SYS));
+        // PLL2 restarts bypassed, then comes back before the roots
+        assert!(first(PLL_SYS) < first(PLL_SYS_NUM));
+        // The dividers change before PERIPH_CLK_SEL, in separate writes
+        let dividers = position(CBCDR, 0x000A_8300 | 1 << 25);
+        assert!(first(PLL_SYS_NUM) < dividers);
+        assert!(dividers < position(CBCDR, 0x000A_8300));
+        assert!(position(CBCDR, 0x000A_8300) < first(CCGR3));
+    }
+}
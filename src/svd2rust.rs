@@ -0,0 +1,245 @@
+//! Adapters for svd2rust peripheral access crates
+//!
+//! A svd2rust PAC generates one type per peripheral instance, like
+//! `pac::LPUART2`. Each type dereferences to the instance's register block.
+//! Wrap the PAC peripheral in a [`Peripheral`] to use it as an [`Instance`].
+//! `Peripheral` identifies the instance by its register block address, so you
+//! don't need to write an `Instance` implementation for each PAC type:
+//!
+//! ```no_run
+//! use imxrt_ccm::{svd2rust::Peripheral, uart::UART, ClockGate, CCM};
+//! # mod pac {
+//! #     pub struct LPUART2;
+//! #     impl core::ops::Deref for LPUART2 {
+//! #         type Target = ();
+//! #         fn deref(&self) -> &() { unsafe { &*(0x4018_8000 as *const ()) } }
+//! #     }
+//! # }
+//! # struct MyClocks;
+//! # impl imxrt_ccm::Clocks for MyClocks {
+//! #     type PIT = ();
+//! #     type GPT = ();
+//! #     type UART = Peripheral<pac::LPUART2, UART>;
+//! #     type SPI = ();
+//! #     type I2C = ();
+//! # }
+//! # let mut ccm = unsafe { CCM::<MyClocks>::new() };
+//! # let lpuart2 = pac::LPUART2;
+//!
+//! let mut uart2: Peripheral<_, UART> = Peripheral::new(lpuart2).unwrap();
+//! let uart_clock = ccm.uart_clock_mut();
+//! uart_clock.configure();
+//! uart_clock.set_clock_gate(&mut uart2, ClockGate::On);
+//! ```
+//!
+//! The register block addresses depend on your chip feature.
+
+use crate::{
+    i2c::I2C,
+    perclock::{GPT, PIT},
+    spi::SPI,
+    uart::UART,
+    usb::USB,
+    ClockGateLocator, Instance, ADC, DCDC, DMA, PWM,
+};
+use core::ops::{Deref, DerefMut};
+
+/// A peripheral instance identifier with a known register block address
+///
+/// If you enable the `"unsealed"` feature, and implement [`ClockGateLocator`]
+/// for your own identifier, you may also implement `Addressed`. Each address
+/// must be the base address of the instance's register block.
+pub trait Addressed: ClockGateLocator + 'static {
+    /// The register block address of each instance on this chip
+    const ADDRESSES: &'static [(usize, Self)];
+}
+
+/// Returns the instance whose register block is at `address`
+fn from_address<I: Addressed>(address: usize) -> Option<I> {
+    I::ADDRESSES
+        .iter()
+        .find(|(addr, _)| *addr == address)
+        .map(|(_, inst)| *inst)
+}
+
+/// A svd2rust peripheral that's an [`Instance`]
+///
+/// `Peripheral` dereferences to the wrapped PAC peripheral.
+#[derive(Debug)]
+pub struct Peripheral<P, I> {
+    periph: P,
+    inst: I,
+}
+
+impl<P, I> Peripheral<P, I>
+where
+    P: Deref,
+    I: Addressed,
+{
+    /// Wrap the svd2rust peripheral `periph`
+    ///
+    /// Returns `None` if `periph`'s register block isn't an `I` instance on
+    /// this chip.
+    pub fn new(periph: P) -> Option<Self> {
+        let address = &*periph as *const P::Target as *const () as usize;
+        from_address(address).map(|inst| Peripheral { periph, inst })
+    }
+}
+
+impl<P, I> Peripheral<P, I> {
+    /// Release the svd2rust peripheral
+    pub fn release(self) -> P {
+        self.periph
+    }
+}
+
+impl<P, I> Deref for Peripheral<P, I> {
+    type Target = P;
+    fn deref(&self) -> &P {
+        &self.periph
+    }
+}
+
+impl<P, I> DerefMut for Peripheral<P, I> {
+    fn deref_mut(&mut self) -> &mut P {
+        &mut self.periph
+    }
+}
+
+unsafe impl<P, I> Instance for Peripheral<P, I>
+where
+    I: Addressed,
+{
+    type Inst = I;
+    #[inline(always)]
+    fn instance(&self) -> I {
+        self.inst
+    }
+    #[inline(always)]
+    fn is_valid(inst: I) -> bool {
+        I::ADDRESSES.iter().any(|(_, valid)| *valid == inst)
+    }
+}
+
+macro_rules! addresses {
+    ($($inst:ty { $($address:literal => $id:expr),+ $(,)? })+) => {
+        $(
+            impl Addressed for $inst {
+                const ADDRESSES: &'static [(usize, Self)] = &[$(($address, $id)),+];
+            }
+        )+
+    };
+}
+
+addresses! {
+    DCDC { 0x4008_0000 => DCDC }
+    DMA { 0x400E_8000 => DMA }
+    PIT { 0x4008_4000 => PIT }
+    GPT {
+        0x401E_C000 => GPT::GPT1,
+        0x401F_0000 => GPT::GPT2,
+    }
+}
+
+#[cfg(not(any(feature = "imxrt1010", feature = "imxrt1060")))]
+compile_error!("Ensure that svd2rust register block addresses are correct");
+
+#[cfg(feature = "imxrt1060")]
+addresses! {
+    UART {
+        0x4018_4000 => UART::UART1,
+        0x4018_8000 => UART::UART2,
+        0x4018_C000 => UART::UART3,
+        0x4019_0000 => UART::UART4,
+        0x4019_4000 => UART::UART5,
+        0x4019_8000 => UART::UART6,
+        0x4019_C000 => UART::UART7,
+        0x401A_0000 => UART::UART8,
+    }
+    SPI {
+        0x4039_4000 => SPI::SPI1,
+        0x4039_8000 => SPI::SPI2,
+        0x4039_C000 => SPI::SPI3,
+        0x403A_0000 => SPI::SPI4,
+    }
+    I2C {
+        0x403F_0000 => I2C::I2C1,
+        0x403F_4000 => I2C::I2C2,
+        0x403F_8000 => I2C::I2C3,
+        0x403F_C000 => I2C::I2C4,
+    }
+    ADC {
+        0x400C_4000 => ADC::ADC1,
+        0x400C_8000 => ADC::ADC2,
+    }
+    PWM {
+        0x403D_C000 => PWM::PWM1,
+        0x403E_0000 => PWM::PWM2,
+        0x403E_4000 => PWM::PWM3,
+        0x403E_8000 => PWM::PWM4,
+    }
+    USB {
+        0x402E_0000 => USB::USB1,
+        0x402E_0200 => USB::USB2,
+    }
+}
+
+#[cfg(all(feature = "imxrt1010", not(feature = "imxrt1060")))]
+addresses! {
+    UART {
+        0x4018_4000 => UART::UART1,
+        0x4018_8000 => UART::UART2,
+        0x4018_C000 => UART::UART3,
+        0x4019_0000 => UART::UART4,
+    }
+    SPI {
+        0x4019_4000 => SPI::SPI1,
+        0x4019_8000 => SPI::SPI2,
+    }
+    I2C {
+        0x401A_4000 => I2C::I2C1,
+        0x401A_8000 => I2C::I2C2,
+    }
+    ADC { 0x400C_4000 => ADC::ADC1 }
+    PWM { 0x401C_C000 => PWM::PWM1 }
+    USB { 0x400E_4000 => USB::USB1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_address, Peripheral};
+    use crate::{perclock::GPT, uart::UART, Instance, DMA};
+    use core::ops::Deref;
+
+    /// A svd2rust-like peripheral at `ADDRESS`
+    struct Pac<const ADDRESS: usize>;
+
+    impl<const ADDRESS: usize> Deref for Pac<ADDRESS> {
+        type Target = ();
+        fn deref(&self) -> &() {
+            // Safety: references to zero-sized types may have any aligned,
+            // non-null address.
+            unsafe { &*(ADDRESS as *const ()) }
+        }
+    }
+
+    #[test]
+    fn svd2rust_peripheral() {
+        let uart2: Peripheral<_, UART> = Peripheral::new(Pac::<0x4018_8000>).unwrap();
+        assert_eq!(uart2.instance(), UART::UART2);
+        let gpt1: Peripheral<_, GPT> = Peripheral::new(Pac::<0x401E_C000>).unwrap();
+        assert_eq!(gpt1.instance(), GPT::GPT1);
+        assert!(Peripheral::<_, DMA>::new(Pac::<0x400E_8000>).is_some());
+
+        // A GPT isn't a UART
+        assert!(Peripheral::<_, UART>::new(Pac::<0x401E_C000>).is_none());
+        assert_eq!(from_address::<GPT>(0x401F_0000), Some(GPT::GPT2));
+        assert_eq!(from_address::<GPT>(0x401F_0004), None);
+
+        assert!(Peripheral::<Pac<0>, UART>::is_valid(UART::UART4));
+        assert_eq!(
+            Peripheral::<Pac<0>, UART>::is_valid(UART::UART8),
+            cfg!(feature = "imxrt1060")
+        );
+    }
+}
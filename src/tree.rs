@@ -0,0 +1,696 @@
+//! Clock tree model
+//!
+//! The module describes the clocks that this crate controls as a graph. The
+//! crystal oscillator is the root of the graph. PLLs multiply the oscillator,
+//! PFDs divide a PLL, multiplexers select a parent, and dividers divide the
+//! selected clock. A clock root, like the UART clock root, is a multiplexer
+//! followed by a divider. Clock gates pass, or block, their clock root.
+//!
+//! Each [`Node`] has at most one parent, selected by the current register
+//! values. [`Tree::link`] describes the edge from a node to its parent, and
+//! [`Tree::frequency`] propagates the oscillator frequency along those edges.
+//!
+//! A [`Tree`] decodes register values that it reads through a function. Use
+//! [`CCM::tree`](crate::CCM::tree) to model the hardware, or supply your own
+//! function to model register values from elsewhere, like a
+//! [`DebugSnapshot`](crate::DebugSnapshot::tree):
+//!
+//! ```
+//! use imxrt_ccm::{raw, tree::{Node, Tree}, ClockRoot, ClockSource};
+//!
+//! // UART_CLK_SEL selects the oscillator; UART_CLK_PODF divides by 4
+//! let tree = Tree::new(|reg| if reg == raw::CSCDR1 { 1 << 6 | 3 } else { 0 });
+//! let uart = Node::Root(ClockRoot::UART);
+//! assert_eq!(tree.parent(uart), Some(Node::Source(ClockSource::Oscillator)));
+//! assert_eq!(tree.frequency(uart), 6_000_000);
+//! ```
+
+use crate::{raw, ClockGateLocation, ClockRoot, ClockRootLocator, ClockSource};
+
+use crate::{pfd::PFD, register::Field, xtalosc::reference_frequency};
+#[cfg(feature = "imxrt1060")]
+use raw::{cbcdr, cs2cdr, cscmr2};
+#[cfg(any(feature = "imxrt1060", feature = "imxrt1010"))]
+use raw::{cs1cdr, cscdr1, cscmr1};
+use raw::{Reg, RegField};
+
+/// A node in the clock tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Node {
+    /// A clock source, like the oscillator, a PLL, or a PFD
+    Source(ClockSource),
+    /// PLL3 divided by 6, also known as `pll3_80m`
+    Pll3Div6,
+    /// PLL3 divided by 8, also known as `pll3_60m`
+    Pll3Div8,
+    /// PLL1 divided by the ARM divider
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    Pll1Divided,
+    /// The PRE_PERIPH_CLK multiplexer
+    PrePeriph,
+    /// The PERIPH_CLK2 multiplexer, after its divider
+    PeriphClk2,
+    /// The glitchless PERIPH_CLK multiplexer
+    Periph,
+    /// The SEMC alternate clock multiplexer
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    SemcAlt,
+    /// The SEMC clock multiplexer, before the SEMC divider
+    #[cfg(feature = "imxrt1060")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "imxrt1060")))]
+    SemcPre,
+    /// A clock root
+    Root(ClockRoot),
+    /// A clock gate, and the clock root that it gates
+    Gate(ClockRoot, ClockGateLocation),
+}
+
+impl Node {
+    /// Returns the clock gate node for a peripheral instance
+    pub fn gate<I: ClockRootLocator>(inst: &I) -> Self {
+        Node::Gate(inst.clock_root(), inst.location())
+    }
+}
+
+/// A node's frequency relative to its parent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ratio {
+    /// Multiplies the parent frequency
+    pub numerator: u64,
+    /// Divides the parent frequency
+    pub denominator: u64,
+}
+
+impl Ratio {
+    /// The node doesn't produce a clock
+    pub const OFF: Ratio = Ratio::new(0, 1);
+    /// The node passes its parent's clock
+    pub const ONE: Ratio = Ratio::new(1, 1);
+
+    /// Returns `numerator / denominator`
+    pub const fn new(numerator: u64, denominator: u64) -> Self {
+        Ratio {
+            numerator,
+            denominator,
+        }
+    }
+
+    /// Returns a ratio that divides by `divider`
+    pub const fn divide(divider: u32) -> Self {
+        Ratio::new(1, divider as u64)
+    }
+
+    /// Apply the ratio to `hz`
+    ///
+    /// Returns 0 if the denominator is 0.
+    pub const fn apply(self, hz: u32) -> u32 {
+        match (hz as u64 * self.numerator).checked_div(self.denominator) {
+            Some(hz) => hz as u32,
+            None => 0,
+        }
+    }
+}
+
+/// The edge from a node to its parent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Link {
+    /// The selected parent
+    pub parent: Node,
+    /// The node's frequency relative to `parent`
+    pub ratio: Ratio,
+}
+
+/// A clock tree, decoded from register values
+///
+/// See the [module documentation](crate::tree) for more information.
+pub struct Tree<R> {
+    read: R,
+    reference_hz: Option<u32>,
+}
+
+/// Reads registers from the hardware
+pub type Hardware = fn(Reg) -> u32;
+
+impl Tree<Hardware> {
+    /// Model the clock tree described by the CCM and CCM_ANALOG registers
+    ///
+    /// # Safety
+    ///
+    /// The tree reads global, mutable memory whenever you query it. Consider
+    /// using [`CCM::tree`](crate::CCM::tree) for a safer interface.
+    pub const unsafe fn hardware() -> Self {
+        Tree::new(|reg| unsafe { reg.read() })
+    }
+}
+
+impl<R: Fn(Reg) -> u32> Tree<R> {
+    /// Model the clock tree described by the register values that `read`
+    /// returns
+    pub const fn new(read: R) -> Self {
+        Tree {
+            read,
+            reference_hz: None,
+        }
+    }
+
+    /// Use `reference_hz` as the oscillator frequency
+    ///
+    /// By default, the tree uses [`reference_frequency`]. Use this to model
+    /// a board that has another reference, like when you decode a
+    /// [`DebugSnapshot`](crate::DebugSnapshot) from that board.
+    pub fn with_reference(mut self, reference_hz: u32) -> Self {
+        self.reference_hz = Some(reference_hz);
+        self
+    }
+
+    fn field(&self, field: RegField) -> u32 {
+        field.get((self.read)(field.reg()))
+    }
+
+    /// Returns the edge from `node` to its selected parent, or `None` if
+    /// `node` is the oscillator
+    pub fn link(&self, node: Node) -> Option<Link> {
+        let link = |parent, ratio| Some(Link { parent, ratio });
+        let source = |source| Node::Source(source);
+        match node {
+            Node::Source(ClockSource::Oscillator) => None,
+            Node::Source(ClockSource::Pll2Bypass) => {
+                link(source(ClockSource::Oscillator), Ratio::ONE)
+            }
+            #[cfg(feature = "imxrt1060")]
+            Node::Source(ClockSource::Pll1) => link(source(ClockSource::Oscillator), self.pll1()),
+            Node::Source(ClockSource::Pll2) => link(source(ClockSource::Oscillator), self.pll2()),
+            Node::Source(ClockSource::Pll3) => link(source(ClockSource::Oscillator), self.pll3()),
+            Node::Source(ClockSource::AudioPll) => link(
+                source(ClockSource::Oscillator),
+                self.fractional(
+                    raw::PLL_AUDIO,
+                    raw::PLL_AUDIO_NUM,
+                    raw::PLL_AUDIO_DENOM,
+                    (15, 23),
+                ),
+            ),
+            #[cfg(feature = "imxrt1060")]
+            Node::Source(ClockSource::VideoPll) => link(
+                source(ClockSource::Oscillator),
+                self.fractional(
+                    raw::PLL_VIDEO,
+                    raw::PLL_VIDEO_NUM,
+                    raw::PLL_VIDEO_DENOM,
+                    (30, 31),
+                ),
+            ),
+            Node::Source(ClockSource::EnetPll) => {
+                link(source(ClockSource::Oscillator), self.pll6())
+            }
+            Node::Source(ClockSource::Pll2Pfd(pfd)) => {
+                link(source(ClockSource::Pll2), self.pfd(raw::PFD_528, pfd))
+            }
+            Node::Source(ClockSource::Pll3Pfd(pfd)) => {
+                link(source(ClockSource::Pll3), self.pfd(raw::PFD_480, pfd))
+            }
+            Node::Source(ClockSource::IPG) => link(Node::Root(ClockRoot::IPG), Ratio::ONE),
+            Node::Pll3Div6 => link(source(ClockSource::Pll3), Ratio::divide(6)),
+            Node::Pll3Div8 => link(source(ClockSource::Pll3), Ratio::divide(8)),
+            #[cfg(feature = "imxrt1060")]
+            Node::Pll1Divided => link(
+                source(ClockSource::Pll1),
+                Ratio::divide(self.field(ARM_PODF) + 1),
+            ),
+            Node::PrePeriph => {
+                let selected = crate::arm::Source::from_field(self.field(PRE_PERIPH_CLK_SEL));
+                let parent = match selected {
+                    #[cfg(feature = "imxrt1060")]
+                    crate::arm::Source::Pll1 => Node::Pll1Divided,
+                    selected => source(selected.into()),
+                };
+                link(parent, Ratio::ONE)
+            }
+            Node::PeriphClk2 => {
+                let selected = crate::arm::PeriphClk2::from_field(self.field(PERIPH_CLK2_SEL));
+                link(
+                    source(selected.into()),
+                    Ratio::divide(self.field(PERIPH_CLK2_PODF) + 1),
+                )
+            }
+            Node::Periph => match self.field(PERIPH_CLK_SEL) {
+                0 => link(Node::PrePeriph, Ratio::ONE),
+                _ => link(Node::PeriphClk2, Ratio::ONE),
+            },
+            Node::Root(ClockRoot::AHB) => {
+                link(Node::Periph, Ratio::divide(self.field(AHB_PODF) + 1))
+            }
+            Node::Root(ClockRoot::IPG) => link(
+                Node::Root(ClockRoot::AHB),
+                Ratio::divide(self.field(IPG_PODF) + 1),
+            ),
+            Node::Root(ClockRoot::PerClock) => {
+                let parent = match self.field(PERCLK_SEL) {
+                    0 => Node::Root(ClockRoot::IPG),
+                    _ => source(ClockSource::Oscillator),
+                };
+                link(parent, Ratio::divide(self.field(PERCLK_PODF) + 1))
+            }
+            Node::Root(ClockRoot::UART) => {
+                let parent = match self.field(UART_CLK_SEL) & 1 {
+                    0 => Node::Pll3Div6,
+                    _ => source(ClockSource::Oscillator),
+                };
+                link(parent, Ratio::divide(self.field(UART_CLK_PODF) + 1))
+            }
+            Node::Root(ClockRoot::SPI) => {
+                let selected = crate::spi::from_field(self.field(LPSPI_CLK_SEL));
+                link(
+                    source(selected.into()),
+                    Ratio::divide(self.field(LPSPI_PODF) + 1),
+                )
+            }
+            Node::Root(ClockRoot::I2C) => {
+                let parent = match self.field(LPI2C_CLK_SEL) {
+                    0 => Node::Pll3Div8,
+                    _ => source(ClockSource::Oscillator),
+                };
+                link(parent, Ratio::divide(self.field(LPI2C_CLK_PODF) + 1))
+            }
+            #[cfg(feature = "imxrt1060")]
+            Node::SemcAlt => {
+                let parent = match self.field(cbcdr::SEMC_ALT_CLK_SEL) {
+                    0 => ClockSource::Pll2Pfd(PFD::PFD2),
+                    _ => ClockSource::Pll3Pfd(PFD::PFD1),
+                };
+                link(source(parent), Ratio::ONE)
+            }
+            #[cfg(feature = "imxrt1060")]
+            Node::SemcPre => match self.field(cbcdr::SEMC_CLK_SEL) {
+                0 => link(Node::Periph, Ratio::ONE),
+                _ => link(Node::SemcAlt, Ratio::ONE),
+            },
+            #[cfg(feature = "imxrt1060")]
+            Node::Root(ClockRoot::USDHC1) => link(
+                self.usdhc(cscmr1::USDHC1_CLK_SEL),
+                Ratio::divide(self.field(cscdr1::USDHC1_PODF) + 1),
+            ),
+            #[cfg(feature = "imxrt1060")]
+            Node::Root(ClockRoot::USDHC2) => link(
+                self.usdhc(cscmr1::USDHC2_CLK_SEL),
+                Ratio::divide(self.field(cscdr1::USDHC2_PODF) + 1),
+            ),
+            #[cfg(any(feature = "imxrt1060", feature = "imxrt1010"))]
+            Node::Root(ClockRoot::SAI1) => self.sai(
+                cscmr1::SAI1_CLK_SEL,
+                cs1cdr::SAI1_CLK_PRED,
+                cs1cdr::SAI1_CLK_PODF,
+            ),
+            #[cfg(feature = "imxrt1060")]
+            Node::Root(ClockRoot::SAI2) => self.sai(
+                cscmr1::SAI2_CLK_SEL,
+                cs2cdr::SAI2_CLK_PRED,
+                cs2cdr::SAI2_CLK_PODF,
+            ),
+            #[cfg(any(feature = "imxrt1060", feature = "imxrt1010"))]
+            Node::Root(ClockRoot::SAI3) => self.sai(
+                cscmr1::SAI3_CLK_SEL,
+                cs1cdr::SAI3_CLK_PRED,
+                cs1cdr::SAI3_CLK_PODF,
+            ),
+            #[cfg(any(feature = "imxrt1060", feature = "imxrt1010"))]
+            Node::Root(ClockRoot::FlexSPI) => {
+                let parent = match self.field(cscmr1::FLEXSPI_CLK_SEL) {
+                    // The 1010 doesn't have a SEMC
+                    #[cfg(feature = "imxrt1060")]
+                    0 => Node::SemcPre,
+                    #[cfg(not(feature = "imxrt1060"))]
+                    0 => source(ClockSource::Pll2),
+                    1 => source(ClockSource::Pll3),
+                    2 => source(ClockSource::Pll2Pfd(PFD::PFD2)),
+                    _ => source(ClockSource::Pll3Pfd(PFD::PFD0)),
+                };
+                link(parent, Ratio::divide(self.field(cscmr1::FLEXSPI_PODF) + 1))
+            }
+            #[cfg(feature = "imxrt1060")]
+            Node::Root(ClockRoot::SEMC) => link(
+                Node::SemcPre,
+                Ratio::divide(self.field(cbcdr::SEMC_PODF) + 1),
+            ),
+            #[cfg(any(feature = "imxrt1060", feature = "imxrt1010"))]
+            Node::Root(ClockRoot::Trace) => {
+                let parent = match self.field(TRACE_CLK_SEL) {
+                    0 => ClockSource::Pll2,
+                    1 => ClockSource::Pll2Pfd(PFD::PFD2),
+                    2 => ClockSource::Pll2Pfd(PFD::PFD0),
+                    _ => ClockSource::Pll2Pfd(PFD::PFD1),
+                };
+                link(
+                    source(parent),
+                    Ratio::divide(self.field(cscdr1::TRACE_PODF) + 1),
+                )
+            }
+            #[cfg(feature = "imxrt1060")]
+            Node::Root(ClockRoot::CAN) => {
+                let divider = Ratio::divide(self.field(cscmr2::CAN_CLK_PODF) + 1);
+                match self.field(cscmr2::CAN_CLK_SEL) {
+                    0 => link(Node::Pll3Div8, divider),
+                    1 => link(source(ClockSource::Oscillator), divider),
+                    2 => link(Node::Pll3Div6, divider),
+                    // Disabled
+                    _ => link(source(ClockSource::Oscillator), Ratio::OFF),
+                }
+            }
+            #[cfg(feature = "imxrt1060")]
+            Node::Root(ClockRoot::FlexIO2) => {
+                let parent = match self.field(cscmr2::FLEXIO2_CLK_SEL) {
+                    0 => ClockSource::AudioPll,
+                    1 => ClockSource::Pll3Pfd(PFD::PFD2),
+                    2 => ClockSource::VideoPll,
+                    _ => ClockSource::Pll3,
+                };
+                let pred = self.field(cs1cdr::FLEXIO2_CLK_PRED) + 1;
+                let podf = self.field(cs1cdr::FLEXIO2_CLK_PODF) + 1;
+                link(source(parent), Ratio::divide(pred * podf))
+            }
+            Node::Gate(root, location) => {
+                // Safety: the location names a valid CCGR register
+                let ccgr = unsafe { Reg::new(raw::CCGR0.address().add(location.offset)) };
+                let value = (self.read)(ccgr);
+                let on = location
+                    .gates
+                    .iter()
+                    .any(|gate| (value >> (gate * 2)) & 0b11 != 0);
+                link(Node::Root(root), if on { Ratio::ONE } else { Ratio::OFF })
+            }
+        }
+    }
+
+    /// Returns the selected parent of `node`, or `None` if `node` is the
+    /// oscillator
+    pub fn parent(&self, node: Node) -> Option<Node> {
+        self.link(node).map(|link| link.parent)
+    }
+
+    /// Returns the frequency of `node`, propagated from the oscillator
+    ///
+    /// Returns 0 if any node between `node` and the oscillator is off.
+    pub fn frequency(&self, node: Node) -> u32 {
+        match self.link(node) {
+            None => self.reference_hz.unwrap_or_else(reference_frequency),
+            Some(Link { parent, ratio }) if ratio != Ratio::OFF => {
+                ratio.apply(self.frequency(parent))
+            }
+            Some(_) => 0,
+        }
+    }
+
+    /// Returns `node`, followed by each parent up to the oscillator
+    pub fn path(&self, node: Node) -> Path<'_, R> {
+        Path {
+            tree: self,
+            next: Some(node),
+        }
+    }
+
+    /// Decode a USDHC clock root multiplexer
+    #[cfg(feature = "imxrt1060")]
+    fn usdhc(&self, sel: RegField) -> Node {
+        match self.field(sel) {
+            0 => Node::Source(ClockSource::Pll2Pfd(PFD::PFD2)),
+            _ => Node::Source(ClockSource::Pll2Pfd(PFD::PFD0)),
+        }
+    }
+
+    /// Decode a SAI clock root multiplexer, pre-divider, and divider
+    #[cfg(any(feature = "imxrt1060", feature = "imxrt1010"))]
+    fn sai(&self, sel: RegField, pred: RegField, podf: RegField) -> Option<Link> {
+        let parent = match self.field(sel) {
+            0 => ClockSource::Pll3Pfd(PFD::PFD2),
+            #[cfg(feature = "imxrt1060")]
+            1 => ClockSource::VideoPll,
+            2 => ClockSource::AudioPll,
+            // Reserved, and the 1010's missing video PLL
+            _ => {
+                return Some(Link {
+                    parent: Node::Source(ClockSource::Oscillator),
+                    ratio: Ratio::OFF,
+                })
+            }
+        };
+        let divider = (self.field(pred) + 1) * (self.field(podf) + 1);
+        Some(Link {
+            parent: Node::Source(parent),
+            ratio: Ratio::divide(divider),
+        })
+    }
+
+    #[cfg(feature = "imxrt1060")]
+    fn pll1(&self) -> Ratio {
+        let pll_arm = (self.read)(raw::PLL_ARM);
+        if POWERDOWN.get(pll_arm) != 0 {
+            Ratio::OFF
+        } else {
+            Ratio::new(FRACPLL_DIV_SELECT.get(pll_arm) as u64, 2)
+        }
+    }
+
+    fn pll2(&self) -> Ratio {
+        let pll_sys = (self.read)(raw::PLL_SYS);
+        if POWERDOWN.get(pll_sys) != 0 || ENABLE.get(pll_sys) == 0 {
+            return Ratio::OFF;
+        }
+        if PLL_BYPASS.get(pll_sys) != 0 {
+            return Ratio::ONE;
+        }
+        let div_select = 20 + 2 * PLL_SYS_DIV_SELECT.get(pll_sys) as u64;
+        let num = MFN.get((self.read)(raw::PLL_SYS_NUM)) as u64;
+        let denom = MFN.get((self.read)(raw::PLL_SYS_DENOM)) as u64;
+        if denom == 0 {
+            Ratio::new(div_select, 1)
+        } else {
+            Ratio::new(div_select * denom + num, denom)
+        }
+    }
+
+    fn pll3(&self) -> Ratio {
+        let pll_usb1 = (self.read)(raw::PLL_USB1);
+        if POWERDOWN.get(pll_usb1) == 0 || ENABLE.get(pll_usb1) == 0 {
+            // PLL3 has a POWER bit in place of POWERDOWN
+            Ratio::OFF
+        } else if PLL_BYPASS.get(pll_usb1) != 0 {
+            Ratio::ONE
+        } else {
+            Ratio::new(20 + 2 * PLL_USB1_DIV_SELECT.get(pll_usb1) as u64, 1)
+        }
+    }
+
+    fn pll6(&self) -> Ratio {
+        let pll_enet = (self.read)(raw::PLL_ENET);
+        if POWERDOWN.get(pll_enet) != 0 {
+            Ratio::OFF
+        } else if PLL_BYPASS.get(pll_enet) != 0 {
+            Ratio::ONE
+        } else {
+            Ratio::new(
+                crate::pll6::PLL6_FREQUENCY_HZ as u64,
+                crate::OSCILLATOR_FREQUENCY_HZ as u64,
+            )
+        }
+    }
+
+    /// Decode a fractional PLL, given the MISC2 divider bits (lsb, msb)
+    fn fractional(&self, pll: Reg, num: Reg, denom: Reg, misc2: (u32, u32)) -> Ratio {
+        let value = (self.read)(pll);
+        if POWERDOWN.get(value) != 0 || ENABLE.get(value) == 0 {
+            return Ratio::OFF;
+        }
+        if PLL_BYPASS.get(value) != 0 {
+            return Ratio::ONE;
+        }
+        let misc2_value = (self.read)(raw::MISC2);
+        let misc2_div = (misc2_value >> misc2.1 & 1) << 1 | (misc2_value >> misc2.0 & 1);
+        let post_divider =
+            crate::fracpll::PostDivider::from_fields(POST_DIV_SELECT.get(value), misc2_div) as u64;
+        let div_select = FRACPLL_DIV_SELECT.get(value) as u64;
+        let num = MFN.get((self.read)(num)) as u64;
+        let denom = MFN.get((self.read)(denom)) as u64;
+        if denom == 0 {
+            Ratio::new(div_select, post_divider)
+        } else {
+            Ratio::new(div_select * denom + num, denom * post_divider)
+        }
+    }
+
+    fn pfd(&self, reg: Reg, pfd: PFD) -> Ratio {
+        let value = (self.read)(reg);
+        let offset = 8 * pfd as u32;
+        if value >> (offset + 7) & 1 != 0 {
+            Ratio::OFF
+        } else {
+            Ratio::new(18, (value >> offset & 0x3F).max(1) as u64)
+        }
+    }
+}
+
+/// The nodes between a node and the oscillator
+///
+/// See [`Tree::path`].
+pub struct Path<'a, R> {
+    tree: &'a Tree<R>,
+    next: Option<Node>,
+}
+
+impl<R: Fn(Reg) -> u32> Iterator for Path<'_, R> {
+    type Item = Node;
+    fn next(&mut self) -> Option<Node> {
+        let node = self.next?;
+        self.next = self.tree.parent(node);
+        Some(node)
+    }
+}
+
+// PLL fields. POWERDOWN, ENABLE, and BYPASS are in the same place in every
+// PLL register.
+const POWERDOWN: Field = Field::new(12, 1);
+const ENABLE: Field = Field::new(13, 1);
+const PLL_BYPASS: Field = Field::new(16, 1);
+const MFN: Field = Field::new(0, 0x3FFF_FFFF);
+const PLL_SYS_DIV_SELECT: Field = Field::new(0, 1);
+const PLL_USB1_DIV_SELECT: Field = Field::new(1, 1);
+/// Also the PLL1 DIV_SELECT field
+const FRACPLL_DIV_SELECT: Field = Field::new(0, 0x7F);
+const POST_DIV_SELECT: Field = Field::new(19, 0b11);
+
+#[cfg(feature = "imxrt1060")]
+const ARM_PODF: RegField = RegField::new(raw::CACRR, 0, 0x7);
+const PRE_PERIPH_CLK_SEL: RegField = RegField::new(raw::CBCMR, 18, 0x3);
+const PERIPH_CLK2_SEL: RegField = RegField::new(raw::CBCMR, 12, 0x3);
+const PERIPH_CLK2_PODF: RegField = RegField::new(raw::CBCDR, 27, 0x7);
+const PERIPH_CLK_SEL: RegField = RegField::new(raw::CBCDR, 25, 0x1);
+const AHB_PODF: RegField = RegField::new(raw::CBCDR, 10, 0x7);
+const IPG_PODF: RegField = RegField::new(raw::CBCDR, 8, 0x3);
+const PERCLK_SEL: RegField = RegField::new(raw::CSCMR1, 6, 0x1);
+const PERCLK_PODF: RegField = RegField::new(raw::CSCMR1, 0, 0x3F);
+const UART_CLK_SEL: RegField = RegField::new(raw::CSCDR1, 6, 0x3);
+const UART_CLK_PODF: RegField = RegField::new(raw::CSCDR1, 0, 0x3F);
+const LPSPI_CLK_SEL: RegField = RegField::new(raw::CBCMR, 4, 0x3);
+#[cfg(not(feature = "imxrt1010"))]
+const LPSPI_PODF: RegField = RegField::new(raw::CBCMR, 26, 0x7);
+#[cfg(feature = "imxrt1010")]
+const LPSPI_PODF: RegField = RegField::new(raw::CBCMR, 26, 0xF);
+const LPI2C_CLK_SEL: RegField = RegField::new(raw::CSCDR2, 18, 0x1);
+const LPI2C_CLK_PODF: RegField = RegField::new(raw::CSCDR2, 19, 0x3F);
+#[cfg(any(feature = "imxrt1060", feature = "imxrt1010"))]
+const TRACE_CLK_SEL: RegField = RegField::new(raw::CBCMR, 14, 0x3);
+
+#[cfg(test)]
+mod tests {
+    use super::{Node, Ratio, Tree};
+    use crate::{pfd::PFD, raw, ClockGateLocation, ClockRoot, ClockSource};
+
+    /// PLL2 and PLL3 run. PRE_PERIPH_CLK selects PLL2, AHB divides by 2, IPG
+    /// divides by 4. UART runs on pll3_80m, and SPI on PLL2 PFD2 / 2.
+    fn read(reg: raw::Reg) -> u32 {
+        const ENABLE: u32 = 1 << 13;
+        match reg {
+            r if r == raw::PLL_SYS => ENABLE | 1,
+            r if r == raw::PLL_USB1 => ENABLE | 1 << 12,
+            r if r == raw::PFD_528 => 1 << 31 | 24 << 16,
+            r if r == raw::CBCMR => 1 << 26 | 3 << 4,
+            r if r == raw::CBCDR => 1 << 10 | 3 << 8,
+            r if r == raw::CSCMR1 => 1 << 6 | 23,
+            r if r == raw::CSCDR1 => 0,
+            r if r == raw::CCGR5 => 0b11 << 24,
+            _ => 0,
+        }
+    }
+
+    #[test]
+    fn tree_frequency() {
+        let tree = Tree::new(read);
+        let hz = |node| tree.frequency(node);
+        assert_eq!(hz(Node::Source(ClockSource::Pll2)), 528_000_000);
+        assert_eq!(hz(Node::Source(ClockSource::Pll3)), 480_000_000);
+        assert_eq!(
+            hz(Node::Source(ClockSource::Pll2Pfd(PFD::PFD2))),
+            396_000_000
+        );
+        // PFD3 is gated
+        assert_eq!(hz(Node::Source(ClockSource::Pll2Pfd(PFD::PFD3))), 0);
+        assert_eq!(hz(Node::Source(ClockSource::AudioPll)), 0);
+        assert_eq!(hz(Node::Root(ClockRoot::AHB)), 264_000_000);
+        assert_eq!(hz(Node::Root(ClockRoot::IPG)), 66_000_000);
+        assert_eq!(hz(Node::Root(ClockRoot::PerClock)), 1_000_000);
+        assert_eq!(hz(Node::Root(ClockRoot::UART)), 80_000_000);
+        assert_eq!(hz(Node::Root(ClockRoot::SPI)), 198_000_000);
+        assert_eq!(hz(Node::Root(ClockRoot::I2C)), 60_000_000);
+
+        let gate = |gate| Node::Gate(ClockRoot::UART, ClockGateLocation::new(5, gate));
+        assert_eq!(hz(gate(&[12])), 80_000_000);
+        assert_eq!(hz(gate(&[13])), 0);
+        assert_eq!(tree.link(gate(&[13])).unwrap().ratio, Ratio::OFF);
+    }
+
+    #[cfg(feature = "imxrt1060")]
+    #[test]
+    fn tree_root_frequency() {
+        let tree = Tree::new(read);
+        let hz = |root| tree.frequency(Node::Root(root));
+        assert_eq!(hz(ClockRoot::USDHC1), 396_000_000);
+        assert_eq!(hz(ClockRoot::SEMC), tree.frequency(Node::Periph));
+        assert_eq!(hz(ClockRoot::FlexSPI), tree.frequency(Node::Periph));
+        assert_eq!(hz(ClockRoot::Trace), 528_000_000);
+        assert_eq!(hz(ClockRoot::CAN), 60_000_000);
+        // AudioPll is off
+        assert_eq!(hz(ClockRoot::FlexIO2), 0);
+
+        // SAI1 selects the reserved input, and SAI3 divides PLL3 PFD2 by 6
+        let tree = Tree::new(|reg| match reg {
+            r if r == raw::CSCMR1 => 3 << 10,
+            r if r == raw::CS1CDR => 1 << 22 | 2 << 16,
+            r if r == raw::PFD_480 => 18 << 16,
+            r => read(r),
+        });
+        let hz = |root| tree.frequency(Node::Root(root));
+        assert_eq!(hz(ClockRoot::SAI1), 0);
+        assert_eq!(hz(ClockRoot::SAI3), 80_000_000);
+    }
+
+    #[cfg(all(feature = "imxrt1010", not(feature = "imxrt1060")))]
+    #[test]
+    fn tree_root_frequency_1010() {
+        // FlexSPI divides PLL2 by 4; SAI1 selects the missing video PLL
+        let tree = Tree::new(|reg| match reg {
+            r if r == raw::CSCMR1 => 3 << 23 | 1 << 10,
+            r => read(r),
+        });
+        let hz = |root| tree.frequency(Node::Root(root));
+        assert_eq!(
+            tree.parent(Node::Root(ClockRoot::FlexSPI)),
+            Some(Node::Source(ClockSource::Pll2))
+        );
+        assert_eq!(hz(ClockRoot::FlexSPI), 132_000_000);
+        assert_eq!(hz(ClockRoot::SAI1), 0);
+        assert_eq!(hz(ClockRoot::Trace), 528_000_000);
+    }
+
+    #[test]
+    fn tree_reference() {
+        let tree = Tree::new(|reg| if reg == raw::CSCDR1 { 1 << 6 | 3 } else { 0 });
+        let uart = Node::Root(ClockRoot::UART);
+        assert_eq!(tree.frequency(uart), 6_000_000);
+        let tree = tree.with_reference(25_000_000);
+        assert_eq!(tree.frequency(uart), 6_250_000);
+    }
+
+    #[test]
+    fn tree_path() {
+        let tree = Tree::new(read);
+        let mut path = tree.path(Node::Root(ClockRoot::IPG));
+        assert_eq!(path.next(), Some(Node::Root(ClockRoot::IPG)));
+        assert_eq!(path.next(), Some(Node::Root(ClockRoot::AHB)));
+        assert_eq!(path.next(), Some(Node::Periph));
+        assert_eq!(path.next(), Some(Node::PrePeriph));
+        assert_eq!(path.next(), Some(Node::Source(ClockSource::Pll2)));
+        assert_eq!(path.next(), Some(Node::Source(ClockSource::Oscillator)));
+        assert_eq!(path.next(), None);
+    }
+}
@@ -1,11 +1,20 @@
 //! UART clock control
 
-use super::{set_clock_gate, ClockGate, ClockGateLocation, ClockGateLocator, Instance};
-use crate::register::{Field, Register};
-use core::marker::PhantomData;
+use super::{
+    set_clock_gate, ClockFor, ClockGate, ClockGateGuard, ClockGateLocation, ClockGateLocator,
+    ClockRoot, ClockRootLocator, Instance,
+};
+use crate::{
+    divider::Choice,
+    register::{Field, Register},
+    shared::SharedClockGate,
+    xtalosc::reference_frequency,
+    ClockError, ClockSource, RootFrequency,
+};
+use core::{convert::TryFrom, marker::PhantomData};
 
-/// UART clock frequency (Hz)
-const CLOCK_FREQUENCY_HZ: u32 = super::OSCILLATOR_FREQUENCY_HZ;
+/// The fixed PLL3 divider for `pll3_80m`
+const PLL3_DIVIDER: u32 = 6;
 const DEFAULT_CLOCK_DIVIDER: u32 = 1;
 
 /// The UART clock
@@ -38,23 +47,155 @@ where
     /// The divider should be between [1, 64]. The function will treat a 0 as 1,
     /// and anything greater than 64 as 64.
     ///
+    /// Returns the UART clock root frequency. Since there's no requested
+    /// frequency, the error is zero; see [`RootFrequency::relative_to`].
+    ///
     /// When `configure_divider` returns, all UART clock gates will be set to off.
     /// Use [`clock_gate`](struct.UARTClock.html#method.clock_gate)
     /// to turn on UART clock gates.
     #[inline(always)]
-    pub fn configure_divider(&mut self, divider: u32) {
+    pub fn configure_divider(&mut self, divider: u32) -> RootFrequency {
         unsafe {
-            set_clock_gate::<U>(UART::UART1, ClockGate::Off);
-            set_clock_gate::<U>(UART::UART2, ClockGate::Off);
-            set_clock_gate::<U>(UART::UART3, ClockGate::Off);
-            set_clock_gate::<U>(UART::UART4, ClockGate::Off);
-            set_clock_gate::<U>(UART::UART5, ClockGate::Off);
-            set_clock_gate::<U>(UART::UART6, ClockGate::Off);
-            set_clock_gate::<U>(UART::UART7, ClockGate::Off);
-            set_clock_gate::<U>(UART::UART8, ClockGate::Off);
+            crate::clock_gates_off::<U>(UARTS);
 
             configure(divider)
         };
+        RootFrequency::exact(frequency())
+    }
+
+    /// Configure the UART clocks with a divider that's known to be in range
+    ///
+    /// Unlike `configure_divider`, the divider can't saturate. See
+    /// [`configure_divider`](UARTClock::configure_divider) for more information.
+    #[inline(always)]
+    pub fn configure_with_divider(&mut self, divider: Divider) -> RootFrequency {
+        self.configure_divider(divider.get())
+    }
+
+    /// Configure the UART clocks to approximate `target_hz`, returning the
+    /// achieved frequency
+    ///
+    /// See [`configure_frequency`] for more
+    /// information.
+    ///
+    /// When `configure_frequency` returns, all UART clock gates will be set to off.
+    /// Use [`clock_gate`](struct.UARTClock.html#method.clock_gate)
+    /// to turn on UART clock gates.
+    #[inline(always)]
+    pub fn configure_frequency(&mut self, target_hz: u32) -> RootFrequency {
+        unsafe {
+            crate::clock_gates_off::<U>(UARTS);
+
+            configure_frequency(target_hz)
+        }
+    }
+
+    /// Configure the UART clocks with a clock divider, leaving the UART clock
+    /// gates untouched
+    ///
+    /// `configure_divider_ungated` behaves like `configure_divider`, but it does
+    /// not turn off any UART clock gates. Use it when you manage the clock gates
+    /// yourself. If a UART clock gate is on, the peripheral may see a glitch as
+    /// the clock root changes; you're responsible for tolerating that glitch.
+    #[inline(always)]
+    pub fn configure_divider_ungated(&mut self, divider: u32) -> RootFrequency {
+        // Safety: we own the CCM peripheral memory
+        unsafe { configure(divider) };
+        RootFrequency::exact(frequency())
+    }
+
+    /// Configure the UART clocks to approximate `target_hz`, leaving the UART
+    /// clock gates untouched
+    ///
+    /// `configure_frequency_ungated` behaves like `configure_frequency`, but it
+    /// does not turn off any UART clock gates. See
+    /// [`configure_divider_ungated`](struct.UARTClock.html#method.configure_divider_ungated)
+    /// for the risks.
+    #[inline(always)]
+    pub fn configure_frequency_ungated(&mut self, target_hz: u32) -> RootFrequency {
+        // Safety: we own the CCM peripheral memory
+        unsafe { configure_frequency(target_hz) }
+    }
+
+    /// Set the UART clock divider, leaving the clock root selection unchanged
+    ///
+    /// See [`set_divider`] for more information. Unlike `configure_divider`,
+    /// `set_divider` does not change the UART clock gates.
+    #[inline(always)]
+    pub fn set_divider(&mut self, divider: u32) {
+        // Safety: we own the CCM peripheral memory
+        unsafe { set_divider(divider) }
+    }
+
+    /// Select the UART clock root source, leaving the divider unchanged
+    ///
+    /// When `set_selection` returns, all UART clock gates will be set to off.
+    /// Use [`clock_gate`](struct.UARTClock.html#method.clock_gate)
+    /// to turn on UART clock gates.
+    #[inline(always)]
+    pub fn set_selection(&mut self, selection: Selection) {
+        unsafe {
+            crate::clock_gates_off::<U>(UARTS);
+
+            set_selection(selection)
+        }
+    }
+
+    /// Configure the UART clocks with a clock divider, or return an error if the
+    /// divider is out of range
+    ///
+    /// Unlike [`configure_divider`](Self::configure_divider),
+    /// `try_configure_divider` doesn't saturate the divider. If it returns an
+    /// error, it makes no changes.
+    pub fn try_configure_divider(&mut self, divider: u32) -> Result<RootFrequency, ClockError> {
+        let divider = Divider::try_from(divider)?;
+        Ok(self.configure_with_divider(divider))
+    }
+
+    /// Configure the UART clocks to within `tolerance_hz` of `target_hz`, or
+    /// return an error if no source and divider are close enough
+    ///
+    /// Unlike [`configure_frequency`](Self::configure_frequency), which settles for
+    /// the closest frequency, `try_configure_frequency` makes no changes if the
+    /// closest frequency isn't within the tolerance.
+    pub fn try_configure_frequency(
+        &mut self,
+        target_hz: u32,
+        tolerance_hz: u32,
+    ) -> Result<RootFrequency, ClockError> {
+        let choice = choose(&Sources::current(), target_hz)
+            // Unwrap OK: the oscillator always runs
+            .unwrap()
+            .within(target_hz, tolerance_hz)?;
+        unsafe {
+            crate::clock_gates_off::<U>(UARTS);
+            CSCDR1.set(choice.divider - 1, choice.selection);
+        }
+        Ok(choice.frequency(target_hz))
+    }
+
+    /// Set the UART clock divider, or return an error if the divider is out of
+    /// range
+    ///
+    /// Unlike [`set_divider`](Self::set_divider), `try_set_divider` doesn't
+    /// saturate the divider. If it returns an error, it makes no changes.
+    pub fn try_set_divider(&mut self, divider: u32) -> Result<(), ClockError> {
+        let divider = Divider::try_from(divider)?;
+        self.set_divider(divider.get());
+        Ok(())
+    }
+
+    /// Select the UART clock root source, or return an error if the source
+    /// isn't running
+    ///
+    /// See [`set_selection`](Self::set_selection). If `try_set_selection`
+    /// returns an error, it makes no changes.
+    pub fn try_set_selection(&mut self, selection: Selection) -> Result<(), ClockError> {
+        if source_frequency(selection) == 0 {
+            return Err(ClockError::SourceUnavailable);
+        }
+        self.set_selection(selection);
+        Ok(())
     }
 }
 
@@ -71,6 +212,30 @@ pub enum UART {
     UART8,
 }
 
+/// All UART instances
+const UARTS: &[UART] = &[
+    UART::UART1,
+    UART::UART2,
+    UART::UART3,
+    UART::UART4,
+    UART::UART5,
+    UART::UART6,
+    UART::UART7,
+    UART::UART8,
+];
+
+/// UART clock root selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    /// PLL3 divided by 6
+    Pll3,
+    /// The crystal oscillator
+    Oscillator,
+}
+
+/// UART clock root divider, between [1, 64]
+pub type Divider = crate::Divider<1, 64>;
+
 impl<U> UARTClock<U>
 where
     U: Instance<Inst = UART>,
@@ -89,11 +254,51 @@ where
         unsafe { set_clock_gate::<U>(uart.instance(), gate) }
     }
 
+    /// Turn on the clock gate for the UART instance, returning a guard
+    /// that restores the previous setting when dropped
+    #[inline(always)]
+    pub fn enable_guard<'a>(&mut self, uart: &'a mut U) -> ClockGateGuard<'a, U> {
+        // Safety: we have exclusive access to the instance
+        unsafe { ClockGateGuard::new(uart) }
+    }
+
+    /// Share the clock gate for the UART instance between multiple users
+    ///
+    /// See [`shared`](crate::shared) for more information.
+    #[inline(always)]
+    pub fn shared_gate(&mut self, uart: U) -> SharedClockGate<U> {
+        // Safety: we own the instance
+        unsafe { SharedClockGate::new(uart) }
+    }
+
     /// Returns the UART clock frequency
     #[inline(always)]
     pub fn frequency(&self) -> u32 {
         frequency()
     }
+
+    /// Returns the clock root selection
+    #[inline(always)]
+    pub fn selection(&self) -> Selection {
+        selection()
+    }
+
+    /// Returns `true` if the UART clock root runs
+    ///
+    /// The clock root runs when its selected source runs. Use this to check
+    /// that the UART clock is configured before you use a UART peripheral.
+    #[inline(always)]
+    pub fn is_configured(&self) -> bool {
+        frequency() != 0
+    }
+
+    /// Returns `true` if any UART clock gate is on
+    ///
+    /// The check only considers the instances that are valid for `U`.
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        crate::any_clock_gate_on::<U>(UARTS)
+    }
 }
 
 impl ClockGateLocator for UART {
@@ -136,6 +341,119 @@ impl ClockGateLocator for UART {
     }
 }
 
+impl ClockRootLocator for UART {
+    #[inline(always)]
+    fn clock_root(&self) -> ClockRoot {
+        ClockRoot::UART
+    }
+}
+
+impl<U> ClockFor<UART> for UARTClock<U>
+where
+    U: Instance<Inst = UART>,
+{
+    #[inline(always)]
+    fn frequency(&self) -> u32 {
+        frequency()
+    }
+}
+
+/// LPUART baud rate settings
+///
+/// Use [`baud`] or [`UARTClock::baud`] to compute the settings for a target
+/// baud rate. The LPUART baud rate is the UART clock root frequency divided by
+/// `osr * sbr`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Baud {
+    /// The oversampling ratio, between 4 and 32
+    ///
+    /// Write `osr - 1` to the BAUD OSR field. Ratios between 4 and 7 require
+    /// the BAUD BOTHEDGE bit.
+    pub osr: u32,
+    /// The baud rate modulo divisor, between 1 and 8191
+    ///
+    /// Write `sbr` to the BAUD SBR field.
+    pub sbr: u32,
+    /// The achieved baud rate
+    pub hz: u32,
+    /// The achieved baud rate minus the target baud rate
+    pub error_hz: i32,
+}
+
+/// The smallest LPUART oversampling ratio
+const MIN_OSR: u32 = 4;
+/// The largest LPUART oversampling ratio
+const MAX_OSR: u32 = 32;
+/// The largest LPUART baud rate modulo divisor
+const MAX_SBR: u32 = 0x1FFF;
+
+/// Returns the LPUART baud rate settings that best approximate `target_baud`,
+/// given the UART clock root frequency `uart_hz`
+///
+/// Returns `None` if `target_baud` is zero, or if no settings are within 3% of
+/// `target_baud`. When two settings have the same error, `baud` prefers the
+/// larger oversampling ratio.
+///
+/// ```
+/// use imxrt_ccm::uart;
+///
+/// let baud = uart::baud(24_000_000, 115_200).unwrap();
+/// assert_eq!(baud.osr * baud.sbr, 208);
+/// assert!(baud.error_hz.abs() < 200);
+/// ```
+pub const fn baud(uart_hz: u32, target_baud: u32) -> Option<Baud> {
+    if target_baud == 0 {
+        return None;
+    }
+    let mut best: Option<Baud> = None;
+    let mut osr = MIN_OSR;
+    while osr <= MAX_OSR {
+        let per_sbr = osr as u64 * target_baud as u64;
+        let mut sbr = ((uart_hz as u64 + per_sbr / 2) / per_sbr) as u32;
+        if sbr == 0 {
+            sbr = 1;
+        } else if sbr > MAX_SBR {
+            sbr = MAX_SBR;
+        }
+        let hz = uart_hz / (osr * sbr);
+        let error_hz = hz as i32 - target_baud as i32;
+        let better = match best {
+            Some(best) => error_hz.unsigned_abs() <= best.error_hz.unsigned_abs(),
+            None => true,
+        };
+        if better {
+            best = Some(Baud {
+                osr,
+                sbr,
+                hz,
+                error_hz,
+            });
+        }
+        osr += 1;
+    }
+    match best {
+        Some(best) if (best.error_hz.unsigned_abs() as u64) * 100 <= target_baud as u64 * 3 => {
+            Some(best)
+        }
+        _ => None,
+    }
+}
+
+impl<U> UARTClock<U>
+where
+    U: Instance<Inst = UART>,
+{
+    /// Returns the LPUART baud rate settings that best approximate
+    /// `target_baud` at the current UART clock frequency
+    ///
+    /// See [`baud`] for more information.
+    #[inline(always)]
+    pub fn baud(&self, target_baud: u32) -> Option<Baud> {
+        baud(self.frequency(), target_baud)
+    }
+}
+
 const UART_CLK_PODF: Field = Field::new(0, 0x3F);
 // Note that the mask is 1 for 1011, but the adjacent bit is reserved
 const UART_CLK_SEL: Field = Field::new(6, 0x3);
@@ -155,35 +473,209 @@ const CSCDR1: Register =
 /// This could be called anywhere, modifying global memory that's owned by
 /// the CCM. Consider using the [`UARTClock`](struct.UARTClock.html) for a
 /// safer interface.
-#[inline(always)]
 pub unsafe fn configure(divider: u32) {
     configure_(divider, &CSCDR1);
 }
 
 #[inline(always)]
 unsafe fn configure_(divider: u32, reg: &Register) {
-    const OSCILLATOR: u32 = 1; // Same value for 1060, 1010
-    reg.set(divider.min(64).max(1).saturating_sub(1), OSCILLATOR);
+    reg.set(divider.clamp(1, 64).saturating_sub(1), OSCILLATOR);
+}
+
+const OSCILLATOR: u32 = 1; // Same value for 1060, 1010
+const PLL3_80M: u32 = 0;
+
+/// Configure the UART clock root to approximate `target_hz`, returning the
+/// achieved frequency
+///
+/// The function selects the source, either the crystal oscillator or PLL3
+/// divided by 6, and the divider, that produce the frequency closest to
+/// `target_hz`. PLL3 is only considered if it's running. Configure will
+/// **not** disable peripheral clock gates.
+///
+/// # Safety
+///
+/// This could be called anywhere, modifying global memory that's owned by
+/// the CCM. Consider using the [`UARTClock`](struct.UARTClock.html) for a
+/// safer interface.
+pub unsafe fn configure_frequency(target_hz: u32) -> RootFrequency {
+    configure_frequency_(target_hz, &CSCDR1, crate::pll3::frequency())
+}
+
+unsafe fn configure_frequency_(target_hz: u32, reg: &Register, pll3_hz: u32) -> RootFrequency {
+    let sources = Sources {
+        oscillator_hz: reference_frequency(),
+        pll3_hz,
+    };
+    // Unwrap OK: the oscillator always runs
+    let choice = choose(&sources, target_hz).unwrap();
+    reg.set(choice.divider - 1, choice.selection);
+    choice.frequency(target_hz)
+}
+
+/// Returns the frequency of the source selected by `selection`
+fn source_frequency(selection: Selection) -> u32 {
+    match selection {
+        Selection::Pll3 => ClockSource::Pll3.frequency() / PLL3_DIVIDER,
+        Selection::Oscillator => ClockSource::Oscillator.frequency(),
+    }
+}
+
+/// Set the UART clock divider, leaving the clock root selection unchanged
+///
+/// Unlike [`configure`], which also selects the clock source, `set_divider`
+/// only writes the divider field. Use it to adjust a clock root that you
+/// configured with [`configure_frequency`] or [`set_selection`].
+///
+/// The divider should be between [1, 64]. The function will treat a 0 as 1,
+/// and anything greater than 64 as 64.
+///
+/// # Safety
+///
+/// This could be called anywhere, modifying global memory that's owned by
+/// the CCM. Consider using the [`UARTClock`](struct.UARTClock.html) for a
+/// safer interface.
+pub unsafe fn set_divider(divider: u32) {
+    set_divider_(divider, &CSCDR1);
+}
+
+#[inline(always)]
+unsafe fn set_divider_(divider: u32, reg: &Register) {
+    reg.set_divider(divider.clamp(1, 64) - 1);
+}
+
+/// Select the UART clock root source, leaving the divider unchanged
+///
+/// Set selection will **not** disable peripheral clock gates. You should disable
+/// clock gates yourself before calling this function.
+///
+/// # Safety
+///
+/// This could be called anywhere, modifying global memory that's owned by
+/// the CCM. Consider using the [`UARTClock`](struct.UARTClock.html) for a
+/// safer interface.
+pub unsafe fn set_selection(selection: Selection) {
+    set_selection_(selection, &CSCDR1);
+}
+
+#[inline(always)]
+unsafe fn set_selection_(selection: Selection, reg: &Register) {
+    reg.set_selection(match selection {
+        Selection::Pll3 => PLL3_80M,
+        Selection::Oscillator => OSCILLATOR,
+    });
 }
 
 /// Returns the UART clock frequency
+///
+/// If the UART clock root runs on PLL3, the frequency reflects the
+/// PLL3 frequency.
 #[inline(always)]
 pub fn frequency() -> u32 {
-    frequency_(&CSCDR1)
+    frequency_(&CSCDR1, crate::pll3::frequency())
 }
 
 #[inline(always)]
-fn frequency_(reg: &Register) -> u32 {
+fn frequency_(reg: &Register, pll3_hz: u32) -> u32 {
     let divider = reg.divider() + 1;
-    CLOCK_FREQUENCY_HZ / divider
+    match reg.selection() & 1 {
+        0 => pll3_hz / PLL3_DIVIDER / divider,
+        _ => reference_frequency() / divider,
+    }
+}
+
+/// Returns the UART clock divider
+#[inline(always)]
+pub fn divider() -> u32 {
+    CSCDR1.divider() + 1
+}
+
+/// Returns the UART clock root selection
+#[inline(always)]
+pub fn selection() -> Selection {
+    selection_(&CSCDR1)
+}
+
+#[inline(always)]
+fn selection_(reg: &Register) -> Selection {
+    from_field(reg.selection())
+}
+
+/// The UART clock root source frequencies
+///
+/// Use `Sources` with [`closest`] and [`root_frequency`] to compute UART clock
+/// root settings without the CCM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sources {
+    /// The crystal oscillator frequency
+    pub oscillator_hz: u32,
+    /// The PLL3 frequency, before the fixed divide by 6, or 0 if PLL3 isn't
+    /// running
+    pub pll3_hz: u32,
+}
+
+impl Sources {
+    /// Returns the source frequencies of this system
+    pub fn current() -> Self {
+        Sources {
+            oscillator_hz: reference_frequency(),
+            pll3_hz: crate::pll3::frequency(),
+        }
+    }
+}
+
+/// An UART clock root selection and divider, and the frequency that they
+/// produce
+pub type Setting = crate::RootSetting<Selection, Divider>;
+
+/// Returns the UART clock root frequency for `selection` and `divider`
+///
+/// Unlike [`frequency`], `root_frequency` doesn't read the CCM.
+pub const fn root_frequency(sources: &Sources, selection: Selection, divider: Divider) -> u32 {
+    let source_hz = match selection {
+        Selection::Pll3 => sources.pll3_hz / PLL3_DIVIDER,
+        Selection::Oscillator => sources.oscillator_hz,
+    };
+    source_hz / divider.get()
+}
+
+/// Returns the UART clock root selection and divider that best approximate
+/// `target_hz`
+///
+/// `closest` makes the same choice as [`configure_frequency`], but it doesn't
+/// read or write the CCM. Returns `None` if no source runs.
+pub const fn closest(sources: &Sources, target_hz: u32) -> Option<Setting> {
+    match choose(sources, target_hz) {
+        Some(choice) => Some(choice.setting(from_field(choice.selection), target_hz)),
+        None => None,
+    }
+}
+
+/// Returns the source and divider that best approximate `target_hz`
+const fn choose(sources: &Sources, target_hz: u32) -> Option<Choice> {
+    let sources = [
+        (OSCILLATOR, sources.oscillator_hz),
+        (PLL3_80M, sources.pll3_hz / PLL3_DIVIDER),
+    ];
+    crate::divider::closest(target_hz, &sources, 64)
+}
+
+/// Returns the selection described by a clock root selection value
+const fn from_field(field: u32) -> Selection {
+    match field & 1 {
+        PLL3_80M => Selection::Pll3,
+        _ => Selection::Oscillator,
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::{
-        configure_, frequency_, Register, CLOCK_FREQUENCY_HZ, UART_CLK_PODF, UART_CLK_SEL,
+        baud, closest, configure_, configure_frequency_, frequency_, root_frequency, selection_,
+        set_divider_, set_selection_, Register, Selection, Sources, UART_CLK_PODF, UART_CLK_SEL,
     };
+    use crate::OSCILLATOR_FREQUENCY_HZ as CLOCK_FREQUENCY_HZ;
 
     unsafe fn register(mem: &mut u32) -> Register {
         Register::new(UART_CLK_PODF, UART_CLK_SEL, mem)
@@ -195,7 +687,7 @@ mod tests {
         unsafe {
             let reg = register(&mut mem);
             configure_(65, &reg);
-            assert_eq!(frequency_(&reg), CLOCK_FREQUENCY_HZ / 64);
+            assert_eq!(frequency_(&reg, 0), CLOCK_FREQUENCY_HZ / 64);
         }
     }
 
@@ -205,7 +697,16 @@ mod tests {
         unsafe {
             let reg = register(&mut mem);
             configure_(0, &reg);
-            assert_eq!(frequency_(&reg), CLOCK_FREQUENCY_HZ);
+            assert_eq!(frequency_(&reg, 0), CLOCK_FREQUENCY_HZ);
+        }
+    }
+
+    #[test]
+    fn uart_pll3() {
+        let mut mem: u32 = 2;
+        unsafe {
+            let reg = register(&mut mem);
+            assert_eq!(frequency_(&reg, 480_000_000), 80_000_000 / 3);
         }
     }
 
@@ -215,7 +716,108 @@ mod tests {
         unsafe {
             let reg = register(&mut mem);
             configure_(7, &reg);
-            assert_eq!(frequency_(&reg), CLOCK_FREQUENCY_HZ / 7);
+            assert_eq!(frequency_(&reg, 0), CLOCK_FREQUENCY_HZ / 7);
         }
     }
+
+    #[test]
+    fn uart_configure_frequency() {
+        let mut mem: u32 = 0;
+        unsafe {
+            let reg = register(&mut mem);
+            let achieved = configure_frequency_(8_000_000, &reg, 480_000_000);
+            assert_eq!((achieved.hz, achieved.error_hz), (8_000_000, 0));
+            assert_eq!(frequency_(&reg, 480_000_000), 8_000_000);
+
+            let achieved = configure_frequency_(80_000_000, &reg, 480_000_000);
+            assert_eq!((achieved.hz, achieved.error_hz), (80_000_000, 0));
+            assert_eq!(frequency_(&reg, 480_000_000), 80_000_000);
+
+            // PLL3 isn't running
+            let achieved = configure_frequency_(80_000_000, &reg, 0);
+            assert_eq!(achieved.hz, CLOCK_FREQUENCY_HZ);
+            assert_eq!(achieved.error_hz, -56_000_000);
+        }
+    }
+
+    #[test]
+    fn uart_selection() {
+        let mut mem: u32 = 0;
+        unsafe {
+            let reg = register(&mut mem);
+            assert_eq!(selection_(&reg), Selection::Pll3);
+            configure_(1, &reg);
+            assert_eq!(selection_(&reg), Selection::Oscillator);
+        }
+    }
+
+    #[test]
+    fn uart_set_divider_selection() {
+        let mut mem: u32 = 0;
+        unsafe {
+            let reg = register(&mut mem);
+            configure_frequency_(80_000_000, &reg, 480_000_000);
+            set_divider_(4, &reg);
+            assert_eq!(selection_(&reg), Selection::Pll3);
+            assert_eq!(frequency_(&reg, 480_000_000), 80_000_000 / 4);
+
+            set_selection_(Selection::Oscillator, &reg);
+            assert_eq!(frequency_(&reg, 480_000_000), CLOCK_FREQUENCY_HZ / 4);
+
+            set_divider_(0, &reg);
+            assert_eq!(frequency_(&reg, 480_000_000), CLOCK_FREQUENCY_HZ);
+        }
+    }
+
+    #[test]
+    fn uart_baud() {
+        let settings = baud(80_000_000, 115_200).unwrap();
+        assert_eq!((settings.osr, settings.sbr), (5, 139));
+        assert_eq!(settings.hz, 115_107);
+        assert_eq!(settings.error_hz, -93);
+
+        // Ties prefer the larger oversampling ratio
+        let settings = baud(24_000_000, 1_000_000).unwrap();
+        assert_eq!((settings.osr, settings.sbr), (24, 1));
+        assert_eq!(settings.error_hz, 0);
+
+        assert_eq!(baud(24_000_000, 0), None);
+        assert_eq!(baud(24_000_000, 10_000_000), None);
+    }
+
+    #[test]
+    fn uart_closest() {
+        const SOURCES: Sources = Sources {
+            oscillator_hz: CLOCK_FREQUENCY_HZ,
+            pll3_hz: 480_000_000,
+        };
+        let setting = closest(&SOURCES, 20_000_000).unwrap();
+        assert_eq!(setting.selection, Selection::Pll3);
+        assert_eq!(setting.divider.get(), 4);
+        assert_eq!(
+            (setting.frequency.hz, setting.frequency.error_hz),
+            (20_000_000, 0)
+        );
+        assert_eq!(
+            root_frequency(&SOURCES, setting.selection, setting.divider),
+            setting.frequency.hz
+        );
+
+        // Same choice as the hardware path
+        let mut mem: u32 = 0;
+        unsafe {
+            let reg = register(&mut mem);
+            let achieved = configure_frequency_(3_000_000, &reg, SOURCES.pll3_hz);
+            let setting = closest(&SOURCES, 3_000_000).unwrap();
+            assert_eq!(achieved, setting.frequency);
+            assert_eq!(selection_(&reg), setting.selection);
+            assert_eq!(reg.divider() + 1, setting.divider.get());
+        }
+
+        let stopped = Sources {
+            oscillator_hz: 0,
+            pll3_hz: 0,
+        };
+        assert_eq!(closest(&stopped, 20_000_000), None);
+    }
 }
@@ -0,0 +1,515 @@
+//! PLL and PFD usage, derived from the clock root multiplexers
+//!
+//! A PLL or PFD is "used" if any clock root multiplexer selects it, or if one of
+//! its dedicated outputs (like the USB PHY clocks) is enabled. A multiplexer's
+//! selection counts even if nothing downstream of the multiplexer is running.
+
+use crate::{
+    pfd::{self, PFD},
+    pll::{Pll, PllUsage},
+    register::{self, Field},
+};
+
+/// A clock source, as seen by a multiplexer
+#[derive(Clone, Copy)]
+enum Source {
+    Pll(Pll),
+    Pfd(Pll, PFD),
+    /// Not a PLL or PFD, or reserved
+    Other,
+}
+
+use Source::{Other, Pfd, Pll as P};
+
+/// A clock root multiplexer
+struct Mux {
+    register: usize,
+    field: Field,
+    /// Indexed by the field value
+    sources: &'static [Source],
+}
+
+#[cfg(feature = "imxrt1060")]
+const CBCDR: usize = 0x400F_C014;
+const CBCMR: usize = 0x400F_C018;
+const CSCMR1: usize = 0x400F_C01C;
+const CSCMR2: usize = 0x400F_C020;
+const CSCDR1: usize = 0x400F_C024;
+const CDCDR: usize = 0x400F_C030;
+const CSCDR2: usize = 0x400F_C038;
+#[cfg(feature = "imxrt1060")]
+const CSCDR3: usize = 0x400F_C03C;
+
+const fn mux(register: usize, offset: u32, mask: u32, sources: &'static [Source]) -> Mux {
+    Mux {
+        register,
+        field: Field::new(offset, mask),
+        sources,
+    }
+}
+
+#[cfg(feature = "imxrt1060")]
+const MUXES: &[Mux] = &[
+    // SEMC_ALT_CLK_SEL
+    mux(
+        CBCDR,
+        7,
+        1,
+        &[Pfd(Pll::Sys, PFD::PFD2), Pfd(Pll::Usb1, PFD::PFD1)],
+    ),
+    // LPSPI_CLK_SEL
+    mux(
+        CBCMR,
+        4,
+        0b11,
+        &[
+            Pfd(Pll::Usb1, PFD::PFD1),
+            Pfd(Pll::Usb1, PFD::PFD0),
+            P(Pll::Sys),
+            Pfd(Pll::Sys, PFD::PFD2),
+        ],
+    ),
+    // FLEXSPI2_CLK_SEL
+    mux(
+        CBCMR,
+        8,
+        0b11,
+        &[
+            Pfd(Pll::Sys, PFD::PFD2),
+            Pfd(Pll::Usb1, PFD::PFD0),
+            Pfd(Pll::Usb1, PFD::PFD1),
+            P(Pll::Sys),
+        ],
+    ),
+    // PERIPH_CLK2_SEL
+    mux(CBCMR, 12, 0b11, &[P(Pll::Usb1), Other, P(Pll::Sys), Other]),
+    // TRACE_CLK_SEL
+    mux(
+        CBCMR,
+        14,
+        0b11,
+        &[
+            P(Pll::Sys),
+            Pfd(Pll::Sys, PFD::PFD2),
+            Pfd(Pll::Sys, PFD::PFD0),
+            Pfd(Pll::Sys, PFD::PFD1),
+        ],
+    ),
+    // SAI1_CLK_SEL
+    mux(
+        CSCMR1,
+        10,
+        0b11,
+        &[
+            Pfd(Pll::Usb1, PFD::PFD2),
+            P(Pll::Video),
+            P(Pll::Audio),
+            Other,
+        ],
+    ),
+    // SAI2_CLK_SEL
+    mux(
+        CSCMR1,
+        12,
+        0b11,
+        &[
+            Pfd(Pll::Usb1, PFD::PFD2),
+            P(Pll::Video),
+            P(Pll::Audio),
+            Other,
+        ],
+    ),
+    // SAI3_CLK_SEL
+    mux(
+        CSCMR1,
+        14,
+        0b11,
+        &[
+            Pfd(Pll::Usb1, PFD::PFD2),
+            P(Pll::Video),
+            P(Pll::Audio),
+            Other,
+        ],
+    ),
+    // USDHC1_CLK_SEL
+    mux(
+        CSCMR1,
+        16,
+        1,
+        &[Pfd(Pll::Sys, PFD::PFD2), Pfd(Pll::Sys, PFD::PFD0)],
+    ),
+    // USDHC2_CLK_SEL
+    mux(
+        CSCMR1,
+        17,
+        1,
+        &[Pfd(Pll::Sys, PFD::PFD2), Pfd(Pll::Sys, PFD::PFD0)],
+    ),
+    // FLEXSPI_CLK_SEL
+    mux(
+        CSCMR1,
+        29,
+        0b11,
+        &[
+            Other,
+            P(Pll::Usb1),
+            Pfd(Pll::Sys, PFD::PFD2),
+            Pfd(Pll::Usb1, PFD::PFD0),
+        ],
+    ),
+    // CAN_CLK_SEL
+    mux(CSCMR2, 8, 0b11, &[P(Pll::Usb1), Other, P(Pll::Usb1), Other]),
+    // FLEXIO2_CLK_SEL
+    mux(
+        CSCMR2,
+        19,
+        0b11,
+        &[
+            P(Pll::Audio),
+            Pfd(Pll::Usb1, PFD::PFD2),
+            P(Pll::Video),
+            P(Pll::Usb1),
+        ],
+    ),
+    // UART_CLK_SEL
+    mux(CSCDR1, 6, 1, &[P(Pll::Usb1), Other]),
+    // FLEXIO1_CLK_SEL
+    mux(
+        CDCDR,
+        7,
+        0b11,
+        &[
+            P(Pll::Audio),
+            Pfd(Pll::Usb1, PFD::PFD2),
+            P(Pll::Video),
+            P(Pll::Usb1),
+        ],
+    ),
+    // SPDIF0_CLK_SEL
+    mux(
+        CDCDR,
+        20,
+        0b11,
+        &[
+            P(Pll::Audio),
+            Pfd(Pll::Usb1, PFD::PFD2),
+            P(Pll::Video),
+            P(Pll::Usb1),
+        ],
+    ),
+    // LCDIF_PRE_CLK_SEL
+    mux(
+        CSCDR2,
+        15,
+        0b111,
+        &[
+            P(Pll::Sys),
+            Pfd(Pll::Usb1, PFD::PFD3),
+            P(Pll::Video),
+            Pfd(Pll::Sys, PFD::PFD0),
+            Pfd(Pll::Sys, PFD::PFD1),
+            Pfd(Pll::Usb1, PFD::PFD1),
+            Other,
+            Other,
+        ],
+    ),
+    // LPI2C_CLK_SEL
+    mux(CSCDR2, 18, 1, &[P(Pll::Usb1), Other]),
+    // CSI_CLK_SEL
+    mux(
+        CSCDR3,
+        9,
+        0b11,
+        &[
+            Other,
+            Pfd(Pll::Sys, PFD::PFD2),
+            P(Pll::Usb1),
+            Pfd(Pll::Usb1, PFD::PFD1),
+        ],
+    ),
+];
+
+#[cfg(not(feature = "imxrt1060"))]
+const MUXES: &[Mux] = &[
+    // LPSPI_CLK_SEL
+    mux(
+        CBCMR,
+        4,
+        0b11,
+        &[
+            Pfd(Pll::Usb1, PFD::PFD1),
+            Pfd(Pll::Usb1, PFD::PFD0),
+            P(Pll::Sys),
+            Pfd(Pll::Sys, PFD::PFD2),
+        ],
+    ),
+    // PERIPH_CLK2_SEL
+    mux(CBCMR, 12, 0b11, &[P(Pll::Usb1), Other, P(Pll::Sys), Other]),
+    // TRACE_CLK_SEL
+    mux(
+        CBCMR,
+        14,
+        0b11,
+        &[
+            P(Pll::Sys),
+            Pfd(Pll::Sys, PFD::PFD2),
+            Pfd(Pll::Sys, PFD::PFD0),
+            Pfd(Pll::Sys, PFD::PFD1),
+        ],
+    ),
+    // SAI1_CLK_SEL
+    mux(
+        CSCMR1,
+        10,
+        0b11,
+        &[
+            Pfd(Pll::Usb1, PFD::PFD2),
+            P(Pll::Usb1),
+            P(Pll::Audio),
+            Other,
+        ],
+    ),
+    // SAI3_CLK_SEL
+    mux(
+        CSCMR1,
+        14,
+        0b11,
+        &[
+            Pfd(Pll::Usb1, PFD::PFD2),
+            P(Pll::Usb1),
+            P(Pll::Audio),
+            Other,
+        ],
+    ),
+    // FLEXSPI_CLK_SEL
+    mux(
+        CSCMR1,
+        29,
+        0b11,
+        &[
+            P(Pll::Sys),
+            P(Pll::Usb1),
+            Pfd(Pll::Sys, PFD::PFD2),
+            Pfd(Pll::Usb1, PFD::PFD0),
+        ],
+    ),
+    // FLEXIO1_CLK_SEL
+    mux(
+        CSCMR2,
+        19,
+        0b11,
+        &[
+            P(Pll::Audio),
+            Pfd(Pll::Usb1, PFD::PFD2),
+            P(Pll::Sys),
+            P(Pll::Usb1),
+        ],
+    ),
+    // UART_CLK_SEL
+    mux(CSCDR1, 6, 0b11, &[P(Pll::Usb1), Other, Other, Other]),
+    // SPDIF0_CLK_SEL
+    mux(
+        CDCDR,
+        20,
+        0b11,
+        &[
+            P(Pll::Audio),
+            Pfd(Pll::Usb1, PFD::PFD2),
+            Other,
+            P(Pll::Usb1),
+        ],
+    ),
+    // LPI2C_CLK_SEL
+    mux(CSCDR2, 18, 1, &[P(Pll::Usb1), Other]),
+];
+
+/// A PLL output enable that's not visible through a CCM multiplexer
+struct Output {
+    register: usize,
+    /// Any set bit indicates that the PLL is used
+    mask: u32,
+    pll: Pll,
+}
+
+#[cfg(feature = "imxrt1060")]
+const OUTPUTS: &[Output] = &[
+    // PLL_USB1[EN_USB_CLKS]
+    Output {
+        register: 0x400D_8010,
+        mask: 1 << 6,
+        pll: Pll::Usb1,
+    },
+    // PLL_USB2[EN_USB_CLKS]
+    Output {
+        register: 0x400D_8020,
+        mask: 1 << 6,
+        pll: Pll::Usb2,
+    },
+    // PLL_ENET[ENABLE | ENET2_REF_EN | ENET_25M_REF_EN | ENET_500M_REF_EN]
+    Output {
+        register: 0x400D_80E0,
+        mask: 1 << 13 | 0b111 << 20,
+        pll: Pll::Enet,
+    },
+];
+
+#[cfg(not(feature = "imxrt1060"))]
+const OUTPUTS: &[Output] = &[
+    // PLL_USB1[EN_USB_CLKS]
+    Output {
+        register: 0x400D_8010,
+        mask: 1 << 6,
+        pll: Pll::Usb1,
+    },
+    // PLL_ENET[ENET_500M_REF_EN]
+    Output {
+        register: 0x400D_80E0,
+        mask: 1 << 22,
+        pll: Pll::Enet,
+    },
+];
+
+/// The multiplexer that selects the core clock source
+///
+/// The core clock only uses this selection when PERIPH_CLK_SEL selects
+/// PRE_PERIPH_CLK.
+#[cfg(feature = "imxrt1060")]
+const PRE_PERIPH_CLK_SEL: Mux = mux(
+    CBCMR,
+    18,
+    0b11,
+    &[
+        P(Pll::Sys),
+        Pfd(Pll::Sys, PFD::PFD2),
+        Pfd(Pll::Sys, PFD::PFD0),
+        P(Pll::Arm),
+    ],
+);
+
+#[cfg(not(feature = "imxrt1060"))]
+const PRE_PERIPH_CLK_SEL: Mux = mux(
+    CBCMR,
+    18,
+    0b11,
+    &[
+        P(Pll::Sys),
+        Pfd(Pll::Usb1, PFD::PFD3),
+        Pfd(Pll::Sys, PFD::PFD3),
+        P(Pll::Enet),
+    ],
+);
+
+/// Compute the PLL and PFD usage
+///
+/// `read` returns the value of the register at the provided address.
+pub(crate) fn usage_(read: impl Fn(usize) -> u32) -> PllUsage {
+    let mut usage = peripheral_usage_(&read);
+    tally(&mut usage, &PRE_PERIPH_CLK_SEL, &read);
+    usage
+}
+
+/// Compute the PLL and PFD usage, ignoring the core clock's PRE_PERIPH_CLK_SEL
+///
+/// Use this when the core clock runs on PERIPH_CLK2.
+pub(crate) fn peripheral_usage_(read: impl Fn(usize) -> u32) -> PllUsage {
+    let mut usage = PllUsage::default();
+    for mux in MUXES {
+        tally(&mut usage, mux, &read);
+    }
+    for output in OUTPUTS {
+        if read(output.register) & output.mask != 0 {
+            usage.use_pll(output.pll);
+        }
+    }
+    usage
+}
+
+fn tally(usage: &mut PllUsage, mux: &Mux, read: impl Fn(usize) -> u32) {
+    let selection = {
+        let value = read(mux.register);
+        // Safety: reading a stack-allocated copy of the register
+        unsafe { mux.field.read(&value) }
+    };
+    match mux.sources.get(selection as usize) {
+        Some(Source::Pll(pll)) => usage.use_pll(*pll),
+        Some(Source::Pfd(pll, pfd)) => usage.use_pfd(*pll, *pfd),
+        Some(Source::Other) | None => {}
+    }
+}
+
+/// Returns the PLL and PFD usage
+pub(crate) fn usage() -> PllUsage {
+    // Safety: atomic reads of valid registers
+    usage_(|address| unsafe { register::load(address as *const u32) })
+}
+
+/// Returns the PLL and PFD usage, ignoring the core clock's PRE_PERIPH_CLK_SEL
+pub(crate) fn peripheral_usage() -> PllUsage {
+    // Safety: atomic reads of valid registers
+    peripheral_usage_(|address| unsafe { register::load(address as *const u32) })
+}
+
+/// Power down every PLL, and gate every PFD, that's not used
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM.
+pub(crate) unsafe fn power_down_unused(usage: &PllUsage) {
+    for &pll in Pll::ALL {
+        if let Some(register) = pll.pfd_register() {
+            for &pfd in &[PFD::PFD0, PFD::PFD1, PFD::PFD2, PFD::PFD3] {
+                if !usage.is_pfd_used(pll, pfd) {
+                    pfd::set_gated(register, pfd, true);
+                }
+            }
+        }
+        if !usage.is_used(pll) {
+            pll.power_down();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{usage_, CBCMR, CSCDR1, CSCDR2};
+    use crate::{pfd::PFD, pll::Pll};
+
+    #[test]
+    fn usage_zeros() {
+        let usage = usage_(|_| 0);
+        assert!(usage.is_used(Pll::Sys));
+        assert!(usage.is_used(Pll::Usb1));
+        assert!(usage.is_pfd_used(Pll::Usb1, PFD::PFD1));
+        assert!(usage.is_used(Pll::Audio));
+        assert!(!usage.is_used(Pll::Enet));
+        assert!(!usage.is_pfd_used(Pll::Sys, PFD::PFD3));
+        assert!(!usage.is_pfd_used(Pll::Enet, PFD::PFD0));
+    }
+
+    #[test]
+    fn usage_pfd_implies_pll() {
+        let usage = usage_(|address| match address {
+            // LPSPI_CLK_SEL = PLL3 PFD0, PERIPH_CLK2_SEL = oscillator,
+            // TRACE_CLK_SEL = PLL2 PFD1
+            CBCMR => 1 << 4 | 1 << 12 | 3 << 14,
+            // UART and I2C select the oscillator
+            CSCDR1 => 1 << 6,
+            CSCDR2 => 1 << 18,
+            _ => 0,
+        });
+        assert!(usage.is_pfd_used(Pll::Usb1, PFD::PFD0));
+        assert!(!usage.is_pfd_used(Pll::Usb1, PFD::PFD1));
+        assert!(usage.is_used(Pll::Usb1));
+        assert!(usage.is_pfd_used(Pll::Sys, PFD::PFD1));
+        assert!(usage.is_used(Pll::Sys));
+    }
+
+    #[test]
+    fn usage_outputs() {
+        let usage = usage_(|address| match address {
+            0x400D_80E0 => 1 << 22,
+            _ => 0,
+        });
+        assert!(usage.is_used(Pll::Enet));
+    }
+}
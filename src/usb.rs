@@ -0,0 +1,202 @@
+//! USB clock bring-up
+//!
+//! A USB controller, and its PHY, need three clocks before a USB driver, like
+//! `imxrt-usbd`, can touch them:
+//!
+//! 1. the USB PLL, powered and locked at 480MHz, with its USB PHY clock
+//!    outputs enabled. USB1 uses PLL3; USB2 uses PLL7.
+//! 2. the USBOH3 clock gate, shared by both USB controllers.
+//! 3. the USB PHY, out of soft reset, with its clocks ungated and powered up.
+//!
+//! [`CCM::enable_usb`] performs all three steps:
+//!
+//! ```no_run
+//! use imxrt_ccm::{usb::USB, Clocks, Instance, CCM};
+//! # struct USB1;
+//! # unsafe impl Instance for USB1 {
+//! #     type Inst = USB;
+//! #     fn instance(&self) -> USB { USB::USB1 }
+//! #     fn is_valid(inst: USB) -> bool { inst == USB::USB1 }
+//! # }
+//! # struct MyClocks;
+//! # impl Clocks for MyClocks {
+//! #     type PIT = ();
+//! #     type GPT = ();
+//! #     type UART = ();
+//! #     type SPI = ();
+//! #     type I2C = ();
+//! # }
+//! # let mut ccm = unsafe { CCM::<MyClocks>::new() };
+//! # let mut usb1 = USB1;
+//!
+//! ccm.enable_usb(&mut usb1);
+//! // Hand the USB1 instance to the USB driver...
+//! ```
+//!
+//! The USB2 controller, and PLL7, are only available on the i.MX RT 1060.
+//!
+//! # References
+//!
+//! i.MX RT 1060 reference manual
+//! - Chapter 14: Clock Control Module (CCM)
+//!   - CCM Analog Memory Map/Register Definition
+//! - Chapter 42: Universal Serial Bus Controller (USB)
+//!   - USB PHY Memory Map/Register Definition
+
+use crate::{
+    check_instance,
+    pll::{LockTimeout, Pll},
+    pll3, register, set_clock_gate, ClockGate, ClockGateLocation, ClockGateLocator, Clocks,
+    Instance, CCM,
+};
+
+const CCM_ANALOG_PLL_USB1: *mut u32 = 0x400D_8010 as _;
+#[cfg(feature = "imxrt1060")]
+const CCM_ANALOG_PLL_USB2: *mut u32 = 0x400D_8020 as _;
+
+const USBPHY1: *mut u32 = 0x400D_9000 as _;
+const USBPHY2: *mut u32 = 0x400D_A000 as _;
+
+/// USBPHY_PWD word offset
+const PWD: usize = 0;
+/// USBPHY_CTRL_CLR word offset
+const CTRL_CLR: usize = 0x38 / 4;
+
+/// USBPHY_CTRL clock gate bit
+const CLKGATE: u32 = 1 << 30;
+/// USBPHY_CTRL soft reset bit
+const SFTRST: u32 = 1 << 31;
+
+/// Peripheral instance identifier for USB
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum USB {
+    USB1,
+    USB2,
+}
+
+impl ClockGateLocator for USB {
+    #[inline(always)]
+    fn location(&self) -> ClockGateLocation {
+        // Both controllers share the USBOH3 clock gate
+        ClockGateLocation {
+            offset: 6,
+            gates: &[0],
+        }
+    }
+}
+
+impl USB {
+    /// Returns the PLL control register, and the PLL, that clock this USB
+    /// instance, or `None` if the chip doesn't have the PLL
+    fn pll(self) -> Option<(*mut u32, Pll)> {
+        match self {
+            USB::USB1 => Some((CCM_ANALOG_PLL_USB1, Pll::Usb1)),
+            #[cfg(feature = "imxrt1060")]
+            USB::USB2 => Some((CCM_ANALOG_PLL_USB2, Pll::Usb2)),
+            #[cfg(not(feature = "imxrt1060"))]
+            USB::USB2 => None,
+        }
+    }
+
+    const fn phy(self) -> *mut u32 {
+        match self {
+            USB::USB1 => USBPHY1,
+            USB::USB2 => USBPHY2,
+        }
+    }
+}
+
+impl<C: Clocks> CCM<C> {
+    /// Bring up the USB clocks for `usb`
+    ///
+    /// `enable_usb` powers and locks the USB PLL, enables the PLL's USB PHY
+    /// clocks, turns on the USBOH3 clock gate, and takes the USB PHY out of
+    /// reset. If the USB PLL is already enabled and locked, `enable_usb` does
+    /// not change the PLL.
+    ///
+    /// `enable_usb` writes the USB PHY registers. Call it before you hand the
+    /// USB instance to your USB driver.
+    #[inline(always)]
+    pub fn enable_usb<U>(&mut self, usb: &mut U)
+    where
+        U: Instance<Inst = USB>,
+    {
+        // Never times out
+        let _ = self.enable_usb_(usb, None);
+    }
+
+    /// Bring up the USB clocks for `usb`, waiting at most `max_iterations` for
+    /// the USB PLL to lock
+    ///
+    /// If the PLL does not lock in time, `try_enable_usb` returns
+    /// [`LockTimeout`], and leaves the clock gate and the USB PHY unchanged.
+    /// Otherwise, `try_enable_usb` behaves like
+    /// [`enable_usb`](CCM::enable_usb).
+    #[inline(always)]
+    pub fn try_enable_usb<U>(&mut self, usb: &mut U, max_iterations: u32) -> Result<(), LockTimeout>
+    where
+        U: Instance<Inst = USB>,
+    {
+        self.enable_usb_(usb, Some(max_iterations))
+    }
+
+    fn enable_usb_<U>(
+        &mut self,
+        usb: &mut U,
+        max_iterations: Option<u32>,
+    ) -> Result<(), LockTimeout>
+    where
+        U: Instance<Inst = USB>,
+    {
+        // Safety: we own the CCM, and we have exclusive access to the USB
+        // instance, and therefore its PHY.
+        unsafe { enable::<U>(usb.instance(), max_iterations) }
+    }
+}
+
+/// Bring up the USB clocks for `usb`, waiting at most `max_iterations` for the
+/// USB PLL to lock, or forever if `None`
+///
+/// See [`CCM::enable_usb`] for more information. `enable` does nothing if `usb`
+/// is invalid for `U`, or if the chip doesn't have the USB PLL.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM, and by the USB PHY.
+/// Consider using [`CCM::enable_usb`] for a safer interface.
+pub unsafe fn enable<U>(usb: USB, max_iterations: Option<u32>) -> Result<(), LockTimeout>
+where
+    U: Instance<Inst = USB>,
+{
+    if let Some((pll_usb, pll)) = check_instance::<U>(usb).and_then(USB::pll) {
+        // The PHY needs the PLL's 480MHz clock, so the PLL must be locked,
+        // not bypassed, and driving the USB clocks before the PHY powers up.
+        pll3::enable_usb_pll(pll_usb, pll, max_iterations)?;
+        set_clock_gate::<U>(usb, ClockGate::On);
+        enable_phy_(usb.phy());
+    }
+    Ok(())
+}
+
+/// Take the USB PHY out of reset, ungate its clocks, and power it up
+unsafe fn enable_phy_(usbphy: *mut u32) {
+    register::write(usbphy.add(CTRL_CLR), u32::MAX, SFTRST | CLKGATE);
+    register::write(usbphy.add(PWD), u32::MAX, 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{enable_phy_, CTRL_CLR, PWD};
+
+    #[test]
+    fn usb_enable_phy() {
+        let mut usbphy = [0xFFFF_FFFFu32; 16];
+        unsafe { enable_phy_(usbphy.as_mut_ptr()) };
+        assert_eq!(usbphy[PWD], 0);
+        assert_eq!(usbphy[CTRL_CLR], 0xC000_0000);
+        assert!(usbphy
+            .iter()
+            .enumerate()
+            .all(|(idx, reg)| idx == PWD || idx == CTRL_CLR || *reg == 0xFFFF_FFFF));
+    }
+}
@@ -0,0 +1,168 @@
+//! Measure clock frequencies against an independent reference
+//!
+//! A wrong crystal, or a misconfigured PLL, produces frequencies that differ
+//! from the frequencies that this crate computes. [`measure`] counts the ticks
+//! of a timer that runs on a clock root, like a GPT that runs on the periodic
+//! clock, while a reference counter advances. The SNVS RTC, which runs on the
+//! 32kHz clock, is a good reference, since it doesn't derive from the 24MHz
+//! reference.
+//!
+//! This crate doesn't own the timers. Supply functions that read the counters:
+//!
+//! ```no_run
+//! # let ccm = unsafe { imxrt_ccm::CCM::<imxrt_ccm::SplitClocks>::new() };
+//! # fn gpt1_cnt() -> u32 { 0 }
+//! # fn snvs_lpsrtclr() -> u32 { 0 }
+//! // GPT1 runs on the periodic clock, without a prescaler
+//! let measurement = ccm
+//!     .verify_perclock(1, 3_277, gpt1_cnt, snvs_lpsrtclr)
+//!     .unwrap();
+//! if !measurement.within_ppm(1_000) {
+//!     // Wrong crystal, or a misconfigured clock...
+//! }
+//! ```
+
+use crate::{xtalosc, Clocks, CCM};
+
+/// The number of unchanged reference reads that indicate a stalled reference
+const STALL_READS: u32 = 1_000_000;
+
+/// The reference counter didn't advance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ReferenceStalled;
+
+/// A measured frequency, and the frequency that this crate expected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Measurement {
+    /// The frequency computed from the clock configuration
+    pub expected_hz: u32,
+    /// The frequency measured against the reference
+    pub measured_hz: u32,
+}
+
+impl Measurement {
+    /// Returns the measured frequency's error, in parts per million of the
+    /// expected frequency
+    ///
+    /// Returns `i32::MAX` if the expected frequency is zero.
+    pub fn error_ppm(&self) -> i32 {
+        if self.expected_hz == 0 {
+            return i32::MAX;
+        }
+        let error = self.measured_hz as i64 - self.expected_hz as i64;
+        (error * 1_000_000 / self.expected_hz as i64) as i32
+    }
+
+    /// Returns `true` if the measured frequency is within `ppm` parts per
+    /// million of the expected frequency
+    pub fn within_ppm(&self, ppm: u32) -> bool {
+        self.error_ppm().unsigned_abs() <= ppm
+    }
+}
+
+/// Measure the frequency of `counter` against `reference`
+///
+/// `counter` and `reference` return free-running, up-counting values that may
+/// wrap. `reference` counts at `reference_hz`. The measurement starts on a
+/// `reference` edge, and lasts for `reference_ticks` reference ticks. Longer
+/// measurements are more precise; the counter must not wrap more than once
+/// during the measurement.
+///
+/// Returns an error if `reference` stops counting.
+pub fn measure(
+    expected_hz: u32,
+    mut counter: impl FnMut() -> u32,
+    reference_hz: u32,
+    mut reference: impl FnMut() -> u32,
+    reference_ticks: u32,
+) -> Result<Measurement, ReferenceStalled> {
+    let mut edge = || {
+        let start = reference();
+        for _ in 0..STALL_READS {
+            let now = reference();
+            if now != start {
+                return Ok(now);
+            }
+        }
+        Err(ReferenceStalled)
+    };
+
+    let begin = edge()?;
+    let count_begin = counter();
+    let mut now = begin;
+    while now.wrapping_sub(begin) < reference_ticks {
+        now = edge()?;
+    }
+    let counts = counter().wrapping_sub(count_begin) as u64;
+    let elapsed = now.wrapping_sub(begin) as u64;
+    Ok(Measurement {
+        expected_hz,
+        measured_hz: (counts * reference_hz as u64 / elapsed) as u32,
+    })
+}
+
+impl<C: Clocks> CCM<C> {
+    /// Measure the periodic clock against the 32kHz clock
+    ///
+    /// `gpt` reads the counter of a timer that runs on the periodic clock
+    /// divided by `divider`, like a GPT with a prescaler. `rtc` reads a counter
+    /// that runs on the 32kHz clock, like the SNVS RTC. The measurement lasts
+    /// for `rtc_ticks` 32kHz ticks. See [`measure`] for more information.
+    pub fn verify_perclock(
+        &self,
+        divider: u32,
+        rtc_ticks: u32,
+        gpt: impl FnMut() -> u32,
+        rtc: impl FnMut() -> u32,
+    ) -> Result<Measurement, ReferenceStalled> {
+        // Safety: we own the CCM peripheral memory
+        let expected_hz = unsafe { crate::perclock::frequency() } / divider.max(1);
+        measure(
+            expected_hz,
+            gpt,
+            xtalosc::slow_clock_frequency(),
+            rtc,
+            rtc_ticks,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{measure, Measurement, ReferenceStalled};
+    use core::cell::Cell;
+
+    #[test]
+    fn verify_measure() {
+        // The reference advances every 8 reads; the counter runs 1000x faster
+        let reads = Cell::new(0u32);
+        let reference = || {
+            reads.set(reads.get() + 1);
+            reads.get() / 8
+        };
+        let counter = || reads.get() / 8 * 1_000;
+        let measurement = measure(32_768_000, counter, 32_768, reference, 100).unwrap();
+        assert_eq!(measurement.measured_hz, 32_768_000);
+        assert_eq!(measurement.error_ppm(), 0);
+
+        // The counter wraps during the measurement
+        let reads = Cell::new(0u32);
+        let reference = || {
+            reads.set(reads.get() + 1);
+            (u32::MAX - 10).wrapping_add(reads.get())
+        };
+        let counter = || (u32::MAX - 500).wrapping_add(reads.get() * 99);
+        let measurement = measure(100_000, counter, 1_000, reference, 20).unwrap();
+        assert_eq!(measurement.measured_hz, 99_000);
+        assert_eq!(measurement.error_ppm(), -10_000);
+        assert!(measurement.within_ppm(10_000));
+        assert!(!measurement.within_ppm(9_999));
+
+        assert_eq!(
+            measure(1, || 0, 1, || 0, 1),
+            Err::<Measurement, _>(ReferenceStalled)
+        );
+    }
+}
@@ -0,0 +1,507 @@
+//! 24MHz oscillator (XTALOSC24M) control
+//!
+//! The 24MHz reference clock derives from either the external crystal oscillator,
+//! or the internal RC oscillator. All PLLs, and all clock roots that select the
+//! oscillator, use the 24MHz reference. The crystal is more accurate; the RC
+//! oscillator uses less power, and keeps running if the crystal fails.
+//!
+//! To switch to the RC oscillator, use [`XTALOSC::set_source`]. The RC oscillator
+//! runs at approximately 24MHz. Enable tuning with [`XTALOSC::enable_rc_tuning`]
+//! to improve its accuracy, or manually trim it with [`XTALOSC::set_rc_trim`].
+//!
+//! `XTALOSC` also manages the crystal oscillator's power, bias current, and
+//! power-up delay. Reducing the bias current saves power, but may prevent some
+//! crystals from oscillating. Test bias changes with your board's crystal.
+//!
+//! # Other reference frequencies
+//!
+//! Boards that drive EXTAL with an external clock, or that use a crystal other
+//! than 24MHz, should declare the reference frequency with
+//! [`XTALOSC::set_reference_frequency`] before they configure any clocks. All
+//! frequency calculations, including the PLL, clock root, and clock tree
+//! frequencies, use [`reference_frequency`].
+//!
+//! # 32kHz clock
+//!
+//! The 32kHz clock drives SNVS, the RTC, and the RC oscillator tuning logic.
+//! The hardware selects the 32kHz source: it uses the external 32.768kHz
+//! crystal if the crystal oscillates, and falls back to an internal ring
+//! oscillator otherwise. The ring oscillator is much less accurate. Use
+//! [`slow_clock_source`] to check which source is in use, and
+//! [`slow_clock_frequency`] for the nominal frequency.
+//!
+//! # References
+//!
+//! i.MX RT 1060 reference manual
+//! - Chapter 13: Crystal Oscillator (XTALOSC)
+
+use crate::register::Field;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+const XTALOSC24M_LOWPWR_CTRL: *mut u32 = 0x400D_8270 as _;
+const XTALOSC24M_OSC_CONFIG0: *mut u32 = 0x400D_82A0 as _;
+const XTALOSC24M_OSC_CONFIG1: *mut u32 = 0x400D_82B0 as _;
+const XTALOSC24M_OSC_CONFIG2: *mut u32 = 0x400D_82C0 as _;
+const XTALOSC24M_MISC0: *mut u32 = 0x400D_8150 as _;
+
+const RC_OSC_EN: Field = Field::new(0, 1);
+const OSC_SEL: Field = Field::new(4, 1);
+const XTALOSC_PWRUP_DELAY: Field = Field::new(14, 0b11);
+const XTALOSC_PWRUP_STAT: Field = Field::new(16, 1);
+
+const OSC_I: Field = Field::new(13, 0b11);
+const OSC_XTALOK: Field = Field::new(15, 1);
+const OSC_XTALOK_EN: Field = Field::new(16, 1);
+const RTC_XTAL_SOURCE: Field = Field::new(29, 1);
+const XTAL_24M_PWD: Field = Field::new(30, 1);
+
+const START: Field = Field::new(0, 1);
+const ENABLE: Field = Field::new(1, 1);
+const BYPASS: Field = Field::new(2, 1);
+const RC_OSC_PROG: Field = Field::new(4, 0xFF);
+const HYST_PLUS: Field = Field::new(12, 0xF);
+const HYST_MINUS: Field = Field::new(16, 0xF);
+const RC_OSC_PROG_CUR: Field = Field::new(24, 0xFF);
+
+const COUNT_RC_TRG: Field = Field::new(0, 0xFFF);
+const COUNT_RC_CUR: Field = Field::new(20, 0xFFF);
+
+const ENABLE_1M: Field = Field::new(16, 1);
+const MUX_1M: Field = Field::new(17, 1);
+const CLK_1M_ERR_FL: Field = Field::new(31, 1);
+
+/// RC oscillator tuning defaults, taken from the NXP SDK
+const RC_TUNING_PROG: u32 = 0xA7;
+const RC_TUNING_HYST: u32 = 0x3;
+const RC_TUNING_COUNT: u32 = 0x2DC;
+
+/// The 24MHz reference source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// The external 24MHz crystal oscillator
+    Crystal,
+    /// The internal RC oscillator
+    RcOscillator,
+}
+
+/// The 32kHz clock source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlowClockSource {
+    /// The internal ring oscillator
+    RingOscillator,
+    /// The external 32.768kHz crystal
+    Crystal,
+}
+
+/// The declared reference frequency
+static REFERENCE_HZ: AtomicU32 = AtomicU32::new(crate::OSCILLATOR_FREQUENCY_HZ);
+
+/// Returns the reference frequency, in Hz
+///
+/// The reference frequency is 24MHz, unless you declare another frequency with
+/// [`XTALOSC::set_reference_frequency`].
+#[inline(always)]
+pub fn reference_frequency() -> u32 {
+    REFERENCE_HZ.load(Ordering::Relaxed)
+}
+
+/// The 32kHz crystal frequency
+pub const SLOW_CRYSTAL_FREQUENCY_HZ: u32 = 32_768;
+/// The nominal frequency of the internal ring oscillator
+///
+/// The ring oscillator frequency varies with process, voltage, and temperature.
+pub const RING_OSCILLATOR_FREQUENCY_HZ: u32 = 40_000;
+
+/// Crystal oscillator bias current
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum Bias {
+    /// Nominal bias current (reset value)
+    Nominal = 0,
+    /// Decrease bias current by 12.5%
+    Minus12_5Percent = 1,
+    /// Decrease bias current by 25%
+    Minus25Percent = 2,
+    /// Decrease bias current by 37.5%
+    Minus37_5Percent = 3,
+}
+
+/// Delay between crystal power-up and crystal ready
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum PowerUpDelay {
+    /// 0.25ms
+    Ms0_25 = 0,
+    /// 0.5ms (reset value)
+    Ms0_5 = 1,
+    /// 1ms
+    Ms1 = 2,
+    /// 2ms
+    Ms2 = 3,
+}
+
+/// The 24MHz oscillator
+///
+/// Use `XTALOSC` to select the 24MHz reference source, to tune the RC oscillator,
+/// and to manage the crystal oscillator.
+pub struct XTALOSC(());
+
+impl XTALOSC {
+    pub(crate) const fn new() -> Self {
+        XTALOSC(())
+    }
+
+    /// Select the 24MHz reference source
+    ///
+    /// When selecting the RC oscillator, `set_source` enables the RC oscillator
+    /// before switching. When selecting the crystal, `set_source` switches, then
+    /// disables the RC oscillator. Make sure that the crystal is ready before
+    /// switching to the crystal; see [`crystal_is_ready`](XTALOSC::crystal_is_ready).
+    #[inline(always)]
+    pub fn set_source(&mut self, source: Source) {
+        // Safety: we own the XTALOSC24M registers
+        unsafe { set_source(source) }
+    }
+
+    /// Returns the 24MHz reference source
+    #[inline(always)]
+    pub fn source(&self) -> Source {
+        source()
+    }
+
+    /// Declare the reference frequency, in Hz
+    ///
+    /// Use this when the reference isn't a 24MHz crystal, like when an external
+    /// clock drives EXTAL. The CCM doesn't measure the reference; the frequency
+    /// only affects this crate's frequency calculations. Declare the frequency
+    /// before you configure any clocks. A `hz` of zero is ignored.
+    ///
+    /// ```no_run
+    /// # let mut ccm = unsafe { imxrt_ccm::CCM::<imxrt_ccm::SplitClocks>::new() };
+    /// ccm.xtalosc_mut().set_reference_frequency(25_000_000);
+    /// assert_eq!(imxrt_ccm::xtalosc::reference_frequency(), 25_000_000);
+    /// ```
+    pub fn set_reference_frequency(&mut self, hz: u32) {
+        if hz != 0 {
+            REFERENCE_HZ.store(hz, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the reference frequency, in Hz
+    ///
+    /// See [`reference_frequency`].
+    #[inline(always)]
+    pub fn reference_frequency(&self) -> u32 {
+        reference_frequency()
+    }
+
+    /// Returns `true` if the crystal oscillator is powered up, stable, and ready to use
+    #[inline(always)]
+    pub fn crystal_is_ready(&self) -> bool {
+        // Safety: atomic read of a valid register
+        unsafe { XTALOSC_PWRUP_STAT.read(XTALOSC24M_LOWPWR_CTRL) != 0 }
+    }
+
+    /// Power down (`true`) or power up (`false`) the crystal oscillator
+    ///
+    /// Switch the 24MHz reference to the RC oscillator before powering down the
+    /// crystal. After powering up the crystal, wait for
+    /// [`crystal_is_ready`](XTALOSC::crystal_is_ready) before switching back to
+    /// the crystal.
+    #[inline(always)]
+    pub fn set_crystal_powered_down(&mut self, power_down: bool) {
+        // Safety: we own the MISC0 crystal oscillator fields
+        unsafe { XTAL_24M_PWD.modify(XTALOSC24M_MISC0, power_down as u32) }
+    }
+
+    /// Returns `true` if the crystal oscillator is powered down
+    #[inline(always)]
+    pub fn crystal_is_powered_down(&self) -> bool {
+        // Safety: atomic read of a valid register
+        unsafe { XTAL_24M_PWD.read(XTALOSC24M_MISC0) != 0 }
+    }
+
+    /// Set the crystal oscillator bias current
+    #[inline(always)]
+    pub fn set_bias(&mut self, bias: Bias) {
+        // Safety: we own the MISC0 crystal oscillator fields
+        unsafe { OSC_I.modify(XTALOSC24M_MISC0, bias as u32) }
+    }
+
+    /// Returns the crystal oscillator bias current
+    #[inline(always)]
+    pub fn bias(&self) -> Bias {
+        // Safety: atomic read of a valid register
+        match unsafe { OSC_I.read(XTALOSC24M_MISC0) } {
+            0 => Bias::Nominal,
+            1 => Bias::Minus12_5Percent,
+            2 => Bias::Minus25Percent,
+            _ => Bias::Minus37_5Percent,
+        }
+    }
+
+    /// Set the delay between crystal power-up and crystal ready
+    ///
+    /// The hardware waits this long after powering up the crystal, for example
+    /// when exiting a low-power mode, before using the crystal.
+    #[inline(always)]
+    pub fn set_power_up_delay(&mut self, delay: PowerUpDelay) {
+        // Safety: we own the XTALOSC24M registers
+        unsafe { XTALOSC_PWRUP_DELAY.modify(XTALOSC24M_LOWPWR_CTRL, delay as u32) }
+    }
+
+    /// Returns the delay between crystal power-up and crystal ready
+    #[inline(always)]
+    pub fn power_up_delay(&self) -> PowerUpDelay {
+        // Safety: atomic read of a valid register
+        match unsafe { XTALOSC_PWRUP_DELAY.read(XTALOSC24M_LOWPWR_CTRL) } {
+            0 => PowerUpDelay::Ms0_25,
+            1 => PowerUpDelay::Ms0_5,
+            2 => PowerUpDelay::Ms1,
+            _ => PowerUpDelay::Ms2,
+        }
+    }
+
+    /// Enable (`true`) or disable (`false`) the crystal stability detector
+    ///
+    /// When enabled, use [`crystal_is_ok`](XTALOSC::crystal_is_ok) to check
+    /// that the crystal is oscillating.
+    #[inline(always)]
+    pub fn set_crystal_detector(&mut self, enable: bool) {
+        // Safety: we own the MISC0 crystal oscillator fields
+        unsafe { OSC_XTALOK_EN.modify(XTALOSC24M_MISC0, enable as u32) }
+    }
+
+    /// Returns `true` if the crystal stability detector reports a stable crystal
+    ///
+    /// Always returns `false` if the detector is disabled.
+    #[inline(always)]
+    pub fn crystal_is_ok(&self) -> bool {
+        // Safety: atomic read of a valid register
+        unsafe {
+            OSC_XTALOK_EN.read(XTALOSC24M_MISC0) != 0 && OSC_XTALOK.read(XTALOSC24M_MISC0) != 0
+        }
+    }
+
+    /// Returns the 32kHz clock source
+    #[inline(always)]
+    pub fn slow_clock_source(&self) -> SlowClockSource {
+        slow_clock_source()
+    }
+
+    /// Returns the nominal 32kHz clock frequency
+    #[inline(always)]
+    pub fn slow_clock_frequency(&self) -> u32 {
+        slow_clock_frequency()
+    }
+
+    /// Enable the 1MHz clock, derived from the 24MHz reference
+    ///
+    /// If `corrected` is `true`, the hardware corrects the 1MHz clock against the
+    /// 32kHz clock. Otherwise, the 1MHz clock is the uncorrected, divided 24MHz
+    /// reference.
+    #[inline(always)]
+    pub fn enable_1mhz_clock(&mut self, corrected: bool) {
+        // Safety: we own the XTALOSC24M registers
+        unsafe { enable_1mhz_clock_(XTALOSC24M_OSC_CONFIG2, corrected) }
+    }
+
+    /// Disable the 1MHz clock
+    #[inline(always)]
+    pub fn disable_1mhz_clock(&mut self) {
+        // Safety: we own the XTALOSC24M registers
+        unsafe { ENABLE_1M.modify(XTALOSC24M_OSC_CONFIG2, 0) }
+    }
+
+    /// Returns `true` if the 1MHz clock correction missed its target count
+    /// within one 32kHz period
+    #[inline(always)]
+    pub fn clock_1mhz_error(&self) -> bool {
+        // Safety: atomic read of a valid register
+        unsafe { CLK_1M_ERR_FL.read(XTALOSC24M_OSC_CONFIG2) != 0 }
+    }
+
+    /// Enable automatic tuning of the RC oscillator
+    ///
+    /// Tuning compares the RC oscillator against the 32kHz clock, and adjusts
+    /// the RC oscillator's trim value. Tuning uses the NXP SDK defaults.
+    #[inline(always)]
+    pub fn enable_rc_tuning(&mut self) {
+        // Safety: we own the XTALOSC24M registers
+        unsafe { enable_rc_tuning_(XTALOSC24M_OSC_CONFIG0, XTALOSC24M_OSC_CONFIG1) }
+    }
+
+    /// Disable automatic tuning, and manually trim the RC oscillator
+    ///
+    /// Larger values increase the RC oscillator frequency.
+    #[inline(always)]
+    pub fn set_rc_trim(&mut self, trim: u8) {
+        // Safety: we own the XTALOSC24M registers
+        unsafe { set_rc_trim_(XTALOSC24M_OSC_CONFIG0, trim) }
+    }
+
+    /// Returns the RC oscillator trim value that's in use
+    ///
+    /// When tuning is enabled, this is the most recently computed value.
+    #[inline(always)]
+    pub fn rc_trim(&self) -> u8 {
+        // Safety: atomic read of a valid register
+        unsafe { RC_OSC_PROG_CUR.read(XTALOSC24M_OSC_CONFIG0) as u8 }
+    }
+
+    /// Returns the most recent RC oscillator count, measured against the 32kHz clock
+    ///
+    /// The tuning logic targets a count of 732 (`0x2DC`), or 24MHz / 32.768kHz.
+    #[inline(always)]
+    pub fn rc_count(&self) -> u32 {
+        // Safety: atomic read of a valid register
+        unsafe { COUNT_RC_CUR.read(XTALOSC24M_OSC_CONFIG1) }
+    }
+}
+
+/// Select the 24MHz reference source
+///
+/// See [`XTALOSC::set_source`] for more information.
+///
+/// # Safety
+///
+/// Modifies global, mutable memory that's owned by the CCM. Consider using
+/// [`XTALOSC`] for a safer interface.
+#[inline(always)]
+pub unsafe fn set_source(source: Source) {
+    set_source_(XTALOSC24M_LOWPWR_CTRL, source)
+}
+
+unsafe fn set_source_(lowpwr_ctrl: *mut u32, source: Source) {
+    match source {
+        Source::RcOscillator => {
+            RC_OSC_EN.modify(lowpwr_ctrl, 1);
+            OSC_SEL.modify(lowpwr_ctrl, 1);
+        }
+        Source::Crystal => {
+            OSC_SEL.modify(lowpwr_ctrl, 0);
+            RC_OSC_EN.modify(lowpwr_ctrl, 0);
+        }
+    }
+}
+
+/// Returns the 24MHz reference source
+#[inline(always)]
+pub fn source() -> Source {
+    // Safety: atomic read of a valid register
+    unsafe { source_(XTALOSC24M_LOWPWR_CTRL) }
+}
+
+unsafe fn source_(lowpwr_ctrl: *const u32) -> Source {
+    if OSC_SEL.read(lowpwr_ctrl) != 0 {
+        Source::RcOscillator
+    } else {
+        Source::Crystal
+    }
+}
+
+/// Returns the 32kHz clock source
+#[inline(always)]
+pub fn slow_clock_source() -> SlowClockSource {
+    // Safety: atomic read of a valid register
+    unsafe { slow_clock_source_(XTALOSC24M_MISC0) }
+}
+
+unsafe fn slow_clock_source_(misc0: *const u32) -> SlowClockSource {
+    if RTC_XTAL_SOURCE.read(misc0) != 0 {
+        SlowClockSource::Crystal
+    } else {
+        SlowClockSource::RingOscillator
+    }
+}
+
+/// Returns the nominal 32kHz clock frequency
+///
+/// Returns [`SLOW_CRYSTAL_FREQUENCY_HZ`] when the 32kHz crystal is in use, and
+/// [`RING_OSCILLATOR_FREQUENCY_HZ`] when the ring oscillator is in use.
+#[inline(always)]
+pub fn slow_clock_frequency() -> u32 {
+    match slow_clock_source() {
+        SlowClockSource::Crystal => SLOW_CRYSTAL_FREQUENCY_HZ,
+        SlowClockSource::RingOscillator => RING_OSCILLATOR_FREQUENCY_HZ,
+    }
+}
+
+unsafe fn enable_1mhz_clock_(osc_config2: *mut u32, corrected: bool) {
+    // MUX_1M = 1 selects the corrected clock
+    MUX_1M.modify(osc_config2, corrected as u32);
+    ENABLE_1M.modify(osc_config2, 1);
+}
+
+unsafe fn enable_rc_tuning_(osc_config0: *mut u32, osc_config1: *mut u32) {
+    COUNT_RC_TRG.modify(osc_config1, RC_TUNING_COUNT);
+    COUNT_RC_CUR.modify(osc_config1, RC_TUNING_COUNT);
+    BYPASS.modify(osc_config0, 0);
+    RC_OSC_PROG.modify(osc_config0, RC_TUNING_PROG);
+    HYST_PLUS.modify(osc_config0, RC_TUNING_HYST);
+    HYST_MINUS.modify(osc_config0, RC_TUNING_HYST);
+    ENABLE.modify(osc_config0, 1);
+    START.modify(osc_config0, 1);
+}
+
+unsafe fn set_rc_trim_(osc_config0: *mut u32, trim: u8) {
+    START.modify(osc_config0, 0);
+    ENABLE.modify(osc_config0, 0);
+    RC_OSC_PROG.modify(osc_config0, trim as u32);
+    BYPASS.modify(osc_config0, 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        enable_1mhz_clock_, enable_rc_tuning_, set_rc_trim_, set_source_, slow_clock_source_,
+        source_, SlowClockSource, Source,
+    };
+
+    #[test]
+    fn xtalosc_source() {
+        let mut lowpwr_ctrl: u32 = 0;
+        unsafe {
+            assert_eq!(source_(&lowpwr_ctrl), Source::Crystal);
+            set_source_(&mut lowpwr_ctrl, Source::RcOscillator);
+            assert_eq!(lowpwr_ctrl, 0b1_0001);
+            assert_eq!(source_(&lowpwr_ctrl), Source::RcOscillator);
+            set_source_(&mut lowpwr_ctrl, Source::Crystal);
+            assert_eq!(lowpwr_ctrl, 0);
+        }
+    }
+
+    #[test]
+    fn xtalosc_rc_tuning() {
+        let (mut osc_config0, mut osc_config1): (u32, u32) = (0, 0);
+        unsafe {
+            enable_rc_tuning_(&mut osc_config0, &mut osc_config1);
+            assert_eq!(osc_config0, 0x0003_3A73);
+            assert_eq!(osc_config1, 0x2DC0_02DC);
+
+            set_rc_trim_(&mut osc_config0, 0x80);
+            assert_eq!(osc_config0, 0x0003_3804);
+        }
+    }
+
+    #[test]
+    fn xtalosc_slow_clock() {
+        let ring: u32 = 0;
+        let crystal: u32 = 1 << 29;
+        unsafe {
+            assert_eq!(slow_clock_source_(&ring), SlowClockSource::RingOscillator);
+            assert_eq!(slow_clock_source_(&crystal), SlowClockSource::Crystal);
+        }
+    }
+
+    #[test]
+    fn xtalosc_1mhz_clock() {
+        let mut osc_config2: u32 = 0x2D7;
+        unsafe {
+            enable_1mhz_clock_(&mut osc_config2, true);
+            assert_eq!(osc_config2, 1 << 17 | 1 << 16 | 0x2D7);
+            enable_1mhz_clock_(&mut osc_config2, false);
+            assert_eq!(osc_config2, 1 << 16 | 0x2D7);
+        }
+    }
+}
@@ -7,6 +7,7 @@ use imxrt_ccm::{
     perclock::{GPT, PIT},
     spi::SPI,
     uart::UART,
+    usb::USB,
     *,
 };
 use imxrt_ral as ral;
@@ -15,73 +16,102 @@ const IMXRT1060: bool = cfg!(feature = "imxrt1060");
 
 #[test]
 fn dcdc_is_valid() {
-    assert!(ral::dcdc::Instance::is_valid(DCDC));
+    assert!(ral::dcdc::DCDC::is_valid(DCDC));
 }
 
 #[test]
 fn dma_is_valid() {
-    assert!(ral::dma0::Instance::is_valid(DMA));
+    assert!(ral::dma::DMA::is_valid(DMA));
 }
 
 #[test]
 fn i2c_is_valid() {
-    assert!(ral::lpi2c::Instance::is_valid(I2C::I2C1));
-    assert!(ral::lpi2c::Instance::is_valid(I2C::I2C2));
-    assert_eq!(ral::lpi2c::Instance::is_valid(I2C::I2C3), IMXRT1060);
-    assert_eq!(ral::lpi2c::Instance::is_valid(I2C::I2C4), IMXRT1060);
+    assert!(ral::lpi2c::LPI2C1::is_valid(I2C::I2C1));
+    assert!(ral::lpi2c::LPI2C1::is_valid(I2C::I2C2));
+    assert_eq!(ral::lpi2c::LPI2C1::is_valid(I2C::I2C3), IMXRT1060);
+    assert_eq!(ral::lpi2c::LPI2C1::is_valid(I2C::I2C4), IMXRT1060);
 }
 
 #[test]
 fn gpt_is_valid() {
-    assert!(ral::gpt::Instance::is_valid(GPT::GPT1));
-    assert!(ral::gpt::Instance::is_valid(GPT::GPT2));
+    assert!(ral::gpt::GPT1::is_valid(GPT::GPT1));
+    assert!(ral::gpt::GPT1::is_valid(GPT::GPT2));
 }
 
 #[test]
 fn pit_is_valid() {
-    assert!(ral::pit::Instance::is_valid(PIT))
+    assert!(ral::pit::PIT::is_valid(PIT))
 }
 
 #[test]
 fn spi_is_valid() {
-    assert!(ral::lpspi::Instance::is_valid(SPI::SPI1));
-    assert!(ral::lpspi::Instance::is_valid(SPI::SPI2));
-    assert_eq!(ral::lpspi::Instance::is_valid(SPI::SPI3), IMXRT1060);
-    assert_eq!(ral::lpspi::Instance::is_valid(SPI::SPI3), IMXRT1060);
+    assert!(ral::lpspi::LPSPI1::is_valid(SPI::SPI1));
+    assert!(ral::lpspi::LPSPI1::is_valid(SPI::SPI2));
+    assert_eq!(ral::lpspi::LPSPI1::is_valid(SPI::SPI3), IMXRT1060);
+    assert_eq!(ral::lpspi::LPSPI1::is_valid(SPI::SPI3), IMXRT1060);
 }
 
 #[test]
 fn uart_is_valid() {
-    assert!(ral::lpuart::Instance::is_valid(UART::UART1));
-    assert!(ral::lpuart::Instance::is_valid(UART::UART2));
-    assert!(ral::lpuart::Instance::is_valid(UART::UART3));
-    assert!(ral::lpuart::Instance::is_valid(UART::UART4));
-    assert_eq!(ral::lpuart::Instance::is_valid(UART::UART5), IMXRT1060);
-    assert_eq!(ral::lpuart::Instance::is_valid(UART::UART6), IMXRT1060);
-    assert_eq!(ral::lpuart::Instance::is_valid(UART::UART7), IMXRT1060);
-    assert_eq!(ral::lpuart::Instance::is_valid(UART::UART8), IMXRT1060);
+    assert!(ral::lpuart::LPUART1::is_valid(UART::UART1));
+    assert!(ral::lpuart::LPUART1::is_valid(UART::UART2));
+    assert!(ral::lpuart::LPUART1::is_valid(UART::UART3));
+    assert!(ral::lpuart::LPUART1::is_valid(UART::UART4));
+    assert_eq!(ral::lpuart::LPUART1::is_valid(UART::UART5), IMXRT1060);
+    assert_eq!(ral::lpuart::LPUART1::is_valid(UART::UART6), IMXRT1060);
+    assert_eq!(ral::lpuart::LPUART1::is_valid(UART::UART7), IMXRT1060);
+    assert_eq!(ral::lpuart::LPUART1::is_valid(UART::UART8), IMXRT1060);
 }
 
+#[cfg(not(feature = "imxrt1060"))]
+use ral::{adc::ADC as ADC_, pwm::PWM as PWM_, usb::USB as USB_};
 #[cfg(feature = "imxrt1060")]
-use ral::adc;
-#[cfg(feature = "imxrt1010")]
-use ral::adc1 as adc;
+use ral::{adc::ADC1 as ADC_, pwm::PWM1 as PWM_, usb::USB1 as USB_};
 
 #[test]
 fn adc_is_valid() {
-    assert!(adc::Instance::is_valid(ADC::ADC1));
-    assert_eq!(adc::Instance::is_valid(ADC::ADC2), IMXRT1060);
+    assert!(ADC_::is_valid(ADC::ADC1));
+    assert_eq!(ADC_::is_valid(ADC::ADC2), IMXRT1060);
 }
 
-#[cfg(feature = "imxrt1060")]
-use ral::pwm;
-#[cfg(feature = "imxrt1010")]
-use ral::pwm1 as pwm;
-
 #[test]
 fn pwm_is_valid() {
-    assert!(pwm::Instance::is_valid(PWM::PWM1));
-    assert_eq!(pwm::Instance::is_valid(PWM::PWM2), IMXRT1060);
-    assert_eq!(pwm::Instance::is_valid(PWM::PWM3), IMXRT1060);
-    assert_eq!(pwm::Instance::is_valid(PWM::PWM4), IMXRT1060);
+    assert!(PWM_::is_valid(PWM::PWM1));
+    assert_eq!(PWM_::is_valid(PWM::PWM2), IMXRT1060);
+    assert_eq!(PWM_::is_valid(PWM::PWM3), IMXRT1060);
+    assert_eq!(PWM_::is_valid(PWM::PWM4), IMXRT1060);
+}
+
+#[test]
+fn usb_is_valid() {
+    assert!(USB_::is_valid(USB::USB1));
+    assert_eq!(USB_::is_valid(USB::USB2), IMXRT1060);
+}
+
+#[test]
+fn instance_numbers() {
+    // Safety: the instances are never dereferenced
+    unsafe {
+        assert_eq!(ral::lpuart::LPUART3::instance().instance(), UART::UART3);
+        assert_eq!(ral::lpspi::LPSPI2::instance().instance(), SPI::SPI2);
+        assert_eq!(ral::lpi2c::LPI2C2::instance().instance(), I2C::I2C2);
+        assert_eq!(ral::gpt::GPT2::instance().instance(), GPT::GPT2);
+        assert_eq!(ADC_::instance().instance(), ADC::ADC1);
+        assert_eq!(PWM_::instance().instance(), PWM::PWM1);
+        assert_eq!(USB_::instance().instance(), USB::USB1);
+    }
+}
+
+#[cfg(feature = "imxrt1060")]
+#[test]
+fn instance_numbers_1060() {
+    // Safety: the instances are never dereferenced
+    unsafe {
+        assert_eq!(ral::lpuart::LPUART8::instance().instance(), UART::UART8);
+        assert_eq!(ral::lpspi::LPSPI4::instance().instance(), SPI::SPI4);
+        assert_eq!(ral::lpi2c::LPI2C3::instance().instance(), I2C::I2C3);
+        assert_eq!(ral::adc::ADC2::instance().instance(), ADC::ADC2);
+        assert_eq!(ral::pwm::PWM4::instance().instance(), PWM::PWM4);
+        assert_eq!(ral::usb::USB2::instance().instance(), USB::USB2);
+    }
 }
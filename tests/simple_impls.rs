@@ -4,6 +4,8 @@
 //!
 //! This test doesn't run. If the test compiles, the test passes.
 
+#![allow(clippy::upper_case_acronyms)]
+
 use imxrt_ccm as ccm;
 
 struct ADC;
@@ -0,0 +1,306 @@
+//! Tests that clock setup runs against the simulated registers, like it would
+//! in a HAL's unit tests.
+
+#![cfg(feature = "simulation")]
+
+use imxrt_ccm::{
+    get_clock_gate, perclock, pll3, shared::SharedClockGate, simulation, spi, uart, usb::USB,
+    CcmConfig, ClockFor, ClockGate, ClockRoot, Clocks, Instance, CCM, ESSENTIAL_CLOCK_GATES, PWM,
+};
+
+struct MyClocks;
+impl Clocks for MyClocks {
+    type PIT = ();
+    type GPT = ();
+    type UART = ();
+    type SPI = ();
+    type I2C = ();
+}
+
+#[derive(Debug, PartialEq)]
+struct PWM2;
+unsafe impl Instance for PWM2 {
+    type Inst = PWM;
+    fn instance(&self) -> PWM {
+        PWM::PWM2
+    }
+    fn is_valid(inst: PWM) -> bool {
+        inst == PWM::PWM2
+    }
+}
+
+struct USB1;
+unsafe impl Instance for USB1 {
+    type Inst = USB;
+    fn instance(&self) -> USB {
+        USB::USB1
+    }
+    fn is_valid(inst: USB) -> bool {
+        inst == USB::USB1
+    }
+}
+
+const CCGR1: usize = 0x400F_C06C;
+const CCGR6: usize = 0x400F_C080;
+const USBPHY1_PWD: usize = 0x400D_9000;
+const PLL_USB1: usize = 0x400D_8010;
+const PFD_480: usize = 0x400D_80F0;
+const CBCDR: usize = 0x400F_C014;
+const CBCMR: usize = 0x400F_C018;
+const CACRR: usize = 0x400F_C010;
+const PLL_ARM: usize = 0x400D_8000;
+const PLL_SYS: usize = 0x400D_8030;
+/// The registers that control the ARM clock
+const ARM_REGISTERS: &[usize] = &[CBCDR, CBCMR, CACRR, PLL_ARM, PLL_SYS];
+
+#[test]
+fn apply_config() {
+    simulation::reset();
+    const CONFIG: CcmConfig = CcmConfig::new()
+        .all_gates(ClockGate::Off, ESSENTIAL_CLOCK_GATES)
+        .perclock(perclock::Selection::Oscillator, 24)
+        .uart(uart::Selection::Oscillator, 1);
+
+    let mut ccm = unsafe { CCM::<MyClocks>::new() };
+    let applied = ccm.apply(&CONFIG).unwrap();
+    assert_eq!(applied.perclock.unwrap().hz, 1_000_000);
+    assert_eq!(applied.uart.unwrap().hz, 24_000_000);
+    assert_eq!(ccm.root_frequency(ClockRoot::UART), 24_000_000);
+
+    // The next test starts from reset
+    simulation::reset();
+    assert_eq!(simulation::read(CCGR6), 0xFFFF_FFFF);
+}
+
+#[test]
+fn apply_spi_pll3_pfd() {
+    simulation::reset();
+    assert!(!pll3::is_enabled());
+    // All PLL3 PFDs are gated
+    let pfd_480 = simulation::read(PFD_480);
+    simulation::write(PFD_480, pfd_480 | 0x8080_8080);
+    // LPSPI1 is off, and the other gates are on
+    simulation::write(CCGR1, !0b11);
+
+    let mut ccm = unsafe { CCM::<MyClocks>::new() };
+    let applied = ccm
+        .apply(&CcmConfig::new().spi(spi::Selection::Pll3Pfd0, 4))
+        .unwrap();
+
+    assert!(pll3::is_enabled());
+    // Only PFD0 is ungated
+    assert_eq!(simulation::read(PFD_480) & 0x8080_8080, 0x8080_8000);
+    assert_eq!(
+        applied.spi.unwrap().hz,
+        pll3::pfd_frequency(pll3::PFD::PFD0) / 4
+    );
+    assert_ne!(applied.spi.unwrap().hz, 0);
+    // The SPI clock gates return to their settings
+    assert_eq!(simulation::read(CCGR1), !0b11);
+}
+
+#[test]
+fn apply_capture_round_trip() {
+    simulation::reset();
+    // The ARM clock runs from the divided oscillator, with dividers that the
+    // CCM wouldn't select for itself
+    let cbcmr = simulation::read(CBCMR) & !(0b11 << 12);
+    simulation::write(CBCMR, cbcmr | 1 << 12);
+    simulation::write(
+        CBCDR,
+        simulation::read(CBCDR) | 1 << 25 | 0b111 << 27 | 0b111 << 10,
+    );
+    let mut ccm = unsafe { CCM::<MyClocks>::new() };
+    let before: Vec<u32> = ARM_REGISTERS.iter().map(|&r| simulation::read(r)).collect();
+    let captured = ccm.capture();
+    let captured_arm_hz = ccm.frequency_arm().0 .0;
+
+    let applied = ccm.apply(&captured).unwrap();
+
+    let after: Vec<u32> = ARM_REGISTERS.iter().map(|&r| simulation::read(r)).collect();
+    assert_eq!(before, after);
+    assert_eq!(applied.arm.unwrap().arm.0, captured_arm_hz);
+    assert_eq!(applied.arm.unwrap().error_hz, 0);
+    assert!(ccm.validate(&captured).is_empty());
+}
+
+#[cfg(feature = "imxrt1060")]
+#[test]
+fn set_frequency_arm() {
+    simulation::reset();
+    let mut ccm = unsafe { CCM::<MyClocks>::new() };
+    let (arm, ipg) = ccm.set_frequency_arm(600_000_000);
+    assert_eq!(arm.0, 600_000_000);
+    assert_eq!(ccm.frequency_arm(), (arm, ipg));
+}
+
+#[cfg(feature = "imxrt1060")]
+#[test]
+fn set_frequency_arm_clamps() {
+    simulation::reset();
+    let mut ccm = unsafe { CCM::<MyClocks>::new() };
+    let (arm, _) = ccm.set_frequency_arm(700_000_000);
+    assert_eq!(arm.0, imxrt_ccm::arm::MAX_FREQUENCY_HZ);
+    // 600MHz is DIV_SELECT 100, divided by 2
+    assert_eq!(simulation::read(PLL_ARM) & 0x7F, 100);
+
+    let (arm, _) = ccm.set_frequency_arm_with_voltage(700_000_000, |_| {});
+    assert_eq!(arm.0, imxrt_ccm::arm::MAX_FREQUENCY_HZ);
+    assert_eq!(simulation::read(PLL_ARM) & 0x7F, 100);
+}
+
+#[test]
+fn enable_usb() {
+    simulation::reset();
+    assert!(!pll3::is_enabled());
+    assert_ne!(simulation::read(USBPHY1_PWD), 0);
+
+    let mut ccm = unsafe { CCM::<MyClocks>::new() };
+    ccm.set_all_clock_gates(ClockGate::Off, &[]);
+    let mut usb1 = USB1;
+    ccm.try_enable_usb(&mut usb1, 1).unwrap();
+
+    assert!(pll3::is_enabled());
+    assert_eq!(get_clock_gate::<USB1>(USB::USB1), Some(ClockGate::On));
+    assert_eq!(simulation::read(USBPHY1_PWD), 0);
+    assert!(ccm
+        .clock_gates()
+        .any(|gate| gate == ("USBOH3", ClockGate::On)));
+}
+
+#[test]
+fn enable_usb_after_bootloader() {
+    simulation::reset();
+    // A bootloader left PLL3 powered and locked, but bypassed, without USB
+    // clocks
+    simulation::write(PLL_USB1, 1 << 16 | 1 << 13 | 1 << 12);
+    assert!(pll3::is_enabled());
+    assert_ne!(pll3::frequency(), 480_000_000);
+
+    let mut ccm = unsafe { CCM::<MyClocks>::new() };
+    let mut usb1 = USB1;
+    ccm.try_enable_usb(&mut usb1, 1).unwrap();
+
+    let pll_usb1 = simulation::read(PLL_USB1);
+    assert_eq!(pll_usb1 & 1 << 16, 0, "PLL3 is still bypassed");
+    assert_ne!(pll_usb1 & 1 << 6, 0, "USB clocks are off");
+    assert_eq!(pll3::frequency(), 480_000_000);
+    assert_eq!(simulation::read(USBPHY1_PWD), 0);
+}
+
+#[test]
+fn freeze() {
+    simulation::reset();
+    let mut ccm = unsafe { CCM::<MyClocks>::new() };
+    ccm.apply(&CcmConfig::new().uart(uart::Selection::Oscillator, 4))
+        .unwrap();
+    let map = ccm.frequency_map();
+    let clocks = ccm.freeze();
+
+    assert_eq!(clocks.uart(), 6_000_000);
+    assert_eq!(ClockFor::<uart::UART>::frequency(&clocks), 6_000_000);
+    assert_eq!((clocks.arm(), clocks.ipg()), map.arm());
+    assert_eq!(clocks.perclock(), map.root(ClockRoot::PerClock));
+}
+
+#[test]
+fn shared_gate_last_lease() {
+    simulation::reset();
+    let mut ccm = unsafe { CCM::<MyClocks>::new() };
+    ccm.set_all_clock_gates(ClockGate::Off, &[]);
+    let pwm = ccm.shared_gate_pwm(PWM2);
+    assert_eq!(pwm.clock_gate(), ClockGate::Off);
+
+    let channel_a = pwm.acquire();
+    assert_eq!(get_clock_gate::<PWM2>(PWM::PWM2), Some(ClockGate::On));
+    let channel_b = channel_a.clone();
+    assert_eq!(pwm.users(), 2);
+
+    drop(channel_a);
+    assert_eq!(pwm.users(), 1);
+    assert_eq!(pwm.clock_gate(), ClockGate::On);
+    drop(channel_b);
+    assert_eq!(pwm.users(), 0);
+    assert_eq!(pwm.clock_gate(), ClockGate::Off);
+
+    assert_eq!(pwm.into_inner(), PWM2);
+}
+
+#[test]
+fn shared_gate_static() {
+    static SHARED: SharedClockGate<PWM2> = unsafe { SharedClockGate::new(PWM2) };
+
+    simulation::reset();
+    let mut ccm = unsafe { CCM::<MyClocks>::new() };
+    ccm.set_all_clock_gates(ClockGate::Off, &[]);
+
+    let lease = SHARED.acquire();
+    let other = SHARED.acquire();
+    assert_eq!(SHARED.clock_gate(), ClockGate::On);
+    drop(lease);
+    assert_eq!(SHARED.clock_gate(), ClockGate::On);
+    drop(other);
+    assert_eq!(SHARED.clock_gate(), ClockGate::Off);
+}
+
+#[test]
+fn enable_guard_restores_on_drop() {
+    simulation::reset();
+    let mut ccm = unsafe { CCM::<MyClocks>::new() };
+    ccm.set_all_clock_gates(ClockGate::Off, &[]);
+    let mut pwm2 = PWM2;
+
+    let guard = ccm.enable_guard_pwm(&mut pwm2);
+    assert_eq!(guard.previous(), ClockGate::Off);
+    assert_eq!(get_clock_gate::<PWM2>(PWM::PWM2), Some(ClockGate::On));
+    // The guard doesn't borrow the CCM
+    ccm.apply(&CcmConfig::new().uart(uart::Selection::Oscillator, 1))
+        .unwrap();
+    assert_eq!(ccm.root_frequency(ClockRoot::UART), 24_000_000);
+    drop(guard);
+    assert_eq!(ccm.clock_gate_pwm(&pwm2), ClockGate::Off);
+
+    // A guard restores a gate that was already on
+    ccm.set_clock_gate_pwm(&mut pwm2, ClockGate::OnlyRun);
+    drop(ccm.enable_guard_pwm(&mut pwm2));
+    assert_eq!(ccm.clock_gate_pwm(&pwm2), ClockGate::OnlyRun);
+}
+
+#[cfg(feature = "imxrt1060")]
+#[test]
+fn deep_sleep_keeps_semc() {
+    const CCGR3: usize = 0x400F_C074;
+    simulation::reset();
+    let mut ccm = unsafe { CCM::<MyClocks>::new() };
+    let sleep = ccm.prepare_deep_sleep(&[]);
+    // SEMC runs external SDRAM, which may hold the program
+    assert_eq!(simulation::read(CCGR3) >> 4 & 0b11, 0b11);
+    assert_eq!(simulation::read(CCGR1), 0);
+    ccm.wake_from_deep_sleep(sleep);
+    assert_eq!(simulation::read(CCGR1), 0xFFFF_FFFF);
+}
+
+#[cfg(not(feature = "imxrt1060"))]
+#[test]
+fn try_set_frequency_arm_without_pll1() {
+    use imxrt_ccm::arm::Root;
+
+    simulation::reset();
+    simulation::write(PLL_SYS, 0x0000_2001); // PLL2 at 528MHz
+    let pll_arm = simulation::read(PLL_ARM);
+    let mut ccm = unsafe { CCM::<MyClocks>::new() };
+
+    let achieved = ccm.try_set_frequency_arm(400_000_000).unwrap();
+    assert!(matches!(achieved.root, Root::PrePeriph(_)));
+    assert!(achieved.arm.0 <= 400_000_000);
+    assert_eq!(achieved.error_hz, achieved.arm.0 as i32 - 400_000_000);
+    let clocks = ccm.clocks_arm();
+    assert_eq!((clocks.root, clocks.arm), (achieved.root, achieved.arm));
+    // There's no PLL1 to program
+    assert_eq!(simulation::read(PLL_ARM), pll_arm);
+
+    // The PLL2 outputs are too fast; the oscillator isn't
+    let achieved = ccm.try_set_frequency_arm(20_000_000).unwrap();
+    assert_eq!(achieved.arm.0, 12_000_000);
+    assert_eq!(ccm.clocks_arm().root, achieved.root);
+}
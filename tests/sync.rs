@@ -0,0 +1,11 @@
+//! Tests that the interrupt-safe clock gate control can live in a `static`.
+//!
+//! This test doesn't run. If the test compiles, the test passes.
+
+#![cfg(feature = "critical-section")]
+
+use imxrt_ccm::GateControl;
+
+static_assertions::assert_impl_all!(GateControl: Sync, Send);
+
+static _GATES: GateControl = GateControl::new();
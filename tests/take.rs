@@ -0,0 +1,30 @@
+//! Tests that `CCM::take` hands out the CCM at most once.
+
+#![cfg(feature = "critical-section")]
+
+use imxrt_ccm as ccm;
+
+struct TestClocks;
+impl ccm::Clocks for TestClocks {
+    type PIT = ();
+    type GPT = ();
+    type SPI = ();
+    type I2C = ();
+    type UART = ();
+}
+
+struct OtherClocks;
+impl ccm::Clocks for OtherClocks {
+    type PIT = ();
+    type GPT = ();
+    type SPI = ();
+    type I2C = ();
+    type UART = ();
+}
+
+#[test]
+fn take_once() {
+    assert!(ccm::CCM::<TestClocks>::take().is_some());
+    assert!(ccm::CCM::<TestClocks>::take().is_none());
+    assert!(ccm::CCM::<OtherClocks>::take().is_none());
+}
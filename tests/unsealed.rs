@@ -0,0 +1,38 @@
+//! Tests a user-defined clock gate locator. The test ensures
+//! that a custom locator works with the `Instance` API.
+//!
+//! This test doesn't run. If the test compiles, the test passes.
+
+#![cfg(feature = "unsealed")]
+
+use ccm::Instance;
+use imxrt_ccm as ccm;
+
+/// FlexIO1 clock gate, which the crate does not model
+#[derive(Clone, Copy, PartialEq)]
+struct FlexIO1;
+
+impl ccm::ClockGateLocator for FlexIO1 {
+    fn location(&self) -> ccm::ClockGateLocation {
+        ccm::ClockGateLocation::new(5, &[1])
+    }
+}
+
+struct MyFlexIO;
+
+unsafe impl ccm::Instance for MyFlexIO {
+    type Inst = FlexIO1;
+    fn instance(&self) -> Self::Inst {
+        FlexIO1
+    }
+    fn is_valid(_: Self::Inst) -> bool {
+        true
+    }
+}
+
+#[allow(unused)]
+fn custom_locator_compiles() {
+    let flexio = MyFlexIO;
+    unsafe { ccm::set_clock_gate::<MyFlexIO>(flexio.instance(), ccm::ClockGate::On) };
+    ccm::get_clock_gate::<MyFlexIO>(flexio.instance());
+}